@@ -1,21 +1,39 @@
-use crate::components::cell_text::CellText;
+use crate::components::cell_text::{
+    auto_size_width, cap_rendered_value, empty_cell_text, format_in_base, format_number,
+    group_thousands, is_too_large_to_render, show_rejected_edit, truncate_cell_value, CellText,
+    LargeValueViewer, AUTO_SIZE_SAMPLED_ROWS,
+};
+use crate::components::debounce::Debounce;
 use crate::components::icon;
 use crate::components::icon::ButtonWithIcon;
 use crate::components::popover::PopupMenu;
-use crate::components::table::{CellLocation, TableBody, TableRow};
-use crate::fonts::{COPY, FILTER, PENCIL, PLUS, SEARCH, TABLE, TABLE_CELLS, THUMBTACK};
-use crate::panels::{SearchReplacePanel, SearchReplaceResponse, PANEL_REPLACE};
-use crate::parser::{replace_occurrences, row_number_entry, search_occurrences};
+use crate::components::table::{CellLocation, CellRange, TableBody, TableRow};
+use crate::fonts::{
+    COLUMNS, COPY, FILTER, LOCK, LOCK_OPEN, PENCIL, PLUS, SEARCH, SORT, SORT_DOWN, SORT_UP, TABLE,
+    TABLE_CELLS, THUMBTACK, TRASH_CAN,
+};
+use crate::history::{Edit, EditHistory, RowChange};
+use crate::panels::{
+    RawJsonPanel, SearchReplacePanel, SearchReplaceResponse, PANEL_RAW_JSON, PANEL_REPLACE,
+};
+use crate::parser::{
+    child_count, coerce_scalars_to_strings, coerce_value, column_aggregates, compare_numbers,
+    cycle_value_type, edited_value_type, escape_pointer_token, find_row_by_pointer, format_json,
+    is_integer, is_pointer_prefix, parse_delimited_block, replace_occurrences,
+    restore_coerced_types, row_number_entry, save_rows_to_buffer, search_occurrences,
+    serialize_to_json_pretty, to_tsv, unescape_pointer_token, Aggregates, SerializeOptions,
+    ROW_NUMBER_COLUMN,
+};
 use crate::subtable_window::SubTable;
 use crate::{
-    concat_string, set_open, ArrayResponse, Window, ACTIVE_COLOR, SHORTCUT_COPY, SHORTCUT_DELETE,
-    SHORTCUT_REPLACE,
+    concat_string, set_open, ArrayResponse, Window, ACTIVE_COLOR, SHORTCUT_COPY,
+    SHORTCUT_CYCLE_TYPE, SHORTCUT_DELETE, SHORTCUT_REDO, SHORTCUT_REPLACE, SHORTCUT_UNDO,
 };
 use eframe::egui::scroll_area::ScrollBarVisibility;
 use eframe::egui::style::Spacing;
 use eframe::egui::{
-    Align, Context, CursorIcon, Id, Key, Label, Sense, Style, TextEdit, Ui, Vec2, Widget,
-    WidgetText,
+    Align, Align2, Color32, Context, CursorIcon, Id, Key, Label, Sense, Style, TextEdit, TextStyle,
+    Ui, Vec2, Widget, WidgetText,
 };
 use eframe::epaint::text::TextWrapMode;
 use egui::{EventFilter, InputState, Modifiers, Rangef, TextBuffer};
@@ -30,8 +48,8 @@ use rayon::prelude::ParallelSliceMut;
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::Ordering;
-use std::collections::{BTreeSet, HashMap};
-use std::hash::{Hash, Hasher};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use std::mem;
 use std::ops::Sub;
 use std::string::ToString;
@@ -106,26 +124,248 @@ impl ScrollToRowMode {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum CellType {
+    Integer,
+    Float,
+    String,
+    Bool,
+    Array,
+    Object,
+    Null,
+}
+
+impl CellType {
+    pub fn of(entry: &FlatJsonValue<String>) -> Self {
+        let value = entry.value.as_deref();
+        match entry.pointer.value_type {
+            ValueType::Number => {
+                if value.is_some_and(is_integer) {
+                    Self::Integer
+                } else {
+                    Self::Float
+                }
+            }
+            ValueType::String => Self::String,
+            ValueType::Array(_) => Self::Array,
+            ValueType::Object(..) => Self::Object,
+            ValueType::Null => Self::Null,
+            _ => match value {
+                Some("true") | Some("false") => Self::Bool,
+                None | Some("null") => Self::Null,
+                Some(_) => Self::String,
+            },
+        }
+    }
+
+    /// Type edits of a column have to match when types are enforced: numeric or boolean columns,
+    /// ignoring nulls. Columns with other or mixed types accept any value.
+    pub fn enforced(column_types: &ColumnTypes) -> Option<Self> {
+        let mut enforced = None;
+        for cell_type in column_types.keys() {
+            enforced = match (enforced, cell_type) {
+                (_, Self::Null) => enforced,
+                (None, Self::Integer | Self::Float | Self::Bool) => Some(*cell_type),
+                (Some(Self::Integer), Self::Float) => Some(Self::Float),
+                _ => return None,
+            };
+        }
+        enforced
+    }
+
+    /// Whether `value` is a valid json value of this type, null is always valid
+    pub fn accepts(&self, value: &str) -> bool {
+        if value.eq("null") {
+            return true;
+        }
+        match self {
+            Self::Integer | Self::Float => {
+                serde_json::from_str::<serde_json::Number>(value).is_ok()
+            }
+            Self::Bool => value.eq("true") || value.eq("false"),
+            _ => true,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Integer => "Integer",
+            Self::Float => "Float",
+            Self::String => "String",
+            Self::Bool => "Bool",
+            Self::Array => "Array",
+            Self::Object => "Object",
+            Self::Null => "Null",
+        }
+    }
+}
+
+/// Number of cells of each type observed in a column, missing cells are counted as null
+pub type ColumnTypes = BTreeMap<CellType, usize>;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct EditOptions {
+    // Reject edits not matching numeric or boolean type of their column, unless forced
+    pub enforce_types: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DisplayOptions {
+    // Longer cell values are truncated, full value is in cell tooltip
+    pub max_cell_chars: usize,
+    // Only changes how numbers are displayed, saved and exported values are not reformatted
+    pub number_format: NumberFormat,
+    // Digits of displayed numbers are grouped by three with this separator
+    pub thousands_separator: Option<char>,
+    // Footer with sum, min, max and mean of the focused column, over filtered rows
+    pub show_aggregates: bool,
+    // Columns without any value other than null in filtered rows are not displayed
+    pub hide_empty_columns: bool,
+    // Object tables show keys indented by depth instead of full pointers
+    pub indent_object_keys: bool,
+    // Null values are shown as a greyed null, cells of missing keys stay blank
+    pub show_null: bool,
+    // Object tables have a Type column with the json type of each value
+    pub show_value_types: bool,
+}
+
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            max_cell_chars: 100,
+            number_format: NumberFormat::default(),
+            thousands_separator: None,
+            show_aggregates: false,
+            hide_empty_columns: false,
+            indent_object_keys: false,
+            show_null: false,
+            show_value_types: false,
+        }
+    }
+}
+
+impl DisplayOptions {
+    /// Number `value` as displayed in cells, formatted with `number_format` then grouped with
+    /// `thousands_separator`
+    pub fn display_number<'a>(&self, value: &'a str) -> Cow<'a, str> {
+        let formatted = format_number(value, self.number_format);
+        match self.thousands_separator {
+            Some(separator) => match group_thousands(&formatted, separator) {
+                Cow::Owned(grouped) => Cow::Owned(grouped),
+                Cow::Borrowed(_) => formatted,
+            },
+            None => formatted,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum NumberFormat {
+    #[default]
+    AsParsed,
+    Scientific,
+    Plain,
+}
+
+impl NumberFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AsParsed => "As parsed",
+            Self::Scientific => "Scientific",
+            Self::Plain => "Plain",
+        }
+    }
+}
+
+/// Base integers of a column are displayed in, values are still edited and saved in decimal
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum NumberBase {
+    #[default]
+    Decimal,
+    Hexadecimal,
+    Binary,
+}
+
+impl NumberBase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Decimal => "Decimal",
+            Self::Hexadecimal => "Hexadecimal",
+            Self::Binary => "Binary",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+/// Rows written by exports
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ExportScope {
+    #[default]
+    All,
+    // Rows kept by filters, in displayed order
+    Filtered,
+    // Rows covered by the selection
+    Selected,
+}
+
 pub struct ArrayTable<'array> {
     table_id: Id,
     all_columns: Vec<Column<'array>>,
     column_selected: Vec<Column<'array>>,
     column_pinned: Vec<Column<'array>>,
+    // Names of unpinned columns in the order chosen by user, empty until a column is moved
+    column_order: Vec<String>,
+    // Names of columns not rendered, their values are still saved and exported
+    hidden_columns: BTreeSet<String>,
+    // Names of columns whose cells can't be edited, they can still be copied
+    locked_columns: BTreeSet<String>,
+    // Base integers of columns are displayed in, decimal when absent
+    column_bases: BTreeMap<String, NumberBase>,
+    // Columns null in every filtered row, only computed when they are hidden
+    empty_columns: BTreeSet<String>,
     pub max_depth: u8,
     last_parsed_max_depth: u8,
     parse_result: Option<ParseResult<String>>,
     pub nodes: Vec<JsonArrayEntries<String>>,
-    filtered_nodes: Vec<usize>,
+    pub(crate) filtered_nodes: Vec<usize>,
     scroll_y: f32,
     pub columns_filter: HashMap<String, Vec<String>>,
+    // Column name and order used to sort filtered_nodes
+    pub column_sort: Option<(String, SortOrder)>,
+    // Only keep rows having a cell containing this term
+    pub filter_rows: String,
+    // Rows are filtered again once typing in the filter field pauses
+    pub filter_rows_debounce: Debounce,
+    pub filter_rows_case_sensitive: bool,
+    // Filter rows term is a regex
+    pub filter_rows_regex: bool,
+    // Filter rows term is a query like `price > 100 AND status == "active"`
+    pub filter_rows_query: bool,
+    // Why filter rows term is not a valid regex or query
+    pub filter_rows_error: Option<String>,
     pub hovered_row_index: Option<usize>,
     columns_offset: Vec<f32>,
     windows: Vec<SubTable<'array>>,
     // Indicate if this array table is a subtable
     pub(crate) is_sub_table: bool,
+    // Rows are parsed with nested arrays elements, displayed as indexed columns like object keys
+    flatten_arrays: bool,
+    // Scalars are typed as strings, see `set_all_as_strings`
+    all_as_strings: bool,
     // For subtable we need to get parent_pointer info
     pub parent_pointer: PointerKey,
     cache: RefCell<crate::components::cache::CacheStorage>,
+    // Fingerprint of all columns names, to remember widths of columns with the same schema
+    schema_fingerprint: u64,
+    // Widths of pinned and unpinned columns at last frame
+    column_widths: [Vec<f32>; 2],
+    // Initial widths of columns never resized, measured from their name and first values
+    auto_column_widths: HashMap<String, f32>,
     seed1: usize, // seed for Id
     seed2: usize, // seed for Id
     pub matching_rows: Vec<usize>,
@@ -138,12 +378,16 @@ pub struct ArrayTable<'array> {
     pub scroll_to_column_number: usize,
     pub scroll_to_row_mode: ScrollToRowMode,
     pub focused_cell: Option<CellLocation>,
+    // Other corner of the selection, focused cell being the moving one
+    pub selection_anchor: Option<CellLocation>,
 
     // Visibility information
     pub first_visible_index: usize,
     pub last_visible_index: usize,
     pub first_visible_offset: f32,
     pub last_visible_offset: f32,
+    // Number of rows fitting in table scroll area, used to move by page
+    pub visible_rows_count: usize,
 
     // Handle interaction
     pub next_frame_reset_scroll: bool,
@@ -161,16 +405,37 @@ pub struct ArrayTable<'array> {
 
     pub editing_index: RefCell<Option<(usize, usize, bool)>>,
     pub editing_value: RefCell<String>,
+    pub edit_history: EditHistory,
+    pub edit_options: EditOptions,
+    pub display_options: DisplayOptions,
+    // Editing index of a rejected edit, and why it was rejected
+    rejected_edit: Option<((usize, usize, bool), String)>,
+    // Rows waiting for the user to confirm their deletion
+    pending_rows_deletion: Option<Vec<usize>>,
+    // Outcome of the last column conversion, until dismissed
+    conversion_summary: Option<String>,
+    // Message shown at the bottom of the table, and time until which it is shown
+    transient_message: Option<(String, f64)>,
+    // Value too large to be edited in a cell, viewed read only
+    large_value_viewer: Option<LargeValueViewer>,
+    // Merge or split of a column, waiting for its separator
+    pending_column_reshape: Option<ColumnReshape>,
 
     opened_windows: BTreeSet<String>,
     search_replace_panel: SearchReplacePanel<'array>,
+    raw_json_panel: RawJsonPanel,
 }
 
 impl super::View<ArrayResponse> for ArrayTable<'_> {
     fn ui(&mut self, ui: &mut egui::Ui) -> ArrayResponse {
         let mut array_response = ArrayResponse::default();
         self.windows(ui.ctx(), &mut array_response);
-        let parent_height_available = ui.available_rect_before_wrap().height();
+        let mut parent_height_available = ui.available_rect_before_wrap().height();
+        if self.display_options.show_aggregates {
+            // Room for aggregates footer
+            parent_height_available -=
+                Self::row_height(ui.style(), ui.spacing()) + ui.spacing().item_spacing.y;
+        }
         let parent_width_available = ui.available_rect_before_wrap().width();
         ui.interact(
             ui.available_rect_before_wrap(),
@@ -247,6 +512,9 @@ impl super::View<ArrayResponse> for ArrayTable<'_> {
                 });
             });
         });
+        if self.display_options.show_aggregates {
+            ui.horizontal(|ui| self.aggregates_footer(ui));
+        }
 
         if self.focused_cell.is_some() && self.editing_index.borrow().is_none() {
             ui.ctx().memory_mut(|m| {
@@ -275,6 +543,18 @@ impl super::View<ArrayResponse> for ArrayTable<'_> {
 #[derive(Default)]
 struct CacheFilterOptions {}
 
+// Drag and drop payload, index of the dragged header in unpinned columns
+struct DraggedColumn(usize);
+
+// Drag and drop payload, index in the array of the row dragged from its row number
+struct DraggedRow(usize);
+
+#[derive(Default)]
+struct CacheColumnTypes {}
+
+#[derive(Default)]
+struct CacheAggregates {}
+
 #[derive(Default)]
 struct CacheGetPointer {}
 
@@ -328,7 +608,7 @@ impl<'array>
 
                 // Compare parsed numbers; handle parse errors by pushing them to the end
                 match (num_a, num_b) {
-                    (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal),
+                    (Ok(_), Ok(_)) => compare_numbers(a, b).unwrap_or(std::cmp::Ordering::Equal),
                     (Ok(_), Err(_)) => std::cmp::Ordering::Less, // Numbers are less than errors
                     (Err(_), Ok(_)) => std::cmp::Ordering::Greater, // Errors are greater than numbers
                     (Err(_), Err(_)) => std::cmp::Ordering::Equal,  // Treat errors equally
@@ -341,6 +621,49 @@ impl<'array>
     }
 }
 
+impl<'array>
+    crate::components::cache::ComputerMut<
+        (&Column<'array>, &String),
+        &Vec<JsonArrayEntries<String>>,
+        ColumnTypes,
+    > for CacheColumnTypes
+{
+    fn compute(
+        &mut self,
+        (column, parent_pointer): (&Column<'array>, &String),
+        nodes: &Vec<JsonArrayEntries<String>>,
+    ) -> ColumnTypes {
+        let mut column_types = ColumnTypes::new();
+        for row in nodes.iter() {
+            let cell_type = ArrayTable::get_pointer_for_column(
+                parent_pointer,
+                &&row.entries,
+                row.index,
+                column,
+            )
+            .map_or(CellType::Null, CellType::of);
+            *column_types.entry(cell_type).or_insert(0) += 1;
+        }
+        column_types
+    }
+}
+
+impl<'array>
+    crate::components::cache::ComputerMut<
+        (&Column<'array>, &String),
+        (&Vec<JsonArrayEntries<String>>, &Vec<usize>),
+        Option<Aggregates>,
+    > for CacheAggregates
+{
+    fn compute(
+        &mut self,
+        (column, parent_pointer): (&Column<'array>, &String),
+        (nodes, filtered_nodes): (&Vec<JsonArrayEntries<String>>, &Vec<usize>),
+    ) -> Option<Aggregates> {
+        column_aggregates(nodes, filtered_nodes, parent_pointer, &column.name)
+    }
+}
+
 impl<'array>
     crate::components::cache::ComputerMut<CachePointerKey, &ArrayTable<'array>, Option<usize>>
     for CacheGetPointer
@@ -367,6 +690,173 @@ impl<'array>
 
 pub const NON_NULL_FILTER_VALUE: &str = "__non_null";
 
+// Merge of a column with another one, or split of a column, as typed in the reshape window
+#[derive(Default)]
+struct ColumnReshape {
+    column: String,
+    is_split: bool,
+    // Merged column, or key of the column created by the split
+    other_column: String,
+    separator: String,
+    error: Option<String>,
+}
+// Deleting more rows at once asks for confirmation
+pub const ROWS_DELETION_CONFIRMATION_THRESHOLD: usize = 100;
+
+// Id of ColumnWidths in egui memory, shared by tables of the session
+const COLUMN_WIDTHS_ID: &str = "column_widths";
+
+/// Widths of columns by column name, for each set of columns (schema) displayed during the session,
+/// so a table having the same columns is displayed with the same widths when opened again
+#[derive(Clone, Default, Debug)]
+pub struct ColumnWidths {
+    // Schema fingerprint and widths by column name, most recently resized schema last
+    schemas: Vec<(u64, HashMap<String, f32>)>,
+}
+
+impl ColumnWidths {
+    /// Fingerprint of a set of column names, in any order
+    pub fn fingerprint<'a>(names: impl Iterator<Item = &'a str>) -> u64 {
+        let mut names = names.collect::<Vec<&str>>();
+        names.sort_unstable();
+        let mut hasher = DefaultHasher::new();
+        names.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn remember(&mut self, fingerprint: u64, name: &str, width: f32) {
+        let index = match self.schemas.iter().position(|(f, _)| *f == fingerprint) {
+            Some(index) => index,
+            None => {
+                self.schemas.push((fingerprint, HashMap::new()));
+                self.schemas.len() - 1
+            }
+        };
+        if index + 1 < self.schemas.len() {
+            let schema = self.schemas.remove(index);
+            self.schemas.push(schema);
+        }
+        self.schemas
+            .last_mut()
+            .unwrap()
+            .1
+            .insert(name.to_string(), width);
+    }
+
+    /// Width of the column in its schema, or when schema has changed, width of a column with the
+    /// same name in the most recently resized schema
+    pub fn width(&self, fingerprint: u64, name: &str) -> Option<f32> {
+        self.schemas
+            .iter()
+            .find(|(f, _)| *f == fingerprint)
+            .and_then(|(_, widths)| widths.get(name))
+            .or_else(|| {
+                self.schemas
+                    .iter()
+                    .rev()
+                    .find_map(|(_, widths)| widths.get(name))
+            })
+            .copied()
+    }
+}
+
+/// Row to focus after pressing Home, End, PageUp or PageDown, a page being `page_rows` rows
+pub(crate) fn row_index_after_key(
+    key: Key,
+    row_index: usize,
+    rows_count: usize,
+    page_rows: usize,
+) -> usize {
+    let last_row_index = rows_count.saturating_sub(1);
+    match key {
+        Key::Home => 0,
+        Key::End => last_row_index,
+        Key::PageUp => row_index.saturating_sub(page_rows),
+        Key::PageDown => (row_index + page_rows).min(last_row_index),
+        _ => row_index,
+    }
+}
+
+/// Cell to focus after pressing ArrowLeft or ArrowRight, moving between pinned and unpinned tables at
+/// their boundary. First pinned column is the row number, it can't be focused.
+/// Hidden columns are not part of selected columns, so they are skipped.
+pub(crate) fn cell_after_horizontal_key(
+    key: Key,
+    mut cell: CellLocation,
+    pinned_columns_count: usize,
+    selected_columns_count: usize,
+) -> CellLocation {
+    match (key, cell.is_pinned_column_table) {
+        (Key::ArrowLeft, false) if cell.column_index > 0 => cell.column_index -= 1,
+        (Key::ArrowLeft, false) if pinned_columns_count > 1 => {
+            cell.is_pinned_column_table = true;
+            cell.column_index = pinned_columns_count - 1;
+        }
+        (Key::ArrowLeft, true) if cell.column_index > 1 => cell.column_index -= 1,
+        (Key::ArrowRight, true) if cell.column_index + 1 < pinned_columns_count => {
+            cell.column_index += 1
+        }
+        (Key::ArrowRight, true) if selected_columns_count > 0 => {
+            cell.is_pinned_column_table = false;
+            cell.column_index = 0;
+        }
+        (Key::ArrowRight, false) if cell.column_index + 1 < selected_columns_count => {
+            cell.column_index += 1
+        }
+        _ => {}
+    }
+    cell
+}
+
+/// Cell to focus after pressing Tab, or Shift+Tab when `backward`: like ArrowRight and ArrowLeft,
+/// but wrapping to the first cell of next row, or last cell of previous row, at row boundaries.
+pub(crate) fn cell_after_tab(
+    backward: bool,
+    cell: CellLocation,
+    pinned_columns_count: usize,
+    selected_columns_count: usize,
+    rows_count: usize,
+) -> CellLocation {
+    let key = if backward {
+        Key::ArrowLeft
+    } else {
+        Key::ArrowRight
+    };
+    let next_cell =
+        cell_after_horizontal_key(key, cell, pinned_columns_count, selected_columns_count);
+    if next_cell != cell {
+        return next_cell;
+    }
+    let (row_index, is_pinned_column_table, column_index) = if backward {
+        if cell.row_index == 0 {
+            return cell;
+        }
+        if selected_columns_count > 0 {
+            (cell.row_index - 1, false, selected_columns_count - 1)
+        } else {
+            (
+                cell.row_index - 1,
+                true,
+                pinned_columns_count.saturating_sub(1),
+            )
+        }
+    } else {
+        if cell.row_index + 1 >= rows_count {
+            return cell;
+        }
+        if pinned_columns_count > 1 {
+            (cell.row_index + 1, true, 1)
+        } else {
+            (cell.row_index + 1, false, 0)
+        }
+    };
+    CellLocation {
+        row_index,
+        column_index,
+        is_pinned_column_table,
+    }
+}
+
 impl<'array> ArrayTable<'array> {
     pub fn new(
         parse_result: Option<ParseResult<String>>,
@@ -378,7 +868,10 @@ impl<'array> ArrayTable<'array> {
         let last_parsed_max_depth = parse_result.as_ref().map_or(depth, |p| p.parsing_max_depth);
         Self {
             table_id: Id::new(format!("table-container-{}", parent_pointer.pointer)),
-            column_selected: Self::selected_columns(&all_columns, depth),
+            column_selected: Self::selected_columns(&all_columns, depth, false),
+            schema_fingerprint: ColumnWidths::fingerprint(
+                all_columns.iter().map(|c| c.name.as_ref()),
+            ),
             all_columns,
             max_depth: depth,
             filtered_nodes: (0..nodes.len()).collect::<Vec<usize>>(),
@@ -386,10 +879,20 @@ impl<'array> ArrayTable<'array> {
             parse_result,
             // states
             next_frame_reset_scroll: false,
-            column_pinned: vec![Column::new("/#".to_string(), ValueType::Number)],
+            column_pinned: vec![Column::new(
+                ROW_NUMBER_COLUMN.to_string(),
+                ValueType::Number,
+            )],
+            column_order: vec![],
+            hidden_columns: BTreeSet::new(),
+            locked_columns: BTreeSet::new(),
+            column_bases: BTreeMap::new(),
+            empty_columns: BTreeSet::new(),
             scroll_y: 0.0,
             hovered_row_index: None,
             columns_offset: vec![],
+            column_widths: [vec![], vec![]],
+            auto_column_widths: HashMap::new(),
             seed1: Id::new(&parent_pointer.pointer).value() as usize,
             seed2: Id::new(format!("{}pinned", &parent_pointer.pointer)).value() as usize,
             parent_pointer,
@@ -402,6 +905,23 @@ impl<'array> ArrayTable<'array> {
             changed_scroll_to_column_value: false,
             last_parsed_max_depth,
             columns_filter: HashMap::new(),
+            column_sort: None,
+            filter_rows: "".to_string(),
+            filter_rows_debounce: Debounce::default(),
+            filter_rows_case_sensitive: false,
+            filter_rows_regex: false,
+            filter_rows_query: false,
+            filter_rows_error: None,
+            edit_history: EditHistory::default(),
+            edit_options: EditOptions::default(),
+            display_options: DisplayOptions::default(),
+            rejected_edit: None,
+            pending_rows_deletion: None,
+            conversion_summary: None,
+            transient_message: None,
+            large_value_viewer: None,
+            pending_column_reshape: None,
+            selection_anchor: None,
             scroll_to_row_mode: ScrollToRowMode::RowNumber,
             scroll_to_row: "".to_string(),
             scroll_to_row_number: 0,
@@ -414,39 +934,101 @@ impl<'array> ArrayTable<'array> {
             editing_index: RefCell::new(None),
             editing_value: RefCell::new(String::new()),
             is_sub_table: false,
+            flatten_arrays: false,
+            all_as_strings: false,
             focused_cell: None,
             first_visible_index: 0,
             last_visible_index: 0,
             first_visible_offset: 0.0,
             last_visible_offset: 0.0,
+            visible_rows_count: 1,
             cache: Default::default(),
             opened_windows: Default::default(),
             search_replace_panel: Default::default(),
+            raw_json_panel: Default::default(),
             was_editing: false,
         }
     }
     pub fn windows(&mut self, ctx: &Context, array_response: &mut ArrayResponse) {
+        if let Some(rows) = self.pending_rows_deletion.take() {
+            let mut confirmed = false;
+            let mut cancelled = false;
+            egui::Window::new("Delete rows")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!("Delete {} selected rows?", rows.len()));
+                    ui.horizontal(|ui| {
+                        confirmed = ui.button("Delete").clicked();
+                        cancelled = ui.button("Cancel").clicked();
+                    });
+                });
+            if confirmed {
+                self.delete_rows(array_response, &rows);
+            } else if !cancelled {
+                self.pending_rows_deletion = Some(rows);
+            }
+        }
+        if let Some(summary) = self.conversion_summary.take() {
+            let mut closed = false;
+            egui::Window::new("Convert column")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(&summary);
+                    closed = ui.button("Ok").clicked();
+                });
+            if !closed {
+                self.conversion_summary = Some(summary);
+            }
+        }
+        if let Some((ref message, until)) = self.transient_message {
+            let time = ctx.input(|i| i.time);
+            if time < until {
+                egui::Area::new(self.table_id.with("transient-message"))
+                    .order(egui::Order::Foreground)
+                    .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -40.0])
+                    .interactable(false)
+                    .show(ctx, |ui| {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| ui.label(message));
+                    });
+                ctx.request_repaint_after(std::time::Duration::from_secs_f64(until - time));
+            } else {
+                self.transient_message = None;
+            }
+        }
+        if let Some(mut reshape) = self.pending_column_reshape.take() {
+            if !self.column_reshape_window(ctx, array_response, &mut reshape) {
+                self.pending_column_reshape = Some(reshape);
+            }
+        }
+        if let Some(ref viewer) = self.large_value_viewer {
+            let mut opened = true;
+            viewer.show(ctx, &mut opened);
+            if !opened {
+                self.large_value_viewer = None;
+            }
+        }
         let mut closed_windows = vec![];
-        let mut updated_values = vec![];
-        for window in self.windows.iter_mut() {
+        let mut edited_values = vec![];
+        for (window_index, window) in self.windows.iter_mut().enumerate() {
             let mut opened = true;
             let maybe_response = window.show(ctx, &mut opened);
-            if let Some(maybe_inner_response) = maybe_response {
-                if let Some(response) = maybe_inner_response {
-                    for entry in response.edited_value {
-                        updated_values.push((entry, window.id(), false));
-                    }
+            if let Some(Some(response)) = maybe_response {
+                if !response.edited_value.is_empty() {
+                    edited_values.push((window_index, response.edited_value));
                 }
             }
             if !opened {
                 closed_windows.push(window.name().clone());
             }
         }
-        for updated_value in updated_values {
-            if self.update_value(updated_value.0.clone(), updated_value.1, updated_value.2) {
-                array_response.edited_value.push(updated_value.0.clone())
-            }
+        for (window_index, edited_value) in edited_values {
+            self.apply_sub_table_edits(array_response, window_index, edited_value);
         }
+        // Edits are applied to the row as they are made, closing a sub table leaves nothing to save
         self.windows.retain(|w| !closed_windows.contains(w.name()));
 
         let mut is_open = self
@@ -459,42 +1041,236 @@ impl<'array> ArrayTable<'array> {
             is_open,
         );
         if let Some(search_replace_response) = response {
-            self.replace_columns(search_replace_response, array_response);
+            let replaced_count = self.replace_columns(search_replace_response, array_response);
+            self.search_replace_panel.set_replaced_count(replaced_count);
+        }
+
+        let mut is_open = self.opened_windows.contains(PANEL_RAW_JSON);
+        if is_open {
+            // Serialized every frame so table edits are reflected in the panel
+            let row_index = self
+                .focused_cell
+                .and_then(|cell| self.filtered_nodes.get(cell.row_index).copied());
+            self.raw_json_panel.sync(
+                row_index,
+                row_index.map(|row_index| self.row_to_json(row_index)),
+            );
+            if let Some((row_index, json)) = self.raw_json_panel.show(ctx, &mut is_open) {
+                let result = self.replace_row_json(array_response, row_index, &json);
+                self.raw_json_panel.set_error(result.err());
+            }
+            set_open(&mut self.opened_windows, PANEL_RAW_JSON, is_open);
+        }
+    }
+
+    /// Report edits made in sub table at `window_index` to the row it was opened from. A sub table
+    /// opened from a cell replaces the cell with its content serialized again.
+    pub(crate) fn apply_sub_table_edits(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        window_index: usize,
+        edited_value: Vec<FlatJsonValue<String>>,
+    ) {
+        let window = &self.windows[window_index];
+        let row_index = window.id();
+        let updated_values = if window.is_cell_value {
+            vec![FlatJsonValue {
+                pointer: window.pointer().clone(),
+                value: Some(window.to_json()),
+            }]
+        } else {
+            edited_value
+        };
+        for updated_value in updated_values {
+            if self.update_value(updated_value.clone(), row_index, false) {
+                array_response.edited_value.push(updated_value)
+            }
+        }
+    }
+
+    /// Open the array or object at `pointer` of row `row_index` in a sub table
+    pub fn open_cell_in_sub_table(
+        &mut self,
+        row_index: usize,
+        pointer: &str,
+    ) -> Result<(), String> {
+        let entry = self
+            .nodes
+            .get(row_index)
+            .and_then(|row| {
+                row.entries
+                    .iter()
+                    .find(|entry| entry.pointer.pointer.eq(pointer))
+            })
+            .ok_or_else(|| format!("No value found at {}", pointer))?;
+        let content = entry
+            .value
+            .clone()
+            .filter(|_| {
+                matches!(
+                    entry.pointer.value_type,
+                    ValueType::Array(_) | ValueType::Object(..)
+                )
+            })
+            .ok_or_else(|| format!("{} is not an array or an object", pointer))?;
+        let mut subtable = SubTable::new(
+            entry.pointer.clone(),
+            content,
+            entry.pointer.value_type,
+            row_index,
+            entry.pointer.depth,
+        );
+        subtable.is_cell_value = true;
+        subtable.set_edit_options(self.edit_options);
+        subtable.set_display_options(self.display_options);
+        subtable.lock_pointers(&self.locked_cell_pointers(row_index));
+        self.windows.push(subtable);
+        Ok(())
+    }
+
+    pub fn open_raw_json_panel(&mut self) {
+        set_open(&mut self.opened_windows, PANEL_RAW_JSON, true);
+    }
+
+    /// Update row cells from `json`, the edited json of the row: changed values are edited like
+    /// cells, keys missing from `json` are removed and new keys are added as columns.
+    /// Nothing is changed when `json` is not valid. Return count of updated cells.
+    pub fn replace_row_json(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        row_index: usize,
+        json: &str,
+    ) -> Result<usize, String> {
+        let root_node = self.nodes[row_index]
+            .entries
+            .last()
+            .unwrap()
+            .pointer
+            .clone();
+        let result = JSONParser::parse(
+            json,
+            ParseOptions::default()
+                .prefix(root_node.pointer.clone())
+                .start_depth(root_node.depth + 1)
+                .parse_array(self.flatten_arrays)
+                .max_depth(self.last_parsed_max_depth),
+        )?
+        .to_owned();
+        let prefix_len = root_node.pointer.len();
+        let is_cell = |entries: &[FlatJsonValue<String>], entry: &FlatJsonValue<String>| {
+            // Containers whose content is parsed are written from their descendants
+            entry.pointer.pointer.len() > prefix_len
+                && !(matches!(
+                    entry.pointer.value_type,
+                    ValueType::Array(_) | ValueType::Object(..)
+                ) && entries.iter().any(|other| {
+                    other.pointer.pointer.len() > entry.pointer.pointer.len()
+                        && is_pointer_prefix(&entry.pointer.pointer, &other.pointer.pointer)
+                }))
+        };
+        let row_entries = &self.nodes[row_index].entries;
+        let mut edits = vec![];
+        for entry in result.json.iter() {
+            if !is_cell(&result.json, entry) {
+                continue;
+            }
+            let old_entry = row_entries
+                .iter()
+                .find(|old_entry| old_entry.pointer.pointer.eq(&entry.pointer.pointer));
+            if old_entry.map_or(true, |old_entry| {
+                old_entry.value != entry.value
+                    || mem::discriminant(&old_entry.pointer.value_type)
+                        != mem::discriminant(&entry.pointer.value_type)
+            }) {
+                edits.push(entry.clone());
+            }
+        }
+        for old_entry in row_entries.iter() {
+            if old_entry.value.is_some()
+                && is_cell(row_entries, old_entry)
+                && !old_entry.pointer.pointer.ends_with(ROW_NUMBER_COLUMN)
+                && !result
+                    .json
+                    .iter()
+                    .any(|entry| entry.pointer.pointer.eq(&old_entry.pointer.pointer))
+            {
+                edits.push(FlatJsonValue {
+                    pointer: old_entry.pointer.clone(),
+                    value: None,
+                });
+            }
+        }
+        let mut has_new_columns = false;
+        let mut updated_count = 0;
+        for mut entry in edits {
+            let key = entry.pointer.pointer[prefix_len..].to_string();
+            if let Some(column) = self.all_columns.iter().find(|column| column.name.eq(&key)) {
+                entry.pointer.column_id = column.id;
+            } else {
+                let mut hasher = DefaultHasher::new();
+                hasher.write(key.as_bytes());
+                let column = Column {
+                    name: Cow::from(key),
+                    depth: entry.pointer.depth,
+                    value_type: entry.pointer.value_type,
+                    seen_count: 1,
+                    order: self.all_columns.len(),
+                    id: hasher.finish() as usize,
+                };
+                entry.pointer.column_id = column.id;
+                self.all_columns.push(column);
+                has_new_columns = true;
+            }
+            if self.edit_cell(array_response, entry, row_index) {
+                updated_count += 1;
+            }
         }
+        if has_new_columns {
+            self.update_selected_columns(self.max_depth);
+        }
+        Ok(updated_count)
     }
 
     pub fn update_selected_columns(&mut self, depth: u8) -> Option<usize> {
         self.cache.borrow_mut().update();
         if depth <= self.last_parsed_max_depth {
-            let mut column_selected = Self::selected_columns(&self.all_columns, depth);
-            column_selected.retain(|c| !self.column_pinned.contains(c));
-            self.column_selected = column_selected;
+            self.column_selected = self.unpinned_columns(depth);
+            self.column_selected.retain(|c| {
+                !self.hidden_columns.contains(c.name.as_ref())
+                    && !self.empty_columns.contains(c.name.as_ref())
+            });
             if self.column_selected.is_empty() {
-                self.column_selected.push(Column {
-                    name: Cow::from(""),
-                    depth,
-                    value_type: Default::default(),
-                    seen_count: 0,
-                    order: 0,
-                    id: 0,
-                })
+                self.column_selected.push(Self::empty_column(depth))
             }
             None
         } else {
             let previous_parse_result = self.parse_result.clone().unwrap();
+            // Rows are parsed once up to the depth of the document, so next depth increases, e.g
+            // while dragging depth slider, only filter columns instead of parsing rows again.
+            // Edited rows are parsed again up to this depth as well.
+            let parse_depth =
+                (previous_parse_result.max_json_depth.min(u8::MAX as usize) as u8).max(depth);
             let (new_json_array, new_columns, new_max_depth) = crate::parser::change_depth_array(
                 previous_parse_result,
                 mem::take(&mut self.nodes),
-                depth as usize,
+                parse_depth as usize,
+                self.flatten_arrays,
             )
             .unwrap();
             self.all_columns = new_columns;
-            let mut column_selected = Self::selected_columns(&self.all_columns, depth);
-            column_selected.retain(|c| !self.column_pinned.contains(c));
-            self.column_selected = column_selected;
+            self.schema_fingerprint =
+                ColumnWidths::fingerprint(self.all_columns.iter().map(|c| c.name.as_ref()));
+            self.column_selected = self.unpinned_columns(depth);
+            self.column_selected.retain(|c| {
+                !self.hidden_columns.contains(c.name.as_ref())
+                    && !self.empty_columns.contains(c.name.as_ref())
+            });
             self.nodes = new_json_array;
-            self.last_parsed_max_depth = depth;
-            self.parse_result.as_mut().unwrap().parsing_max_depth = depth;
+            if self.all_as_strings {
+                self.coerce_scalars_to_strings();
+            }
+            self.last_parsed_max_depth = parse_depth;
+            self.parse_result.as_mut().unwrap().parsing_max_depth = parse_depth;
             self.parse_result.as_mut().unwrap().max_json_depth = new_max_depth;
             if self.opened_windows.contains(PANEL_REPLACE) {
                 // Refresh list of columns
@@ -508,38 +1284,476 @@ impl<'array> ArrayTable<'array> {
         self.update_selected_columns(depth)
     }
 
-    fn selected_columns(all_columns: &Vec<Column<'array>>, depth: u8) -> Vec<Column<'array>> {
-        let mut column_selected: Vec<Column<'array>> = vec![];
-        for col in Self::visible_columns(all_columns, depth) {
-            column_selected.push(col.clone())
-        }
-        column_selected
+    /// To be set when rows have been parsed with `ParseOptions::parse_array`: elements of nested
+    /// arrays are shown as `field/0`, `field/1`... columns at next depth, instead of the array
+    /// serialized in a single cell. Rows parsed again when depth increases keep nested arrays elements.
+    pub fn set_flatten_arrays(&mut self, flatten_arrays: bool) {
+        self.flatten_arrays = flatten_arrays;
+        self.update_selected_columns(self.max_depth);
     }
 
-    pub fn all_columns(&self) -> &Vec<Column<'array>> {
-        &self.all_columns
+    /// Type every number, boolean and null cell as a string, to view and edit everything as text
+    /// without type semantics. Edited rows are still saved with original types of their values,
+    /// unless a value is edited to something which is not a literal of its type.
+    pub fn set_all_as_strings(&mut self, all_as_strings: bool) {
+        self.all_as_strings = all_as_strings;
+        if all_as_strings {
+            self.coerce_scalars_to_strings();
+        }
     }
 
-    pub fn visible_columns<'a>(
-        all_columns: &'a Vec<Column<'array>>,
-        depth: u8,
-    ) -> impl Iterator<Item = &'a Column<'array>> {
-        all_columns.iter().filter(move |column: &&Column<'array>| {
-            column.depth == depth
-                || (column.depth < depth && !matches!(column.value_type, ValueType::Object(_, _)))
-        })
+    fn coerce_scalars_to_strings(&mut self) {
+        for row in self.nodes.iter_mut() {
+            coerce_scalars_to_strings(&mut row.entries);
+        }
+        for column in self
+            .all_columns
+            .iter_mut()
+            .chain(self.column_selected.iter_mut())
+            .chain(self.column_pinned.iter_mut())
+        {
+            if matches!(
+                column.value_type,
+                ValueType::Number | ValueType::Bool | ValueType::Null
+            ) && column.name != ROW_NUMBER_COLUMN
+            {
+                column.value_type = ValueType::String;
+            }
+        }
+        self.cache.borrow_mut().evict();
     }
 
-    fn table_ui(&mut self, ui: &mut egui::Ui, pinned: bool) -> ArrayResponse {
-        let text_height = Self::row_height(ui.style(), ui.spacing());
-
-        self.draw_table(ui, text_height, 7.0, pinned)
+    // Displayed in unpinned table when it has no column
+    fn empty_column(depth: u8) -> Column<'array> {
+        Column {
+            name: Cow::from(""),
+            depth,
+            value_type: Default::default(),
+            seen_count: 0,
+            order: 0,
+            id: 0,
+        }
     }
 
-    pub fn row_height(style: &Arc<Style>, spacing: &Spacing) -> f32 {
-        egui::TextStyle::Body
-            .resolve(style)
-            .size
+    /// Move column displayed at `index` of pinned or unpinned table to the other one. Row number
+    /// column stays pinned, when all other columns are pinned unpinned table shows an empty column.
+    pub fn toggle_column_pin(&mut self, pinned_column_table: bool, index: usize) {
+        if pinned_column_table {
+            if index == 0 || index >= self.column_pinned.len() {
+                return;
+            }
+            let column = self.column_pinned.remove(index);
+            self.column_selected.retain(|c| !c.name.is_empty());
+            self.column_selected.push(column);
+            self.column_selected.sort();
+            self.apply_column_order();
+        } else {
+            if !self
+                .column_selected
+                .get(index)
+                .is_some_and(|c| !c.name.is_empty())
+            {
+                return;
+            }
+            let column = self.column_selected.remove(index);
+            self.column_pinned.push(column);
+            if self.column_selected.is_empty() {
+                self.column_selected
+                    .push(Self::empty_column(self.max_depth));
+            }
+        }
+        // Column indexes of both tables changed
+        self.focused_cell = None;
+        self.selection_anchor = None;
+        self.cache.borrow_mut().evict();
+    }
+
+    /// Move unpinned column displayed at `from` to `to`, the order is kept when depth changes
+    pub fn move_column(&mut self, from: usize, to: usize) {
+        if from == to || from >= self.column_selected.len() || to >= self.column_selected.len() {
+            return;
+        }
+        // Hidden columns keep their relative order, after displayed ones
+        let hidden_columns = self
+            .unpinned_columns(self.max_depth)
+            .into_iter()
+            .filter(|column| self.is_column_hidden(column.name.as_ref()))
+            .map(|column| column.name.to_string())
+            .collect::<Vec<String>>();
+        let column = self.column_selected.remove(from);
+        self.column_selected.insert(to, column);
+        self.column_order = self
+            .column_selected
+            .iter()
+            .map(|column| column.name.to_string())
+            .chain(hidden_columns)
+            .collect();
+        self.cache.borrow_mut().evict();
+    }
+
+    // Columns which were not displayed when user moved a column are displayed last
+    fn apply_column_order(&mut self) {
+        Self::sort_by_column_order(&self.column_order, &mut self.column_selected);
+    }
+
+    fn sort_by_column_order(column_order: &[String], columns: &mut [Column<'array>]) {
+        if column_order.is_empty() {
+            return;
+        }
+        columns.sort_by_key(|column| {
+            column_order
+                .iter()
+                .position(|name| name.eq(column.name.as_ref()))
+                .unwrap_or(usize::MAX)
+        });
+    }
+
+    /// Unpinned columns at `depth` in display order, including hidden columns
+    fn unpinned_columns(&self, depth: u8) -> Vec<Column<'array>> {
+        let mut columns = Self::selected_columns(&self.all_columns, depth, self.flatten_arrays);
+        columns.retain(|c| !self.column_pinned.contains(c));
+        Self::sort_by_column_order(&self.column_order, &mut columns);
+        columns
+    }
+
+    pub fn is_column_hidden(&self, name: &str) -> bool {
+        self.hidden_columns.contains(name)
+    }
+
+    pub fn set_column_hidden(&mut self, name: &str, hidden: bool) {
+        if hidden {
+            self.hidden_columns.insert(name.to_string());
+        } else {
+            self.hidden_columns.remove(name);
+        }
+        self.focused_cell = None;
+        self.selection_anchor = None;
+        self.update_selected_columns(self.max_depth);
+        self.cache.borrow_mut().evict();
+    }
+
+    pub fn is_column_locked(&self, name: &str) -> bool {
+        self.locked_columns.contains(name)
+    }
+
+    /// Locked columns can't be edited, pasted into or cleared from the table
+    pub fn set_column_locked(&mut self, name: &str, locked: bool) {
+        if locked {
+            self.locked_columns.insert(name.to_string());
+        } else {
+            self.locked_columns.remove(name);
+        }
+    }
+
+    pub fn column_base(&self, name: &str) -> NumberBase {
+        self.column_bases.get(name).copied().unwrap_or_default()
+    }
+
+    /// Display integers of column `name` in `base`, other numbers stay in decimal
+    pub fn set_column_base(&mut self, name: &str, base: NumberBase) {
+        if base == NumberBase::Decimal {
+            self.column_bases.remove(name);
+        } else {
+            self.column_bases.insert(name.to_string(), base);
+        }
+    }
+
+    fn is_cell_locked(&self, cell: &CellLocation) -> bool {
+        self.columns(cell.is_pinned_column_table)
+            .get(cell.column_index)
+            .is_some_and(|column| self.is_column_locked(&column.name))
+    }
+
+    fn is_entry_locked(&self, entry: &FlatJsonValue<String>) -> bool {
+        self.all_columns
+            .iter()
+            .find(|column| column.id == entry.pointer.column_id)
+            .is_some_and(|column| self.is_column_locked(&column.name))
+    }
+
+    fn check_column_unlocked(&self, column: &Column) -> Result<(), String> {
+        if self.is_column_locked(&column.name) {
+            return Err(format!("Column {} is locked", column.name));
+        }
+        Ok(())
+    }
+
+    /// Lock or unlock every column, e.g. of a sub table opened from a cell of a locked column
+    pub fn set_all_columns_locked(&mut self, locked: bool) {
+        let names = self
+            .all_columns
+            .iter()
+            .map(|column| column.name.to_string())
+            .collect::<Vec<String>>();
+        for name in names {
+            self.set_column_locked(&name, locked);
+        }
+    }
+
+    /// Pointers of cells of `row_index` which columns are locked, they stay locked in sub tables
+    fn locked_cell_pointers(&self, row_index: usize) -> Vec<String> {
+        self.locked_columns
+            .iter()
+            .map(|name| self.cell_pointer(self.nodes[row_index].index, name))
+            .collect()
+    }
+
+    /// Popup with a checkbox per unpinned column to hide or show it
+    pub fn column_picker(&mut self, ui: &mut Ui) {
+        let mut toggled_column: Option<(String, bool)> = None;
+        PopupMenu::new(self.table_id.with("column-picker")).show_ui(
+            ui,
+            |ui| {
+                icon::button(
+                    ui,
+                    COLUMNS,
+                    Some("Show or hide columns"),
+                    if self.hidden_columns.is_empty() {
+                        None
+                    } else {
+                        Some(ACTIVE_COLOR)
+                    },
+                )
+            },
+            |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(400.0)
+                    .show(ui, |ui| {
+                        for column in self.unpinned_columns(self.max_depth) {
+                            if column.name.is_empty() {
+                                continue;
+                            }
+                            let mut visible = !self.is_column_hidden(column.name.as_ref());
+                            if ui.checkbox(&mut visible, column.name.as_ref()).clicked() {
+                                toggled_column = Some((column.name.to_string(), !visible));
+                            }
+                        }
+                    });
+            },
+        );
+        if let Some((name, hidden)) = toggled_column {
+            self.set_column_hidden(&name, hidden);
+        }
+    }
+
+    fn selected_columns(
+        all_columns: &Vec<Column<'array>>,
+        depth: u8,
+        flatten_arrays: bool,
+    ) -> Vec<Column<'array>> {
+        let mut column_selected: Vec<Column<'array>> = vec![];
+        for col in Self::visible_columns(all_columns, depth, flatten_arrays) {
+            column_selected.push(col.clone())
+        }
+        column_selected
+    }
+
+    pub fn all_columns(&self) -> &Vec<Column<'array>> {
+        &self.all_columns
+    }
+
+    /// Selection between anchor and focused cell, column indexes are counted across pinned then unpinned columns
+    pub fn selected_range(&self) -> Option<CellRange> {
+        let anchor = self.selection_anchor?;
+        let focused_cell = self.focused_cell?;
+        let anchor_column = self.grid_column_index(&anchor);
+        let focused_column = self.grid_column_index(&focused_cell);
+        Some(CellRange {
+            first_row: anchor.row_index.min(focused_cell.row_index),
+            last_row: anchor.row_index.max(focused_cell.row_index),
+            first_column: anchor_column.min(focused_column),
+            last_column: anchor_column.max(focused_column),
+        })
+    }
+
+    #[inline]
+    fn grid_column_index(&self, cell: &CellLocation) -> usize {
+        if cell.is_pinned_column_table {
+            cell.column_index
+        } else {
+            self.column_pinned.len() + cell.column_index
+        }
+    }
+
+    fn local_selected_range(&self, pinned_column_table: bool) -> Option<CellRange> {
+        let mut range = self.selected_range()?;
+        let pinned_count = self.column_pinned.len();
+        if pinned_column_table {
+            if range.first_column >= pinned_count {
+                return None;
+            }
+            range.last_column = range.last_column.min(pinned_count - 1);
+        } else {
+            if range.last_column < pinned_count {
+                return None;
+            }
+            range.first_column = range.first_column.max(pinned_count) - pinned_count;
+            range.last_column -= pinned_count;
+        }
+        Some(range)
+    }
+
+    /// Selected cells as tab separated values, to be pasted in spreadsheets
+    pub fn selection_to_tsv(&self, range: CellRange) -> String {
+        let columns = self
+            .column_pinned
+            .iter()
+            .chain(self.column_selected.iter())
+            .skip(range.first_column)
+            .take(range.last_column - range.first_column + 1)
+            .cloned()
+            .collect::<Vec<Column>>();
+        let rows = self
+            .filtered_nodes
+            .get(range.first_row..=range.last_row)
+            .unwrap_or(&[]);
+        to_tsv(&self.nodes, rows, &self.parent_pointer.pointer, &columns)
+    }
+
+    /// Values of the column in displayed rows, one per line
+    pub fn copy_column(&self, column: &Column) -> String {
+        crate::parser::column_to_lines(
+            &self.nodes,
+            &self.filtered_nodes,
+            &self.parent_pointer.pointer,
+            column,
+        )
+    }
+
+    /// Indexes in the array of rows exported with `scope`
+    pub fn export_rows(&self, scope: ExportScope) -> Vec<usize> {
+        match scope {
+            ExportScope::All => (0..self.nodes.len()).collect::<Vec<usize>>(),
+            ExportScope::Filtered => self.filtered_nodes.clone(),
+            ExportScope::Selected => self.selected_row_indexes(),
+        }
+    }
+
+    /// Export rows of `scope` as CSV, hidden columns included. `#` column, the index of each
+    /// element, is only written `with_index`.
+    pub fn export_csv(&self, scope: ExportScope, with_index: bool) -> String {
+        let columns = self
+            .column_pinned
+            .iter()
+            .cloned()
+            .chain(self.unpinned_columns(self.max_depth))
+            .filter(|c| !c.name.is_empty() && (with_index || c.name != ROW_NUMBER_COLUMN))
+            .collect::<Vec<Column<'array>>>();
+        crate::parser::to_csv(
+            &self.nodes,
+            &self.export_rows(scope),
+            &self.parent_pointer.pointer,
+            &columns,
+        )
+    }
+
+    /// Export rows of `scope` as json, nested in the parent pointer like when saving. Exported
+    /// rows are the elements of a standalone array.
+    pub fn export_json(
+        &self,
+        scope: ExportScope,
+        options: &SerializeOptions,
+    ) -> Result<String, String> {
+        if scope == ExportScope::All {
+            return serialize_to_json_pretty(&self.parent_pointer.pointer, &self.nodes, options);
+        }
+        let mut buffer: Vec<u8> = vec![];
+        save_rows_to_buffer(
+            &self.parent_pointer.pointer,
+            &self.nodes,
+            &self.export_rows(scope),
+            &mut buffer,
+        )
+        .map_err(|e| e.to_string())?;
+        format_json(&String::from_utf8_lossy(&buffer), options)
+    }
+
+    /// Whether rows changed since the table was opened or last saved. Undoing edits back to that
+    /// state makes the table clean again.
+    pub fn is_dirty(&self) -> bool {
+        !self.edit_history.is_saved()
+    }
+
+    /// To be called once rows are saved
+    pub fn mark_saved(&mut self) {
+        self.edit_history.mark_saved();
+    }
+
+    /// Edits of the session as a JSON Patch (RFC 6902). Patch is built from the edit history, it
+    /// can't be exported once history misses changes, when more edits than its depth were made.
+    /// Return None then.
+    pub fn export_json_patch(&self) -> Option<String> {
+        self.edit_history
+            .is_complete()
+            .then(|| crate::parser::json_patch(self.edit_history.edits()))
+    }
+
+    /// Apply a JSON Patch (RFC 6902) to rows of the table, like edits made by the user and undone
+    /// at once. Nothing is applied when one of the operations does not target a cell of the table.
+    /// Return count of updated cells.
+    pub fn apply_json_patch(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        patch: &str,
+    ) -> Result<usize, String> {
+        let rows = (0..self.nodes.len()).collect::<Vec<usize>>();
+        let mut edits = vec![];
+        for mut entry in crate::parser::parse_json_patch(patch)? {
+            let (row_index, key) = find_row_by_pointer(
+                &self.nodes,
+                &rows,
+                &self.parent_pointer.pointer,
+                &entry.pointer.pointer,
+            )
+            .ok_or_else(|| format!("Pointer {} is not in the table", entry.pointer.pointer))?;
+            if key.is_empty() || key.ends_with("/-") {
+                return Err(format!(
+                    "Pointer {} does not target a cell",
+                    entry.pointer.pointer
+                ));
+            }
+            if let Some(column) = self.all_columns.iter().find(|column| column.name.eq(key)) {
+                entry.pointer.depth = column.depth;
+                entry.pointer.column_id = column.id;
+            }
+            edits.push((entry, row_index));
+        }
+        let mut updated_count = 0;
+        let edit_history = self.start_edit_group();
+        for (entry, row_index) in edits {
+            if self.edit_cell(array_response, entry, row_index) {
+                updated_count += 1;
+            }
+        }
+        self.push_edit_group(edit_history);
+        Ok(updated_count)
+    }
+
+    /// Columns at `depth` and scalar columns of lower depths. Once flattened, nested arrays are
+    /// replaced by their elements columns at next depth, like objects by their keys.
+    pub fn visible_columns<'a>(
+        all_columns: &'a Vec<Column<'array>>,
+        depth: u8,
+        flatten_arrays: bool,
+    ) -> impl Iterator<Item = &'a Column<'array>> {
+        all_columns.iter().filter(move |column: &&Column<'array>| {
+            column.depth == depth
+                || (column.depth < depth
+                    && !matches!(column.value_type, ValueType::Object(_, _))
+                    && !(flatten_arrays && matches!(column.value_type, ValueType::Array(_))))
+        })
+    }
+
+    fn table_ui(&mut self, ui: &mut egui::Ui, pinned: bool) -> ArrayResponse {
+        let text_height = Self::row_height(ui.style(), ui.spacing());
+
+        self.draw_table(ui, text_height, 7.0, pinned)
+    }
+
+    pub fn row_height(style: &Arc<Style>, spacing: &Spacing) -> f32 {
+        egui::TextStyle::Body
+            .resolve(style)
+            .size
             .max(spacing.interact_size.y)
     }
     fn draw_table(
@@ -551,6 +1765,8 @@ impl<'array> ArrayTable<'array> {
     ) -> ArrayResponse {
         use crate::components::table::{Column, TableBuilder};
         let parent_height = ui.available_rect_before_wrap().height();
+        let ctx = ui.ctx().clone();
+        let font_id = egui::TextStyle::Body.resolve(ui.style());
         let mut array_response = ArrayResponse::default();
         let mut table = TableBuilder::new(ui)
             .striped(true)
@@ -560,6 +1776,7 @@ impl<'array> ArrayTable<'array> {
             .min_scrolled_height(0.0)
             .max_scroll_height(parent_height)
             .set_is_pinned_column_table(pinned_column_table)
+            .selected_range(self.local_selected_range(pinned_column_table))
             .scroll_bar_visibility(if pinned_column_table {
                 ScrollBarVisibility::AlwaysHidden
             } else {
@@ -615,7 +1832,32 @@ impl<'array> ArrayTable<'array> {
         } else {
             self.column_selected.len()
         };
-        let columns = self.columns(pinned_column_table);
+        // Borrow fields only, auto sized widths are cached while columns are borrowed
+        let columns = if pinned_column_table {
+            &self.column_pinned
+        } else {
+            &self.column_selected
+        };
+        let remembered_widths = ui
+            .ctx()
+            .data(|d| d.get_temp::<ColumnWidths>(Id::new(COLUMN_WIDTHS_ID)))
+            .unwrap_or_default();
+        // Name and values of first displayed rows, wide chars and emoji included
+        let measure_column = |name: &str| {
+            let values = self
+                .filtered_nodes
+                .iter()
+                .take(AUTO_SIZE_SAMPLED_ROWS)
+                .filter_map(|row_index| {
+                    crate::parser::column_value(
+                        &self.nodes[*row_index],
+                        &self.parent_pointer.pointer,
+                        name,
+                    )
+                });
+            let measured = auto_size_width(&ctx, &font_id, std::iter::once(name).chain(values));
+            (measured + 3.0 * text_width).max(10.0 * text_width)
+        };
         if columns_count <= 3 {
             for i in 0..columns_count {
                 if pinned_column_table && i == 0 {
@@ -631,11 +1873,16 @@ impl<'array> ArrayTable<'array> {
                 } else if i == columns_count - 1 {
                     table = table.column(Column::remainder().clip(false).resizable(true).range(Rangef::new(240.0, f32::INFINITY)));
                 } else {
-                    table = table.column(
-                        Column::initial((columns[i].name.len() + 3).max(10) as f32 * text_width)
-                            .clip(true)
-                            .resizable(true),
-                    );
+                    let name: &str = &columns[i].name;
+                    let width = remembered_widths
+                        .width(self.schema_fingerprint, name)
+                        .unwrap_or_else(|| {
+                            *self
+                                .auto_column_widths
+                                .entry(name.to_string())
+                                .or_insert_with(|| measure_column(name))
+                        });
+                    table = table.column(Column::initial(width).clip(true).resizable(true));
                 }
                 // table = table.column(Column::initial(10.0).clip(true).resizable(true));
 
@@ -657,7 +1904,7 @@ impl<'array> ArrayTable<'array> {
         ));
         let table_response = table
             .header(text_height * 2.0, |header| {
-                self.header(pinned_column_table, header);
+                self.header(pinned_column_table, &mut array_response, header);
             })
             .body(
                 self.hovered_row_index,
@@ -674,6 +1921,31 @@ impl<'array> ArrayTable<'array> {
                 },
             );
 
+        let widths = &mut self.column_widths[pinned_column_table as usize];
+        if *widths != table_response.column_widths {
+            // Same count of columns, widths changed because user resized columns
+            if widths.len() == table_response.column_widths.len() {
+                let columns = if pinned_column_table {
+                    &self.column_pinned
+                } else {
+                    &self.column_selected
+                };
+                ui.ctx().data_mut(|d| {
+                    let remembered_widths =
+                        d.get_temp_mut_or_default::<ColumnWidths>(Id::new(COLUMN_WIDTHS_ID));
+                    for (i, width) in table_response.column_widths.iter().enumerate() {
+                        if widths[i] != *width && !columns[i].name.is_empty() {
+                            remembered_widths.remember(
+                                self.schema_fingerprint,
+                                &columns[i].name,
+                                *width,
+                            );
+                        }
+                    }
+                });
+            }
+            *widths = table_response.column_widths.clone();
+        }
         let table_scroll_output = table_response.scroll_area_output;
         if self.scroll_y != table_scroll_output.state.offset.y {
             self.scroll_y = table_scroll_output.state.offset.y;
@@ -684,6 +1956,10 @@ impl<'array> ArrayTable<'array> {
             self.first_visible_offset = table_response.first_visible_offset;
             self.last_visible_index = table_response.last_visible_index;
             self.last_visible_offset = table_response.last_visible_offset;
+            let row_height_with_spacing = text_height + ui.spacing().item_spacing.y;
+            self.visible_rows_count = ((table_scroll_output.inner_rect.height()
+                / row_height_with_spacing) as usize)
+                .max(1);
         }
         if request_repaint {
             ui.ctx().request_repaint();
@@ -691,22 +1967,131 @@ impl<'array> ArrayTable<'array> {
         array_response
     }
 
-    fn header(&mut self, pinned_column_table: bool, mut header: TableRow) {
+    fn header(
+        &mut self,
+        pinned_column_table: bool,
+        array_response: &mut ArrayResponse,
+        mut header: TableRow,
+    ) {
         // Mutation after interaction
         let mut clicked_filter_non_null_column: Option<String> = None;
         let mut clicked_filter_column_value: Option<(String, String)> = None;
         let mut pinned_column: Option<usize> = None;
         let mut clicked_replace_column: Option<usize> = None;
+        let mut clicked_sort_column: Option<String> = None;
+        let mut moved_column: Option<(usize, usize)> = None;
+        let mut converted_column: Option<(Column, ValueType)> = None;
+        let mut reshaped_column: Option<ColumnReshape> = None;
+        let mut locked_column: Option<(String, bool)> = None;
+        let mut column_base: Option<(String, NumberBase)> = None;
         header.cols(true, |ui, index| {
             let columns = self.columns(pinned_column_table);
             let column = columns.get(index).unwrap();
             let name = column.name.as_str();
-            let strong = Label::new(WidgetText::RichText(egui::RichText::from(name)));
-            let label = Label::new(name);
+            // Row number column shows index of elements in the array, whatever sort and filters
+            let (displayed_name, hover_text) = if name == ROW_NUMBER_COLUMN {
+                ("#", "Index of the element in the array")
+            } else {
+                (name, name)
+            };
+            let strong = Label::new(WidgetText::RichText(egui::RichText::from(displayed_name)));
+            let label = Label::new(hover_text);
             let response = ui.vertical(|ui| {
-                let response = ui.add(strong).on_hover_ui(|ui| {
+                let response = if !pinned_column_table && !column.name.eq("") {
+                    ui.dnd_drag_source(Id::new(("drag-column", name)), DraggedColumn(index), |ui| {
+                        ui.add(strong)
+                    })
+                    .inner
+                } else {
+                    ui.add(strong)
+                };
+                let response = response.on_hover_ui(|ui| {
                     ui.add(label);
+                    if !column.name.eq("") && !column.name.eq(ROW_NUMBER_COLUMN) {
+                        for (cell_type, count) in self.column_types(column) {
+                            ui.label(format!("{}: {}", cell_type.as_str(), count));
+                        }
+                        if let Some(aggregates) = self.column_aggregates(column) {
+                            ui.label(format!(
+                                "Sum: {}, average: {}",
+                                aggregates.sum,
+                                aggregates.mean()
+                            ));
+                        }
+                    }
                 });
+                if !column.name.eq("") && (!pinned_column_table || index > 0) {
+                    response.context_menu(|ui| {
+                        let text = if pinned_column_table {
+                            "Unpin column"
+                        } else {
+                            "Pin column to left"
+                        };
+                        if ui.add(ButtonWithIcon::new(text, THUMBTACK)).clicked() {
+                            pinned_column = Some(index);
+                            ui.close_menu();
+                        }
+                        if ui.add(ButtonWithIcon::new("Copy column", COPY)).clicked() {
+                            ui.ctx().copy_text(self.copy_column(column));
+                            ui.close_menu();
+                        }
+                        let is_locked = self.is_column_locked(name);
+                        let button = if is_locked {
+                            ButtonWithIcon::new("Unlock column", LOCK_OPEN)
+                        } else {
+                            ButtonWithIcon::new("Lock column", LOCK)
+                        };
+                        if ui.add(button).clicked() {
+                            locked_column = Some((name.to_string(), !is_locked));
+                            ui.close_menu();
+                        }
+                        if matches!(column.value_type, ValueType::Number) {
+                            let current_base = self.column_base(name);
+                            ui.menu_button("Display integers as", |ui| {
+                                for base in [
+                                    NumberBase::Decimal,
+                                    NumberBase::Hexadecimal,
+                                    NumberBase::Binary,
+                                ] {
+                                    if ui.radio(current_base == base, base.as_str()).clicked() {
+                                        column_base = Some((name.to_string(), base));
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                        }
+                        if ui.button("Merge with another column…").clicked() {
+                            reshaped_column = Some(ColumnReshape {
+                                column: column.name.to_string(),
+                                separator: " ".to_string(),
+                                ..Default::default()
+                            });
+                            ui.close_menu();
+                        }
+                        if ui.button("Split column…").clicked() {
+                            let key = column.name.rsplit('/').next().unwrap_or_default();
+                            reshaped_column = Some(ColumnReshape {
+                                column: column.name.to_string(),
+                                is_split: true,
+                                other_column: concat_string!(unescape_pointer_token(key), "_2"),
+                                separator: ",".to_string(),
+                                ..Default::default()
+                            });
+                            ui.close_menu();
+                        }
+                        ui.separator();
+                        for (text, value_type) in [
+                            ("Convert to number", ValueType::Number),
+                            ("Convert to string", ValueType::String),
+                            ("Convert to boolean", ValueType::Bool),
+                        ] {
+                            if ui.button(text).clicked() {
+                                converted_column = Some((column.clone(), value_type));
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                }
 
                 if !pinned_column_table || index > 0 {
                     ui.horizontal(|ui| {
@@ -726,6 +2111,10 @@ impl<'array> ArrayTable<'array> {
                         if response.clicked() {
                             pinned_column = Some(index);
                         }
+                        if self.is_column_locked(name) {
+                            ui.label(LOCK)
+                                .on_hover_text("Locked, cells can't be edited");
+                        }
                         let column_id = Id::new(name);
                         let checked_filtered_values = self.columns_filter.get(column.name.as_str());
                         PopupMenu::new(column_id.with("filter")).show_ui(
@@ -790,23 +2179,51 @@ impl<'array> ArrayTable<'array> {
                                 clicked_replace_column = Some(index);
                             }
                         }
+
+                        if Self::is_sortable(column) {
+                            let (sort_icon, sort_tooltip, sort_color) = match self.column_sort {
+                                Some((ref sorted_column, SortOrder::Ascending))
+                                    if sorted_column.eq(name) =>
+                                {
+                                    (SORT_UP, "Sort descending", Some(ACTIVE_COLOR))
+                                }
+                                Some((ref sorted_column, SortOrder::Descending))
+                                    if sorted_column.eq(name) =>
+                                {
+                                    (SORT_DOWN, "Remove sort", Some(ACTIVE_COLOR))
+                                }
+                                _ => (SORT, "Sort ascending", None),
+                            };
+                            let response = icon::button(ui, sort_icon, Some(sort_tooltip), sort_color);
+                            if response.clicked() {
+                                clicked_sort_column = Some(name.to_string());
+                            }
+                        }
                     });
                 }
 
                 response
             });
+            if !pinned_column_table {
+                if let Some(dragged_column) =
+                    response.response.dnd_release_payload::<DraggedColumn>()
+                {
+                    moved_column = Some((dragged_column.0, index));
+                }
+            }
             Some(response.inner)
         });
+        if let Some((from, to)) = moved_column {
+            self.move_column(from, to);
+        }
         if let Some(pinned_column) = pinned_column {
-            if pinned_column_table {
-                let column = self.column_pinned.remove(pinned_column);
-                self.column_selected.push(column);
-                self.column_selected.sort();
-            } else {
-                let column = self.column_selected.remove(pinned_column);
-                self.column_pinned.push(column);
-            }
-            self.cache.borrow_mut().evict();
+            self.toggle_column_pin(pinned_column_table, pinned_column);
+        }
+        if let Some((name, locked)) = locked_column {
+            self.set_column_locked(&name, locked);
+        }
+        if let Some((name, base)) = column_base {
+            self.set_column_base(&name, base);
         }
         if let Some(replace_column) = clicked_replace_column {
             let column = self.columns(pinned_column_table)[replace_column].clone();
@@ -818,6 +2235,29 @@ impl<'array> ArrayTable<'array> {
         if let Some(clicked_column) = clicked_filter_column_value {
             self.on_filter_column_value(clicked_column);
         }
+        if let Some(clicked_column) = clicked_sort_column {
+            self.on_sort_column(clicked_column);
+        }
+        if reshaped_column.is_some() {
+            self.pending_column_reshape = reshaped_column;
+        }
+        if let Some((column, value_type)) = converted_column {
+            let summary = match self.convert_column(array_response, &column, value_type) {
+                Ok((converted_count, skipped_count)) => {
+                    let summary = format!("{} cells of {} converted", converted_count, column.name);
+                    if skipped_count > 0 {
+                        format!(
+                            "{}, {} cells could not be converted",
+                            summary, skipped_count
+                        )
+                    } else {
+                        summary
+                    }
+                }
+                Err(error) => error,
+            };
+            self.conversion_summary = Some(summary);
+        }
     }
 
     fn body(
@@ -830,11 +2270,24 @@ impl<'array> ArrayTable<'array> {
     ) {
         // Mutation after interaction
         let mut subtable = None;
+        let mut open_cell_subtable = None;
         let mut focused_cell = None;
         let mut focused_changed = false;
+        let mut extend_selection = false;
         let mut updated_value: Option<(PointerKey, String)> = None;
+        let mut force_edit = false;
+        let mut cancel_rejected_edit = false;
         let mut filter_by_value: Option<(String, String)> = None; // col name, value
         let mut insert_row_at_index: Option<(usize, u8)> = None; // table_row_index, 0 = above, 1 = below
+        let mut delete_row_index: Option<usize> = None;
+        let mut duplicate_row_index: Option<usize> = None;
+        let mut moved_row: Option<(usize, usize)> = None;
+        let mut large_value: Option<String> = None;
+        let mut delete_selected_rows = false;
+        let selected_rows = self
+            .selected_range()
+            .map(|range| range.first_row..=range.last_row)
+            .filter(|rows| rows.end() > rows.start());
         let columns = self.columns(pinned_column_table);
         let hover_data = body.rows(text_height, self.filtered_nodes.len(), |mut row| {
             let table_row_index = row.index();
@@ -853,31 +2306,77 @@ impl<'array> ArrayTable<'array> {
                     let index =
                         self.get_pointer_index_from_cache(pinned_column_table, row_data, col_index);
                     let mut editing_index = self.editing_index.borrow_mut();
+                    let is_locked = self.is_column_locked(&columns[col_index].name);
                     if editing_index.is_some()
                         && editing_index.unwrap() == (col_index, row_index, pinned_column_table)
                     {
                         focused_changed = true;
                         focused_cell = None;
                         let ref_mut = &mut *self.editing_value.borrow_mut();
+                        if self.is_bool_column(&columns[col_index]) {
+                            let pointer = PointerKey {
+                                pointer: Self::pointer_key(
+                                    &self.parent_pointer.pointer,
+                                    row_index,
+                                    &columns[col_index].name,
+                                ),
+                                value_type: columns[col_index].value_type,
+                                depth: columns[col_index].depth,
+                                position: 0,
+                                column_id: columns[col_index].id,
+                            };
+                            match Self::bool_editor(ui, cell_id, ref_mut) {
+                                Some(Some(value)) => {
+                                    updated_value = Some((pointer, value));
+                                    focused_cell = Some(CellLocation {
+                                        column_index: col_index,
+                                        row_index: table_row_index,
+                                        is_pinned_column_table: pinned_column_table,
+                                    });
+                                }
+                                Some(None) => *editing_index = None,
+                                None => {}
+                            }
+                            return None;
+                        }
                         let text_edit = TextEdit::singleline(ref_mut);
-                        let textedit_response = ui.add(text_edit.desired_width(f32::INFINITY));
+                        let mut textedit_response = ui.add(text_edit.desired_width(f32::INFINITY));
+                        if let Some((_, error)) = self
+                            .rejected_edit
+                            .as_ref()
+                            .filter(|(index, _)| *editing_index == Some(*index))
+                        {
+                            let cancelled;
+                            (textedit_response, cancelled) =
+                                show_rejected_edit(ui, textedit_response, error);
+                            if cancelled {
+                                *editing_index = None;
+                                cancel_rejected_edit = true;
+                                return None;
+                            }
+                        }
                         if textedit_response.lost_focus()
                             || ui
                                 .ctx()
                                 .input_mut(|input| input.consume_key(Modifiers::NONE, Key::Enter))
                         {
+                            let value = mem::take(ref_mut);
                             let pointer = PointerKey {
                                 pointer: Self::pointer_key(
                                     &self.parent_pointer.pointer,
                                     row_index,
                                     &columns.get(col_index).as_ref().unwrap().name,
                                 ),
-                                value_type: columns[col_index].value_type,
+                                value_type: edited_value_type(
+                                    columns[col_index].value_type,
+                                    &value,
+                                ),
                                 depth: columns[col_index].depth,
                                 position: 0,
                                 column_id: columns[col_index].id,
                             };
-                            updated_value = Some((pointer, mem::take(ref_mut)));
+                            updated_value = Some((pointer, value));
+                            force_edit = ui.input(|i| i.modifiers.shift);
                             focused_changed = true;
                             focused_cell = Some(CellLocation {
                                 column_index: col_index,
@@ -892,17 +2391,66 @@ impl<'array> ArrayTable<'array> {
 
                         if pinned_column_table && col_index == 0 {
                             let label = Label::new(row_index.to_string());
-                            return Some(label.ui(ui));
+                            // Displayed order is the array order only when rows are not sorted
+                            if self.column_sort.is_some() {
+                                return Some(label.ui(ui));
+                            }
+                            let response = ui
+                                .dnd_drag_source(
+                                    Id::new(("drag-row", cell_id)),
+                                    DraggedRow(row_index),
+                                    |ui| label.ui(ui),
+                                )
+                                .response;
+                            if response.dnd_hover_payload::<DraggedRow>().is_some() {
+                                let rect = ui.max_rect();
+                                ui.painter().hline(
+                                    rect.x_range(),
+                                    rect.top(),
+                                    ui.visuals().selection.stroke,
+                                );
+                            }
+                            if let Some(dragged_row) = response.dnd_release_payload::<DraggedRow>()
+                            {
+                                moved_row = Some((dragged_row.0, row_index));
+                            }
+                            return Some(response.on_hover_text("Drag to move the row"));
                         } else if let Some(value) = entry.value.as_ref() {
                             if !matches!(entry.pointer.value_type, ValueType::Null) {
-                                let label = CellText::new(value.clone());
+                                let displayed_value =
+                                    if matches!(entry.pointer.value_type, ValueType::Number) {
+                                        let base = self.column_base(&columns[col_index].name);
+                                        match format_in_base(value, base) {
+                                            Cow::Owned(formatted) => Cow::Owned(formatted),
+                                            Cow::Borrowed(_) => {
+                                                self.display_options.display_number(value)
+                                            }
+                                        }
+                                    } else {
+                                        Cow::from(crate::parser::strip_non_finite_number_marker(
+                                            value,
+                                        ))
+                                    };
+                                let text = truncate_cell_value(
+                                    &displayed_value,
+                                    self.display_options.max_cell_chars,
+                                );
+                                let is_truncated = matches!(text, Cow::Owned(_));
+                                let label = CellText::new(text.into_owned());
 
                                 let mut response = label.ui(ui, cell_id);
+                                let is_too_large = is_too_large_to_render(value);
 
-                                if response.double_clicked() {
-                                    *self.editing_value.borrow_mut() = value.clone();
-                                    *editing_index =
-                                        Some((col_index, row_index, pinned_column_table));
+                                if response.double_clicked() && !is_locked {
+                                    if is_too_large {
+                                        large_value = Some(value.clone());
+                                    } else {
+                                        *self.editing_value.borrow_mut() =
+                                            crate::parser::strip_non_finite_number_marker(value)
+                                                .to_string();
+                                        *editing_index =
+                                            Some((col_index, row_index, pinned_column_table));
+                                    }
                                 }
                                 if response.secondary_clicked() || response.clicked() {
                                     focused_cell = Some(CellLocation {
@@ -914,18 +2462,24 @@ impl<'array> ArrayTable<'array> {
                                     ui.ctx().memory_mut(|m| m.request_focus(self.table_id));
 
                                     focused_changed = true;
+                                    extend_selection =
+                                        response.clicked() && ui.input(|i| i.modifiers.shift);
                                 }
 
                                 if response.hovered() {
                                     ui.ctx().set_cursor_icon(CursorIcon::Cell);
                                 }
 
-                                if value.len() > 100 {
+                                if is_truncated {
                                     response = response.on_hover_ui(|ui| {
                                         ui.style_mut().interaction.selectable_labels = true;
                                         let scroll_area = egui::ScrollArea::vertical();
                                         scroll_area.show(ui, |ui| {
-                                            ui.label(value).request_focus();
+                                            ui.label(cap_rendered_value(value).into_owned())
+                                                .request_focus();
+                                            if is_too_large {
+                                                ui.weak("Value too large, double click to view");
+                                            }
                                         });
                                     });
                                 };
@@ -935,8 +2489,18 @@ impl<'array> ArrayTable<'array> {
                     }
                     // No value cell
                     let rect = ui.available_rect_before_wrap();
+                    let entry = index.map(|index| &row_data.entries()[index]);
+                    if let Some(text) = empty_cell_text(entry, self.display_options.show_null) {
+                        ui.painter().text(
+                            rect.left_center(),
+                            Align2::LEFT_CENTER,
+                            text,
+                            TextStyle::Body.resolve(ui.style()),
+                            ui.visuals().weak_text_color(),
+                        );
+                    }
                     let response = ui.interact(rect, Id::new(cell_id), Sense::click());
-                    if response.double_clicked() {
+                    if response.double_clicked() && !is_locked {
                         *self.editing_value.borrow_mut() = String::new();
                         *editing_index = Some((col_index, row_index, pinned_column_table));
                     }
@@ -949,6 +2513,7 @@ impl<'array> ArrayTable<'array> {
                         });
                         ui.ctx().memory_mut(|m| m.request_focus(self.table_id));
                         focused_changed = true;
+                        extend_selection = response.clicked() && ui.input(|i| i.modifiers.shift);
                     }
 
                     if response.hovered() {
@@ -987,11 +2552,14 @@ impl<'array> ArrayTable<'array> {
                                 edit_entry = Some(entry);
                             }
                             // Context menu: edit
+                            let is_locked = self.is_cell_locked(hover_cell);
                             let button = ButtonWithIcon::new("Edit", PENCIL);
-                            if ui.add(button).clicked() {
+                            if ui.add_enabled(!is_locked, button).clicked() {
                                 *self.editing_index.borrow_mut() =
                                     Some((col_index, row_index, pinned_column_table));
-                                *self.editing_value.borrow_mut() = mem::take(&mut edit_value);
+                                *self.editing_value.borrow_mut() =
+                                    crate::parser::strip_non_finite_number_marker(&edit_value)
+                                        .to_string();
                                 ui.close_menu();
                             }
                             if !edit_value.is_empty() {
@@ -999,7 +2567,10 @@ impl<'array> ArrayTable<'array> {
                                 let button = ButtonWithIcon::new("Copy", COPY)
                                     .shortcut_text(ui.ctx().format_shortcut(&SHORTCUT_COPY));
                                 if ui.add(button).clicked() {
-                                    ui.ctx().copy_text(edit_value.clone());
+                                    ui.ctx().copy_text(
+                                        crate::parser::strip_non_finite_number_marker(&edit_value)
+                                            .to_string(),
+                                    );
                                     ui.close_menu();
                                 }
                                 // Context menu: filter by value
@@ -1029,6 +2600,32 @@ impl<'array> ArrayTable<'array> {
                                 insert_row_at_index = Some((table_row_index, 1));
                                 ui.close_menu();
                             }
+
+                            // Context menu: duplicate row
+                            let button = ButtonWithIcon::new("Duplicate row", COPY);
+                            if ui.add(button).clicked() {
+                                duplicate_row_index = Some(row_index);
+                                ui.close_menu();
+                            }
+
+                            // Context menu: delete row
+                            let button = ButtonWithIcon::new("Delete row", TRASH_CAN);
+                            if ui.add(button).clicked() {
+                                delete_row_index = Some(row_index);
+                                ui.close_menu();
+                            }
+                            if let Some(ref rows) = selected_rows {
+                                if rows.contains(&table_row_index) {
+                                    let button = ButtonWithIcon::new(
+                                        format!("Delete {} selected rows", rows.clone().count()),
+                                        TRASH_CAN,
+                                    );
+                                    if ui.add(button).clicked() {
+                                        delete_selected_rows = true;
+                                        ui.close_menu();
+                                    }
+                                }
+                            }
                             // Context menu: Open array or object in subtable
                             if let Some(entry) = edit_entry {
                                 let is_array =
@@ -1039,15 +2636,16 @@ impl<'array> ArrayTable<'array> {
                                     ui.separator();
                                     let button = ButtonWithIcon::new(
                                         format!(
-                                            "Open {} in sub table",
-                                            if is_array { "array" } else { "object" }
+                                            "Open {} in sub table ({} items)",
+                                            if is_array { "array" } else { "object" },
+                                            child_count(&entry.pointer.value_type).unwrap_or(0)
                                         ),
                                         TABLE_CELLS,
                                     );
                                     if ui.add(button).clicked() {
                                         ui.close_menu();
-                                        let content = edit_value.clone();
-                                        subtable = Self::open_subtable(row_index, entry, content);
+                                        open_cell_subtable =
+                                            Some((row_index, entry.pointer.pointer.clone()));
                                     }
                                 }
                             }
@@ -1076,6 +2674,11 @@ impl<'array> ArrayTable<'array> {
                                     ui.close_menu();
                                 }
                             }
+                            // Context menu: copy row as json
+                            if ui.button("Copy row as JSON").clicked() {
+                                ui.ctx().copy_text(self.row_to_json(row_index));
+                                ui.close_menu();
+                            }
                         }
                     }
                 });
@@ -1083,28 +2686,83 @@ impl<'array> ArrayTable<'array> {
         }
 
         if focused_changed {
+            if extend_selection && focused_cell.is_some() {
+                if self.selection_anchor.is_none() {
+                    self.selection_anchor = self.focused_cell;
+                }
+            } else {
+                self.selection_anchor = None;
+            }
             self.focused_cell = focused_cell;
         }
-        if let Some(subtable) = subtable {
+        if let Some(mut subtable) = subtable {
+            subtable.set_edit_options(self.edit_options);
+            subtable.set_display_options(self.display_options);
+            subtable.lock_pointers(&self.locked_cell_pointers(subtable.id()));
             self.windows.push(subtable);
         }
+        if let Some((row_index, pointer)) = open_cell_subtable {
+            // Menu is only shown for arrays and objects of the row
+            let _ = self.open_cell_in_sub_table(row_index, &pointer);
+        }
         if let Some((column_name, filter_value)) = filter_by_value {
             self.on_filter_column_value((column_name, filter_value));
         }
         if let Some((table_row_index, above_or_below)) = insert_row_at_index {
-            self.insert_new_row(table_row_index, above_or_below);
+            self.insert_new_row(array_response, table_row_index, above_or_below);
+        }
+        if let Some(value) = large_value {
+            self.large_value_viewer = Some(LargeValueViewer::new(value));
+        }
+        if let Some(row_index) = duplicate_row_index {
+            self.duplicate_row(array_response, row_index);
+        }
+        if let Some((from, to)) = moved_row {
+            self.move_row(array_response, from, to);
+        }
+        if let Some(row_index) = delete_row_index {
+            self.delete_row(array_response, row_index);
+        }
+        if delete_selected_rows {
+            let rows = self.selected_row_indexes();
+            if rows.len() > ROWS_DELETION_CONFIRMATION_THRESHOLD {
+                self.pending_rows_deletion = Some(rows);
+            } else {
+                self.delete_rows(array_response, &rows);
+            }
+        }
+        if cancel_rejected_edit {
+            self.rejected_edit = None;
         }
         if let Some((pointer, value)) = updated_value {
             let editing_index = mem::take(&mut *self.editing_index.borrow_mut());
-            let value = if value.is_empty() { None } else { Some(value) };
             let (_, row_index, _) = editing_index.unwrap();
+            let value = if value.is_empty() {
+                None
+            } else {
+                // Literals of lenient documents are edited without their marker
+                let current = self.nodes[row_index]
+                    .find_node_at(&pointer.pointer)
+                    .and_then(|entry| entry.value.as_deref());
+                Some(crate::parser::keep_non_finite_number_marker(current, value))
+            };
             let value_changed = FlatJsonValue {
                 pointer: pointer.clone(),
                 value: value.clone(),
             };
 
-            self.edit_cell(array_response, value_changed, row_index);
-            self.was_editing = true;
+            match self.try_edit_cell(array_response, value_changed, row_index, force_edit) {
+                Ok(_) => {
+                    self.rejected_edit = None;
+                    self.was_editing = true;
+                }
+                Err(error) => {
+                    // Keep editing so the value can be fixed
+                    *self.editing_index.borrow_mut() = editing_index;
+                    *self.editing_value.borrow_mut() = value.unwrap_or_default();
+                    self.rejected_edit = Some((editing_index.unwrap(), error));
+                }
+            }
         }
         if self.hovered_row_index != hover_data.hovered_row {
             self.hovered_row_index = hover_data.hovered_row;
@@ -1113,100 +2771,598 @@ impl<'array> ArrayTable<'array> {
         array_response.hover_data = hover_data;
     }
 
+    pub fn set_edit_options(&mut self, edit_options: EditOptions) {
+        self.edit_options = edit_options;
+        self.rejected_edit = None;
+        for window in self.windows.iter_mut() {
+            window.set_edit_options(edit_options);
+        }
+    }
+
+    pub fn set_display_options(&mut self, display_options: DisplayOptions) {
+        let hide_empty_columns_changed =
+            self.display_options.hide_empty_columns != display_options.hide_empty_columns;
+        self.display_options = display_options;
+        for window in self.windows.iter_mut() {
+            window.set_display_options(display_options);
+        }
+        if hide_empty_columns_changed {
+            self.update_empty_columns();
+        }
+    }
+
+    /// Columns null in every filtered row change with filters, displayed columns are updated
+    fn update_empty_columns(&mut self) {
+        let empty_columns = if self.display_options.hide_empty_columns {
+            crate::parser::empty_columns(
+                &self.nodes,
+                &self.filtered_nodes,
+                &self.parent_pointer.pointer,
+                &self.all_columns,
+            )
+        } else {
+            BTreeSet::new()
+        };
+        if empty_columns != self.empty_columns {
+            self.empty_columns = empty_columns;
+            self.focused_cell = None;
+            self.selection_anchor = None;
+            self.update_selected_columns(self.max_depth);
+            self.cache.borrow_mut().evict();
+        }
+    }
+
+    /// Same as `edit_cell`, unless types are enforced and `force` is not set: then edit fails when
+    /// value is not of the type of its column.
+    pub fn try_edit_cell(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        new_entry: FlatJsonValue<String>,
+        row_index: usize,
+        force: bool,
+    ) -> Result<bool, String> {
+        // Array and object cells hold raw json, it can't be forced as it would corrupt the row
+        if Self::is_invalid_raw_json(&new_entry) {
+            return Err(format!("{} is not valid json", new_entry.value.unwrap()));
+        }
+        if self.edit_options.enforce_types && !force {
+            self.validate_entry(&new_entry)?;
+        }
+        Ok(self.edit_cell(array_response, new_entry, row_index))
+    }
+
+    fn is_invalid_raw_json(entry: &FlatJsonValue<String>) -> bool {
+        matches!(
+            entry.pointer.value_type,
+            ValueType::Array(_) | ValueType::Object(..)
+        ) && entry
+            .value
+            .as_ref()
+            .is_some_and(|value| serde_json::from_str::<serde_json::Value>(value).is_err())
+    }
+
+    /// Check new value against the type of its column, regardless of edit options
+    fn validate_entry(&self, new_entry: &FlatJsonValue<String>) -> Result<(), String> {
+        let column = self
+            .all_columns
+            .iter()
+            .find(|column| column.id == new_entry.pointer.column_id);
+        if let (Some(column), Some(value)) = (column, new_entry.value.as_ref()) {
+            if let Some(cell_type) = CellType::enforced(&self.column_types(column)) {
+                if !cell_type.accepts(value) {
+                    return Err(format!(
+                        "{} is not a valid {} value",
+                        value,
+                        cell_type.as_str().to_lowercase()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn edit_cell(
         &mut self,
         array_response: &mut ArrayResponse,
         new_entry: FlatJsonValue<String>,
         row_index: usize,
-    ) {
+    ) -> bool {
         if self.is_sub_table {
             let value_changed = self.update_value(new_entry, row_index, false);
 
             if value_changed {
-                let mut entries = self
-                    .nodes
-                    .iter()
-                    .flat_map(|row| row.entries.clone())
-                    .collect::<Vec<FlatJsonValue<String>>>();
-                let mut parent_pointer = PointerKey {
-                    pointer: String::new(),
-                    value_type: ValueType::Array(self.nodes.len()),
-                    depth: 0,
-                    position: 0,
-                    column_id: 0,
-                };
-                entries.push(FlatJsonValue {
-                    pointer: parent_pointer.clone(),
-                    value: None,
-                });
-                // entries.iter().for_each(|e| println!("{} -> {:?}", e.pointer.pointer, e.value));
-                let updated_array = serialize_to_json_with_option::<String>(
-                    &mut entries,
-                    self.parent_pointer.depth + 1,
-                )
-                .to_json();
-                parent_pointer.pointer = self.parent_pointer.pointer.clone();
-                array_response.edited_value.push(FlatJsonValue {
-                    pointer: parent_pointer,
-                    value: Some(updated_array),
-                });
+                array_response
+                    .edited_value
+                    .push(self.serialize_parent_array());
             }
+            value_changed
         } else {
             let value_changed = self.update_value(new_entry.clone(), row_index, true);
             if value_changed {
                 array_response.edited_value.push(new_entry);
             }
+            value_changed
+        }
+    }
+
+    /// Paste `text` at the given cell: tab or comma separated rows are written with `paste_block`,
+    /// other text replaces the value of the cell. Cells of locked columns are left unchanged.
+    /// Return count of updated cells.
+    pub fn paste(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        cell_location: CellLocation,
+        text: &str,
+    ) -> usize {
+        if let Some(block) = parse_delimited_block(text) {
+            return self.paste_block(array_response, cell_location, block);
+        }
+        if self.is_cell_locked(&cell_location) {
+            return 0;
+        }
+        let row_index = self.filtered_nodes[cell_location.row_index];
+        let column =
+            &self.columns(cell_location.is_pinned_column_table)[cell_location.column_index];
+        let flat_json_value = FlatJsonValue::<String> {
+            pointer: PointerKey {
+                pointer: Self::pointer_key(&self.parent_pointer.pointer, row_index, &column.name),
+                value_type: edited_value_type(column.value_type, text),
+                depth: column.depth,
+                position: 0,
+                column_id: column.id,
+            },
+            value: Some(text.to_string()),
+        };
+        usize::from(self.edit_cell(array_response, flat_json_value, row_index))
+    }
+
+    /// Write values from the given cell, filling right then down. Values out of the table are ignored.
+    /// Cells of locked columns are skipped. Return count of updated cells.
+    pub fn paste_block(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        start: CellLocation,
+        block: Vec<Vec<String>>,
+    ) -> usize {
+        let first_column = self.grid_column_index(&start);
+        let pinned_count = self.column_pinned.len();
+        let columns_count = pinned_count + self.column_selected.len();
+        let mut updated_count = 0;
+        let edit_history = self.start_edit_group();
+        for (i, values) in block.into_iter().enumerate() {
+            let row_index = match self.filtered_nodes.get(start.row_index + i) {
+                Some(row_index) => *row_index,
+                None => break,
+            };
+            for (j, value) in values.into_iter().enumerate() {
+                let grid_column = first_column + j;
+                if grid_column >= columns_count {
+                    break;
+                }
+                // Row number column is not editable
+                if grid_column == 0 {
+                    continue;
+                }
+                let column = if grid_column < pinned_count {
+                    &self.column_pinned[grid_column]
+                } else {
+                    &self.column_selected[grid_column - pinned_count]
+                };
+                if self.is_column_locked(&column.name) {
+                    continue;
+                }
+                let value_type = edited_value_type(column.value_type, &value);
+                let flat_json_value = FlatJsonValue::<String> {
+                    pointer: PointerKey {
+                        pointer: Self::pointer_key(
+                            &self.parent_pointer.pointer,
+                            row_index,
+                            &column.name,
+                        ),
+                        value_type,
+                        depth: column.depth,
+                        position: 0,
+                        column_id: column.id,
+                    },
+                    value: if value.is_empty() { None } else { Some(value) },
+                };
+                if self.edit_cell(array_response, flat_json_value, row_index) {
+                    updated_count += 1;
+                }
+            }
         }
+        // Pasted block is undone at once
+        self.push_edit_group(edit_history);
+        updated_count
     }
 
-    fn insert_new_row(&mut self, table_row_index: usize, above_or_below: u8) {
+    fn insert_new_row(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        table_row_index: usize,
+        above_or_below: u8,
+    ) {
         let row_index = self.filtered_nodes[table_row_index];
-        let depth = self.nodes[row_index].entries.last().unwrap().pointer.depth;
-        let new_table_row_index = table_row_index + above_or_below as usize;
-        let new_index = row_index + above_or_below as usize;
-        for i in new_table_row_index..self.filtered_nodes.len() {
-            self.filtered_nodes[i] += 1;
+        self.insert_row_at(
+            array_response,
+            row_index + above_or_below as usize,
+            table_row_index + above_or_below as usize,
+        );
+    }
+
+    /// Insert a new element at `at` in the array, with every first level column set to null
+    fn insert_row_at(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        at: usize,
+        table_row_index: usize,
+    ) {
+        let depth = self
+            .nodes
+            .first()
+            .and_then(|row| row.entries.last())
+            .map_or(self.parent_pointer.depth, |root| root.pointer.depth);
+        let new_entry_pointer = concat_string!(self.parent_pointer.pointer, "/", at.to_string());
+        let mut entries = vec![row_number_entry(at, 0, new_entry_pointer.as_str())];
+        let mut object = serde_json::Map::new();
+        for column in self.all_columns.iter().filter(|c| c.depth == depth + 1) {
+            entries.push(FlatJsonValue {
+                pointer: PointerKey {
+                    pointer: concat_string!(new_entry_pointer, column.name),
+                    value_type: ValueType::Null,
+                    depth: column.depth,
+                    position: 0,
+                    column_id: column.id,
+                },
+                value: Some("null".to_string()),
+            });
+            object.insert(
+                unescape_pointer_token(&column.name[1..]).to_string(),
+                serde_json::Value::Null,
+            );
+        }
+        let root_entry = FlatJsonValue {
+            pointer: PointerKey {
+                pointer: new_entry_pointer,
+                value_type: ValueType::Object(true, object.len()),
+                depth,
+                position: 0,
+                column_id: 0,
+            },
+            value: Some(serde_json::to_string_pretty(&object).unwrap()),
+        };
+        entries.push(root_entry);
+        let row = JsonArrayEntries { entries, index: at };
+        self.insert_row_entries(array_response, row, at, Some(table_row_index));
+    }
+
+    /// Insert `row` at `at` in the array, displayed at `table_row_index` or by default where the
+    /// row it pushes down was displayed. Pointers of `row` are renumbered from its index.
+    fn insert_row_entries(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        row: JsonArrayEntries<String>,
+        at: usize,
+        table_row_index: Option<usize>,
+    ) {
+        let table_row_index = table_row_index.unwrap_or_else(|| {
+            self.filtered_nodes
+                .iter()
+                .position(|row_index| *row_index == at)
+                .unwrap_or(self.filtered_nodes.len())
+        });
+        self.nodes.insert(at, row);
+        self.renumber_rows(at);
+        for row_index in self.filtered_nodes.iter_mut() {
+            if *row_index >= at {
+                *row_index += 1;
+            }
+        }
+        let table_row_index = table_row_index.min(self.filtered_nodes.len());
+        self.filtered_nodes.insert(table_row_index, at);
+        let root_entry = self.nodes[at].entries.last().unwrap().clone();
+        self.edit_history.push(Edit {
+            row_index: at,
+            pointer: root_entry.pointer.clone(),
+            old_value: None,
+            new_value: root_entry.value.clone(),
+            old_value_type: None,
+            reorders_keys: false,
+            row_change: Some(RowChange::Inserted(self.nodes[at].entries.clone())),
+        });
+        self.refresh_rows(array_response, at, root_entry);
+    }
+
+    /// Insert a copy of element at `at`, nested values included, right after it
+    pub fn duplicate_row(&mut self, array_response: &mut ArrayResponse, at: usize) {
+        let Some(row) = self.nodes.get(at) else {
+            return;
+        };
+        // Copy keeps pointers of the original row until it is renumbered
+        let copy = JsonArrayEntries {
+            entries: row.entries.clone(),
+            index: row.index,
+        };
+        let table_row_index = self
+            .filtered_nodes
+            .iter()
+            .position(|row_index| *row_index == at)
+            .map_or(self.filtered_nodes.len(), |position| position + 1);
+        self.insert_row_entries(array_response, copy, at + 1, Some(table_row_index));
+    }
+
+    /// Remove element at `at` from the array
+    pub fn delete_row(&mut self, array_response: &mut ArrayResponse, at: usize) {
+        self.delete_rows(array_response, &[at]);
+    }
+
+    /// Indexes in the array of rows covered by the selection, rows hidden by filters are not
+    /// part of the selection
+    pub fn selected_row_indexes(&self) -> Vec<usize> {
+        self.selected_range().map_or(vec![], |range| {
+            self.filtered_nodes
+                .iter()
+                .skip(range.first_row)
+                .take(range.last_row + 1 - range.first_row)
+                .copied()
+                .collect()
+        })
+    }
+
+    /// Remove elements at `rows` from the array, rows are renumbered once and a single response
+    /// is emitted. Return count of removed rows.
+    pub fn delete_rows(&mut self, array_response: &mut ArrayResponse, rows: &[usize]) -> usize {
+        let mut rows = rows
+            .iter()
+            .copied()
+            .filter(|row_index| *row_index < self.nodes.len())
+            .collect::<Vec<usize>>();
+        rows.sort_unstable();
+        rows.dedup();
+        let Some(&from) = rows.first() else {
+            return 0;
+        };
+        let mut root_entry = None;
+        // Removed from the last one so recorded indexes are the ones at removal
+        let mut edits = Vec::with_capacity(rows.len());
+        for row_index in rows.iter().rev() {
+            let entries = self.nodes.remove(*row_index).entries;
+            if let Some(removed_root_entry) = entries.last().cloned() {
+                edits.push(Edit {
+                    row_index: *row_index,
+                    pointer: removed_root_entry.pointer.clone(),
+                    old_value: removed_root_entry.value.clone(),
+                    new_value: None,
+                    old_value_type: None,
+                    reorders_keys: false,
+                    row_change: Some(RowChange::Removed(entries)),
+                });
+                root_entry = Some(removed_root_entry);
+            }
+        }
+        self.edit_history.push_group(edits);
+        // Removed row is emitted without value, a row without entries is emitted by its pointer
+        let root_entry = FlatJsonValue {
+            pointer: root_entry.map_or_else(
+                || {
+                    PointerKey::from_pointer(
+                        concat_string!(self.parent_pointer.pointer, "/", from.to_string()),
+                        ValueType::Null,
+                        self.parent_pointer.depth + 1,
+                        0,
+                    )
+                },
+                |root_entry| root_entry.pointer,
+            ),
+            value: None,
+        };
+        self.renumber_rows(from);
+        self.filtered_nodes
+            .retain(|row_index| rows.binary_search(row_index).is_err());
+        for row_index in self.filtered_nodes.iter_mut() {
+            // Count of removed rows before this one
+            *row_index -= rows.partition_point(|removed| removed < row_index);
+        }
+        if let Some(focused_cell) = self.focused_cell {
+            if focused_cell.row_index >= self.filtered_nodes.len() {
+                self.focused_cell = None;
+            }
+        }
+        self.refresh_rows(array_response, from, root_entry);
+        rows.len()
+    }
+
+    /// Move element at `from` to `to` in the array, elements in between are shifted. Rows can't be
+    /// moved while sorted as displayed order is not the array order, return false then.
+    pub fn move_row(&mut self, array_response: &mut ArrayResponse, from: usize, to: usize) -> bool {
+        if from == to || from >= self.nodes.len() || to >= self.nodes.len() {
+            return false;
+        }
+        if self.column_sort.is_some() {
+            return false;
+        }
+        self.move_row_entries(array_response, from, to);
+        true
+    }
+
+    /// Move element at `from` to `to` in the array, sorted rows keep their displayed order
+    fn move_row_entries(&mut self, array_response: &mut ArrayResponse, from: usize, to: usize) {
+        let row = self.nodes.remove(from);
+        if let Some(root_entry) = row.entries.last() {
+            self.edit_history.push(Edit {
+                row_index: from,
+                pointer: root_entry.pointer.clone(),
+                old_value: root_entry.value.clone(),
+                new_value: root_entry.value.clone(),
+                old_value_type: None,
+                reorders_keys: false,
+                row_change: Some(RowChange::Moved(to)),
+            });
+        }
+        self.nodes.insert(to, row);
+        let first = from.min(to);
+        self.renumber_rows(first);
+        let new_index = |row_index: usize| {
+            if row_index == from {
+                to
+            } else if from < to && (from + 1..=to).contains(&row_index) {
+                row_index - 1
+            } else if to < from && (to..from).contains(&row_index) {
+                row_index + 1
+            } else {
+                row_index
+            }
+        };
+        for row_index in self.filtered_nodes.iter_mut() {
+            *row_index = new_index(*row_index);
+        }
+        // Without sort rows are displayed in array order
+        if self.column_sort.is_none() {
+            self.filtered_nodes.sort_unstable();
+        }
+        if let Some(ref mut focused_cell) = self.focused_cell {
+            if let Some(table_row_index) = self
+                .filtered_nodes
+                .iter()
+                .position(|row_index| *row_index == to)
+            {
+                focused_cell.row_index = table_row_index;
+            }
         }
+        // Moved row is emitted at its new position, like a removed row
+        let root_entry = self.nodes[to]
+            .entries
+            .last()
+            .cloned()
+            .unwrap_or_else(|| FlatJsonValue {
+                pointer: PointerKey::from_pointer(
+                    concat_string!(self.parent_pointer.pointer, "/", to.to_string()),
+                    ValueType::Null,
+                    self.parent_pointer.depth + 1,
+                    0,
+                ),
+                value: None,
+            });
+        self.refresh_rows(array_response, first, root_entry);
+    }
+
+    /// After insertion or deletion, pointers of rows starting at `from` contain their previous index
+    fn renumber_rows(&mut self, from: usize) {
         // Performance are not good on large json but hopefully the feature is used rarely
-        // We need to update all json pointer coming after the new row
+        // We need to update all json pointer coming after the changed row
         // For that we substring the pointer to remove the "prefix" containing the index in the json array
         let substring_len = self.parent_pointer.pointer.len() + 1;
-        for i in new_index..self.nodes.len() {
-            self.nodes[i].index = i + 1;
-            let substring_len = substring_len + (i.checked_ilog10().unwrap_or(0) + 1) as usize;
-            let new_prefix = concat_string!(self.parent_pointer.pointer, "/", (i + 1).to_string());
+        for i in from..self.nodes.len() {
+            let previous_index = self.nodes[i].index;
+            let substring_len =
+                substring_len + (previous_index.checked_ilog10().unwrap_or(0) + 1) as usize;
+            let new_prefix = concat_string!(self.parent_pointer.pointer, "/", i.to_string());
+            self.nodes[i].index = i;
             self.nodes[i].entries.iter_mut().for_each(|e| {
                 e.pointer.pointer = concat_string!(new_prefix, e.pointer.pointer[substring_len..]);
-            })
+            });
+            self.nodes[i].entries[0].value = Some(i.to_string());
         }
-        let new_entry_pointer =
-            concat_string!(self.parent_pointer.pointer, "/", new_index.to_string());
-        self.nodes.insert(
-            new_index,
-            JsonArrayEntries {
-                entries: vec![
-                    row_number_entry(new_index, 0, new_entry_pointer.as_str()),
-                    FlatJsonValue {
-                        pointer: PointerKey {
-                            pointer: new_entry_pointer,
-                            value_type: ValueType::Object(true, 0),
-                            depth,
-                            position: 0,
-                            column_id: 0,
-                        },
-                        value: Some("{}".to_string()),
-                    },
-                ],
-                index: new_index,
+    }
+
+    /// Once rows starting at `from` changed of index, opened sub tables of these rows are closed and
+    /// the change is emitted
+    fn refresh_rows(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        from: usize,
+        changed_root_entry: FlatJsonValue<String>,
+    ) {
+        self.windows.retain(|window| window.id() < from);
+        self.selection_anchor = None;
+        self.cache.borrow_mut().evict();
+        self.parent_pointer.value_type = ValueType::Array(self.nodes.len());
+        if self.is_sub_table {
+            array_response
+                .edited_value
+                .push(self.serialize_parent_array());
+        } else {
+            array_response.edited_value.push(changed_root_entry);
+        }
+    }
+
+    pub(crate) fn serialize_parent_array(&self) -> FlatJsonValue<String> {
+        let mut entries = self
+            .nodes
+            .iter()
+            .flat_map(|row| row.entries.clone())
+            .collect::<Vec<FlatJsonValue<String>>>();
+        let mut parent_pointer = PointerKey {
+            pointer: String::new(),
+            value_type: ValueType::Array(self.nodes.len()),
+            depth: 0,
+            position: 0,
+            column_id: 0,
+        };
+        entries.push(FlatJsonValue {
+            pointer: parent_pointer.clone(),
+            value: None,
+        });
+        // entries.iter().for_each(|e| println!("{} -> {:?}", e.pointer.pointer, e.value));
+        let updated_array =
+            serialize_to_json_with_option::<String>(&mut entries, self.parent_pointer.depth + 1)
+                .to_json();
+        parent_pointer.pointer = self.parent_pointer.pointer.clone();
+        FlatJsonValue {
+            pointer: parent_pointer,
+            value: Some(updated_array),
+        }
+    }
+
+    /// Column only containing booleans and nulls, edited with a dropdown instead of a text field
+    pub fn is_bool_column(&self, column: &Column<'array>) -> bool {
+        let column_types = self.column_types(column);
+        column_types.contains_key(&CellType::Bool)
+            && column_types
+                .keys()
+                .all(|cell_type| matches!(cell_type, CellType::Bool | CellType::Null))
+    }
+
+    /// Dropdown opened on true, false and null. Return the selected value, empty for null, or None
+    /// when dropdown is closed without selection. Return None while nothing happened.
+    fn bool_editor(ui: &mut Ui, cell_id: usize, value: &str) -> Option<Option<String>> {
+        let popup_menu_id = Id::new(("bool_editor", cell_id));
+        let popup_id = ui.make_persistent_id(popup_menu_id).with("popup");
+        if !ui.data(|d| d.get_temp::<bool>(popup_id)).unwrap_or(false) {
+            // Dropdown is opened as soon as cell is edited
+            ui.data_mut(|d| d.insert_temp(popup_id, true));
+            ui.memory_mut(|m| m.open_popup(popup_id));
+        }
+        let mut selected_value = None;
+        let response = PopupMenu::new(popup_menu_id).show_ui(
+            ui,
+            |ui| {
+                ui.add(egui::Button::new(if value.is_empty() {
+                    "null"
+                } else {
+                    value
+                }))
+            },
+            |ui| {
+                for option in ["true", "false", "null"] {
+                    let option_value = if option == "null" { "" } else { option };
+                    if ui.selectable_label(value == option_value, option).clicked() {
+                        selected_value = Some(option_value.to_string());
+                    }
+                }
             },
         );
-        self.filtered_nodes
-            .insert(table_row_index + above_or_below as usize, new_index);
-        self.cache.borrow_mut().evict();
+        if selected_value.is_some() {
+            ui.memory_mut(|m| m.close_popup());
+        } else if response.inner.is_some() {
+            return None;
+        }
+        ui.data_mut(|d| d.remove::<bool>(popup_id));
+        Some(selected_value)
     }
 
     #[inline]
-    fn columns<'a>(&'a self, pinned_column_table: bool) -> &'a Vec<Column<'array>> {
+    pub(crate) fn columns<'a>(&'a self, pinned_column_table: bool) -> &'a Vec<Column<'array>> {
         if pinned_column_table {
             &self.column_pinned
         } else {
@@ -1214,6 +3370,68 @@ impl<'array> ArrayTable<'array> {
         }
     }
 
+    /// Element at `row_index` serialized as json, the same way root element is serialized after an update
+    pub fn row_to_json(&self, row_index: usize) -> String {
+        let (root_node, entries) = self.nodes[row_index].entries.split_last().unwrap();
+        let value = serialize_to_json_with_option::<String>(
+            &mut entries.to_vec(),
+            root_node.pointer.depth + 1,
+        );
+        serde_json::to_string_pretty(&value).unwrap()
+    }
+
+    /// Count of each type of value in the column, cached until next change on data
+    pub fn column_types(&self, column: &Column<'array>) -> ColumnTypes {
+        let mut cache_ref_mut = self.cache.borrow_mut();
+        let cache = cache_ref_mut
+            .cache::<crate::components::cache::FrameCache<ColumnTypes, CacheColumnTypes>>();
+        cache.get((column, &self.parent_pointer.pointer), &self.nodes)
+    }
+
+    /// Aggregates of the numbers of the column over filtered rows, cached until next change on data
+    /// or filters
+    pub fn column_aggregates(&self, column: &Column<'array>) -> Option<Aggregates> {
+        let mut cache_ref_mut = self.cache.borrow_mut();
+        let cache = cache_ref_mut
+            .cache::<crate::components::cache::FrameCache<Option<Aggregates>, CacheAggregates>>();
+        cache.get(
+            (column, &self.parent_pointer.pointer),
+            (&self.nodes, &self.filtered_nodes),
+        )
+    }
+
+    fn aggregates_footer(&self, ui: &mut Ui) {
+        let column = self.focused_cell.as_ref().and_then(|cell| {
+            self.columns(cell.is_pinned_column_table)
+                .get(cell.column_index)
+                .filter(|column| !column.name.is_empty() && column.name != ROW_NUMBER_COLUMN)
+        });
+        let Some(column) = column else {
+            ui.label("Focus a cell to show aggregates of its column");
+            return;
+        };
+        let Some(aggregates) = self.column_aggregates(column) else {
+            ui.label(format!("No number in column {}", column.name));
+            return;
+        };
+        let display_options = self.display_options;
+        let number = |value: f64| {
+            display_options
+                .display_number(&value.to_string())
+                .into_owned()
+        };
+        ui.label(format!(
+            "{}: Sum {}  Min {}  Max {}  Average {}  ({} numbers, {} other cells excluded)",
+            column.name,
+            number(aggregates.sum),
+            number(aggregates.min),
+            number(aggregates.max),
+            number(aggregates.mean()),
+            aggregates.count,
+            aggregates.excluded_count
+        ));
+    }
+
     fn get_pointer_index_from_cache(
         &self,
         pinned_column_table: bool,
@@ -1241,18 +3459,10 @@ impl<'array> ArrayTable<'array> {
             || matches!(column.value_type, ValueType::Null))
     }
 
-    fn open_subtable(
-        row_index: usize,
-        entry: &FlatJsonValue<String>,
-        content: String,
-    ) -> Option<SubTable<'array>> {
-        Some(SubTable::new(
-            entry.pointer.clone(),
-            content,
-            entry.pointer.value_type,
-            row_index,
-            entry.pointer.depth,
-        ))
+    #[inline]
+    fn is_sortable(column: &Column) -> bool {
+        !(matches!(column.value_type, ValueType::Object(_, _))
+            || matches!(column.value_type, ValueType::Array(_)))
     }
 
     #[inline]
@@ -1266,13 +3476,30 @@ impl<'array> ArrayTable<'array> {
             self.update_sub_tables_value(&mut updated_entry, row_index);
         }
 
+        let old_value = self.nodes[row_index]
+            .entries
+            .iter()
+            .find(|entry| entry.pointer.pointer.eq(&updated_entry.pointer.pointer))
+            .and_then(|entry| entry.value.clone());
+        let pointer = updated_entry.pointer.clone();
+        let new_value = updated_entry.value.clone();
         let value_changed = Self::update_row(
             &mut self.nodes[row_index].entries,
             updated_entry,
             self.is_sub_table,
             self.last_parsed_max_depth,
+            self.all_as_strings,
         );
         if value_changed {
+            self.edit_history.push(Edit {
+                row_index,
+                pointer,
+                old_value,
+                new_value,
+                old_value_type: None,
+                reorders_keys: false,
+                row_change: None,
+            });
             self.cache.borrow_mut().evict();
         }
         value_changed
@@ -1280,9 +3507,16 @@ impl<'array> ArrayTable<'array> {
 
     #[inline]
     fn update_sub_tables_value(&mut self, updated_entry: &FlatJsonValue<String>, row_index: usize) {
+        let locked_pointers = self.locked_cell_pointers(row_index);
         for subtable in self.windows.iter_mut() {
             if subtable.id() == row_index {
                 subtable.update_nodes(updated_entry.pointer.clone(), updated_entry.value.clone());
+                if subtable.is_cell_value {
+                    // Sub table may have been parsed again from the updated cell
+                    subtable.set_edit_options(self.edit_options);
+                    subtable.set_display_options(self.display_options);
+                    subtable.lock_pointers(&locked_pointers);
+                }
                 break;
             }
         }
@@ -1294,6 +3528,7 @@ impl<'array> ArrayTable<'array> {
         mut updated_entry: FlatJsonValue<String>,
         is_sub_table: bool,
         last_parsed_max_depth: u8,
+        all_as_strings: bool,
     ) -> bool {
         let mut value_changed = false;
         if let Some(entry) = row_entries
@@ -1318,48 +3553,627 @@ impl<'array> ArrayTable<'array> {
                 },
             );
         }
-        // After update we serialize root element then parse it again so nested serialized object are updated as well
         if value_changed && !is_sub_table {
-            let root_node = row_entries.pop().unwrap();
-            let value1 = serialize_to_json_with_option::<String>(
-                &mut row_entries.clone(),
-                root_node.pointer.depth + 1,
-            );
-            let new_root_node_serialized_json = serde_json::to_string_pretty(&value1).unwrap();
-            let result = JSONParser::parse(
-                new_root_node_serialized_json.as_str(),
-                ParseOptions::default()
-                    .prefix(root_node.pointer.pointer.clone())
-                    .start_depth(root_node.pointer.depth + 1)
-                    .parse_array(false)
-                    .max_depth(last_parsed_max_depth),
-            )
-            .unwrap()
-            .to_owned();
-            for newly_updated_value in result.json {
+            Self::serialize_row(row_entries, last_parsed_max_depth, all_as_strings);
+        }
+        value_changed
+    }
+
+    /// Replace the row root by `updated_entry`, the same value with its keys reordered. Unlike an
+    /// edit, the row is not serialized again from its entries, see `follow_key_order`.
+    fn reorder_row_keys(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        updated_entry: FlatJsonValue<String>,
+        row_index: usize,
+    ) {
+        let row_entries = &mut self.nodes[row_index].entries;
+        let Some(root_node) = row_entries.last_mut() else {
+            return;
+        };
+        root_node.value = updated_entry.value.clone();
+        if self.is_sub_table {
+            array_response
+                .edited_value
+                .push(self.serialize_parent_array());
+        } else {
+            Self::follow_key_order(row_entries, self.last_parsed_max_depth);
+            array_response.edited_value.push(updated_entry);
+        }
+        self.cache.borrow_mut().evict();
+    }
+
+    /// Once the row root is replaced with its keys reordered, entries take the position their key
+    /// has in it, so the row keeps being serialized with keys in that order.
+    fn follow_key_order(row_entries: &mut [FlatJsonValue<String>], last_parsed_max_depth: u8) {
+        let (root_node, entries) = row_entries.split_last_mut().unwrap();
+        let options = ParseOptions::default()
+            .prefix(root_node.pointer.pointer.clone())
+            .start_depth(root_node.pointer.depth + 1)
+            .parse_array(false)
+            .max_depth(last_parsed_max_depth);
+        let Some(Ok(result)) = root_node
+            .value
+            .as_ref()
+            .map(|json| JSONParser::parse(json.as_str(), options))
+        else {
+            return;
+        };
+        for parsed_entry in result.to_owned().json {
+            if let Some(entry) = entries
+                .iter_mut()
+                .find(|entry| entry.pointer.pointer.eq(&parsed_entry.pointer.pointer))
+            {
+                entry.pointer.position = parsed_entry.pointer.position;
+                // Raw json of nested values has its keys in the new order as well
                 if matches!(
-                    newly_updated_value.pointer.value_type,
-                    ValueType::Object(..)
+                    entry.pointer.value_type,
+                    ValueType::Object(..) | ValueType::Array(_)
                 ) {
-                    row_entries
-                        .iter_mut()
-                        .find(|e| e.pointer.pointer.eq(&newly_updated_value.pointer.pointer))
-                        .map(|entry_to_update| entry_to_update.value = newly_updated_value.value);
+                    entry.value = parsed_entry.value;
                 }
             }
-            // let line_number_entry = mem::take(&mut self.nodes[row_index].entries[0]);
-            // self.nodes[row_index].entries.clear();
-            // self.nodes[row_index].entries.push(line_number_entry);
-            // self.nodes[row_index].entries.extend(result.json);
-            row_entries.push(FlatJsonValue {
-                pointer: root_node.pointer,
-                value: Some(new_root_node_serialized_json),
-            });
         }
-        value_changed
     }
 
-    #[inline]
+    /// Serialize root element then parse it again so nested serialized object are updated as well.
+    /// With `all_as_strings`, values coerced to strings are written with their original type.
+    fn serialize_row(
+        row_entries: &mut Vec<FlatJsonValue<String>>,
+        last_parsed_max_depth: u8,
+        all_as_strings: bool,
+    ) {
+        let root_node = row_entries.pop().unwrap();
+        let options = ParseOptions::default()
+            .prefix(root_node.pointer.pointer.clone())
+            .start_depth(root_node.pointer.depth + 1)
+            .parse_array(false)
+            .max_depth(last_parsed_max_depth);
+        let mut entries = row_entries.clone();
+        if all_as_strings {
+            // Previous serialization still has original types
+            if let Some(Ok(result)) = root_node
+                .value
+                .as_ref()
+                .map(|json| JSONParser::parse(json.as_str(), options.clone()))
+            {
+                restore_coerced_types(&mut entries, &result.to_owned().json);
+            }
+        }
+        let value1 =
+            serialize_to_json_with_option::<String>(&mut entries, root_node.pointer.depth + 1);
+        let new_root_node_serialized_json = serde_json::to_string_pretty(&value1).unwrap();
+        let result = JSONParser::parse(new_root_node_serialized_json.as_str(), options)
+            .unwrap()
+            .to_owned();
+        for newly_updated_value in result.json {
+            // Serialized nested arrays are refreshed as well, their elements being entries of
+            // the row once flattened
+            if matches!(
+                newly_updated_value.pointer.value_type,
+                ValueType::Object(..) | ValueType::Array(_)
+            ) {
+                row_entries
+                    .iter_mut()
+                    .find(|e| e.pointer.pointer.eq(&newly_updated_value.pointer.pointer))
+                    .map(|entry_to_update| entry_to_update.value = newly_updated_value.value);
+            }
+        }
+        // let line_number_entry = mem::take(&mut self.nodes[row_index].entries[0]);
+        // self.nodes[row_index].entries.clear();
+        // self.nodes[row_index].entries.push(line_number_entry);
+        // self.nodes[row_index].entries.extend(result.json);
+        row_entries.push(FlatJsonValue {
+            pointer: root_node.pointer,
+            value: Some(new_root_node_serialized_json),
+        });
+    }
+
+    /// Convert cells of `column` to `value_type`, Number, String or Bool, e.g. numbers stored as
+    /// strings. Null cells are left untouched. Converted cells are recorded as a single edit,
+    /// undoing it restores their previous value and type.
+    /// Return count of converted cells and count of cells which could not be converted, or an
+    /// error when the column is locked.
+    pub fn convert_column(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        column: &Column,
+        value_type: ValueType,
+    ) -> Result<(usize, usize), String> {
+        self.check_column_unlocked(column)?;
+        let mut converted_count = 0;
+        let mut skipped_count = 0;
+        let mut edits = vec![];
+        for row_index in 0..self.nodes.len() {
+            let row = &mut self.nodes[row_index];
+            let pointer = concat_string!(
+                self.parent_pointer.pointer,
+                "/",
+                row.index.to_string(),
+                column.name
+            );
+            let Some(entry) = row
+                .entries
+                .iter_mut()
+                .find(|entry| entry.pointer.pointer.eq(&pointer))
+            else {
+                continue;
+            };
+            let Some(ref value) = entry.value else {
+                continue;
+            };
+            if matches!(entry.pointer.value_type, ValueType::Null)
+                || mem::discriminant(&entry.pointer.value_type) == mem::discriminant(&value_type)
+            {
+                continue;
+            }
+            let Some(new_value) = coerce_value(value, entry.pointer.value_type, value_type) else {
+                skipped_count += 1;
+                continue;
+            };
+            let old_value_type = entry.pointer.value_type;
+            let old_value = entry.value.replace(new_value);
+            entry.pointer.value_type = value_type;
+            let updated_entry = entry.clone();
+            converted_count += 1;
+            edits.push(Edit {
+                row_index,
+                pointer: updated_entry.pointer.clone(),
+                old_value,
+                new_value: updated_entry.value.clone(),
+                old_value_type: Some(old_value_type),
+                reorders_keys: false,
+                row_change: None,
+            });
+            if !self.is_sub_table {
+                Self::serialize_row(
+                    &mut self.nodes[row_index].entries,
+                    self.last_parsed_max_depth,
+                    self.all_as_strings,
+                );
+                array_response.edited_value.push(updated_entry.clone());
+            }
+            self.update_sub_tables_value(&updated_entry, row_index);
+        }
+        for c in self
+            .all_columns
+            .iter_mut()
+            .chain(self.column_selected.iter_mut())
+            .chain(self.column_pinned.iter_mut())
+        {
+            if c.name == column.name {
+                c.value_type = value_type;
+            }
+        }
+        if converted_count > 0 {
+            self.edit_history.push_group(edits);
+            self.cache.borrow_mut().evict();
+            if self.is_sub_table {
+                array_response
+                    .edited_value
+                    .push(self.serialize_parent_array());
+            }
+        }
+        Ok((converted_count, skipped_count))
+    }
+
+    /// Change type of the value at `cell_location` with `cycle_value_type`, e.g. string "42" to
+    /// number 42. Return the new type, or why the value can't change of type.
+    pub fn cycle_cell_type(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        cell_location: CellLocation,
+    ) -> Result<ValueType, String> {
+        let row_index = *self
+            .filtered_nodes
+            .get(cell_location.row_index)
+            .ok_or_else(|| format!("No row at index {}", cell_location.row_index))?;
+        let column = self
+            .columns(cell_location.is_pinned_column_table)
+            .get(cell_location.column_index)
+            .ok_or_else(|| format!("No column at index {}", cell_location.column_index))?;
+        self.check_column_unlocked(column)?;
+        let pointer = concat_string!(
+            self.parent_pointer.pointer,
+            "/",
+            self.nodes[row_index].index.to_string(),
+            column.name
+        );
+        let entry = self.nodes[row_index]
+            .find_node_at(&pointer)
+            .filter(|entry| entry.value.is_some())
+            .ok_or_else(|| "Cell has no value".to_string())?;
+        let value = entry.value.as_ref().unwrap();
+        let Some((new_value, value_type)) = cycle_value_type(value, entry.pointer.value_type)
+        else {
+            return Err(if matches!(entry.pointer.value_type, ValueType::String) {
+                format!("\"{}\" is neither a number nor a boolean", value)
+            } else {
+                "Only strings, numbers and booleans can change of type".to_string()
+            });
+        };
+        let mut updated_entry = FlatJsonValue {
+            pointer: entry.pointer.clone(),
+            value: Some(new_value),
+        };
+        updated_entry.pointer.value_type = value_type;
+        self.edit_history.push(Edit {
+            row_index,
+            pointer: updated_entry.pointer.clone(),
+            old_value: entry.value.clone(),
+            new_value: updated_entry.value.clone(),
+            old_value_type: Some(entry.pointer.value_type),
+            reorders_keys: false,
+            row_change: None,
+        });
+        self.set_cell_type(array_response, updated_entry, row_index);
+        Ok(value_type)
+    }
+
+    /// Write value and type of `updated_entry`, `edit_cell` only changing the type of null values
+    fn set_cell_type(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        updated_entry: FlatJsonValue<String>,
+        row_index: usize,
+    ) {
+        let row_entries = &mut self.nodes[row_index].entries;
+        let updated_entry = if let Some(entry) = row_entries
+            .iter_mut()
+            .find(|entry| entry.pointer.pointer.eq(&updated_entry.pointer.pointer))
+        {
+            entry.value = updated_entry.value;
+            entry.pointer.value_type = updated_entry.pointer.value_type;
+            entry.clone()
+        } else if updated_entry.value.is_some() {
+            // Cell removed by a merge of columns
+            row_entries.insert(row_entries.len() - 1, updated_entry.clone());
+            updated_entry
+        } else {
+            return;
+        };
+        if self.is_sub_table {
+            array_response
+                .edited_value
+                .push(self.serialize_parent_array());
+        } else {
+            Self::serialize_row(
+                &mut self.nodes[row_index].entries,
+                self.last_parsed_max_depth,
+                self.all_as_strings,
+            );
+            array_response.edited_value.push(updated_entry.clone());
+        }
+        self.update_sub_tables_value(&updated_entry, row_index);
+        self.cache.borrow_mut().evict();
+    }
+
+    // Return true once the window is closed
+    fn column_reshape_window(
+        &mut self,
+        ctx: &Context,
+        array_response: &mut ArrayResponse,
+        reshape: &mut ColumnReshape,
+    ) -> bool {
+        let Some(column) = self
+            .all_columns
+            .iter()
+            .find(|c| c.name == reshape.column)
+            .cloned()
+        else {
+            return true;
+        };
+        let mut applied = false;
+        let mut cancelled = false;
+        let title = if reshape.is_split {
+            "Split column"
+        } else {
+            "Merge columns"
+        };
+        egui::Window::new(title)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("column_reshape").show(ui, |ui| {
+                    if reshape.is_split {
+                        ui.label("Delimiter");
+                        ui.text_edit_singleline(&mut reshape.separator);
+                        ui.end_row();
+                        ui.label("New column");
+                        ui.text_edit_singleline(&mut reshape.other_column);
+                        ui.end_row();
+                    } else {
+                        ui.label(format!("Merge {} with", column.name));
+                        egui::ComboBox::from_id_salt("merged_column")
+                            .selected_text(reshape.other_column.as_str())
+                            .show_ui(ui, |ui| {
+                                for other in self.all_columns.iter().filter(|c| {
+                                    c.name != column.name
+                                        && c.name != ROW_NUMBER_COLUMN
+                                        && !matches!(
+                                            c.value_type,
+                                            ValueType::Array(_) | ValueType::Object(..)
+                                        )
+                                }) {
+                                    ui.selectable_value(
+                                        &mut reshape.other_column,
+                                        other.name.to_string(),
+                                        other.name.as_ref(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                        ui.label("Separator");
+                        ui.text_edit_singleline(&mut reshape.separator);
+                        ui.end_row();
+                    }
+                });
+                if let Some(ref error) = reshape.error {
+                    ui.colored_label(Color32::RED, error);
+                }
+                ui.horizontal(|ui| {
+                    applied = ui
+                        .add_enabled(!reshape.other_column.is_empty(), egui::Button::new(title))
+                        .clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+        if applied {
+            if reshape.is_split {
+                // New column is a sibling of the split one
+                let parent = column
+                    .name
+                    .rsplit_once('/')
+                    .map_or("", |(parent, _)| parent);
+                let new_column =
+                    concat_string!(parent, "/", escape_pointer_token(&reshape.other_column));
+                match self.split_column(array_response, &column, &reshape.separator, &new_column) {
+                    Ok(_) => return true,
+                    Err(error) => reshape.error = Some(error),
+                }
+            } else if let Some(other) = self
+                .all_columns
+                .iter()
+                .find(|c| c.name == reshape.other_column)
+                .cloned()
+            {
+                match self.merge_columns(array_response, &column, &other, &reshape.separator) {
+                    Ok(_) => return true,
+                    Err(error) => reshape.error = Some(error),
+                }
+            }
+        }
+        cancelled
+    }
+
+    /// Concatenate values of `first` and `second` columns with `separator` into `first`, `second`
+    /// cells are removed. Rows missing one of the columns, or having it null, get the other value
+    /// alone, rows with an array or an object in one of them are left as they are. Merged value
+    /// keeps the type of its sources when they share it and it is still a literal of that type,
+    /// e.g. numbers merged without separator, otherwise it is a string. Merge is undone at once.
+    /// Return count of merged rows, or an error when one of the columns is locked.
+    pub fn merge_columns(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        first: &Column,
+        second: &Column,
+        separator: &str,
+    ) -> Result<usize, String> {
+        self.check_column_unlocked(first)?;
+        self.check_column_unlocked(second)?;
+        let mut edits = vec![];
+        for row_index in 0..self.nodes.len() {
+            let row = &self.nodes[row_index];
+            let first_entry = row.find_node_at(&self.cell_pointer(row.index, &first.name));
+            let second_entry = row.find_node_at(&self.cell_pointer(row.index, &second.name));
+            let scalar_value = |entry: Option<&FlatJsonValue<String>>| match entry {
+                Some(entry) => match entry.pointer.value_type {
+                    ValueType::Array(_) | ValueType::Object(..) => Err(()),
+                    ValueType::Null => Ok(None),
+                    value_type => Ok(entry.value.clone().map(|value| (value, value_type))),
+                },
+                None => Ok(None),
+            };
+            let (Ok(first_value), Ok(second_value)) =
+                (scalar_value(first_entry), scalar_value(second_entry))
+            else {
+                continue;
+            };
+            let (merged_value, value_type) = match (first_value, second_value) {
+                (Some((first_value, first_type)), Some((second_value, second_type))) => {
+                    let merged_value = concat_string!(first_value, separator, second_value);
+                    let value_type = if first_type == second_type
+                        && edited_value_type(ValueType::Null, &merged_value) == first_type
+                    {
+                        first_type
+                    } else {
+                        ValueType::String
+                    };
+                    (merged_value, value_type)
+                }
+                (Some(value), None) | (None, Some(value)) => value,
+                (None, None) => continue,
+            };
+            let mut cells = vec![self.cell_entry(row_index, first, Some(merged_value), value_type)];
+            if second_entry.is_some() {
+                cells.push(self.cell_entry(row_index, second, None, ValueType::String));
+            }
+            edits.extend(self.set_row_cells(array_response, row_index, cells));
+        }
+        // Both cells of a row are edited
+        let merged_count = edits
+            .iter()
+            .filter(|edit| edit.pointer.column_id == first.id)
+            .count();
+        self.on_columns_reshaped(array_response, edits);
+        Ok(merged_count)
+    }
+
+    /// Split values of `column` at the first `delimiter`: the part before it stays in `column` and
+    /// the part after it goes to `new_column`, added to columns. Values without delimiter, as well as
+    /// arrays and objects, are left as they are. Split is undone at once. Return count of split rows,
+    /// or an error when the column is locked.
+    pub fn split_column(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        column: &Column,
+        delimiter: &str,
+        new_column: &str,
+    ) -> Result<usize, String> {
+        self.check_column_unlocked(column)?;
+        if delimiter.is_empty() {
+            return Err("Delimiter can't be empty".to_string());
+        }
+        if self.all_columns.iter().any(|c| c.name == new_column) {
+            return Err(format!("Column {} already exists", new_column));
+        }
+        let mut hasher = DefaultHasher::new();
+        hasher.write(new_column.as_bytes());
+        let new_column = Column {
+            name: Cow::from(new_column.to_string()),
+            depth: column.depth,
+            value_type: ValueType::String,
+            seen_count: 0,
+            order: self.all_columns.len(),
+            id: hasher.finish() as usize,
+        };
+        let mut edits = vec![];
+        for row_index in 0..self.nodes.len() {
+            let row = &self.nodes[row_index];
+            let Some(entry) = row.find_node_at(&self.cell_pointer(row.index, &column.name)) else {
+                continue;
+            };
+            if matches!(
+                entry.pointer.value_type,
+                ValueType::Array(_) | ValueType::Object(..) | ValueType::Null
+            ) {
+                continue;
+            }
+            let Some((before, after)) = entry
+                .value
+                .as_ref()
+                .and_then(|value| value.split_once(delimiter))
+            else {
+                continue;
+            };
+            let cells = vec![
+                self.cell_entry(
+                    row_index,
+                    column,
+                    Some(before.to_string()),
+                    ValueType::String,
+                ),
+                self.cell_entry(
+                    row_index,
+                    &new_column,
+                    Some(after.to_string()),
+                    ValueType::String,
+                ),
+            ];
+            edits.extend(self.set_row_cells(array_response, row_index, cells));
+        }
+        let split_count = edits.len() / 2;
+        if split_count > 0 {
+            let mut new_column = new_column;
+            new_column.seen_count = split_count;
+            self.all_columns.push(new_column);
+            self.update_selected_columns(self.max_depth);
+        }
+        self.on_columns_reshaped(array_response, edits);
+        Ok(split_count)
+    }
+
+    fn cell_pointer(&self, row_index: usize, column_name: &str) -> String {
+        concat_string!(
+            self.parent_pointer.pointer,
+            "/",
+            row_index.to_string(),
+            column_name
+        )
+    }
+
+    // Cell of `column` in row at `row_index`, or the removal of the cell when value is None
+    fn cell_entry(
+        &self,
+        row_index: usize,
+        column: &Column,
+        value: Option<String>,
+        value_type: ValueType,
+    ) -> FlatJsonValue<String> {
+        FlatJsonValue {
+            pointer: PointerKey {
+                pointer: self.cell_pointer(self.nodes[row_index].index, &column.name),
+                value_type,
+                depth: column.depth,
+                position: usize::MAX,
+                column_id: column.id,
+            },
+            value,
+        }
+    }
+
+    // Set value and type of row cells, new keys are added, then serialize the row once. Return
+    // the edits to record.
+    fn set_row_cells(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        row_index: usize,
+        cells: Vec<FlatJsonValue<String>>,
+    ) -> Vec<Edit> {
+        let row_entries = &mut self.nodes[row_index].entries;
+        let mut edits = Vec::with_capacity(cells.len());
+        for cell in cells.iter() {
+            let (old_value, old_value_type) = if let Some(entry) = row_entries
+                .iter_mut()
+                .find(|entry| entry.pointer.pointer.eq(&cell.pointer.pointer))
+            {
+                let old_value = mem::replace(&mut entry.value, cell.value.clone());
+                let old_value_type =
+                    mem::replace(&mut entry.pointer.value_type, cell.pointer.value_type);
+                (old_value, old_value_type)
+            } else {
+                if cell.value.is_some() {
+                    row_entries.insert(row_entries.len() - 1, cell.clone());
+                }
+                // Once undone, the added cell is left without value like a removed one
+                (None, cell.pointer.value_type)
+            };
+            edits.push(Edit {
+                row_index,
+                pointer: cell.pointer.clone(),
+                old_value,
+                new_value: cell.value.clone(),
+                old_value_type: Some(old_value_type),
+                reorders_keys: false,
+                row_change: None,
+            });
+        }
+        if !self.is_sub_table {
+            Self::serialize_row(row_entries, self.last_parsed_max_depth, self.all_as_strings);
+        }
+        for cell in cells {
+            self.update_sub_tables_value(&cell, row_index);
+            if !self.is_sub_table {
+                array_response.edited_value.push(cell);
+            }
+        }
+        edits
+    }
+
+    fn on_columns_reshaped(&mut self, array_response: &mut ArrayResponse, edits: Vec<Edit>) {
+        if !edits.is_empty() {
+            self.edit_history.push_group(edits);
+            self.cache.borrow_mut().evict();
+            if self.is_sub_table {
+                array_response
+                    .edited_value
+                    .push(self.serialize_parent_array());
+            }
+        }
+    }
+
+    #[inline]
     fn get_pointer_index(
         parent_pointer: &PointerKey,
         columns: &Vec<Column>,
@@ -1427,7 +4241,20 @@ impl<'array> ArrayTable<'array> {
         self.do_filter_column();
     }
 
-    fn do_filter_column(&mut self) {
+    fn on_sort_column(&mut self, column: String) {
+        // Toggle: ascending -> descending -> unsorted
+        self.column_sort = match self.column_sort.take() {
+            Some((sorted_column, SortOrder::Ascending)) if sorted_column.eq(&column) => {
+                Some((sorted_column, SortOrder::Descending))
+            }
+            Some((sorted_column, SortOrder::Descending)) if sorted_column.eq(&column) => None,
+            _ => Some((column, SortOrder::Ascending)),
+        };
+        self.do_filter_column();
+    }
+
+    pub fn do_filter_column(&mut self) {
+        self.filter_rows_debounce.cancel();
         if self.columns_filter.is_empty() {
             self.filtered_nodes = (0..self.nodes.len()).collect::<Vec<usize>>();
         } else {
@@ -1437,7 +4264,53 @@ impl<'array> ArrayTable<'array> {
                 &self.columns_filter,
             );
         }
+        self.filter_rows_error = None;
+        if !self.filter_rows.is_empty() && self.filter_rows_query {
+            match crate::parser::filter_rows_by_query(
+                &self.nodes,
+                &self.filtered_nodes,
+                &self.parent_pointer.pointer,
+                &self.filter_rows,
+                self.filter_rows_case_sensitive,
+            ) {
+                Ok(filtered_nodes) => self.filtered_nodes = filtered_nodes,
+                Err(error) => self.filter_rows_error = Some(error),
+            }
+        } else if !self.filter_rows.is_empty() && self.filter_rows_regex {
+            match crate::parser::filter_rows_by_regex(
+                &self.nodes,
+                &self.filtered_nodes,
+                &self.filter_rows,
+                self.filter_rows_case_sensitive,
+            ) {
+                Ok(filtered_nodes) => self.filtered_nodes = filtered_nodes,
+                Err(error) => self.filter_rows_error = Some(error),
+            }
+        } else if !self.filter_rows.is_empty() {
+            self.filtered_nodes = crate::parser::filter_rows_by_term(
+                &self.nodes,
+                &self.filtered_nodes,
+                &self.filter_rows,
+                self.filter_rows_case_sensitive,
+            );
+        }
+        if let Some((ref sorted_column, sort_order)) = self.column_sort {
+            if let Some(column) = self.all_columns.iter().find(|c| c.name.eq(sorted_column)) {
+                crate::parser::sort_rows(
+                    &self.nodes,
+                    &mut self.filtered_nodes,
+                    &self.parent_pointer.pointer,
+                    column,
+                    sort_order,
+                );
+            }
+        }
+        // Aggregates are computed over filtered rows
+        self.cache.borrow_mut().evict();
         self.next_frame_reset_scroll = true;
+        if self.display_options.hide_empty_columns {
+            self.update_empty_columns();
+        }
     }
 
     #[inline]
@@ -1445,6 +4318,51 @@ impl<'array> ArrayTable<'array> {
         &self.nodes
     }
 
+    /// Scroll to and focus the cell at `pointer`, nested pointers focus their displayed parent column
+    pub fn scroll_to_pointer(&mut self, pointer: &str) -> Result<(), String> {
+        let (table_row_index, key) = find_row_by_pointer(
+            &self.nodes,
+            &self.filtered_nodes,
+            &self.parent_pointer.pointer,
+            pointer,
+        )
+        .ok_or_else(|| format!("No row found at {}", pointer))?;
+        let mut focused_cell = CellLocation {
+            row_index: table_row_index,
+            column_index: 0,
+            is_pinned_column_table: false,
+        };
+        if !key.is_empty() {
+            let pinned_columns = self
+                .column_pinned
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, column)| (i, column, true));
+            let selected_columns = self
+                .column_selected
+                .iter()
+                .enumerate()
+                .map(|(i, column)| (i, column, false));
+            let (column_index, _, is_pinned_column_table) = pinned_columns
+                .chain(selected_columns)
+                .filter(|(_, column, _)| is_pointer_prefix(&column.name, key))
+                .max_by_key(|(_, column, _)| column.name.len())
+                .ok_or_else(|| format!("No column found at {}", pointer))?;
+            focused_cell.column_index = column_index;
+            focused_cell.is_pinned_column_table = is_pinned_column_table;
+        }
+        if !focused_cell.is_pinned_column_table {
+            self.scroll_to_column_number = focused_cell.column_index;
+            self.changed_arrow_horizontal_scroll = true;
+        }
+        self.scroll_to_row_number = table_row_index;
+        self.changed_arrow_vertical_scroll = true;
+        self.focused_cell = Some(focused_cell);
+        self.selection_anchor = None;
+        Ok(())
+    }
+
     pub fn reset_search(&mut self) {
         self.scroll_to_row.clear();
         self.matching_rows.clear();
@@ -1453,12 +4371,101 @@ impl<'array> ArrayTable<'array> {
         self.matching_row_selected = 0;
     }
 
+    /// Cells of displayed rows and columns containing the searched term, in display order. Like
+    /// `search_occurrences`, only string values are searched, ignoring case.
+    pub fn matching_cells(&self) -> Vec<CellLocation> {
+        if self.scroll_to_row.is_empty()
+            || !matches!(self.scroll_to_row_mode, ScrollToRowMode::MatchingTerm)
+        {
+            return vec![];
+        }
+        let term = self.scroll_to_row.to_lowercase();
+        let mut cells = vec![];
+        for (row_index, node_index) in self.filtered_nodes.iter().enumerate() {
+            let row = &self.nodes[*node_index];
+            let row_pointer =
+                concat_string!(self.parent_pointer.pointer, "/", row.index.to_string());
+            let columns = self
+                .column_pinned
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, column)| (i, true, column))
+                .chain(
+                    self.column_selected
+                        .iter()
+                        .enumerate()
+                        .map(|(i, column)| (i, false, column)),
+                );
+            for (column_index, is_pinned_column_table, column) in columns {
+                let is_match = row
+                    .find_node_at(&concat_string!(row_pointer, column.name))
+                    .filter(|entry| matches!(entry.pointer.value_type, ValueType::String))
+                    .and_then(|entry| entry.value.as_ref())
+                    .is_some_and(|value| value.to_lowercase().contains(&term));
+                if is_match {
+                    cells.push(CellLocation {
+                        row_index,
+                        column_index,
+                        is_pinned_column_table,
+                    });
+                }
+            }
+        }
+        cells
+    }
+
+    /// Focus the next cell containing the searched term after the focused cell, or the previous one
+    /// when `backward`, wrapping at the end. Return false when there is no match.
+    pub fn focus_next_matching_cell(&mut self, backward: bool) -> bool {
+        let cells = self.matching_cells();
+        if cells.is_empty() {
+            return false;
+        }
+        let position = |cell: &CellLocation| (cell.row_index, self.grid_column_index(cell));
+        let cell = match self.focused_cell.as_ref().map(position) {
+            Some(focused) if backward => cells
+                .iter()
+                .rev()
+                .find(|cell| position(cell) < focused)
+                .unwrap_or(cells.last().unwrap()),
+            Some(focused) => cells
+                .iter()
+                .find(|cell| position(cell) > focused)
+                .unwrap_or(&cells[0]),
+            None if backward => cells.last().unwrap(),
+            None => &cells[0],
+        };
+        let cell = *cell;
+        self.focused_cell = Some(cell);
+        self.selection_anchor = None;
+        self.scroll_to_row_number = cell.row_index;
+        self.changed_arrow_vertical_scroll = true;
+        if !cell.is_pinned_column_table {
+            self.scroll_to_column_number = cell.column_index;
+            self.changed_arrow_horizontal_scroll = true;
+        }
+        true
+    }
+
     fn handle_shortcut(&mut self, ui: &mut Ui, array_response: &mut ArrayResponse) {
         let mut copied_value = None;
+        let mut should_undo = false;
+        let mut should_redo = false;
+        let mut should_cycle_type = false;
+        let mut goto_matching_cell = None;
+        let selected_range = self.selected_range();
         let maybe_focused_id = ui.ctx().memory(|m| m.focused());
         ui.input_mut(|i| {
             if i.key_pressed(Key::Escape) {
                 self.focused_cell = None;
+                self.selection_anchor = None;
+            }
+            // Works from search field too, to cycle through matches while typing
+            if i.consume_key(Modifiers::SHIFT, Key::F3) {
+                goto_matching_cell = Some(true);
+            } else if i.consume_key(Modifiers::NONE, Key::F3) {
+                goto_matching_cell = Some(false);
             }
 
             let mut is_table_focused = false;
@@ -1469,53 +4476,40 @@ impl<'array> ArrayTable<'array> {
             }
             if is_table_focused {
                 if let Some(focused_cell) = self.focused_cell.as_mut() {
-                    if i.consume_key(Modifiers::NONE, Key::Tab) {
-                        if !focused_cell.is_pinned_column_table
-                            && focused_cell.column_index < self.column_selected.len() - 1
-                        {
-                            focused_cell.column_index += 1;
-                            self.scroll_to_column_number = focused_cell.column_index;
-                            self.changed_arrow_horizontal_scroll = true;
-                        } else if !focused_cell.is_pinned_column_table
-                            && focused_cell.row_index < self.filtered_nodes.len() - 1
-                        {
-                            focused_cell.column_index = 0;
-                            focused_cell.row_index += 1;
-                            self.scroll_to_row_number = focused_cell.row_index;
-                            self.changed_arrow_vertical_scroll = true;
-                        } else if focused_cell.is_pinned_column_table
-                            && focused_cell.column_index < self.column_pinned.len() - 1
-                        {
-                            focused_cell.column_index += 1;
-                        } else if focused_cell.is_pinned_column_table {
-                            focused_cell.column_index = 1;
-                            focused_cell.row_index += 1;
-                            self.scroll_to_row_number = focused_cell.row_index;
-                            self.changed_arrow_vertical_scroll = true;
-                        }
-                    }
-                    if i.consume_key(Modifiers::NONE, Key::ArrowLeft) {
-                        if !focused_cell.is_pinned_column_table && focused_cell.column_index > 0 {
-                            focused_cell.column_index -= 1;
-                            self.scroll_to_column_number = focused_cell.column_index;
-                            self.changed_arrow_horizontal_scroll = true;
-                        } else if focused_cell.is_pinned_column_table
-                            && focused_cell.column_index > 1
-                        {
-                            focused_cell.column_index -= 1;
+                    let previous_focused_cell = *focused_cell;
+                    for modifiers in [Modifiers::NONE, Modifiers::SHIFT] {
+                        if i.consume_key(modifiers, Key::Tab) {
+                            let cell = *focused_cell;
+                            *focused_cell = cell_after_tab(
+                                modifiers.shift,
+                                cell,
+                                self.column_pinned.len(),
+                                self.column_selected.len(),
+                                self.filtered_nodes.len(),
+                            );
+                            if !focused_cell.is_pinned_column_table && *focused_cell != cell {
+                                self.scroll_to_column_number = focused_cell.column_index;
+                                self.changed_arrow_horizontal_scroll = true;
+                            }
+                            if focused_cell.row_index != cell.row_index {
+                                self.scroll_to_row_number = focused_cell.row_index;
+                                self.changed_arrow_vertical_scroll = true;
+                            }
                         }
                     }
-                    if i.consume_key(Modifiers::NONE, Key::ArrowRight) {
-                        if !focused_cell.is_pinned_column_table
-                            && focused_cell.column_index < self.column_selected.len() - 1
-                        {
-                            focused_cell.column_index += 1;
-                            self.scroll_to_column_number = focused_cell.column_index;
-                            self.changed_arrow_horizontal_scroll = true;
-                        } else if focused_cell.is_pinned_column_table
-                            && focused_cell.column_index < self.column_pinned.len() - 1
-                        {
-                            focused_cell.column_index += 1;
+                    for key in [Key::ArrowLeft, Key::ArrowRight] {
+                        if i.consume_key(Modifiers::NONE, key) {
+                            let cell = *focused_cell;
+                            *focused_cell = cell_after_horizontal_key(
+                                key,
+                                cell,
+                                self.column_pinned.len(),
+                                self.column_selected.len(),
+                            );
+                            if !focused_cell.is_pinned_column_table && *focused_cell != cell {
+                                self.scroll_to_column_number = focused_cell.column_index;
+                                self.changed_arrow_horizontal_scroll = true;
+                            }
                         }
                     }
                     if i.consume_key(Modifiers::NONE, Key::ArrowUp) && focused_cell.row_index > 0 {
@@ -1528,9 +4522,40 @@ impl<'array> ArrayTable<'array> {
                         self.scroll_to_row_number = focused_cell.row_index;
                         self.changed_arrow_vertical_scroll = true;
                     }
+                    for key in [Key::Home, Key::End, Key::PageUp, Key::PageDown] {
+                        if i.consume_key(Modifiers::NONE, key) {
+                            focused_cell.row_index = row_index_after_key(
+                                key,
+                                focused_cell.row_index,
+                                self.filtered_nodes.len(),
+                                self.visible_rows_count,
+                            );
+                            self.scroll_to_row_number = focused_cell.row_index;
+                            self.changed_arrow_vertical_scroll = true;
+                        }
+                    }
+                    if previous_focused_cell != *focused_cell {
+                        // Moving with shift pressed extends selection from where it started
+                        if i.modifiers.shift {
+                            if self.selection_anchor.is_none() {
+                                self.selection_anchor = Some(previous_focused_cell);
+                            }
+                        } else {
+                            self.selection_anchor = None;
+                        }
+                    }
+                    let columns = if focused_cell.is_pinned_column_table {
+                        &self.column_pinned
+                    } else {
+                        &self.column_selected
+                    };
+                    let is_locked = columns
+                        .get(focused_cell.column_index)
+                        .is_some_and(|column| self.locked_columns.contains(column.name.as_ref()));
                     let typed_alphanum = Self::get_typed_alphanum_from_events(i);
                     if (typed_alphanum.is_some() || i.consume_key(Modifiers::NONE, Key::Enter))
                         && !self.was_editing
+                        && !is_locked
                     {
                         let row_index = self.filtered_nodes[focused_cell.row_index];
                         *self.editing_index.borrow_mut() = Some((
@@ -1553,15 +4578,22 @@ impl<'array> ArrayTable<'array> {
                                         col_index,
                                     );
                                     if let Some(index) = index {
-                                        row_data.entries()[index]
-                                            .value
-                                            .clone()
-                                            .map(|v| editing_value = v);
+                                        row_data.entries()[index].value.clone().map(|v| {
+                                            editing_value =
+                                                crate::parser::strip_non_finite_number_marker(&v)
+                                                    .to_string()
+                                        });
                                     }
                                 }
                             }
                         }
-                        *self.editing_value.borrow_mut() = editing_value;
+                        if is_too_large_to_render(&editing_value) {
+                            // Editing would give the whole value to the text edit
+                            *self.editing_index.borrow_mut() = None;
+                            self.large_value_viewer = Some(LargeValueViewer::new(editing_value));
+                        } else {
+                            *self.editing_value.borrow_mut() = editing_value;
+                        }
                     }
                 }
 
@@ -1577,6 +4609,15 @@ impl<'array> ArrayTable<'array> {
                 if i.consume_shortcut(&SHORTCUT_REPLACE) {
                     self.open_replace_panel(None);
                 }
+                if i.consume_shortcut(&SHORTCUT_CYCLE_TYPE) {
+                    should_cycle_type = true;
+                }
+                // Redo first as undo shortcut also matches when shift is pressed
+                if i.consume_shortcut(&SHORTCUT_REDO) {
+                    should_redo = true;
+                } else if i.consume_shortcut(&SHORTCUT_UNDO) {
+                    should_undo = true;
+                }
             }
             let hovered_cell = array_response.hover_data.hovered_cell;
             for event in i.events.iter().filter(|e| match e {
@@ -1596,6 +4637,9 @@ impl<'array> ArrayTable<'array> {
                 );
 
                 match event {
+                    egui::Event::Key {
+                        key: Key::Delete, ..
+                    } if self.is_cell_locked(&cell_location) => {}
                     egui::Event::Key {
                         key: Key::Delete, ..
                     } => {
@@ -1623,39 +4667,18 @@ impl<'array> ArrayTable<'array> {
                         self.update_value(flat_json_value, row_index, !self.is_sub_table);
                     }
                     egui::Event::Paste(v) => {
-                        let columns = self.columns(cell_location.is_pinned_column_table);
-                        let pointer = Self::pointer_key(
-                            &self.parent_pointer.pointer,
-                            row_index,
-                            &columns
-                                .get(cell_location.column_index)
-                                .as_ref()
-                                .unwrap()
-                                .name,
-                        );
-                        let mut flat_json_value = FlatJsonValue::<String> {
-                            pointer: PointerKey {
-                                pointer,
-                                value_type: columns[cell_location.column_index].value_type,
-                                depth: columns[cell_location.column_index].depth,
-                                position: 0,
-                                column_id: columns[cell_location.column_index].id,
-                            },
-                            value: Some(v.clone()),
-                        };
-                        match flat_json_value.pointer.value_type {
-                            // When we paste an object it should not be considered as parsed
-                            ValueType::Object(..) => {
-                                flat_json_value.pointer.value_type = ValueType::Object(false, 0)
-                            }
-                            _ => {}
-                        }
-                        self.edit_cell(array_response, flat_json_value, row_index);
+                        self.paste(array_response, cell_location, v);
+                    }
+                    egui::Event::Copy if selected_range.is_some() => {
+                        copied_value = Some(self.selection_to_tsv(selected_range.unwrap()));
                     }
                     egui::Event::Copy => {
                         if let Some(index) = index {
                             if let Some(value) = &self.nodes[row_index].entries()[index].value {
-                                copied_value = Some(value.clone());
+                                copied_value = Some(
+                                    crate::parser::strip_non_finite_number_marker(value)
+                                        .to_string(),
+                                );
                             }
                         }
                     }
@@ -1666,16 +4689,129 @@ impl<'array> ArrayTable<'array> {
         if let Some(value) = copied_value {
             ui.ctx().copy_text(value.clone());
         }
+        if let Some(backward) = goto_matching_cell {
+            self.focus_next_matching_cell(backward);
+        }
+        if should_undo || should_redo {
+            self.replay_edit(array_response, should_redo);
+        }
+        if let Some(focused_cell) = self.focused_cell.filter(|_| should_cycle_type) {
+            if let Err(error) = self.cycle_cell_type(array_response, focused_cell) {
+                let until = ui.input(|i| i.time) + 3.0;
+                self.transient_message = Some((error, until));
+            }
+        }
     }
 
-    pub fn get_typed_alphanum_from_events(i: &mut InputState) -> Option<String> {
-        let mut typed_alphanum: Option<String> = None;
-        i.events.retain(|e| match e {
-            egui::Event::Key { key, modifiers, .. }
-                if matches!(
-                    key,
-                    Key::A
-                        | Key::B
+    /// Reorder keys of objects alphabetically in every row and at every level, arrays keep the
+    /// order of their elements. With a start pointer, rows are the whole saved document as they are
+    /// saved under their pointer alone. Sorting is undone as a single edit. Return the number of
+    /// rows whose keys were reordered.
+    pub fn sort_all_keys(&mut self, array_response: &mut ArrayResponse) -> usize {
+        let sorted = SerializeOptions {
+            sort_keys: true,
+            ..SerializeOptions::default()
+        };
+        let mut edits = vec![];
+        for row_index in 0..self.nodes.len() {
+            let root_node = self.nodes[row_index].entries.last().unwrap();
+            let Some(ref json) = root_node.value else {
+                continue;
+            };
+            let Ok(sorted_json) = format_json(json, &sorted) else {
+                continue;
+            };
+            // Rows whose keys are already sorted are not rewritten
+            if format_json(json, &SerializeOptions::default()).is_ok_and(|json| json == sorted_json)
+            {
+                continue;
+            }
+            let updated_entry = FlatJsonValue {
+                pointer: root_node.pointer.clone(),
+                value: Some(sorted_json),
+            };
+            edits.push(Edit {
+                row_index,
+                pointer: updated_entry.pointer.clone(),
+                old_value: root_node.value.clone(),
+                new_value: updated_entry.value.clone(),
+                old_value_type: None,
+                reorders_keys: true,
+                row_change: None,
+            });
+            self.reorder_row_keys(array_response, updated_entry, row_index);
+        }
+        let sorted_rows = edits.len();
+        self.edit_history.push_group(edits);
+        self.cache.borrow_mut().evict();
+        sorted_rows
+    }
+
+    /// Record following edits in a history of their own, to be given back to `push_edit_group`
+    fn start_edit_group(&mut self) -> EditHistory {
+        mem::replace(&mut self.edit_history, EditHistory::new(usize::MAX))
+    }
+
+    /// Restore `edit_history` returned by `start_edit_group`, edits made since being recorded in
+    /// it as a single one
+    fn push_edit_group(&mut self, edit_history: EditHistory) {
+        let edits = mem::replace(&mut self.edit_history, edit_history).into_edits();
+        self.edit_history.push_group(edits);
+    }
+
+    /// Revert last edit, or with `redo` replay last reverted edit
+    pub fn replay_edit(&mut self, array_response: &mut ArrayResponse, redo: bool) {
+        // Re-applied edit goes through edit_cell, or set_cell_type for a change of type,
+        // reorder_row_keys for sorted keys and row insertion, deletion or move for a row change,
+        // so parent array is serialized again, but it should not be recorded
+        let mut edit_history = mem::take(&mut self.edit_history);
+        let edits = if redo {
+            edit_history.redo()
+        } else {
+            edit_history.undo()
+        };
+        for edit in edits.unwrap_or_default() {
+            let row_index = edit.row_index;
+            let entry = FlatJsonValue {
+                pointer: edit.pointer,
+                value: edit.new_value,
+            };
+            match edit.row_change {
+                Some(RowChange::Inserted(entries)) => {
+                    let row = JsonArrayEntries {
+                        entries,
+                        index: row_index,
+                    };
+                    self.insert_row_entries(array_response, row, row_index, None);
+                }
+                Some(RowChange::Removed(_)) => {
+                    self.delete_rows(array_response, &[row_index]);
+                }
+                Some(RowChange::Moved(to)) => {
+                    self.move_row_entries(array_response, row_index, to);
+                }
+                None if edit.reorders_keys => {
+                    self.reorder_row_keys(array_response, entry, row_index)
+                }
+                None if edit.old_value_type.is_some() => {
+                    self.set_cell_type(array_response, entry, row_index)
+                }
+                None => {
+                    self.edit_cell(array_response, entry, row_index);
+                }
+            }
+        }
+        self.edit_history = edit_history;
+    }
+
+    pub fn get_typed_alphanum_from_events(i: &mut InputState) -> Option<String> {
+        let mut typed_alphanum: Option<String> = None;
+        i.events.retain(|e| match e {
+            egui::Event::Key { key, modifiers, .. }
+                if matches!(
+                    key,
+                    Key::A
+                        | Key::B
                         | Key::C
                         | Key::D
                         | Key::E
@@ -1729,26 +4865,56 @@ impl<'array> ArrayTable<'array> {
         typed_alphanum
     }
 
+    /// Return the number of cells which value has been replaced, cells of locked columns are skipped.
+    /// Replaced cells are undone at once.
     pub fn replace_columns(
         &mut self,
         search_replace_response: SearchReplaceResponse,
         array_response: &mut ArrayResponse,
-    ) {
+    ) -> usize {
         // let start = std::time::Instant::now();
         if let Some(ref columns) = search_replace_response.selected_column {
             for column in columns {
                 self.columns_filter.remove(column.name.as_str());
             }
         }
-        let mut occurrences = replace_occurrences(&mut self.nodes, search_replace_response);
+        let mut occurrences = replace_occurrences(&self.nodes, search_replace_response);
+        if self.edit_options.enforce_types {
+            // e.g: numeric cells are left untouched when replacement is not a number anymore
+            occurrences.retain(|(flat_json_value, _)| self.validate_entry(flat_json_value).is_ok());
+        }
+        occurrences.retain(|(flat_json_value, _)| !self.is_entry_locked(flat_json_value));
+        let mut replaced_count = 0;
         if self.is_sub_table || occurrences.len() < 100 {
+            let edit_history = self.start_edit_group();
             for (flat_json_value, row_index) in occurrences {
-                self.edit_cell(array_response, flat_json_value, row_index);
+                if self.edit_cell(array_response, flat_json_value, row_index) {
+                    replaced_count += 1;
+                }
             }
+            self.push_edit_group(edit_history);
         } else {
+            replaced_count = occurrences.len();
+            let mut edits = Vec::with_capacity(occurrences.len());
             for (flat_json_value, row_index) in occurrences.iter() {
                 self.update_sub_tables_value(flat_json_value, *row_index);
+                let old_value = self.nodes[*row_index]
+                    .entries
+                    .iter()
+                    .find(|entry| entry.pointer.pointer.eq(&flat_json_value.pointer.pointer))
+                    .and_then(|entry| entry.value.clone());
+                edits.push(Edit {
+                    row_index: *row_index,
+                    pointer: flat_json_value.pointer.clone(),
+                    old_value,
+                    new_value: flat_json_value.value.clone(),
+                    old_value_type: None,
+                    reorders_keys: false,
+                    row_change: None,
+                });
+                array_response.edited_value.push(flat_json_value.clone());
             }
+            self.edit_history.push_group(edits);
             let json_array = mem::take(&mut self.nodes);
             let mut len = json_array.len();
             let new_json_array = Arc::new(Mutex::new(json_array));
@@ -1768,6 +4934,7 @@ impl<'array> ArrayTable<'array> {
                         mem::take(updated_entry),
                         self.is_sub_table,
                         self.last_parsed_max_depth,
+                        self.all_as_strings,
                     );
                     let mut new_json_array_guard = new_json_array.lock().unwrap();
                     new_json_array_guard[*row_index].entries = json_array_entry;
@@ -1779,6 +4946,7 @@ impl<'array> ArrayTable<'array> {
         }
         // println!("took {}ms to update columns", start.elapsed().as_millis());
         self.do_filter_column();
+        replaced_count
     }
 
     pub fn open_replace_panel(&mut self, selected_column: Option<Column<'array>>) {
@@ -1794,3 +4962,1771 @@ impl<'array> ArrayTable<'array> {
             .set_columns(self.all_columns().clone());
     }
 }
+
+/// Table of the root array of `json` parsed until `max_depth`, like a document opened in the app
+#[cfg(test)]
+pub(crate) fn table_from_json<'array>(json: &str, max_depth: u8) -> ArrayTable<'array> {
+    let result = JSONParser::parse(
+        json,
+        ParseOptions::default()
+            .parse_array(false)
+            .max_depth(max_depth),
+    )
+    .unwrap()
+    .to_owned();
+    let (array, columns) = crate::parser::as_array(result).unwrap();
+    let len = array.len();
+    ArrayTable::new(
+        None,
+        array,
+        columns,
+        max_depth,
+        PointerKey::from_pointer("".to_string(), ValueType::Array(len), 0, 0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cell_after_horizontal_key, cell_after_tab, row_index_after_key, table_from_json,
+        ArrayTable, CellType, Column, ColumnWidths, DisplayOptions, EditOptions, ExportScope,
+        ScrollToRowMode, SortOrder, NON_NULL_FILTER_VALUE,
+    };
+    use crate::components::table::CellLocation;
+    use crate::history::{Edit, EditHistory, RowChange};
+    use crate::panels::{ReplaceMode, SearchReplaceResponse};
+    use crate::parser::{
+        as_array, coerce_value, column_to_lines, column_value, cycle_value_type, edited_value_type,
+        filter_rows_by_query, format_json, is_pointer_prefix, parse_delimited_block,
+        save_rows_to_buffer, save_to_buffer, SerializeOptions, ROW_NUMBER_COLUMN,
+    };
+    use crate::ArrayResponse;
+    use egui::Key;
+    use json_flat_parser::{FlatJsonValue, JSONParser, ParseOptions, PointerKey, ValueType};
+
+    #[test]
+    fn test_paste_block() {
+        let json = r#"[{"a": 1, "b": 2}, {"a": 3, "b": 4}, {"a": 5, "b": 6}]"#;
+        let mut table = table_from_json(json, 1);
+        let mut array_response = ArrayResponse::default();
+        let start = CellLocation {
+            row_index: 0,
+            column_index: 0,
+            is_pinned_column_table: false,
+        };
+        let block = parse_delimited_block("10\t20\n30\t40").unwrap();
+        assert_eq!(table.paste_block(&mut array_response, start, block), 4);
+
+        let columns = table.columns(false).clone();
+        let value_at = |table: &ArrayTable, row: usize, column: &Column| {
+            table.nodes[row]
+                .find_node_at(&format!("/{}{}", row, column.name))
+                .and_then(|entry| entry.value.clone())
+                .unwrap()
+        };
+        assert_eq!(value_at(&table, 0, &columns[0]), "10");
+        assert_eq!(value_at(&table, 0, &columns[1]), "20");
+        assert_eq!(value_at(&table, 1, &columns[0]), "30");
+        assert_eq!(value_at(&table, 1, &columns[1]), "40");
+        assert_eq!(array_response.edited_value.len(), 4);
+        // Pasted block is undone at once
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(value_at(&table, 0, &columns[0]), "1");
+        assert_eq!(value_at(&table, 1, &columns[1]), "4");
+        assert!(!table.is_dirty());
+        table.replay_edit(&mut array_response, true);
+        assert_eq!(value_at(&table, 1, &columns[1]), "40");
+
+        // Values out of the table are ignored
+        let start = CellLocation {
+            row_index: 2,
+            column_index: 1,
+            is_pinned_column_table: false,
+        };
+        let block = parse_delimited_block("7\t8\n9\t10").unwrap();
+        assert_eq!(table.paste_block(&mut array_response, start, block), 1);
+        assert_eq!(value_at(&table, 2, &columns[1]), "7");
+    }
+
+    #[test]
+    fn test_delete_and_insert_row() {
+        let json = r#"[{"a": 1}, {"a": 2}, {"a": 3}]"#;
+        let mut table = table_from_json(json, 1);
+        let mut array_response = ArrayResponse::default();
+        table.delete_row(&mut array_response, 1);
+        assert_eq!(table.nodes.len(), 2);
+        assert_eq!(table.nodes[1].index, 1);
+        assert_eq!(table.nodes[1].entries[0].value, Some("1".to_string()));
+        assert_eq!(
+            table.nodes[1]
+                .find_node_at("/1/a")
+                .and_then(|entry| entry.value.clone()),
+            Some("3".to_string())
+        );
+        assert_eq!(table.filtered_nodes, vec![0, 1]);
+        assert_eq!(array_response.edited_value.len(), 1);
+
+        // Insert above the second displayed row
+        table.insert_new_row(&mut array_response, 1, 0);
+        assert_eq!(table.nodes.len(), 3);
+        assert_eq!(table.filtered_nodes, vec![0, 1, 2]);
+        assert_eq!(
+            table.nodes[1]
+                .find_node_at("/1/a")
+                .and_then(|entry| entry.value.clone()),
+            Some("null".to_string())
+        );
+        assert!(table.nodes[2].find_node_at("/2/a").is_some());
+
+        table.delete_row(&mut array_response, 0);
+        table.delete_row(&mut array_response, 0);
+        table.delete_row(&mut array_response, 0);
+        // Nothing left to delete
+        let responses_count = array_response.edited_value.len();
+        table.delete_row(&mut array_response, 0);
+        assert_eq!(array_response.edited_value.len(), responses_count);
+        let mut buffer: Vec<u8> = vec![];
+        save_to_buffer("", &table.nodes, &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "[]");
+
+        // Deletions and insertion are undone like edits
+        for _ in 0..5 {
+            table.replay_edit(&mut array_response, false);
+        }
+        let mut buffer: Vec<u8> = vec![];
+        save_to_buffer("", &table.nodes, &mut buffer).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&buffer).unwrap(),
+            serde_json::json!([{"a": 1}, {"a": 2}, {"a": 3}])
+        );
+        assert_eq!(table.filtered_nodes, vec![0, 1, 2]);
+        assert!(!table.is_dirty());
+    }
+
+    #[test]
+    fn test_column_types() {
+        let json = r#"[{"a": 1, "b": "x"}, {"a": 2, "b": 1.5}, {"a": 3, "b": 4}, {"a": 4},
+            {"a": 5}, {"a": 6}, {"a": 7}, {"a": 8}, {"a": 9}, {"a": null}]"#;
+        let table = table_from_json(json, 1);
+        let columns = table.columns(false).clone();
+
+        let column_types = table.column_types(&columns[0]);
+        assert_eq!(column_types.len(), 2);
+        assert_eq!(column_types[&CellType::Integer], 9);
+        assert_eq!(column_types[&CellType::Null], 1);
+
+        let column_types = table.column_types(&columns[1]);
+        assert_eq!(column_types.len(), 4);
+        assert_eq!(column_types[&CellType::String], 1);
+        assert_eq!(column_types[&CellType::Float], 1);
+        assert_eq!(column_types[&CellType::Integer], 1);
+        assert_eq!(column_types[&CellType::Null], 7);
+    }
+
+    #[test]
+    fn test_row_to_json_reparse() {
+        let element = r#"{"a": 1, "b": {"c": [1, {"d": null}]}, "e": "x"}"#;
+        let json = format!("[{{\"a\": 0}}, {}]", element);
+        let table = table_from_json(json.as_str(), 1);
+        let scalar_entries = |json: &str| {
+            JSONParser::parse(json, ParseOptions::default().parse_array(false))
+                .unwrap()
+                .to_owned()
+                .json
+                .into_iter()
+                .filter(|e| {
+                    !matches!(
+                        e.pointer.value_type,
+                        ValueType::Array(_) | ValueType::Object(..)
+                    )
+                })
+                .map(|e| (e.pointer.pointer, e.value))
+                .collect::<Vec<(String, Option<String>)>>()
+        };
+        let copied = table.row_to_json(1);
+        assert_eq!(scalar_entries(&copied), scalar_entries(element));
+    }
+
+    #[test]
+    fn test_enforce_column_type_on_edit() {
+        let json = r#"[{"a": 1}, {"a": 2}, {"a": null}]"#;
+        let mut table = table_from_json(json, 1);
+        let column = table.columns(false)[0].clone();
+        let entry = |value: &str| FlatJsonValue {
+            pointer: PointerKey {
+                pointer: "/0/a".to_string(),
+                value_type: column.value_type,
+                depth: column.depth,
+                position: 0,
+                column_id: column.id,
+            },
+            value: Some(value.to_string()),
+        };
+        let mut array_response = ArrayResponse::default();
+        table.set_edit_options(EditOptions {
+            enforce_types: true,
+        });
+        assert!(table
+            .try_edit_cell(&mut array_response, entry("abc"), 0, false)
+            .is_err());
+        assert!(array_response.edited_value.is_empty());
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry("-1.5e3"), 0, false),
+            Ok(true)
+        );
+        // Forced edit is not validated
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry("abc"), 0, true),
+            Ok(true)
+        );
+
+        table.set_edit_options(EditOptions {
+            enforce_types: false,
+        });
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry("def"), 0, false),
+            Ok(true)
+        );
+        assert_eq!(array_response.edited_value.len(), 3);
+    }
+
+    #[test]
+    fn test_move_column() {
+        let json = r#"[{"a": 1, "b": 2, "c": 3}, {"a": 4, "b": 5, "c": 6}]"#;
+        let mut table = table_from_json(json, 1);
+        let column_names = |table: &ArrayTable| {
+            table
+                .columns(false)
+                .iter()
+                .map(|c| c.name.to_string())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(column_names(&table), vec!["/a", "/b", "/c"]);
+        table.move_column(0, 2);
+        assert_eq!(column_names(&table), vec!["/b", "/c", "/a"]);
+        // Order is kept when columns are computed again and is used by export
+        table.update_selected_columns(1);
+        assert_eq!(column_names(&table), vec!["/b", "/c", "/a"]);
+        assert_eq!(
+            table.export_csv(ExportScope::All, false),
+            "/b,/c,/a\r\n2,3,1\r\n5,6,4\r\n"
+        );
+        // Data is unchanged
+        let mut buffer: Vec<u8> = vec![];
+        save_to_buffer("", &table.nodes, &mut buffer).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&buffer).unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hide_column() {
+        let json = r#"[{"a": 1, "b": 2, "c": 3}, {"a": 4, "b": 5, "c": 6}]"#;
+        let mut table = table_from_json(json, 1);
+        table.set_column_hidden("/b", true);
+        assert!(table.is_column_hidden("/b"));
+        let column_names = table
+            .columns(false)
+            .iter()
+            .map(|c| c.name.to_string())
+            .collect::<Vec<String>>();
+        assert_eq!(column_names, vec!["/a", "/c"]);
+        // Hidden values are still saved and exported
+        let mut buffer: Vec<u8> = vec![];
+        save_to_buffer("", &table.nodes, &mut buffer).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&buffer).unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+        assert!(table
+            .export_csv(ExportScope::All, false)
+            .starts_with("/a,/b,/c\r\n1,2,3"));
+
+        table.set_column_hidden("/b", false);
+        assert_eq!(table.columns(false).len(), 3);
+    }
+
+    #[test]
+    fn test_replace_across_column() {
+        let json = r#"[{"a": "foo", "n": 1}, {"a": "xFoo", "n": 2}, {"a": "baz", "n": 12}]"#;
+        let mut table = table_from_json(json, 1);
+        let column = |table: &ArrayTable, name: &str| {
+            table
+                .columns(false)
+                .iter()
+                .filter(|c| c.name.eq(name))
+                .cloned()
+                .collect::<Vec<Column>>()
+        };
+        let response = |search: &str, replace: &str, column, whole_cell| SearchReplaceResponse {
+            search_criteria: search.to_string(),
+            replace_value: Some(replace.to_string()),
+            selected_column: Some(column),
+            replace_mode: ReplaceMode::Simple,
+            whole_cell,
+        };
+        let values = |table: &ArrayTable, name: &str| {
+            (0..3)
+                .map(|i| {
+                    table.nodes[i]
+                        .find_node_at(&format!("/{}{}", i, name))
+                        .unwrap()
+                        .value
+                        .clone()
+                        .unwrap()
+                })
+                .collect::<Vec<String>>()
+        };
+        let mut array_response = ArrayResponse::default();
+
+        let a = column(&table, "/a");
+        assert_eq!(
+            table.replace_columns(response("foo", "bar", a.clone(), true), &mut array_response),
+            1
+        );
+        assert_eq!(values(&table, "/a"), vec!["bar", "xFoo", "baz"]);
+        assert_eq!(
+            table.replace_columns(response("foo", "bar", a, false), &mut array_response),
+            1
+        );
+        assert_eq!(values(&table, "/a"), vec!["bar", "xbar", "baz"]);
+        assert_eq!(array_response.edited_value.len(), 2);
+
+        // Numeric cells are kept when replacement would not be a number anymore
+        table.set_edit_options(EditOptions {
+            enforce_types: true,
+        });
+        let n = column(&table, "/n");
+        assert_eq!(
+            table.replace_columns(response("2", "two", n.clone(), false), &mut array_response),
+            0
+        );
+        assert_eq!(
+            table.replace_columns(response("1", "3", n, false), &mut array_response),
+            2
+        );
+        assert_eq!(values(&table, "/n"), vec!["3", "2", "32"]);
+        // Replaced cells are undone at once
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(values(&table, "/n"), vec!["1", "2", "12"]);
+        table.replay_edit(&mut array_response, true);
+
+        // Cells of locked columns are skipped
+        table.set_column_locked("/a", true);
+        let a = column(&table, "/a");
+        assert_eq!(
+            table.replace_columns(response("bar", "qux", a, false), &mut array_response),
+            0
+        );
+        assert_eq!(values(&table, "/a"), vec!["bar", "xbar", "baz"]);
+    }
+
+    #[test]
+    fn test_row_index_after_navigation_key() {
+        let row_after = |key, row_index| row_index_after_key(key, row_index, 100, 20);
+        assert_eq!(row_after(Key::Home, 50), 0);
+        assert_eq!(row_after(Key::End, 50), 99);
+        assert_eq!(row_after(Key::PageUp, 50), 30);
+        assert_eq!(row_after(Key::PageDown, 50), 70);
+        assert_eq!(row_after(Key::PageUp, 5), 0);
+        assert_eq!(row_after(Key::PageDown, 95), 99);
+        assert_eq!(row_index_after_key(Key::End, 0, 0, 20), 0);
+    }
+
+    #[test]
+    fn test_cell_after_horizontal_key_crosses_pinned_boundary() {
+        let cell = |column_index, is_pinned_column_table| CellLocation {
+            row_index: 3,
+            column_index,
+            is_pinned_column_table,
+        };
+        // Row number and one pinned column, 4 unpinned columns
+        let move_to = |key, from| cell_after_horizontal_key(key, from, 2, 4);
+        assert_eq!(move_to(Key::ArrowLeft, cell(0, false)), cell(1, true));
+        assert_eq!(move_to(Key::ArrowRight, cell(1, true)), cell(0, false));
+        assert_eq!(move_to(Key::ArrowLeft, cell(1, true)), cell(1, true));
+        assert_eq!(move_to(Key::ArrowRight, cell(3, false)), cell(3, false));
+        assert_eq!(move_to(Key::ArrowLeft, cell(2, false)), cell(1, false));
+        // Without pinned column, only the row number is pinned
+        assert_eq!(
+            cell_after_horizontal_key(Key::ArrowLeft, cell(0, false), 1, 4),
+            cell(0, false)
+        );
+    }
+
+    #[test]
+    fn test_bool_column_editing() {
+        let json = r#"[{"b": true, "c": 1}, {"b": null, "c": false}, {"b": false, "c": null}]"#;
+        let mut table = table_from_json(json, 1);
+        let column = |name: &str| {
+            let columns = table.all_columns();
+            columns.iter().find(|c| c.name.eq(name)).unwrap().clone()
+        };
+        let column_b = column("/b");
+        let column_c = column("/c");
+        assert!(table.is_bool_column(&column_b));
+        assert!(!table.is_bool_column(&column_c));
+
+        let entry = FlatJsonValue {
+            pointer: PointerKey {
+                pointer: "/1/b".to_string(),
+                value_type: column_b.value_type,
+                depth: column_b.depth,
+                position: 0,
+                column_id: column_b.id,
+            },
+            value: Some("false".to_string()),
+        };
+        let mut array_response = ArrayResponse::default();
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry, 1, false),
+            Ok(true)
+        );
+        assert!(table.row_to_json(1).contains(r#""b": false"#));
+    }
+
+    #[test]
+    fn test_key_order_preserved_on_save() {
+        let json = r#"[{"z": 1, "a": {"y": true, "b": null}, "m": "x"}]"#;
+        let mut table = table_from_json(json, 1);
+        let entry = |pointer: &str, value_type: ValueType, value: &str| FlatJsonValue {
+            pointer: PointerKey {
+                pointer: pointer.to_string(),
+                value_type,
+                depth: 1,
+                position: 0,
+                column_id: 0,
+            },
+            value: Some(value.to_string()),
+        };
+        let mut array_response = ArrayResponse::default();
+        // Editing a value serializes the element again, a new key is added last
+        for entry in [
+            entry("/0/z", ValueType::Number, "2"),
+            entry("/0/c", ValueType::String, "new"),
+        ] {
+            assert_eq!(
+                table.try_edit_cell(&mut array_response, entry, 0, false),
+                Ok(true)
+            );
+        }
+        let mut buffer: Vec<u8> = vec![];
+        save_to_buffer("", &table.nodes, &mut buffer).unwrap();
+        let saved = String::from_utf8(buffer).unwrap();
+        let positions = [r#""z""#, r#""a""#, r#""y""#, r#""b""#, r#""m""#, r#""c""#]
+            .map(|key| saved.find(key).unwrap());
+        assert!(positions.windows(2).all(|w| w[0] < w[1]), "{}", saved);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&saved).unwrap()[0]["z"],
+            2
+        );
+    }
+
+    #[test]
+    fn test_toggle_column_pin() {
+        let json = r#"[{"a": 1, "b": 2}, {"a": 3, "b": 4}]"#;
+        let mut table = table_from_json(json, 1);
+        let names = |table: &ArrayTable, pinned: bool| {
+            table
+                .columns(pinned)
+                .iter()
+                .map(|c| c.name.to_string())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(names(&table, true), vec![ROW_NUMBER_COLUMN]);
+        assert_eq!(names(&table, false), vec!["/a", "/b"]);
+
+        table.toggle_column_pin(false, 1);
+        assert_eq!(names(&table, true), vec![ROW_NUMBER_COLUMN, "/b"]);
+        assert_eq!(names(&table, false), vec!["/a"]);
+        // Pinned columns come first in exported columns
+        assert!(table
+            .export_csv(ExportScope::All, false)
+            .starts_with("/b,/a"));
+
+        // Unpinned table keeps an empty column when everything is pinned
+        table.toggle_column_pin(false, 0);
+        assert_eq!(names(&table, true), vec![ROW_NUMBER_COLUMN, "/b", "/a"]);
+        assert_eq!(names(&table, false), vec![""]);
+        table.toggle_column_pin(false, 0);
+        assert_eq!(names(&table, false), vec![""]);
+
+        // Row number column can't be unpinned
+        table.toggle_column_pin(true, 0);
+        table.toggle_column_pin(true, 1);
+        assert_eq!(names(&table, true), vec![ROW_NUMBER_COLUMN, "/a"]);
+        assert_eq!(names(&table, false), vec!["/b"]);
+    }
+
+    #[test]
+    fn test_deepen_parses_rows_once() {
+        let json =
+            r##"[{"a": {"b": {"c": 1}}, "d": 2, "#": 5}, {"a": {"b": {"c": 3}, "e": [4]}}]"##;
+        let res = JSONParser::parse(
+            json,
+            ParseOptions::default().parse_array(false).max_depth(1),
+        )
+        .unwrap()
+        .to_owned();
+        let parse_result = res.clone_except_json();
+        let (array, columns) = as_array(res).unwrap();
+        let mut table = ArrayTable::new(
+            Some(parse_result),
+            array,
+            columns,
+            1,
+            PointerKey::from_pointer("".to_string(), ValueType::Array(2), 0, 0),
+        );
+        assert!(table.update_max_depth(2).is_some());
+        // Deeper columns are already parsed, next depth does not parse again
+        assert!(table.all_columns().iter().any(|c| c.name.eq("/a/b/c")));
+        assert!(table.update_max_depth(3).is_none());
+        // Key `#` keeps its column, row number column is not one of them
+        assert!(table.all_columns().iter().any(|c| c.name.eq("/#")));
+        assert!(!table
+            .all_columns()
+            .iter()
+            .any(|c| c.name.eq(ROW_NUMBER_COLUMN)));
+
+        let deep = JSONParser::parse(json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (deep_array, _) = as_array(deep).unwrap();
+        let scalars = |entries: &[FlatJsonValue<String>]| {
+            let mut scalars = entries
+                .iter()
+                .filter(|e| {
+                    !e.pointer.pointer.ends_with(ROW_NUMBER_COLUMN)
+                        && !matches!(
+                            e.pointer.value_type,
+                            ValueType::Array(_) | ValueType::Object(..)
+                        )
+                })
+                .map(|e| (e.pointer.pointer.clone(), e.value.clone()))
+                .collect::<Vec<(String, Option<String>)>>();
+            scalars.sort();
+            scalars
+        };
+        assert_eq!(table.nodes.len(), deep_array.len());
+        for (row, deep_row) in table.nodes.iter().zip(deep_array.iter()) {
+            assert_eq!(scalars(&row.entries), scalars(&deep_row.entries));
+        }
+    }
+
+    #[test]
+    fn test_json_patch_round_trip() {
+        let json = r#"[{"a/b": "x", "c": 1}, {"c": 2}]"#;
+        let new_table = || table_from_json(json, 1);
+        let mut table = new_table();
+        let mut array_response = ArrayResponse::default();
+        let entry = |pointer: &str, value_type: ValueType, value: Option<&str>| FlatJsonValue {
+            pointer: PointerKey {
+                pointer: pointer.to_string(),
+                value_type,
+                depth: 1,
+                position: 0,
+                column_id: 0,
+            },
+            value: value.map(|value| value.to_string()),
+        };
+        for (pointer, value_type, value, row_index) in [
+            ("/0/a~1b", ValueType::String, Some("y"), 0),
+            ("/0/a~1b", ValueType::String, Some("null"), 0),
+            ("/1/a~1b", ValueType::String, Some("z"), 1),
+            ("/0/c", ValueType::Number, None, 0),
+            ("/1/c", ValueType::Number, Some("3"), 1),
+        ] {
+            let edit = entry(pointer, value_type, value);
+            assert_eq!(
+                table.try_edit_cell(&mut array_response, edit, row_index, true),
+                Ok(true)
+            );
+        }
+
+        let patch = table.export_json_patch().unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&patch).unwrap(),
+            serde_json::json!([
+                {"op": "replace", "path": "/0/a~1b", "value": "null"},
+                {"op": "add", "path": "/1/a~1b", "value": "z"},
+                {"op": "remove", "path": "/0/c"},
+                {"op": "replace", "path": "/1/c", "value": 3},
+            ])
+        );
+
+        let mut patched_table = new_table();
+        assert_eq!(
+            patched_table.apply_json_patch(&mut ArrayResponse::default(), &patch),
+            Ok(4)
+        );
+        for row_index in 0..2 {
+            assert_eq!(
+                patched_table.row_to_json(row_index),
+                table.row_to_json(row_index)
+            );
+        }
+        // Applied patch is undone at once
+        patched_table.replay_edit(&mut ArrayResponse::default(), false);
+        assert!(!patched_table.is_dirty());
+        assert_eq!(patched_table.row_to_json(1), new_table().row_to_json(1));
+        patched_table.replay_edit(&mut ArrayResponse::default(), true);
+        assert!(patched_table
+            .apply_json_patch(
+                &mut ArrayResponse::default(),
+                r#"[{"op": "move", "from": "/0/c", "path": "/1/c"}]"#
+            )
+            .is_err());
+
+        // Inserted and removed rows are added and removed whole
+        table.insert_new_row(&mut array_response, 0, 0);
+        let edit = entry("/0/c", ValueType::Number, Some("5"));
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, edit, 0, true),
+            Ok(true)
+        );
+        table.delete_row(&mut array_response, 1);
+        let patch: serde_json::Value =
+            serde_json::from_str(&table.export_json_patch().unwrap()).unwrap();
+        assert_eq!(
+            patch.as_array().unwrap()[4..],
+            [
+                serde_json::json!({"op": "add", "path": "/0", "value": {"a/b": null, "c": null}}),
+                serde_json::json!({"op": "replace", "path": "/0/c", "value": 5}),
+                serde_json::json!({"op": "remove", "path": "/1"}),
+            ]
+        );
+        // Oldest edits are dropped once history is full
+        let mut table = new_table();
+        table.edit_history.max_depth = 1;
+        for value in ["1", "2"] {
+            let edit = entry("/0/c", ValueType::Number, Some(value));
+            assert_eq!(
+                table.try_edit_cell(&mut array_response, edit, 0, true),
+                Ok(true)
+            );
+        }
+        assert_eq!(table.export_json_patch(), None);
+    }
+
+    #[test]
+    fn test_flatten_nested_arrays() {
+        let json = r#"[{"id": 1, "tags": ["a", "b"]}, {"id": 2, "tags": ["c", "d", "e"]}, {"id": 3, "tags": []}]"#;
+        let res = JSONParser::parse(json, ParseOptions::default().parse_array(true).max_depth(2))
+            .unwrap()
+            .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        let mut table = ArrayTable::new(
+            None,
+            array,
+            columns,
+            2,
+            PointerKey::from_pointer("".to_string(), ValueType::Array(3), 0, 0),
+        );
+        table.set_flatten_arrays(true);
+        let mut column_names = ArrayTable::visible_columns(table.all_columns(), 2, true)
+            .map(|column| column.name.to_string())
+            .collect::<Vec<String>>();
+        column_names.sort();
+        // As many columns as elements of the longest array
+        assert_eq!(column_names, vec!["/id", "/tags/0", "/tags/1", "/tags/2"]);
+        assert!(
+            ArrayTable::visible_columns(table.all_columns(), 2, false).any(|c| c.name.eq("/tags"))
+        );
+
+        assert_eq!(column_value(&table.nodes[1], "", "/tags/2"), Some("e"));
+        // Missing elements are blank cells
+        assert_eq!(column_value(&table.nodes[0], "", "/tags/2"), None);
+        assert_eq!(column_value(&table.nodes[2], "", "/tags/0"), None);
+
+        let column = table
+            .all_columns()
+            .iter()
+            .find(|c| c.name.eq("/tags/1"))
+            .unwrap()
+            .clone();
+        let entry = FlatJsonValue {
+            pointer: PointerKey {
+                pointer: "/0/tags/1".to_string(),
+                value_type: ValueType::String,
+                depth: column.depth,
+                position: 0,
+                column_id: column.id,
+            },
+            value: Some("z".to_string()),
+        };
+        let mut array_response = ArrayResponse::default();
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry, 0, true),
+            Ok(true)
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&table.row_to_json(0)).unwrap(),
+            serde_json::json!({"id": 1, "tags": ["a", "z"]})
+        );
+    }
+
+    #[test]
+    fn test_replace_row_json() {
+        let json = r#"[{"id": 1, "name": "a", "extra": true}, {"id": 2, "name": "b"}]"#;
+        let mut table = table_from_json(json, 2);
+        let mut array_response = ArrayResponse::default();
+        let updated_count = table
+            .replace_row_json(
+                &mut array_response,
+                0,
+                r#"{"id": 10, "name": "a", "new": "x"}"#,
+            )
+            .unwrap();
+        // id changed, extra removed and new added
+        assert_eq!(updated_count, 3);
+        assert_eq!(column_value(&table.nodes[0], "", "/id"), Some("10"));
+        assert_eq!(column_value(&table.nodes[0], "", "/name"), Some("a"));
+        assert_eq!(column_value(&table.nodes[0], "", "/extra"), None);
+        assert_eq!(column_value(&table.nodes[0], "", "/new"), Some("x"));
+        assert!(table.all_columns().iter().any(|c| c.name.eq("/new")));
+        assert_eq!(column_value(&table.nodes[1], "", "/id"), Some("2"));
+
+        // Invalid json is rejected without changing the row
+        let serialized = table.row_to_json(0);
+        assert!(table
+            .replace_row_json(&mut array_response, 0, r#"{"id": 3"#)
+            .is_err());
+        assert_eq!(table.row_to_json(0), serialized);
+    }
+
+    #[test]
+    fn test_cell_after_tab_wraps_rows() {
+        let cell = |row_index, column_index, is_pinned_column_table| CellLocation {
+            row_index,
+            column_index,
+            is_pinned_column_table,
+        };
+        // Row number and one pinned column, 4 unpinned columns, 5 rows
+        let tab = |from| cell_after_tab(false, from, 2, 4, 5);
+        let shift_tab = |from| cell_after_tab(true, from, 2, 4, 5);
+        assert_eq!(tab(cell(1, 2, false)), cell(1, 3, false));
+        assert_eq!(tab(cell(1, 1, true)), cell(1, 0, false));
+        // Last column wraps to the first column of next row
+        assert_eq!(tab(cell(1, 3, false)), cell(2, 1, true));
+        assert_eq!(tab(cell(4, 3, false)), cell(4, 3, false));
+        // First column wraps to the last column of previous row
+        assert_eq!(shift_tab(cell(2, 1, true)), cell(1, 3, false));
+        assert_eq!(shift_tab(cell(2, 0, false)), cell(2, 1, true));
+        assert_eq!(shift_tab(cell(0, 1, true)), cell(0, 1, true));
+        // Without pinned column, only the row number is pinned
+        assert_eq!(
+            cell_after_tab(false, cell(1, 3, false), 1, 4, 5),
+            cell(2, 0, false)
+        );
+        assert_eq!(
+            cell_after_tab(true, cell(2, 0, false), 1, 4, 5),
+            cell(1, 3, false)
+        );
+    }
+
+    #[test]
+    fn test_delete_rows() {
+        let json = r#"[{"id": 0}, {"id": 1}, {"id": 2}, {"id": 3}, {"id": 4}, {"id": 5}]"#;
+        let mut table = table_from_json(json, 2);
+        let mut array_response = ArrayResponse::default();
+        assert_eq!(table.delete_rows(&mut array_response, &[5, 1, 3]), 3);
+        // Rows are renumbered once, with a single response
+        assert_eq!(array_response.edited_value.len(), 1);
+        assert_eq!(table.nodes.len(), 3);
+        for (index, id) in [0, 2, 4].iter().enumerate() {
+            let row = &table.nodes[index];
+            assert_eq!(row.index, index);
+            assert_eq!(row.entries[0].value, Some(index.to_string()));
+            assert_eq!(column_value(row, "", "/id"), Some(id.to_string().as_str()));
+            assert!(row
+                .entries
+                .iter()
+                .all(|entry| entry.pointer.pointer.starts_with(&format!("/{}", index))));
+        }
+        assert_eq!(table.filtered_nodes, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_column_to_lines() {
+        let json = r#"[{"id": 3, "tags": ["a", "b"]}, {"id": 1}, {"id": null}, {"id": 2, "tags": {"k": 1}}]"#;
+        let mut table = table_from_json(json, 1);
+        let column = |name: &str| {
+            table
+                .all_columns()
+                .iter()
+                .find(|c| c.name.eq(name))
+                .unwrap()
+                .clone()
+        };
+        let (id, tags) = (column("/id"), column("/tags"));
+        assert_eq!(table.copy_column(&id), "3\n1\n\n2");
+        assert_eq!(table.copy_column(&tags), "[\"a\",\"b\"]\n\n\n{\"k\":1}");
+        // Display order follows sort
+        table.filtered_nodes = vec![1, 3, 0];
+        assert_eq!(table.copy_column(&id), "1\n2\n3");
+        assert_eq!(column_to_lines(table.nodes(), &[2, 1], "", &id), "\n1");
+    }
+
+    #[test]
+    fn test_convert_column() {
+        let json = r#"[{"id": "1"}, {"id": " 2.5 "}, {"id": "abc"}, {"id": null}, {"id": "1e3"}, {"id": "+4"}]"#;
+        let mut table = table_from_json(json, 1);
+        let column = table
+            .all_columns()
+            .iter()
+            .find(|c| c.name.eq("/id"))
+            .unwrap()
+            .clone();
+        let mut array_response = ArrayResponse::default();
+        // "abc" and "+4" are not json numbers, null is left untouched
+        assert_eq!(
+            table.convert_column(&mut array_response, &column, ValueType::Number),
+            Ok((3, 2))
+        );
+        assert_eq!(column_value(&table.nodes[1], "", "/id"), Some("2.5"));
+        assert_eq!(column_value(&table.nodes[2], "", "/id"), Some("abc"));
+        // Numbers are serialized without quotes
+        let mut buffer = vec![];
+        save_to_buffer("", table.nodes(), &mut buffer).unwrap();
+        let saved: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(
+            saved,
+            serde_json::json!([{"id": 1}, {"id": 2.5}, {"id": "abc"}, {"id": null}, {"id": 1e3}, {"id": "+4"}])
+        );
+        assert!(matches!(
+            table
+                .all_columns()
+                .iter()
+                .find(|c| c.name.eq("/id"))
+                .unwrap()
+                .value_type,
+            ValueType::Number
+        ));
+        // Converted cells are undone at once, back to strings
+        table.replay_edit(&mut array_response, false);
+        let mut buffer = vec![];
+        save_to_buffer("", table.nodes(), &mut buffer).unwrap();
+        let saved: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+        assert_eq!(
+            saved,
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+        assert!(!table.is_dirty());
+
+        assert_eq!(
+            coerce_value("1", ValueType::Number, ValueType::String),
+            Some("1".to_string())
+        );
+        assert_eq!(
+            coerce_value("TRUE", ValueType::String, ValueType::Bool),
+            Some("true".to_string())
+        );
+        assert_eq!(
+            coerce_value("yes", ValueType::String, ValueType::Bool),
+            None
+        );
+        assert_eq!(
+            coerce_value("false", ValueType::Bool, ValueType::Number),
+            Some("0".to_string())
+        );
+    }
+
+    #[test]
+    fn test_arrays_as_raw_json_cells() {
+        let json = r#"[{"id": 1, "tags": [1, [2, 3], {"a": "b"}], "meta": {"list": [4]}}]"#;
+        let res = JSONParser::parse(
+            json,
+            ParseOptions::default().parse_array(false).max_depth(3),
+        )
+        .unwrap()
+        .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        let tags = array[0]
+            .entries
+            .iter()
+            .filter(|entry| is_pointer_prefix("/0/tags", &entry.pointer.pointer))
+            .collect::<Vec<&FlatJsonValue<String>>>();
+        // A single cell holding the raw array
+        assert_eq!(tags.len(), 1);
+        assert!(matches!(tags[0].pointer.value_type, ValueType::Array(3)));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(tags[0].value.as_ref().unwrap()).unwrap(),
+            serde_json::json!([1, [2, 3], {"a": "b"}])
+        );
+        assert_eq!(
+            array[0]
+                .entries
+                .iter()
+                .filter(|entry| is_pointer_prefix("/0/meta/list", &entry.pointer.pointer))
+                .count(),
+            1
+        );
+
+        let mut table = ArrayTable::new(
+            None,
+            array,
+            columns,
+            3,
+            PointerKey::from_pointer("".to_string(), ValueType::Array(1), 0, 0),
+        );
+        let column = table
+            .all_columns()
+            .iter()
+            .find(|c| c.name.eq("/tags"))
+            .unwrap()
+            .clone();
+        let entry = |value: &str| FlatJsonValue {
+            pointer: PointerKey {
+                pointer: "/0/tags".to_string(),
+                value_type: edited_value_type(column.value_type, value),
+                depth: column.depth,
+                position: 0,
+                column_id: column.id,
+            },
+            value: Some(value.to_string()),
+        };
+        let mut array_response = ArrayResponse::default();
+        // Editing the cell edits the raw json
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry("[true, {\"c\": []}]"), 0, false),
+            Ok(true)
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&table.row_to_json(0)).unwrap()["tags"],
+            serde_json::json!([true, {"c": []}])
+        );
+        for force in [false, true] {
+            assert!(table
+                .try_edit_cell(&mut array_response, entry("[1, "), 0, force)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_column_widths_by_schema() {
+        let schema = ColumnWidths::fingerprint(["/id", "/name", "/age"].into_iter());
+        assert_eq!(
+            schema,
+            ColumnWidths::fingerprint(["/age", "/id", "/name"].into_iter())
+        );
+        let other_schema = ColumnWidths::fingerprint(["/id", "/email"].into_iter());
+        assert_ne!(schema, other_schema);
+
+        let mut widths = ColumnWidths::default();
+        widths.remember(schema, "/name", 120.0);
+        widths.remember(schema, "/id", 40.0);
+        assert_eq!(widths.width(schema, "/name"), Some(120.0));
+        assert_eq!(widths.width(schema, "/id"), Some(40.0));
+        assert_eq!(widths.width(schema, "/age"), None);
+        // Changed schema only gets widths of matching columns
+        assert_eq!(widths.width(other_schema, "/id"), Some(40.0));
+        assert_eq!(widths.width(other_schema, "/email"), None);
+        widths.remember(other_schema, "/id", 60.0);
+        assert_eq!(widths.width(other_schema, "/id"), Some(60.0));
+        assert_eq!(widths.width(schema, "/id"), Some(40.0));
+    }
+
+    #[test]
+    fn test_focus_next_matching_cell() {
+        let json = r#"[{"a": "foo", "b": "bar"}, {"a": "x", "b": "y"}, {"a": "y", "b": "Food"}]"#;
+        let mut table = table_from_json(json, 1);
+        // No active search
+        assert!(!table.focus_next_matching_cell(false));
+        assert_eq!(table.focused_cell, None);
+
+        table.scroll_to_row_mode = ScrollToRowMode::MatchingTerm;
+        table.scroll_to_row = "foo".to_string();
+        let column_index = |name: &str| {
+            table
+                .columns(false)
+                .iter()
+                .position(|c| c.name.eq(name))
+                .unwrap()
+        };
+        let cell = |row_index, column_index| CellLocation {
+            row_index,
+            column_index,
+            is_pinned_column_table: false,
+        };
+        let (a, b) = (column_index("/a"), column_index("/b"));
+        let expected = [cell(0, a), cell(2, b)];
+        assert_eq!(table.matching_cells(), expected);
+        for expected_cell in expected.iter().chain(expected.iter()) {
+            assert!(table.focus_next_matching_cell(false));
+            assert_eq!(table.focused_cell, Some(*expected_cell));
+            assert_eq!(table.scroll_to_row_number, expected_cell.row_index);
+        }
+        // Backward from first match wraps to the last one
+        table.focused_cell = Some(cell(0, a));
+        assert!(table.focus_next_matching_cell(true));
+        assert_eq!(table.focused_cell, Some(cell(2, b)));
+    }
+
+    #[test]
+    fn test_duplicate_row() {
+        let json = r#"[{"id": 0}, {"id": 1, "tags": ["a", {"b": 2}]}, {"id": 2}]"#;
+        let mut table = table_from_json(json, 10);
+        let mut array_response = ArrayResponse::default();
+        let original = table.row_to_json(1);
+        table.duplicate_row(&mut array_response, 1);
+        assert_eq!(array_response.edited_value.len(), 1);
+        assert_eq!(table.nodes.len(), 4);
+        assert_eq!(table.row_to_json(2), original);
+        assert_eq!(table.nodes[2].entries.len(), table.nodes[1].entries.len());
+        for (index, id) in [0, 1, 1, 2].iter().enumerate() {
+            let row = &table.nodes[index];
+            assert_eq!(row.index, index);
+            assert_eq!(row.entries[0].value, Some(index.to_string()));
+            assert_eq!(column_value(row, "", "/id"), Some(id.to_string().as_str()));
+            assert!(row
+                .entries
+                .iter()
+                .all(|entry| entry.pointer.pointer.starts_with(&format!("/{}", index))));
+        }
+        assert_eq!(table.filtered_nodes, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_all_as_strings() {
+        let json = r#"[{"id": 1, "enabled": true, "note": null, "name": "a"}, {"id": 2.5, "enabled": false}]"#;
+        let mut table = table_from_json(json, 2);
+        table.set_all_as_strings(true);
+        for row in table.nodes.iter() {
+            let (_, entries) = row.entries.split_last().unwrap();
+            assert!(entries
+                .iter()
+                .filter(|entry| !entry.pointer.pointer.ends_with(ROW_NUMBER_COLUMN))
+                .all(|entry| matches!(entry.pointer.value_type, ValueType::String)));
+        }
+
+        let saved_row = |table: &ArrayTable| {
+            let root_entry = table.nodes[0].entries.last().unwrap();
+            serde_json::from_str::<serde_json::Value>(root_entry.value.as_ref().unwrap()).unwrap()
+        };
+        let entry = |pointer: &str, value: &str| FlatJsonValue {
+            pointer: PointerKey {
+                pointer: pointer.to_string(),
+                value_type: ValueType::String,
+                depth: 1,
+                position: 0,
+                column_id: 0,
+            },
+            value: Some(value.to_string()),
+        };
+        let mut array_response = ArrayResponse::default();
+        // Unchanged values keep their original type
+        table
+            .try_edit_cell(&mut array_response, entry("/0/id", "7"), 0, false)
+            .unwrap();
+        assert_eq!(
+            saved_row(&table),
+            serde_json::json!({"id": 7, "enabled": true, "note": null, "name": "a"})
+        );
+        table
+            .try_edit_cell(&mut array_response, entry("/0/id", "seven"), 0, false)
+            .unwrap();
+        assert_eq!(saved_row(&table)["id"], serde_json::json!("seven"));
+    }
+
+    #[test]
+    fn test_hide_empty_columns() {
+        let json = r#"[{"id": 1, "note": null, "tag": null}, {"id": 2, "note": null, "tag": "a"}, {"id": 3}]"#;
+        let mut table = table_from_json(json, 2);
+        let displayed_columns = |table: &ArrayTable| {
+            table
+                .columns(false)
+                .iter()
+                .map(|column| column.name.to_string())
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(displayed_columns(&table), vec!["/id", "/note", "/tag"]);
+        table.set_display_options(DisplayOptions {
+            hide_empty_columns: true,
+            ..Default::default()
+        });
+        assert_eq!(displayed_columns(&table), vec!["/id", "/tag"]);
+
+        // Empty columns are computed again when filters change
+        table
+            .columns_filter
+            .insert("/tag".to_string(), vec![NON_NULL_FILTER_VALUE.to_string()]);
+        table.do_filter_column();
+        assert_eq!(table.filtered_nodes, vec![1]);
+        assert_eq!(displayed_columns(&table), vec!["/id", "/tag"]);
+        table.columns_filter.clear();
+        table.filter_rows = "3".to_string();
+        table.do_filter_column();
+        assert_eq!(displayed_columns(&table), vec!["/id"]);
+
+        table.set_display_options(DisplayOptions::default());
+        assert_eq!(displayed_columns(&table), vec!["/id", "/note", "/tag"]);
+    }
+
+    #[test]
+    fn test_merge_and_split_columns() {
+        let json = r#"[{"first": "Ada", "last": "Lovelace"}, {"first": "Alan"}, {"last": "Hopper", "age": 85}, {"age": 41}]"#;
+        let mut table = table_from_json(json, 2);
+        let column = |table: &ArrayTable, name: &str| {
+            table
+                .all_columns()
+                .iter()
+                .find(|column| column.name == name)
+                .unwrap()
+                .clone()
+        };
+        let row = |table: &ArrayTable, row_index: usize| {
+            let root_entry = table.nodes[row_index].entries.last().unwrap();
+            serde_json::from_str::<serde_json::Value>(root_entry.value.as_ref().unwrap()).unwrap()
+        };
+        let mut array_response = ArrayResponse::default();
+        let (first, last) = (column(&table, "/first"), column(&table, "/last"));
+        // Row without any of the columns is not changed
+        assert_eq!(
+            table.merge_columns(&mut array_response, &first, &last, " "),
+            Ok(3)
+        );
+        assert_eq!(row(&table, 0), serde_json::json!({"first": "Ada Lovelace"}));
+        assert_eq!(row(&table, 1), serde_json::json!({"first": "Alan"}));
+        assert_eq!(
+            row(&table, 2),
+            serde_json::json!({"age": 85, "first": "Hopper"})
+        );
+        assert_eq!(row(&table, 3), serde_json::json!({"age": 41}));
+
+        let first = column(&table, "/first");
+        assert!(table
+            .split_column(&mut array_response, &first, " ", "/first")
+            .is_err());
+        assert_eq!(
+            table.split_column(&mut array_response, &first, " ", "/last_name"),
+            Ok(1)
+        );
+        assert_eq!(
+            row(&table, 0),
+            serde_json::json!({"first": "Ada", "last_name": "Lovelace"})
+        );
+        // Values without delimiter are left as they are
+        assert_eq!(row(&table, 1), serde_json::json!({"first": "Alan"}));
+        assert!(table
+            .all_columns()
+            .iter()
+            .any(|column| column.name == "/last_name"));
+
+        // Split and merge are undone at once
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(row(&table, 0), serde_json::json!({"first": "Ada Lovelace"}));
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(
+            row(&table, 0),
+            serde_json::json!({"first": "Ada", "last": "Lovelace"})
+        );
+        assert_eq!(
+            row(&table, 2),
+            serde_json::json!({"last": "Hopper", "age": 85})
+        );
+
+        // Numbers merged without separator are still numbers
+        let mut table = table_from_json(r#"[{"a": 1, "b": 2}, {"a": 3}, {"a": 4, "b": "x"}]"#, 2);
+        let (a, b) = (column(&table, "/a"), column(&table, "/b"));
+        assert_eq!(table.merge_columns(&mut array_response, &a, &b, ""), Ok(3));
+        assert_eq!(row(&table, 0), serde_json::json!({"a": 12}));
+        assert_eq!(row(&table, 1), serde_json::json!({"a": 3}));
+        assert_eq!(row(&table, 2), serde_json::json!({"a": "4x"}));
+        let mut table = table_from_json(r#"[{"a": 1, "b": 2}]"#, 2);
+        let (a, b) = (column(&table, "/a"), column(&table, "/b"));
+        assert_eq!(table.merge_columns(&mut array_response, &a, &b, "-"), Ok(1));
+        assert_eq!(row(&table, 0), serde_json::json!({"a": "1-2"}));
+    }
+
+    #[test]
+    fn test_filter_rows_by_query() {
+        let json = r#"[{"price": 150, "status": "active", "name": "Desk"}, {"price": 50, "status": "active"}, {"price": "200", "status": "sold", "name": "desk lamp"}, {"price": "n/a", "status": null}, {"price": true}]"#;
+        let res = JSONParser::parse(
+            json,
+            ParseOptions::default().parse_array(false).max_depth(1),
+        )
+        .unwrap()
+        .to_owned();
+        let (array, _columns) = as_array(res).unwrap();
+        let rows = (0..array.len()).collect::<Vec<usize>>();
+        let query = |query: &str| filter_rows_by_query(&array, &rows, "", query, false);
+        assert_eq!(query(r#"price > 100 AND status == "active""#), Ok(vec![0]));
+        // Numeric strings compare as numbers, other types never match an ordering
+        assert_eq!(query("price >= 150"), Ok(vec![0, 2]));
+        assert_eq!(query("price != 50"), Ok(vec![0, 2, 3, 4]));
+        assert_eq!(query("price == true"), Ok(vec![4]));
+        assert_eq!(query("status == null"), Ok(vec![3, 4]));
+        assert_eq!(query(r#"name contains "DESK""#), Ok(vec![0, 2]));
+        assert_eq!(
+            filter_rows_by_query(&array, &rows, "", r#"name contains "DESK""#, true),
+            Ok(vec![])
+        );
+        assert_eq!(
+            query(r#"(price < 100 OR status == "sold") and /name != null"#),
+            Ok(vec![2])
+        );
+        assert_eq!(
+            query(r#"price > 100 OR price < 100 AND status == "x""#),
+            Ok(vec![0, 2])
+        );
+
+        assert!(query("price >").is_err());
+        assert!(query("price > 10 AND").is_err());
+        assert!(query("(price > 10").is_err());
+        assert!(query(r#"status == "active"#).is_err());
+        assert_eq!(
+            query("status == active"),
+            Err("Expected a value at position 10, quote text as \"active\"".to_string())
+        );
+
+        // Invalid query is reported and does not filter rows
+        let mut table = ArrayTable::new(
+            None,
+            array.clone(),
+            vec![],
+            1,
+            PointerKey::from_pointer("".to_string(), ValueType::Array(5), 0, 0),
+        );
+        table.filter_rows_query = true;
+        table.filter_rows = "price >> 1".to_string();
+        table.do_filter_column();
+        assert!(table.filter_rows_error.is_some());
+        assert_eq!(table.filtered_nodes, vec![0, 1, 2, 3, 4]);
+        table.filter_rows = "price < 100".to_string();
+        table.do_filter_column();
+        assert_eq!(table.filter_rows_error, None);
+        assert_eq!(table.filtered_nodes, vec![1]);
+    }
+
+    #[test]
+    fn test_export_filtered_rows() {
+        let json = r#"[{"id": 1, "tag": "a"}, {"id": 2, "tag": "b"}, {"id": 3, "tag": "a"}, {"id": 4, "tag": "b"}]"#;
+        let mut table = table_from_json(json, 1);
+        table.filter_rows = "b".to_string();
+        table.do_filter_column();
+        assert_eq!(table.filtered_nodes, vec![1, 3]);
+
+        let exported = table
+            .export_json(ExportScope::Filtered, &SerializeOptions::default())
+            .unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&exported).unwrap(),
+            serde_json::json!([{"id": 2, "tag": "b"}, {"id": 4, "tag": "b"}])
+        );
+        // Exported rows form a standalone array, renumbered from 0
+        let res = JSONParser::parse(
+            &exported,
+            ParseOptions::default().parse_array(false).max_depth(1),
+        )
+        .unwrap()
+        .to_owned();
+        let (reparsed, _) = as_array(res).unwrap();
+        assert_eq!(reparsed.len(), 2);
+        assert_eq!(column_value(&reparsed[1], "", "/id"), Some("4"));
+        assert_eq!(
+            table.export_csv(ExportScope::Filtered, false),
+            "/id,/tag\r\n2,b\r\n4,b\r\n"
+        );
+        assert_eq!(table.export_rows(ExportScope::All), vec![0, 1, 2, 3]);
+
+        // Selection covers displayed rows
+        let cell = |row_index| CellLocation {
+            row_index,
+            column_index: 0,
+            is_pinned_column_table: false,
+        };
+        assert_eq!(
+            table.export_rows(ExportScope::Selected),
+            Vec::<usize>::new()
+        );
+        table.selection_anchor = Some(cell(1));
+        table.focused_cell = Some(cell(1));
+        assert_eq!(table.export_rows(ExportScope::Selected), vec![3]);
+        let mut buffer = vec![];
+        save_rows_to_buffer("", table.nodes(), &[], &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "[]");
+    }
+
+    #[test]
+    fn test_index_column_after_sort() {
+        let json = r#"[{"v": "b"}, {"v": "c"}, {"v": "a"}]"#;
+        let mut table = table_from_json(json, 1);
+        table.column_sort = Some(("/v".to_string(), SortOrder::Ascending));
+        table.do_filter_column();
+        // `#` is the index of elements in the array, not their display order
+        assert_eq!(
+            table.export_csv(ExportScope::Filtered, true),
+            "#,/v\r\n2,a\r\n0,b\r\n1,c\r\n"
+        );
+        assert_eq!(
+            table.export_csv(ExportScope::Filtered, false),
+            "/v\r\na\r\nb\r\nc\r\n"
+        );
+
+        // Elements after a deleted one are renumbered
+        let mut array_response = ArrayResponse::default();
+        table.delete_row(&mut array_response, 0);
+        assert_eq!(
+            table.export_csv(ExportScope::Filtered, true),
+            "#,/v\r\n1,a\r\n0,c\r\n"
+        );
+    }
+
+    #[test]
+    fn test_dirty_state() {
+        let json = r#"[{"a": "x"}, {"a": "y"}]"#;
+        let mut table = table_from_json(json, 1);
+        let column = table.columns(false)[0].clone();
+        let entry = |value: &str| FlatJsonValue {
+            pointer: PointerKey {
+                pointer: "/0/a".to_string(),
+                value_type: column.value_type,
+                depth: column.depth,
+                position: 0,
+                column_id: column.id,
+            },
+            value: Some(value.to_string()),
+        };
+        let mut array_response = ArrayResponse::default();
+        assert!(!table.is_dirty());
+        table
+            .try_edit_cell(&mut array_response, entry("first"), 0, false)
+            .unwrap();
+        assert!(table.is_dirty());
+        table.mark_saved();
+        assert!(!table.is_dirty());
+
+        // Undo back to saved baseline is clean, also from the other side of the baseline
+        table
+            .try_edit_cell(&mut array_response, entry("second"), 0, false)
+            .unwrap();
+        assert!(table.is_dirty());
+        table.replay_edit(&mut array_response, false);
+        assert!(!table.is_dirty());
+        table.replay_edit(&mut array_response, false);
+        assert!(table.is_dirty());
+        table.replay_edit(&mut array_response, true);
+        assert!(!table.is_dirty());
+
+        // Saved state can't be reached once redo stack is dropped by a new edit
+        table.replay_edit(&mut array_response, false);
+        table
+            .try_edit_cell(&mut array_response, entry("third"), 0, false)
+            .unwrap();
+        table.replay_edit(&mut array_response, false);
+        assert!(table.is_dirty());
+
+        // Deleting rows is undone back to the saved state
+        table.mark_saved();
+        table.delete_row(&mut array_response, 1);
+        assert!(table.is_dirty());
+        table.replay_edit(&mut array_response, false);
+        assert!(!table.is_dirty());
+
+        let mut history = EditHistory::new(1);
+        let edit = Edit {
+            row_index: 0,
+            pointer: entry("").pointer,
+            old_value: None,
+            new_value: None,
+            old_value_type: None,
+            reorders_keys: false,
+            row_change: None,
+        };
+        history.push(edit.clone());
+        history.mark_saved();
+        history.push(edit.clone());
+        history.undo();
+        assert!(history.is_saved());
+        // Opened state is before the dropped oldest edit, it can't be undone to anymore
+        let mut history = EditHistory::new(1);
+        history.push(edit.clone());
+        history.push(edit);
+        history.undo();
+        assert!(!history.is_saved());
+    }
+
+    #[test]
+    fn test_sub_table_writes_back_cell() {
+        let json = r#"[{"id": 1, "user": {"name": "a", "age": 3}}]"#;
+        let mut table = table_from_json(json, 1);
+        let cell = |table: &ArrayTable| {
+            let entry = table.nodes[0]
+                .entries
+                .iter()
+                .find(|entry| entry.pointer.pointer.eq("/0/user"))
+                .unwrap();
+            serde_json::from_str::<serde_json::Value>(entry.value.as_ref().unwrap()).unwrap()
+        };
+        assert!(table.open_cell_in_sub_table(0, "/0/id").is_err());
+        table.open_cell_in_sub_table(0, "/0/user").unwrap();
+
+        // Edit made in the sub table, written back as the serialized object
+        let edited = FlatJsonValue {
+            pointer: PointerKey::from_pointer("/0/user/name".to_string(), ValueType::String, 2, 0),
+            value: Some("b".to_string()),
+        };
+        let sub_table = &mut table.windows[0];
+        sub_table.update_nodes(edited.pointer.clone(), edited.value.clone());
+        let mut array_response = ArrayResponse::default();
+        table.apply_sub_table_edits(&mut array_response, 0, vec![edited]);
+        assert_eq!(cell(&table), serde_json::json!({"name": "b", "age": 3}));
+        assert_eq!(array_response.edited_value.len(), 1);
+        assert_eq!(array_response.edited_value[0].pointer.pointer, "/0/user");
+        let row = serde_json::from_str::<serde_json::Value>(&table.row_to_json(0)).unwrap();
+        assert_eq!(row["user"]["name"], "b");
+
+        // Undo in the parent reloads the sub table
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(cell(&table), serde_json::json!({"name": "a", "age": 3}));
+        let sub_table = &table.windows[0];
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&sub_table.to_json()).unwrap(),
+            serde_json::json!({"name": "a", "age": 3})
+        );
+    }
+
+    #[test]
+    fn test_reshape_locked_column() {
+        let json = r#"[{"a": "1", "b": "x y"}, {"a": "2", "b": "z"}]"#;
+        let mut table = table_from_json(json, 1);
+        let column = |table: &ArrayTable, name: &str| {
+            table
+                .all_columns()
+                .iter()
+                .find(|column| column.name == name)
+                .unwrap()
+                .clone()
+        };
+        let (a, b) = (column(&table, "/a"), column(&table, "/b"));
+        let mut array_response = ArrayResponse::default();
+        table.set_column_locked("/a", true);
+        let locked = "Column /a is locked".to_string();
+        assert_eq!(
+            table.convert_column(&mut array_response, &a, ValueType::Number),
+            Err(locked.clone())
+        );
+        assert_eq!(
+            table.merge_columns(&mut array_response, &b, &a, " "),
+            Err(locked.clone())
+        );
+        assert_eq!(
+            table.merge_columns(&mut array_response, &a, &b, " "),
+            Err(locked.clone())
+        );
+        assert_eq!(
+            table.split_column(&mut array_response, &a, " ", "/c"),
+            Err(locked)
+        );
+        assert!(array_response.edited_value.is_empty());
+        assert_eq!(column_value(&table.nodes[0], "", "/a"), Some("1"));
+        assert_eq!(column_value(&table.nodes[0], "", "/b"), Some("x y"));
+
+        // Other columns are still reshaped
+        assert_eq!(
+            table.split_column(&mut array_response, &b, " ", "/c"),
+            Ok(1)
+        );
+        table.set_column_locked("/a", false);
+        assert_eq!(
+            table.convert_column(&mut array_response, &a, ValueType::Number),
+            Ok((2, 0))
+        );
+    }
+
+    #[test]
+    fn test_paste_into_locked_column() {
+        let json = r#"[{"a": "x", "b": "y"}, {"a": "z", "b": "w"}]"#;
+        let mut table = table_from_json(json, 1);
+        let cell = |table: &ArrayTable, name: &str| CellLocation {
+            column_index: table
+                .columns(false)
+                .iter()
+                .position(|column| column.name == name)
+                .unwrap(),
+            row_index: 0,
+            is_pinned_column_table: false,
+        };
+        let value = |table: &ArrayTable, pointer: &str| {
+            table.nodes[0]
+                .entries()
+                .iter()
+                .find(|entry| entry.pointer.pointer.eq(pointer))
+                .and_then(|entry| entry.value.clone())
+        };
+        table.set_column_locked("/b", true);
+        assert!(table.is_column_locked("/b"));
+        let mut array_response = ArrayResponse::default();
+        let locked_cell = cell(&table, "/b");
+        assert_eq!(table.paste(&mut array_response, locked_cell, "pasted"), 0);
+        assert_eq!(value(&table, "/0/b"), Some("y".to_string()));
+        assert!(array_response.edited_value.is_empty());
+        let unlocked_cell = cell(&table, "/a");
+        assert_eq!(table.paste(&mut array_response, unlocked_cell, "pasted"), 1);
+        assert_eq!(value(&table, "/0/a"), Some("pasted".to_string()));
+
+        // Locked columns of a pasted block are skipped
+        assert_eq!(unlocked_cell.column_index + 1, locked_cell.column_index);
+        assert_eq!(table.paste(&mut array_response, unlocked_cell, "1\t2\n"), 1);
+        assert_eq!(value(&table, "/0/a"), Some("1".to_string()));
+        assert_eq!(value(&table, "/0/b"), Some("y".to_string()));
+        table.set_column_locked("/b", false);
+        assert_eq!(table.paste(&mut array_response, locked_cell, "pasted"), 1);
+        assert_eq!(value(&table, "/0/b"), Some("pasted".to_string()));
+    }
+
+    #[test]
+    fn test_cycle_cell_type() {
+        assert_eq!(
+            cycle_value_type("42", ValueType::String),
+            Some(("42".to_string(), ValueType::Number))
+        );
+        assert_eq!(
+            cycle_value_type("42", ValueType::Number),
+            Some(("42".to_string(), ValueType::String))
+        );
+        assert_eq!(
+            cycle_value_type("true", ValueType::String),
+            Some(("true".to_string(), ValueType::Bool))
+        );
+        assert_eq!(
+            cycle_value_type("true", ValueType::Bool),
+            Some(("true".to_string(), ValueType::String))
+        );
+        assert_eq!(cycle_value_type("abc", ValueType::String), None);
+        assert_eq!(cycle_value_type("null", ValueType::Null), None);
+
+        let json = r#"[{"id": "42", "ok": "true", "name": "abc"}]"#;
+        let mut table = table_from_json(json, 2);
+        let cell = |table: &ArrayTable, name: &str| CellLocation {
+            row_index: 0,
+            column_index: table
+                .columns(false)
+                .iter()
+                .position(|column| column.name == name)
+                .unwrap(),
+            is_pinned_column_table: false,
+        };
+        let mut array_response = ArrayResponse::default();
+        let id = cell(&table, "/id");
+        assert_eq!(
+            table.cycle_cell_type(&mut array_response, id),
+            Ok(ValueType::Number)
+        );
+        let ok = cell(&table, "/ok");
+        assert_eq!(
+            table.cycle_cell_type(&mut array_response, ok),
+            Ok(ValueType::Bool)
+        );
+        let name = cell(&table, "/name");
+        assert!(table.cycle_cell_type(&mut array_response, name).is_err());
+        let serialized = table.nodes()[0]
+            .entries()
+            .last()
+            .unwrap()
+            .value
+            .clone()
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({"id": 42, "ok": true, "name": "abc"})
+        );
+        assert_eq!(array_response.edited_value.len(), 2);
+
+        assert_eq!(
+            table.cycle_cell_type(&mut array_response, id),
+            Ok(ValueType::String)
+        );
+        let serialized = table.nodes()[0]
+            .entries()
+            .last()
+            .unwrap()
+            .value
+            .clone()
+            .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(value["id"], serde_json::json!("42"));
+
+        // Changes of type are undone like edits
+        assert_eq!(table.edit_history.edits().count(), 3);
+        let root = |table: &ArrayTable| {
+            let serialized = table.nodes()[0].entries().last().unwrap().value.clone();
+            serde_json::from_str::<serde_json::Value>(&serialized.unwrap()).unwrap()
+        };
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(root(&table)["id"], serde_json::json!(42));
+        let id_type = |table: &ArrayTable| {
+            table.nodes[0]
+                .find_node_at("/0/id")
+                .unwrap()
+                .pointer
+                .value_type
+        };
+        assert_eq!(id_type(&table), ValueType::Number);
+        table.replay_edit(&mut array_response, false);
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(
+            root(&table),
+            serde_json::json!({"id": "42", "ok": "true", "name": "abc"})
+        );
+        assert_eq!(id_type(&table), ValueType::String);
+        table.replay_edit(&mut array_response, true);
+        assert_eq!(root(&table)["id"], serde_json::json!(42));
+        let patch: serde_json::Value =
+            serde_json::from_str(&table.export_json_patch().unwrap()).unwrap();
+        assert_eq!(
+            patch,
+            serde_json::json!([{"op": "replace", "path": "/0/id", "value": 42}])
+        );
+    }
+
+    #[test]
+    fn test_move_row() {
+        let json = r#"[{"id": 0}, {"id": 1}, {"id": 2}, {"id": 3}]"#;
+        let mut table = table_from_json(json, 2);
+        let mut array_response = ArrayResponse::default();
+        let saved = |table: &ArrayTable| {
+            let mut buffer = vec![];
+            save_to_buffer("", table.nodes(), &mut buffer).unwrap();
+            serde_json::from_slice::<serde_json::Value>(&buffer).unwrap()
+        };
+        let mut entry = table.nodes()[0].find_node_at("/0/id").unwrap().clone();
+        entry.value = Some("10".to_string());
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry, 0, false),
+            Ok(true)
+        );
+        table.mark_saved();
+        assert!(table.move_row(&mut array_response, 0, 2));
+        assert_eq!(
+            array_response.edited_value.last().unwrap().pointer.pointer,
+            "/2"
+        );
+        assert_eq!(
+            saved(&table),
+            serde_json::json!([{"id": 1}, {"id": 2}, {"id": 10}, {"id": 3}])
+        );
+        assert!(table.is_dirty());
+        // Move is undone like an edit
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(
+            saved(&table),
+            serde_json::json!([{"id": 10}, {"id": 1}, {"id": 2}, {"id": 3}])
+        );
+        assert!(!table.is_dirty());
+        assert_eq!(table.filtered_nodes, vec![0, 1, 2, 3]);
+        // Edits made before and after the move are undone and redone on the moved row
+        table.replay_edit(&mut array_response, true);
+        let mut entry = table.nodes()[2].find_node_at("/2/id").unwrap().clone();
+        entry.value = Some("20".to_string());
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry, 2, false),
+            Ok(true)
+        );
+        for _ in 0..3 {
+            table.replay_edit(&mut array_response, false);
+        }
+        assert_eq!(
+            saved(&table),
+            serde_json::json!([{"id": 0}, {"id": 1}, {"id": 2}, {"id": 3}])
+        );
+        for _ in 0..3 {
+            table.replay_edit(&mut array_response, true);
+        }
+        assert_eq!(
+            saved(&table),
+            serde_json::json!([{"id": 1}, {"id": 2}, {"id": 20}, {"id": 3}])
+        );
+        // Row numbers and pointers follow new positions
+        for (i, row) in table.nodes().iter().enumerate() {
+            assert_eq!(row.index(), i);
+            assert_eq!(row.entries()[0].value, Some(i.to_string()));
+        }
+        let moved = &table.nodes()[2];
+        assert!(moved
+            .entries()
+            .iter()
+            .any(|entry| entry.pointer.pointer == "/2/id" && entry.value.as_deref() == Some("20")));
+        let patch: serde_json::Value =
+            serde_json::from_str(&table.export_json_patch().unwrap()).unwrap();
+        assert_eq!(
+            patch,
+            serde_json::json!([
+                {"op": "replace", "path": "/0/id", "value": 10},
+                {"op": "move", "from": "/0", "path": "/2"},
+                {"op": "replace", "path": "/2/id", "value": 20},
+            ])
+        );
+
+        table.column_sort = Some(("/id".to_string(), SortOrder::Ascending));
+        assert!(!table.move_row(&mut array_response, 0, 1));
+    }
+
+    #[test]
+    fn test_sort_all_keys() {
+        let json = r#"[{"b": 1, "a": {"d": [{"z": 1, "y": 2}], "c": 2}}, {"a": 1, "b": 2}]"#;
+        let mut table = table_from_json(json, 3);
+        let compact = SerializeOptions {
+            pretty: false,
+            ..SerializeOptions::default()
+        };
+        let saved = |table: &ArrayTable| {
+            let mut buffer = vec![];
+            save_to_buffer("", table.nodes(), &mut buffer).unwrap();
+            format_json(&String::from_utf8(buffer).unwrap(), &compact).unwrap()
+        };
+        let mut array_response = ArrayResponse::default();
+        // Second row keys are already sorted
+        assert_eq!(table.sort_all_keys(&mut array_response), 1);
+        let sorted = r#"[{"a":{"c":2,"d":[{"y":2,"z":1}]},"b":1},{"a":1,"b":2}]"#;
+        assert_eq!(saved(&table), sorted);
+
+        // Keys stay sorted once row is serialized again
+        let mut entry = table.nodes()[0].find_node_at("/0/b").unwrap().clone();
+        entry.value = Some("3".to_string());
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry, 0, false),
+            Ok(true)
+        );
+        assert_eq!(saved(&table), sorted.replace("\"b\":1", "\"b\":3"));
+
+        // Sorting is undone at once
+        table.replay_edit(&mut array_response, false);
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(
+            saved(&table),
+            r#"[{"b":1,"a":{"d":[{"z":1,"y":2}],"c":2}},{"a":1,"b":2}]"#
+        );
+        table.replay_edit(&mut array_response, true);
+        assert_eq!(saved(&table), sorted);
+
+        // With a start pointer, rows are saved under their pointer
+        let json = r#"{"results": [{"b": 1, "a": {"d": 2, "c": 3}}]}"#;
+        let result = JSONParser::parse(
+            json,
+            ParseOptions::default()
+                .parse_array(false)
+                .max_depth(3)
+                .start_parse_at("/results".to_string()),
+        )
+        .unwrap()
+        .to_owned();
+        let (array, columns) = as_array(result).unwrap();
+        let mut table = ArrayTable::new(
+            None,
+            array,
+            columns,
+            3,
+            PointerKey::from_pointer("/results".to_string(), ValueType::Array(1), 1, 0),
+        );
+        assert_eq!(table.sort_all_keys(&mut array_response), 1);
+        let mut buffer = vec![];
+        save_to_buffer("/results", table.nodes(), &mut buffer).unwrap();
+        assert_eq!(
+            format_json(&String::from_utf8(buffer).unwrap(), &compact).unwrap(),
+            r#"{"results":[{"a":{"c":3,"d":2},"b":1}]}"#
+        );
+    }
+}
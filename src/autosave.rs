@@ -0,0 +1,152 @@
+use crate::log;
+use crate::parser::write_atomically;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
+
+pub const AUTOSAVE_EXTENSION: &str = "autosave";
+
+/// Intervals offered in the menu, in seconds
+pub const AUTOSAVE_INTERVALS: [u64; 4] = [30, 60, 300, 900];
+
+pub struct Autosave {
+    // Seconds between two saves, None when autosave is disabled
+    pub interval: Option<u64>,
+    // Time of last save, or of last frame when document was clean
+    last_save_time: Option<f64>,
+    // Last write of the autosave file, running without blocking the ui
+    pending_write: Option<JoinHandle<()>>,
+}
+
+impl Autosave {
+    pub fn new(interval: Option<u64>) -> Self {
+        Self {
+            interval,
+            last_save_time: None,
+            pending_write: None,
+        }
+    }
+
+    /// Whether document should be saved at `time`, in seconds. Interval is counted from last save, or
+    /// from when document got dirty: a clean document is never saved.
+    pub fn should_save(&mut self, is_dirty: bool, time: f64) -> bool {
+        let Some(interval) = self.interval else {
+            self.last_save_time = None;
+            return false;
+        };
+        if !is_dirty {
+            self.last_save_time = Some(time);
+            return false;
+        }
+        let last_save_time = *self.last_save_time.get_or_insert(time);
+        if time - last_save_time >= interval as f64 {
+            self.last_save_time = Some(time);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Seconds until next save of a dirty document, to wake up the ui without user input
+    pub fn remaining(&self, time: f64) -> Option<f64> {
+        let interval = self.interval? as f64;
+        let last_save_time = self.last_save_time.unwrap_or(time);
+        Some((interval - (time - last_save_time)).max(0.0))
+    }
+
+    /// Write `content` to the autosave file of `path` on another thread, once the previous write is
+    /// done so an older content never replaces a newer one
+    pub fn write(&mut self, path: &Path, content: Vec<u8>) {
+        let previous_write = self.pending_write.take();
+        let autosave_path = autosave_path(path);
+        self.pending_write = Some(std::thread::spawn(move || {
+            if let Some(previous_write) = previous_write {
+                let _ = previous_write.join();
+            }
+            let result = write_atomically(&autosave_path, |file| file.write_all(&content));
+            if let Err(err) = result {
+                log!("Failed to autosave: {}", err);
+            }
+        }));
+    }
+
+    /// Delete the autosave file of `path`, after the pending write which would create it again
+    pub fn remove(&mut self, path: &Path) {
+        if let Some(pending_write) = self.pending_write.take() {
+            let _ = pending_write.join();
+        }
+        let _ = std::fs::remove_file(autosave_path(path));
+    }
+}
+
+/// Sidecar file holding autosaved content of `path`, e.g. `data.json.autosave`
+pub fn autosave_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(AUTOSAVE_EXTENSION);
+    path.with_file_name(file_name)
+}
+
+/// Autosave of `path` left by a session which did not save its changes, when it is newer than the file
+pub fn recoverable_autosave(path: &Path) -> Option<PathBuf> {
+    let autosave_path = autosave_path(path);
+    let autosave_modified = autosave_path.metadata().and_then(|m| m.modified()).ok()?;
+    match path.metadata().and_then(|m| m.modified()) {
+        Ok(modified) if modified > autosave_modified => None,
+        _ => Some(autosave_path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{autosave_path, recoverable_autosave, Autosave};
+    use std::path::Path;
+
+    #[test]
+    fn test_autosave_trigger() {
+        let mut autosave = Autosave::new(Some(30));
+        // Clean document is never saved
+        assert!(!autosave.should_save(false, 0.0));
+        assert!(!autosave.should_save(false, 100.0));
+        // Interval is counted from when document got dirty
+        assert!(!autosave.should_save(true, 110.0));
+        assert_eq!(autosave.remaining(110.0), Some(20.0));
+        assert!(autosave.should_save(true, 130.0));
+        assert!(!autosave.should_save(true, 140.0));
+        assert!(autosave.should_save(true, 160.0));
+        assert!(!autosave.should_save(false, 200.0));
+
+        let mut disabled = Autosave::new(None);
+        assert!(!disabled.should_save(true, 0.0));
+        assert!(!disabled.should_save(true, 1000.0));
+        assert_eq!(disabled.remaining(1000.0), None);
+
+        assert_eq!(
+            autosave_path(Path::new("dir/data.json")),
+            Path::new("dir/data.json.autosave")
+        );
+    }
+
+    #[test]
+    fn test_autosave_removed_after_pending_write() {
+        let directory = std::env::temp_dir().join(format!("autosave-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("data.json");
+        std::fs::write(&path, "[]").unwrap();
+        let mut autosave = Autosave::new(Some(30));
+        for content in ["[1]", "[1, 2]"] {
+            autosave.write(&path, content.as_bytes().to_vec());
+        }
+        // Saving removes the autosave, even while it is still being written
+        autosave.remove(&path);
+        assert_eq!(recoverable_autosave(&path), None);
+
+        autosave.write(&path, b"[1, 2, 3]".to_vec());
+        autosave.pending_write.take().unwrap().join().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(autosave_path(&path)).unwrap(),
+            "[1, 2, 3]"
+        );
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
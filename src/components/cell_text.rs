@@ -1,6 +1,197 @@
+use crate::array_table::{NumberBase, NumberFormat};
+use crate::concat_string;
 use eframe::emath::Align;
 use eframe::epaint;
-use egui::{FontSelection, Id, Response, RichText, Sense, Ui, WidgetText};
+use egui::{
+    Color32, Context, FontId, FontSelection, Id, Key, Label, Response, RichText, Sense, Stroke, Ui,
+    WidgetText,
+};
+use json_flat_parser::{FlatJsonValue, ValueType};
+use std::borrow::Cow;
+
+// Plain notation of numbers with a larger exponent is not displayed, value is shown as parsed
+const MAX_PLAIN_EXPONENT: i64 = 1000;
+// Longer values are never given whole to a label or a text edit, egui layout of a huge text is very slow
+pub const MAX_RENDERED_CHARS: usize = 10_000;
+// Characters per line of the large value viewer
+const LARGE_VALUE_LINE_CHARS: usize = 200;
+// Rows whose values are measured to size a column on first display
+pub const AUTO_SIZE_SAMPLED_ROWS: usize = 50;
+// Longer sampled values are measured on their first characters only, column is not sized to fit them
+const AUTO_SIZE_MAX_CHARS: usize = 40;
+
+/// Keep at most `max_chars` characters of a value to display it in a cell, followed by an ellipsis
+/// when it has been truncated
+pub fn truncate_cell_value(value: &str, max_chars: usize) -> Cow<str> {
+    match value.char_indices().nth(max_chars) {
+        Some((index, _)) => Cow::from(format!("{}…", &value[..index])),
+        None => Cow::from(value),
+    }
+}
+
+/// Whether `value` has more than `MAX_RENDERED_CHARS` characters
+pub fn is_too_large_to_render(value: &str) -> bool {
+    value.len() > MAX_RENDERED_CHARS && value.char_indices().nth(MAX_RENDERED_CHARS).is_some()
+}
+
+/// Part of `value` which can be given to a widget, at most `MAX_RENDERED_CHARS` characters followed by
+/// an ellipsis. Full value stays in the model.
+pub fn cap_rendered_value(value: &str) -> Cow<str> {
+    if value.len() <= MAX_RENDERED_CHARS {
+        return Cow::from(value);
+    }
+    truncate_cell_value(value, MAX_RENDERED_CHARS)
+}
+
+/// Widest laid out width of `samples`, so East Asian text and emoji are sized with their glyphs.
+/// Every sample is measured, on its first `AUTO_SIZE_MAX_CHARS` characters.
+pub fn auto_size_width<'a>(
+    ctx: &Context,
+    font_id: &FontId,
+    samples: impl Iterator<Item = &'a str>,
+) -> f32 {
+    ctx.fonts(|fonts| {
+        samples
+            .map(|sample| {
+                let sample = truncate_cell_value(sample, AUTO_SIZE_MAX_CHARS).into_owned();
+                fonts
+                    .layout_no_wrap(sample, font_id.clone(), Color32::PLACEHOLDER)
+                    .size()
+                    .x
+            })
+            .fold(0.0, f32::max)
+    })
+}
+
+/// Display a number in scientific or plain notation, digits are moved as text so no precision is
+/// lost. Value is returned as is when it is not a valid json number.
+pub fn format_number(value: &str, number_format: NumberFormat) -> Cow<str> {
+    if number_format == NumberFormat::AsParsed {
+        return Cow::from(value);
+    }
+    let Some((negative, digits, point_position)) = decompose_number(value) else {
+        return Cow::from(value);
+    };
+    if digits.is_empty() {
+        return Cow::from("0");
+    }
+    let mut formatted = String::with_capacity(digits.len() + 8);
+    if negative {
+        formatted.push('-');
+    }
+    match number_format {
+        NumberFormat::Scientific => {
+            formatted.push_str(&digits[..1]);
+            if digits.len() > 1 {
+                formatted.push('.');
+                formatted.push_str(&digits[1..]);
+            }
+            formatted.push_str(&format!("e{}", point_position - 1));
+        }
+        _ => {
+            if point_position.abs() > MAX_PLAIN_EXPONENT {
+                return Cow::from(value);
+            }
+            if point_position <= 0 {
+                formatted.push_str("0.");
+                formatted.push_str(&"0".repeat(-point_position as usize));
+                formatted.push_str(&digits);
+            } else if point_position as usize >= digits.len() {
+                formatted.push_str(&digits);
+                formatted.push_str(&"0".repeat(point_position as usize - digits.len()));
+            } else {
+                formatted.push_str(&digits[..point_position as usize]);
+                formatted.push('.');
+                formatted.push_str(&digits[point_position as usize..]);
+            }
+        }
+    }
+    Cow::from(formatted)
+}
+
+/// Text of a cell without a displayed value: with `show_null`, a key holding null shows `null`
+/// while a missing key (no `entry`) stays blank
+pub fn empty_cell_text(
+    entry: Option<&FlatJsonValue<String>>,
+    show_null: bool,
+) -> Option<&'static str> {
+    let entry = entry?;
+    let is_null = entry.value.is_none() || matches!(entry.pointer.value_type, ValueType::Null);
+    (show_null && is_null).then_some("null")
+}
+
+/// Integer `value` written in `base`, e.g. `0x1F` or `0b11111` for `31`. Other numbers, and every
+/// number in decimal base, are returned unchanged, as are integers out of `i128` range.
+pub fn format_in_base(value: &str, base: NumberBase) -> Cow<str> {
+    let Ok(integer) = value.parse::<i128>() else {
+        return Cow::from(value);
+    };
+    let sign = if integer < 0 { "-" } else { "" };
+    let magnitude = integer.unsigned_abs();
+    match base {
+        NumberBase::Hexadecimal => Cow::from(format!("{}0x{:X}", sign, magnitude)),
+        NumberBase::Binary => Cow::from(format!("{}0b{:b}", sign, magnitude)),
+        NumberBase::Decimal => Cow::from(value),
+    }
+}
+
+/// Digits of the integer part of `number` grouped by three with `separator`, e.g. `-1,234.5678`.
+/// Fraction and exponent are left as is, values which are not numbers are returned unchanged.
+pub fn group_thousands(number: &str, separator: char) -> Cow<str> {
+    let (sign, unsigned) = match number.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", number),
+    };
+    let (integer, rest) =
+        unsigned.split_at(unsigned.find(['.', 'e', 'E']).unwrap_or(unsigned.len()));
+    if integer.len() <= 3 || !integer.bytes().all(|b| b.is_ascii_digit()) {
+        return Cow::from(number);
+    }
+    let mut grouped =
+        String::with_capacity(number.len() + integer.len() / 3 * separator.len_utf8());
+    grouped.push_str(sign);
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push(separator);
+        }
+        grouped.push(digit);
+    }
+    grouped.push_str(rest);
+    Cow::from(grouped)
+}
+
+// Sign, significant digits without leading and trailing zeros, and position of the decimal point
+// relative to the first significant digit.
+fn decompose_number(value: &str) -> Option<(bool, String, i64)> {
+    let (negative, unsigned) = match value.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, value),
+    };
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(index) => (
+            &unsigned[..index],
+            unsigned[index + 1..].parse::<i64>().ok()?,
+        ),
+        None => (unsigned, 0),
+    };
+    let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if integer.is_empty()
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    let digits = concat_string!(integer, fraction);
+    let leading_zeros = digits.len() - digits.trim_start_matches('0').len();
+    let point_position = integer.len() as i64 + exponent - leading_zeros as i64;
+    Some((
+        negative,
+        digits.trim_matches('0').to_string(),
+        point_position,
+    ))
+}
 
 pub struct CellText {
     text: String,
@@ -42,3 +233,255 @@ impl CellText {
         cell_zone
     }
 }
+
+/// Read only view of a value too large to be rendered in a cell, only visible lines are laid out
+pub struct LargeValueViewer {
+    value: String,
+    chars_count: usize,
+    // Byte offset of each line start
+    line_starts: Vec<usize>,
+}
+
+impl LargeValueViewer {
+    pub fn new(value: String) -> Self {
+        let line_starts = value
+            .char_indices()
+            .step_by(LARGE_VALUE_LINE_CHARS)
+            .map(|(index, _)| index)
+            .collect();
+        Self {
+            chars_count: value.chars().count(),
+            value,
+            line_starts,
+        }
+    }
+
+    pub fn show(&self, ctx: &Context, open: &mut bool) {
+        egui::Window::new("Value too large")
+            .open(open)
+            .default_size([640.0, 480.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} characters", self.chars_count));
+                    if ui.button("Copy").clicked() {
+                        ui.ctx().copy_text(self.value.clone());
+                    }
+                });
+                ui.separator();
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                egui::ScrollArea::both().auto_shrink(false).show_rows(
+                    ui,
+                    row_height,
+                    self.line_starts.len(),
+                    |ui, lines| {
+                        for line in lines {
+                            let end = self
+                                .line_starts
+                                .get(line + 1)
+                                .copied()
+                                .unwrap_or(self.value.len());
+                            let text = &self.value[self.line_starts[line]..end];
+                            ui.add(Label::new(RichText::new(text).monospace()).extend());
+                        }
+                    },
+                );
+            });
+    }
+}
+
+/// Outline in red the text edit of an edit rejected because of `error`, which is shown on hover.
+/// Return the response with its tooltip and whether Escape was pressed to cancel the edit.
+pub fn show_rejected_edit(ui: &Ui, text_edit_response: Response, error: &str) -> (Response, bool) {
+    ui.painter()
+        .rect_stroke(text_edit_response.rect, 2.0, Stroke::new(1.0, Color32::RED));
+    let text_edit_response = text_edit_response.on_hover_text(format!(
+        "{}, press Shift+Enter to force or Escape to cancel",
+        error
+    ));
+    let cancelled = ui.input(|i| i.key_pressed(Key::Escape));
+    (text_edit_response, cancelled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        auto_size_width, cap_rendered_value, empty_cell_text, format_in_base, format_number,
+        group_thousands, is_too_large_to_render, truncate_cell_value, MAX_RENDERED_CHARS,
+    };
+    use crate::array_table::{DisplayOptions, NumberBase, NumberFormat};
+    use crate::parser::as_array;
+    use egui::Color32;
+    use json_flat_parser::{JSONParser, ParseOptions};
+    use std::borrow::Cow;
+
+    #[test]
+    fn test_truncate_cell_value() {
+        assert_eq!(truncate_cell_value("short", 10), "short");
+        assert!(matches!(truncate_cell_value("short", 10), Cow::Borrowed(_)));
+        assert_eq!(truncate_cell_value("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate_cell_value("longer than ten", 10), "longer tha…");
+        // Characters are counted, not bytes
+        assert_eq!(truncate_cell_value("ééééé", 3), "ééé…");
+    }
+
+    #[test]
+    fn test_format_number() {
+        let scientific = |value| format_number(value, NumberFormat::Scientific).into_owned();
+        let plain = |value| format_number(value, NumberFormat::Plain).into_owned();
+        assert_eq!(scientific("602200000000000000000000"), "6.022e23");
+        assert_eq!(scientific("-12.50"), "-1.25e1");
+        assert_eq!(scientific("0.00012"), "1.2e-4");
+        assert_eq!(scientific("100"), "1e2");
+        assert_eq!(scientific("0.0"), "0");
+        assert_eq!(plain("6.022e23"), "602200000000000000000000");
+        assert_eq!(plain("-1.5E-3"), "-0.0015");
+        assert_eq!(plain("1234.5e+1"), "12345");
+        assert_eq!(plain("1e-1"), "0.1");
+        assert_eq!(plain("-0"), "0");
+        assert_eq!(plain("1e100000"), "1e100000");
+        assert_eq!(plain("abc"), "abc");
+        assert!(matches!(
+            format_number("6.022e23", NumberFormat::AsParsed),
+            Cow::Borrowed("6.022e23")
+        ));
+    }
+
+    #[test]
+    fn test_cap_rendered_value() {
+        let small = "é".repeat(MAX_RENDERED_CHARS);
+        assert!(!is_too_large_to_render(&small));
+        assert!(matches!(cap_rendered_value(&small), Cow::Borrowed(_)));
+
+        let large = "é".repeat(1024 * 1024);
+        assert!(is_too_large_to_render(&large));
+        let capped = cap_rendered_value(&large);
+        assert_eq!(capped.chars().count(), MAX_RENDERED_CHARS + 1);
+        assert!(capped.ends_with('…'));
+        assert!(large.starts_with(capped.trim_end_matches('…')));
+    }
+
+    #[test]
+    fn test_auto_size_width() {
+        let ctx = egui::Context::default();
+        let _ = ctx.run(egui::RawInput::default(), |ctx| {
+            let font_id = egui::FontId::proportional(14.0);
+            let measure = |text: &str| {
+                ctx.fonts(|fonts| {
+                    fonts
+                        .layout_no_wrap(text.to_string(), font_id.clone(), Color32::PLACEHOLDER)
+                        .size()
+                        .x
+                })
+            };
+            assert_eq!(auto_size_width(ctx, &font_id, std::iter::empty()), 0.0);
+            // Every sample is laid out, the widest one gives the width
+            let samples = [
+                "id",
+                "abcdef",
+                "東京東京",
+                "카페",
+                "😀😀😀",
+                "👨\u{200D}👩\u{200D}👧",
+            ];
+            for sample in samples {
+                assert_eq!(
+                    auto_size_width(ctx, &font_id, std::iter::once(sample)),
+                    measure(sample)
+                );
+            }
+            let widest = samples
+                .iter()
+                .map(|sample| measure(sample))
+                .fold(0.0, f32::max);
+            assert_eq!(auto_size_width(ctx, &font_id, samples.into_iter()), widest);
+            let long_value = "a".repeat(100);
+            assert_eq!(
+                auto_size_width(ctx, &font_id, std::iter::once(long_value.as_str())),
+                measure(&format!("{}…", "a".repeat(40)))
+            );
+        });
+    }
+
+    #[test]
+    fn test_empty_cell_text() {
+        let json = r#"[{"a": 1, "b": null}, {"a": 2}]"#;
+        let res = JSONParser::parse(json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _) = as_array(res).unwrap();
+        let entry = |row_index: usize, pointer: &str| {
+            array[row_index]
+                .entries()
+                .iter()
+                .find(|entry| entry.pointer.pointer.eq(pointer))
+        };
+        // Present but null
+        assert_eq!(empty_cell_text(entry(0, "/0/b"), true), Some("null"));
+        assert_eq!(empty_cell_text(entry(0, "/0/b"), false), None);
+        // Absent key
+        assert!(entry(1, "/1/b").is_none());
+        assert_eq!(empty_cell_text(entry(1, "/1/b"), true), None);
+        // Values are displayed as is
+        assert_eq!(empty_cell_text(entry(0, "/0/a"), true), None);
+    }
+
+    #[test]
+    fn test_group_thousands() {
+        let grouped = |value| group_thousands(value, ',').into_owned();
+        assert_eq!(grouped("0"), "0");
+        assert_eq!(grouped("999"), "999");
+        assert_eq!(grouped("1000"), "1,000");
+        assert_eq!(grouped("-1000"), "-1,000");
+        assert_eq!(grouped("123456"), "123,456");
+        assert_eq!(grouped("1234567"), "1,234,567");
+        assert_eq!(grouped("-1234567.891011"), "-1,234,567.891011");
+        assert_eq!(grouped("0.123456"), "0.123456");
+        assert_eq!(grouped("12345e10"), "12,345e10");
+        assert_eq!(grouped("1e100"), "1e100");
+        assert_eq!(grouped("not a number"), "not a number");
+        assert!(matches!(group_thousands("999", ','), Cow::Borrowed(_)));
+        assert_eq!(group_thousands("1234567", ' '), "1 234 567");
+
+        let display_options = DisplayOptions {
+            number_format: NumberFormat::Plain,
+            thousands_separator: Some('\''),
+            ..DisplayOptions::default()
+        };
+        assert_eq!(display_options.display_number("1.5e6"), "1'500'000");
+        assert_eq!(display_options.display_number("-12345.5"), "-12'345.5");
+        assert_eq!(
+            DisplayOptions::default().display_number("1234567"),
+            "1234567"
+        );
+    }
+
+    #[test]
+    fn test_format_in_base() {
+        assert_eq!(format_in_base("31", NumberBase::Hexadecimal), "0x1F");
+        assert_eq!(format_in_base("31", NumberBase::Binary), "0b11111");
+        assert_eq!(format_in_base("0", NumberBase::Hexadecimal), "0x0");
+        assert_eq!(format_in_base("0", NumberBase::Binary), "0b0");
+        assert_eq!(format_in_base("255", NumberBase::Hexadecimal), "0xFF");
+        assert_eq!(
+            format_in_base("4096", NumberBase::Binary),
+            "0b1000000000000"
+        );
+        assert_eq!(format_in_base("-16", NumberBase::Hexadecimal), "-0x10");
+        // Non integers and decimal base are unchanged
+        assert_eq!(format_in_base("1.5", NumberBase::Hexadecimal), "1.5");
+        assert_eq!(format_in_base("1e3", NumberBase::Binary), "1e3");
+        let beyond_i128 = "1".repeat(40);
+        assert_eq!(
+            format_in_base(&beyond_i128, NumberBase::Hexadecimal),
+            beyond_i128
+        );
+        assert_eq!(
+            format_in_base(&i128::MIN.to_string(), NumberBase::Hexadecimal),
+            "-0x80000000000000000000000000000000"
+        );
+        assert!(matches!(
+            format_in_base("31", NumberBase::Decimal),
+            Cow::Borrowed("31")
+        ));
+    }
+}
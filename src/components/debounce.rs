@@ -0,0 +1,82 @@
+/// Seconds typing has to pause before rows are filtered again
+pub const FILTER_DEBOUNCE_DELAY: f64 = 0.15;
+
+/// Postpone an expensive update until changes pause for `delay` seconds, so a burst of changes,
+/// like keystrokes, triggers a single update with the last value.
+pub struct Debounce {
+    delay: f64,
+    // Time of the last change not applied yet
+    changed_at: Option<f64>,
+}
+
+impl Debounce {
+    pub fn new(delay: f64) -> Self {
+        Self {
+            delay,
+            changed_at: None,
+        }
+    }
+
+    pub fn on_change(&mut self, time: f64) {
+        self.changed_at = Some(time);
+    }
+
+    /// Whether the pending change should be applied at `time`, true once per burst of changes
+    pub fn is_due(&mut self, time: f64) -> bool {
+        match self.changed_at {
+            Some(changed_at) if time - changed_at >= self.delay => {
+                self.changed_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Forget the pending change, once it was applied by other means
+    pub fn cancel(&mut self) {
+        self.changed_at = None;
+    }
+
+    /// Seconds until the pending change is due, to wake up the ui without user input
+    pub fn remaining(&self, time: f64) -> Option<f64> {
+        self.changed_at
+            .map(|changed_at| (self.delay - (time - changed_at)).max(0.0))
+    }
+}
+
+impl Default for Debounce {
+    fn default() -> Self {
+        Self::new(FILTER_DEBOUNCE_DELAY)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Debounce;
+
+    #[test]
+    fn test_debounce() {
+        let mut debounce = Debounce::new(0.15);
+        assert!(!debounce.is_due(0.0));
+        assert_eq!(debounce.remaining(0.0), None);
+        // Rapid inputs collapse to a single update, after the last one
+        let mut updates = 0;
+        for time in [1.0, 1.05, 1.1, 1.2, 1.3] {
+            debounce.on_change(time);
+            updates += usize::from(debounce.is_due(time));
+        }
+        assert_eq!(updates, 0);
+        assert!(!debounce.is_due(1.4));
+        assert!(debounce
+            .remaining(1.4)
+            .is_some_and(|remaining| remaining > 0.0));
+        assert!(debounce.is_due(1.45));
+        assert!(!debounce.is_due(2.0));
+        assert_eq!(debounce.remaining(2.0), None);
+
+        // Update applied by other means is not applied again
+        debounce.on_change(3.0);
+        debounce.cancel();
+        assert!(!debounce.is_due(4.0));
+    }
+}
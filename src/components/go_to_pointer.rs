@@ -0,0 +1,47 @@
+use eframe::egui::{Color32, Key, Label, TextEdit, Ui};
+use std::time::Duration;
+
+// Seconds during which a pointer not found error is displayed
+const ERROR_DISPLAY_DURATION: f64 = 3.0;
+
+#[derive(Default)]
+pub struct GoToPointer {
+    pub pointer: String,
+    error: Option<(String, f64)>,
+}
+
+impl GoToPointer {
+    /// Return typed pointer when user press enter
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<String> {
+        ui.add(Label::new("Go to pointer: ").extend());
+        let text_edit = TextEdit::singleline(&mut self.pointer)
+            .hint_text("/0/key")
+            .desired_width(140.0);
+        let response = ui.add(text_edit);
+        if let Some((error, time)) = self.error.as_ref() {
+            let elapsed = ui.input(|i| i.time) - time;
+            if elapsed < ERROR_DISPLAY_DURATION {
+                ui.colored_label(Color32::RED, error);
+                ui.ctx().request_repaint_after(Duration::from_secs_f64(
+                    ERROR_DISPLAY_DURATION - elapsed,
+                ));
+            } else {
+                self.error = None;
+            }
+        }
+        if response.changed() {
+            self.error = None;
+        }
+        if response.lost_focus()
+            && ui.input(|i| i.key_pressed(Key::Enter))
+            && !self.pointer.is_empty()
+        {
+            return Some(self.pointer.clone());
+        }
+        None
+    }
+
+    pub fn set_error(&mut self, ui: &Ui, error: String) {
+        self.error = Some((error, ui.input(|i| i.time)));
+    }
+}
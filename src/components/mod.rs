@@ -1,6 +1,8 @@
 pub(crate) mod cache;
 pub mod cell_text;
+pub mod debounce;
 pub mod fps;
+pub mod go_to_pointer;
 pub mod icon;
 pub mod popover;
 pub mod table;
@@ -147,7 +147,7 @@ impl From<Vec<Size>> for Sizing {
 use eframe::egui::scroll_area::ScrollAreaOutput;
 use eframe::egui::{
     scroll_area::ScrollBarVisibility, Align, Color32, Id, NumExt as _, Pos2, Rangef, Rect,
-    Response, ScrollArea, Sense, Stroke, Ui, Vec2, Vec2b,
+    Response, ScrollArea, Sense, Shape, Stroke, Ui, Vec2, Vec2b,
 };
 use egui::UiBuilder;
 use std::mem;
@@ -184,6 +184,54 @@ pub(crate) struct StripLayoutFlags {
     pub(crate) selected: bool,
     pub(crate) selected_cell: bool,
     pub(crate) highlighted: bool,
+    // Sides of the cell on the border of the selected range: top, right, bottom, left
+    pub(crate) range_edges: [bool; 4],
+}
+
+/// Outline of the focused cell and border of the selected range drawn over the cell contents,
+/// `rect` being the gapless rect of the cell. Colored like selection so it is visible with dark
+/// and light themes.
+pub(crate) fn focus_outline(
+    rect: Rect,
+    flags: &StripLayoutFlags,
+    stroke_color: Color32,
+) -> Vec<Shape> {
+    let mut shapes = vec![];
+    let [top, right, bottom, left] = flags.range_edges;
+    let range_stroke = Stroke::new(1.0, stroke_color);
+    let rect = rect.shrink(0.5);
+    if top {
+        shapes.push(Shape::line_segment(
+            [rect.left_top(), rect.right_top()],
+            range_stroke,
+        ));
+    }
+    if right {
+        shapes.push(Shape::line_segment(
+            [rect.right_top(), rect.right_bottom()],
+            range_stroke,
+        ));
+    }
+    if bottom {
+        shapes.push(Shape::line_segment(
+            [rect.left_bottom(), rect.right_bottom()],
+            range_stroke,
+        ));
+    }
+    if left {
+        shapes.push(Shape::line_segment(
+            [rect.left_top(), rect.left_bottom()],
+            range_stroke,
+        ));
+    }
+    if flags.selected_cell {
+        shapes.push(Shape::rect_stroke(
+            rect.shrink(1.0),
+            egui::Rounding::ZERO,
+            Stroke::new(2.0, stroke_color),
+        ));
+    }
+    shapes
 }
 
 /// Positions cells in [`CellDirection`] and starts a new line on [`StripLayout::end_line`]
@@ -296,17 +344,6 @@ impl<'l> StripLayout<'l> {
                 .painter()
                 .rect_filled(gapless_rect, egui::Rounding::ZERO, Color32::YELLOW);
         }
-        if flags.selected_cell {
-            self.ui.painter().rect_stroke(
-                gapless_rect.shrink(2.0),
-                egui::Rounding::ZERO,
-                Stroke {
-                    color: Color32::DARK_GRAY,
-                    width: 2.0,
-                },
-            );
-        }
-
         let (child_ui, child_response) = self.cell(
             flags,
             max_rect,
@@ -314,6 +351,11 @@ impl<'l> StripLayout<'l> {
             cell_index,
             add_cell_contents,
         );
+        // Painted last so cell contents don't hide it
+        let stroke_color = self.ui.visuals().selection.stroke.color;
+        self.ui
+            .painter()
+            .extend(focus_outline(gapless_rect, &flags, stroke_color));
 
         let used_rect = child_ui.min_rect();
 
@@ -603,6 +645,7 @@ pub struct TableBuilder<'a> {
     scroll_options: TableScrollOptions,
     sense: egui::Sense,
     is_pinned_column_table: bool,
+    selected_range: Option<CellRange>,
 }
 
 impl<'a> TableBuilder<'a> {
@@ -617,6 +660,7 @@ impl<'a> TableBuilder<'a> {
             scroll_options: Default::default(),
             sense: egui::Sense::hover(),
             is_pinned_column_table: false,
+            selected_range: None,
         }
     }
 
@@ -628,6 +672,12 @@ impl<'a> TableBuilder<'a> {
         self.is_pinned_column_table = is_pinned_column_table;
         self
     }
+    /// Highlight cells of this range, column indexes are local to this table
+    #[inline]
+    pub fn selected_range(mut self, selected_range: Option<CellRange>) -> Self {
+        self.selected_range = selected_range;
+        self
+    }
     #[inline]
     pub fn striped(mut self, striped: bool) -> Self {
         self.striped = Some(striped);
@@ -683,8 +733,9 @@ impl<'a> TableBuilder<'a> {
 
     /// Don't make the scroll area higher than this (add scroll-bars instead!).
     ///
-    /// In other words: add scroll-bars when this height is reached.
-    /// Default: `800.0`.
+    /// In other words: add scroll-bars when this height is reached. The scroll area is also never
+    /// higher than the space left below the header.
+    /// Default: `f32::MAX`.
     #[inline]
     pub fn max_scroll_height(mut self, max_scroll_height: f32) -> Self {
         self.scroll_options.max_scroll_height = max_scroll_height;
@@ -733,6 +784,7 @@ impl<'a> TableBuilder<'a> {
             scroll_options,
             sense,
             is_pinned_column_table,
+            selected_range,
         } = self;
 
         let striped = striped.unwrap_or(ui.visuals().striped);
@@ -799,6 +851,7 @@ impl<'a> TableBuilder<'a> {
                 highlighted: false,
                 highlighted_cell: None,
                 selected_cell: None,
+                selected_range: None,
                 hovered_cell_index_id: None,
                 is_pinned_column_table,
             });
@@ -820,6 +873,7 @@ impl<'a> TableBuilder<'a> {
             scroll_options,
             sense,
             is_pinned_column_table,
+            selected_range,
         }
     }
 }
@@ -893,6 +947,7 @@ pub struct Table<'a> {
 
     sense: egui::Sense,
     is_pinned_column_table: bool,
+    selected_range: Option<CellRange>,
 }
 
 pub struct TableResponse {
@@ -902,6 +957,7 @@ pub struct TableResponse {
     pub last_visible_index: usize,
     pub first_visible_offset: f32,
     pub last_visible_offset: f32,
+    pub column_widths: Vec<f32>,
 }
 
 impl Table<'_> {
@@ -931,6 +987,7 @@ impl Table<'_> {
             scroll_options,
             sense,
             is_pinned_column_table,
+            selected_range,
         } = self;
 
         let TableScrollOptions {
@@ -946,6 +1003,11 @@ impl Table<'_> {
         } = scroll_options;
 
         let cursor_position = ui.cursor().min;
+        // Callers give the height of the whole table: once the header is laid out the body only
+        // gets the height left below it, so the table fits and the header is never scrolled away
+        // by a parent container.
+        let max_scroll_height =
+            max_scroll_height.min(ui.available_rect_before_wrap().height().max(0.0));
 
         let mut scroll_area = ScrollArea::new([false, vscroll])
             .id_salt(self.state_id.with("__scroll_area"))
@@ -1041,6 +1103,7 @@ impl Table<'_> {
                     search_matching_row_index,
                     hovered_cell_index_id,
                     is_pinned_column_table,
+                    selected_range,
                 });
 
                 if scroll_to_row.is_some() && scroll_to_y_range.is_none() {
@@ -1153,6 +1216,7 @@ impl Table<'_> {
 
             available_width -= *column_width + spacing_x;
         }
+        let column_widths = state.column_widths.clone();
         state.store(ui, state_id);
         TableResponse {
             scroll_area_output,
@@ -1161,17 +1225,50 @@ impl Table<'_> {
             last_visible_index: last_col_visible_index,
             first_visible_offset: first_col_visible_offset,
             last_visible_offset: last_col_visible_offset,
+            column_widths,
         }
     }
 }
 
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
 pub struct CellLocation {
     pub row_index: usize,
     pub column_index: usize,
     pub is_pinned_column_table: bool,
 }
 
+/// Rectangular range of cells, bounds are inclusive
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CellRange {
+    pub first_row: usize,
+    pub last_row: usize,
+    pub first_column: usize,
+    pub last_column: usize,
+}
+
+impl CellRange {
+    pub fn contains(&self, row_index: usize, column_index: usize) -> bool {
+        self.first_row <= row_index
+            && row_index <= self.last_row
+            && self.first_column <= column_index
+            && column_index <= self.last_column
+    }
+
+    /// Sides of the cell on the border of the range: top, right, bottom, left. All false for a
+    /// cell outside of the range.
+    pub fn edges(&self, row_index: usize, column_index: usize) -> [bool; 4] {
+        if !self.contains(row_index, column_index) {
+            return [false; 4];
+        }
+        [
+            row_index == self.first_row,
+            column_index == self.last_column,
+            row_index == self.last_row,
+            column_index == self.first_column,
+        ]
+    }
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct HoverData {
     pub hovered_row: Option<usize>,
@@ -1238,6 +1335,7 @@ pub struct TableBody<'a> {
     pub first_col_visible_width: f32,
     pub search_matching_row_index: Option<usize>,
     is_pinned_column_table: bool,
+    selected_range: Option<CellRange>,
 }
 
 impl TableBody<'_> {
@@ -1301,6 +1399,7 @@ impl TableBody<'_> {
                 highlighted: self.search_matching_row_index == Some(row_index),
                 highlighted_cell: None,
                 selected_cell: self.hovered_cell_index,
+                selected_range: self.selected_range,
                 selected: false,
                 response: &mut response,
                 hover_data: &mut hover_data,
@@ -1379,6 +1478,7 @@ pub struct TableRow<'a, 'b> {
     pub highlighted: bool,
     pub highlighted_cell: Option<usize>,
     pub selected_cell: Option<CellLocation>,
+    pub selected_range: Option<CellRange>,
     hovered_cell_index_id: Option<egui::Id>,
     is_pinned_column_table: bool,
 }
@@ -1437,6 +1537,7 @@ impl TableRow<'_, '_> {
             selected: self.selected,
             selected_cell,
             highlighted: self.highlighted,
+            range_edges: [false; 4],
         };
 
         let (used_rect, response) = self.layout.add(
@@ -1508,13 +1609,20 @@ impl TableRow<'_, '_> {
             } else {
                 false
             };
+            let selected = self.selected
+                || self
+                    .selected_range
+                    .is_some_and(|range| range.contains(self.row_index, *col_index));
             let flags = StripLayoutFlags {
                 clip,
                 striped: self.striped,
                 hovered: self.hovered,
-                selected: self.selected,
+                selected,
                 selected_cell,
                 highlighted: self.highlighted,
+                range_edges: self
+                    .selected_range
+                    .map_or([false; 4], |range| range.edges(self.row_index, *col_index)),
             };
 
             let (used_rect, response) = self.layout.add(
@@ -1583,3 +1691,90 @@ impl Drop for TableRow<'_, '_> {
         self.layout.end_line();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{focus_outline, CellRange, StripLayoutFlags};
+    use crate::array_table::Column;
+    use egui::{Color32, Pos2, Rect, Vec2};
+
+    #[test]
+    fn test_focus_outline() {
+        let range = CellRange {
+            first_row: 1,
+            last_row: 3,
+            first_column: 2,
+            last_column: 4,
+        };
+        assert_eq!(range.edges(1, 2), [true, false, false, true]);
+        assert_eq!(range.edges(2, 3), [false; 4]);
+        assert_eq!(range.edges(3, 4), [false, true, true, false]);
+        assert_eq!(range.edges(0, 2), [false; 4]);
+
+        let rect = Rect::from_min_size(Pos2::new(10.0, 20.0), Vec2::new(100.0, 24.0));
+        let flags = StripLayoutFlags {
+            selected_cell: true,
+            ..Default::default()
+        };
+        let shapes = focus_outline(rect, &flags, Color32::WHITE);
+        assert_eq!(shapes.len(), 1);
+        // Stroke stays inside of the cell
+        assert_eq!(shapes[0].visual_bounding_rect(), rect.shrink(0.5));
+        let flags = StripLayoutFlags {
+            range_edges: range.edges(1, 2),
+            ..Default::default()
+        };
+        assert_eq!(focus_outline(rect, &flags, Color32::WHITE).len(), 2);
+        assert!(focus_outline(rect, &StripLayoutFlags::default(), Color32::WHITE).is_empty());
+    }
+
+    #[test]
+    fn test_header_stays_in_view_while_scrolling() {
+        use crate::components::table::{Column as TableColumn, TableBuilder};
+        let ctx = egui::Context::default();
+        let screen_rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(400.0, 300.0));
+        let layout = |offset: f32| {
+            let mut rects = None;
+            // First frame is a sizing pass
+            for _ in 0..2 {
+                let input = egui::RawInput {
+                    screen_rect: Some(screen_rect),
+                    ..Default::default()
+                };
+                let _ = ctx.run(input, |ctx| {
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        let parent_rect = ui.available_rect_before_wrap();
+                        let mut header_rect = Rect::NOTHING;
+                        let response = TableBuilder::new(ui)
+                            .min_scrolled_height(0.0)
+                            .max_scroll_height(parent_rect.height())
+                            .vertical_scroll_offset(offset)
+                            .column(TableColumn::remainder())
+                            .header(40.0, |mut header| {
+                                header_rect = header.col(|ui, _| Some(ui.label("Header"))).0;
+                            })
+                            .body(None, None, None, |body| {
+                                body.rows(20.0, 1000, |mut row| {
+                                    row.col(|ui, _| Some(ui.label("Row")));
+                                });
+                            });
+                        rects = Some((
+                            parent_rect,
+                            header_rect,
+                            response.scroll_area_output.inner_rect,
+                        ));
+                    });
+                });
+            }
+            rects.unwrap()
+        };
+        let (parent_rect, header_rect, body_rect) = layout(0.0);
+        let (_, scrolled_header_rect, scrolled_body_rect) = layout(5000.0);
+        assert_eq!(header_rect, scrolled_header_rect);
+        assert_eq!(body_rect, scrolled_body_rect);
+        // Body is below the header and does not overflow the parent
+        assert!(header_rect.top() >= parent_rect.top());
+        assert!(body_rect.top() >= header_rect.bottom());
+        assert!(body_rect.bottom() <= parent_rect.bottom() + 0.5);
+    }
+}
@@ -0,0 +1,231 @@
+use json_flat_parser::{FlatJsonValue, PointerKey, ValueType};
+use std::collections::VecDeque;
+
+pub const DEFAULT_EDIT_HISTORY_DEPTH: usize = 100;
+
+#[derive(Clone, Debug)]
+pub struct Edit {
+    pub row_index: usize,
+    pub pointer: PointerKey,
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    // Type of the value before the edit when the edit changed it, e.g. with `cycle_cell_type`,
+    // the new type being the one of `pointer`
+    pub old_value_type: Option<ValueType>,
+    // The edit replaces a row root with its keys reordered, see `ArrayTable::sort_all_keys`
+    pub reorders_keys: bool,
+    // The edit inserts, removes or moves the whole row at `row_index`, `pointer` being its root
+    pub row_change: Option<RowChange>,
+}
+
+/// Insertion, removal or move of a row recorded by an edit
+#[derive(Clone, Debug)]
+pub enum RowChange {
+    // Entries of the inserted row
+    Inserted(Vec<FlatJsonValue<String>>),
+    // Entries of the removed row, to insert it back
+    Removed(Vec<FlatJsonValue<String>>),
+    // Index the row is moved to, rows in between being shifted
+    Moved(usize),
+}
+
+impl Edit {
+    /// Root pointer of the row once moved, for an edit moving a row
+    pub fn moved_row_pointer(&self) -> Option<String> {
+        let Some(RowChange::Moved(to)) = self.row_change else {
+            return None;
+        };
+        // Root pointer of a row ends with its index
+        let array_pointer = self
+            .pointer
+            .pointer
+            .rsplit_once('/')
+            .map_or("", |(array_pointer, _)| array_pointer);
+        Some(format!("{}/{}", array_pointer, to))
+    }
+
+    /// Edit applying the previous value, type and row of this one
+    fn reverted(&self) -> Edit {
+        let mut pointer = self.pointer.clone();
+        if let Some(old_value_type) = self.old_value_type {
+            pointer.value_type = old_value_type;
+        }
+        let mut row_index = self.row_index;
+        if let Some(RowChange::Moved(to)) = self.row_change {
+            // Row is moved back from the index it was moved to
+            pointer.pointer = self.moved_row_pointer().unwrap();
+            row_index = to;
+        }
+        Edit {
+            row_index,
+            pointer,
+            old_value: self.new_value.clone(),
+            new_value: self.old_value.clone(),
+            old_value_type: self.old_value_type.map(|_| self.pointer.value_type),
+            reorders_keys: self.reorders_keys,
+            row_change: self.row_change.as_ref().map(|row_change| match row_change {
+                RowChange::Inserted(entries) => RowChange::Removed(entries.clone()),
+                RowChange::Removed(entries) => RowChange::Inserted(entries.clone()),
+                RowChange::Moved(_) => RowChange::Moved(self.row_index),
+            }),
+        }
+    }
+}
+
+pub struct EditHistory {
+    // Edits grouped by operation, a group is undone and redone at once
+    undo_stack: VecDeque<Vec<Edit>>,
+    redo_stack: Vec<Vec<Edit>>,
+    // Maximum number of operations kept, oldest are dropped first
+    pub max_depth: usize,
+    // Count of applied edits when document was last saved, None once undo and redo can't go back to it
+    saved_len: Option<usize>,
+    // Every edit made since the history was created is kept, see `is_complete`
+    complete: bool,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_EDIT_HISTORY_DEPTH)
+    }
+}
+
+impl EditHistory {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::with_capacity(max_depth.min(DEFAULT_EDIT_HISTORY_DEPTH)),
+            redo_stack: vec![],
+            max_depth,
+            saved_len: Some(0),
+            complete: true,
+        }
+    }
+
+    pub fn push(&mut self, edit: Edit) {
+        self.push_group(vec![edit]);
+    }
+
+    /// Record edits of a single operation, e.g. sorting keys of all rows, undone as one edit
+    pub fn push_group(&mut self, edits: Vec<Edit>) {
+        if edits.is_empty() {
+            return;
+        }
+        if self.max_depth == 0 {
+            self.saved_len = None;
+            self.complete = false;
+            return;
+        }
+        // Saved state is lost with the redo stack or with the oldest edit
+        if self
+            .saved_len
+            .is_some_and(|saved_len| saved_len > self.undo_stack.len())
+        {
+            self.saved_len = None;
+        }
+        self.redo_stack.clear();
+        if self.undo_stack.len() >= self.max_depth {
+            self.undo_stack.pop_front();
+            self.complete = false;
+            self.saved_len = self
+                .saved_len
+                .and_then(|saved_len| saved_len.checked_sub(1));
+        }
+        self.undo_stack.push_back(edits);
+    }
+
+    /// Return edits to apply, in that order, in order to revert last edit
+    pub fn undo(&mut self) -> Option<Vec<Edit>> {
+        let edits = self.undo_stack.pop_back()?;
+        let reverted_edits = edits.iter().rev().map(Edit::reverted).collect();
+        self.redo_stack.push(edits);
+        Some(reverted_edits)
+    }
+
+    /// Return edits to apply, in that order, in order to replay last undone edit
+    pub fn redo(&mut self) -> Option<Vec<Edit>> {
+        let edits = self.redo_stack.pop()?;
+        self.undo_stack.push_back(edits.clone());
+        Some(edits)
+    }
+
+    /// Applied edits, oldest first, to record them again as a single group
+    pub fn into_edits(self) -> Vec<Edit> {
+        self.undo_stack.into_iter().flatten().collect()
+    }
+
+    /// Edits currently applied, oldest first. Edits dropped because of `max_depth` are not included.
+    pub fn edits(&self) -> impl Iterator<Item = &Edit> {
+        self.undo_stack.iter().flatten()
+    }
+
+    /// Whether applied edits are all the changes made since the history was created: false once
+    /// the oldest edit was dropped because of `max_depth`.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Remember current state as the saved one
+    pub fn mark_saved(&mut self) {
+        self.saved_len = Some(self.undo_stack.len());
+    }
+
+    /// Whether edits applied are the ones applied when document was last saved, or opened
+    pub fn is_saved(&self) -> bool {
+        self.saved_len == Some(self.undo_stack.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::array_table::{table_from_json, ArrayTable};
+    use crate::ArrayResponse;
+    use json_flat_parser::FlatJsonValue;
+
+    #[test]
+    fn test_undo_edits_restore_original_value() {
+        let json = r#"[{"a": "original", "b": 1}]"#;
+        let mut table = table_from_json(json, 1);
+        let mut array_response = ArrayResponse::default();
+        let pointer = table.nodes[0].find_node_at("/0/a").unwrap().pointer.clone();
+
+        for new_value in ["first", "second"] {
+            let updated_entry = FlatJsonValue {
+                pointer: pointer.clone(),
+                value: Some(new_value.to_string()),
+            };
+            assert_eq!(
+                table.try_edit_cell(&mut array_response, updated_entry, 0, false),
+                Ok(true)
+            );
+        }
+        assert_eq!(table.edit_history.edits().count(), 2);
+        table.replay_edit(&mut array_response, false);
+        table.replay_edit(&mut array_response, false);
+
+        let value_at = |table: &ArrayTable| {
+            table.nodes[0]
+                .find_node_at("/0/a")
+                .unwrap()
+                .value
+                .clone()
+                .unwrap()
+        };
+        assert_eq!(value_at(&table), "original");
+        let root: serde_json::Value = serde_json::from_str(
+            table.nodes[0]
+                .entries
+                .last()
+                .unwrap()
+                .value
+                .as_ref()
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(root, serde_json::json!({"a": "original", "b": 1}));
+        // Replayed edits are not recorded again
+        assert_eq!(table.edit_history.edits().count(), 0);
+        table.replay_edit(&mut array_response, true);
+        assert_eq!(value_at(&table), "first");
+        assert_eq!(table.edit_history.edits().count(), 1);
+    }
+}
@@ -3,9 +3,11 @@
 extern crate core;
 
 mod array_table;
+mod autosave;
 mod compatibility;
 mod components;
 pub mod fonts;
+mod history;
 mod object_table;
 mod panels;
 pub mod parser;
@@ -19,17 +21,23 @@ use std::fs::File;
 use std::io::Read;
 use std::{env, mem};
 
+use crate::components::cell_text::group_thousands;
 use crate::components::fps::FrameHistory;
+use crate::components::go_to_pointer::GoToPointer;
 use parking_lot_mpsc::{Receiver, SyncSender};
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use crate::array_table::{ArrayTable, ScrollToRowMode};
+use crate::array_table::{ArrayTable, EditOptions, ExportScope, NumberFormat, ScrollToRowMode};
+use crate::autosave::{autosave_path, Autosave, AUTOSAVE_INTERVALS};
 use crate::components::icon;
 use crate::components::table::HoverData;
 use crate::fonts::{CHEVRON_DOWN, CHEVRON_UP};
 use crate::panels::{AboutPanel, PANEL_ABOUT};
-use crate::parser::{save_to_buffer, save_to_file};
+use crate::parser::{
+    format_json, save_jsonl_to_buffer, save_jsonl_to_file, save_root_object_to_buffer,
+    save_to_buffer, save_to_path, write_atomically, ParseStats, SerializeOptions, Truncation,
+};
 use eframe::egui::Context;
 use eframe::egui::{
     Align, Align2, Button, Color32, ComboBox, CursorIcon, Id, Key, KeyboardShortcut, Label,
@@ -51,6 +59,12 @@ pub const SHORTCUT_COPY: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COM
 pub const SHORTCUT_PASTE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::V);
 pub const SHORTCUT_DELETE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::D);
 pub const SHORTCUT_REPLACE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::R);
+pub const SHORTCUT_UNDO: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+pub const SHORTCUT_REDO: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z);
+pub const SHORTCUT_CYCLE_TYPE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::T);
+// Rows kept when only first rows of a large array are loaded
+const FIRST_ROWS_COUNT: usize = 5000;
 
 /// Something to view in the demo windows
 pub trait View<R> {
@@ -123,7 +137,7 @@ fn main() {
                 let args: Vec<_> = env::args().collect();
                 if args.len() >= 2 {
                     println!("Opening {}", args[1].as_str());
-                    app.selected_file = Some(PathBuf::from(args[1].as_str()));
+                    app.select_file(PathBuf::from(args[1].as_str()));
                     app.should_parse_again = true;
                 }
                 if args.len() >= 3 {
@@ -149,11 +163,60 @@ struct MyApp<'array> {
     parsing_invalid_pointers: Vec<String>,
     selected_pointer: Option<String>,
     min_depth: u8,
+    // Content not saved yet, like a pasted document. Edits of the table are tracked by `ArrayTable::is_dirty`
     unsaved_changes: bool,
+    // Content of the selected file is read from its autosave, see `autosave::recoverable_autosave`
+    autosave_recovered: bool,
+    autosave: Autosave,
     show_fps: bool,
     web_loaded_json: Option<Vec<u8>>,
     async_events_channel: (SyncSender<AsyncEvent>, Receiver<AsyncEvent>),
     failed_to_load_sample_json: Option<String>,
+    parsing_error: Option<String>,
+    // Show a document whose root is an object as a table with a single row
+    open_root_object_as_row: bool,
+    root_object_wrapped: bool,
+    // Accept NaN, Infinity and -Infinity numbers and raw control characters in strings, always
+    // enabled for jsonc and json5 files
+    lenient: bool,
+    // Next pasted text is opened as a new table, even when a table is already opened
+    paste_json_requested: bool,
+    // Why the last pasted text could not replace the opened table
+    paste_error: Option<String>,
+    // Parse elements of nested arrays to show them as indexed columns
+    flatten_arrays: bool,
+    // Every scalar is typed as a string once parsed
+    all_as_strings: bool,
+    // First row of csv and tsv files holds the keys of the other rows
+    csv_has_header: bool,
+    // CSV exports start with the `#` column, index of elements in the array
+    export_row_index: bool,
+    // Pretty JSON exports write non ascii chars as \uXXXX escapes
+    export_escape_unicode: bool,
+    // Object keys are renamed to snake_case once parsed, see `parser::normalize_keys`
+    normalize_keys: bool,
+    // Depths are shown and selected from the array at start pointer instead of the document root
+    depth_relative_to_start: bool,
+    is_jsonl: bool,
+    jsonl_invalid_lines: Vec<(usize, String)>,
+    // Next parsing keeps elements of root array before a parse error, see `parser::parse_lossy`
+    parse_lossy: bool,
+    // Errors which made content to be skipped by last lossy parsing
+    lossy_parse_errors: Vec<String>,
+    // Only part of the document is in the table, Save can't overwrite the file with it
+    partially_opened: bool,
+    // Rows are built for the first `FIRST_ROWS_COUNT` elements of the array only
+    load_first_rows_only: bool,
+    // Set when elements of the array were not loaded because of `load_first_rows_only`
+    truncation: Option<Truncation>,
+    duplicate_pointers: Vec<String>,
+    // Keys not normalized as another key of their object has the same normalized name
+    unnormalized_keys: Vec<String>,
+    // Counts of values of the opened document
+    parse_stats: Option<ParseStats>,
+    // Set when the document is nested deeper than it can be parsed, deeper values are json text
+    nesting_warning: Option<String>,
+    go_to_pointer: GoToPointer,
     force_repaint: bool,
 }
 
@@ -190,10 +253,37 @@ impl MyApp<'_> {
             selected_pointer: None,
             min_depth: 0,
             unsaved_changes: false,
+            autosave_recovered: false,
+            autosave: Autosave::new(None),
             show_fps: true,
             web_loaded_json: None,
             async_events_channel: (sender, receiver),
             failed_to_load_sample_json: None,
+            parsing_error: None,
+            open_root_object_as_row: false,
+            root_object_wrapped: false,
+            lenient: false,
+            paste_json_requested: false,
+            paste_error: None,
+            flatten_arrays: false,
+            all_as_strings: false,
+            csv_has_header: true,
+            export_row_index: false,
+            export_escape_unicode: false,
+            normalize_keys: false,
+            depth_relative_to_start: false,
+            is_jsonl: false,
+            jsonl_invalid_lines: vec![],
+            parse_lossy: false,
+            lossy_parse_errors: vec![],
+            partially_opened: false,
+            load_first_rows_only: false,
+            truncation: None,
+            duplicate_pointers: vec![],
+            unnormalized_keys: vec![],
+            parse_stats: None,
+            nesting_warning: None,
+            go_to_pointer: GoToPointer::default(),
             force_repaint: false,
         }
     }
@@ -207,38 +297,135 @@ impl MyApp<'_> {
     pub fn open_json(&mut self) {
         #[cfg(not(target_arch = "wasm32"))]
         {
-            let mut file = File::open(self.selected_file.as_ref().unwrap()).unwrap();
+            let path = self.selected_file.as_ref().unwrap();
+            // Autosave may have been removed since recovery was accepted, the file is opened then
+            let autosave = self
+                .autosave_recovered
+                .then(|| File::open(autosave_path(path)).ok())
+                .flatten();
+            self.autosave_recovered = autosave.is_some();
+            let mut file = match autosave.map_or_else(|| File::open(path), Ok) {
+                Ok(file) => file,
+                Err(err) => {
+                    self.on_parsing_error(err.to_string());
+                    return;
+                }
+            };
             let metadata1 = file.metadata().unwrap();
 
             let size = (metadata1.len() / 1024 / 1024) as usize;
-            let max_depth = if size < 100 {
-                // 1
-                u8::MAX
-            } else {
-                1 // should start after prefix
-            };
-            let mut content = String::with_capacity(metadata1.len() as usize);
+            let mut content = Vec::with_capacity(metadata1.len() as usize);
             // let mut reader = LfToCrlfReader::new(file);
             // reader.read_to_string(&mut content);
-            file.read_to_string(&mut content).unwrap();
+            file.read_to_end(&mut content).unwrap();
 
-            self.open_json_content(max_depth, content.as_bytes());
+            self.is_jsonl = Self::is_jsonl_file(self.selected_file.as_ref().unwrap());
+            self.jsonl_invalid_lines.clear();
+            let with_comments =
+                Self::is_json_with_comments_file(self.selected_file.as_ref().unwrap());
+            // Autosave of a converted file holds json, as it would be saved
+            let is_converted_autosave = self.autosave_recovered
+                && Self::is_converted_file(self.selected_file.as_ref().unwrap());
+            let is_yaml =
+                Self::is_yaml_file(self.selected_file.as_ref().unwrap()) && !is_converted_autosave;
+            let delimiter = Self::delimiter_of_file(self.selected_file.as_ref().unwrap())
+                .filter(|_| !is_converted_autosave);
+            let lenient = self.lenient || with_comments;
+            // Plain json is parsed from bytes, utf-8 is only validated when content has to be rewritten
+            if self.is_jsonl || lenient || is_yaml || delimiter.is_some() {
+                let text = match crate::parser::decode_utf8(content) {
+                    Ok(text) => text,
+                    Err(err) => {
+                        self.on_parsing_error(err);
+                        return;
+                    }
+                };
+                // Byte order mark of json is skipped by `open_json_content`, converted documents
+                // lose it before conversion
+                let converted_text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+                content = if self.is_jsonl {
+                    let (json_array, invalid_lines) =
+                        crate::parser::jsonl_to_json_array(converted_text);
+                    self.jsonl_invalid_lines = invalid_lines;
+                    json_array.into_bytes()
+                } else if is_yaml {
+                    // Saved to a json file, see `overwritten_file`
+                    match crate::parser::yaml_to_json(converted_text) {
+                        Ok(json) => json.into_bytes(),
+                        Err(err) => {
+                            self.on_parsing_error(err);
+                            return;
+                        }
+                    }
+                } else if let Some(delimiter) = delimiter {
+                    // Rows are saved as a json array, to another file
+                    match crate::parser::csv_to_json(converted_text, self.csv_has_header, delimiter)
+                    {
+                        Ok(json) => json.into_bytes(),
+                        Err(err) => {
+                            self.on_parsing_error(err);
+                            return;
+                        }
+                    }
+                } else if with_comments {
+                    let text = crate::parser::strip_comments_and_trailing_commas(&text);
+                    crate::parser::quote_non_finite_numbers(&text).into_bytes()
+                } else {
+                    crate::parser::quote_non_finite_numbers(&text).into_bytes()
+                };
+            }
+
+            // Yaml and csv strings were escaped when converted to json
+            self.open_json_content(&content, !lenient, size < 100);
+            if self.autosave_recovered && self.table.is_some() {
+                // Recovered changes are only in the autosave until the file is saved
+                self.unsaved_changes = true;
+            }
         }
         #[cfg(target_arch = "wasm32")]
         {
             if self.web_loaded_json.is_some() {
                 let json = mem::take(&mut self.web_loaded_json);
-                self.open_json_content(u8::MAX, json.unwrap().as_slice());
+                self.open_json_content(json.unwrap().as_slice(), !self.lenient, true);
                 self.selected_file = Some(PathBuf::default());
             }
         }
     }
 
-    fn open_json_content(&mut self, max_depth: u8, json: &[u8]) {
+    /// Open `json` once it passed a scan of the whole document, raw control characters in strings
+    /// are rejected when `strict`. Unless `parse_all_levels`, only the first level is parsed.
+    fn open_json_content(&mut self, json: &[u8], strict: bool, parse_all_levels: bool) {
+        let json = crate::parser::strip_bom(json);
+        let scan = crate::parser::scan_document(json);
+        self.nesting_warning = scan.nesting_warning();
+        if let Some(err) = scan.error(strict) {
+            self.on_parsing_error(err);
+            return;
+        }
+        self.parse_stats = Some(scan.stats);
+        let max_depth = if parse_all_levels {
+            // Parse up to actual depth of the document, or as deep as parser can recurse
+            scan.max_depth()
+        } else {
+            1 // should start after prefix
+        };
+        self.open_scanned_json(max_depth, json);
+    }
+
+    fn open_scanned_json(&mut self, max_depth: u8, json: &[u8]) {
+        if self.open_root_object_as_row && self.selected_pointer.is_none() {
+            if let Some(wrapped_json) = crate::parser::wrap_root_object(json) {
+                // Object is one level deeper once wrapped
+                self.open_scanned_json(max_depth.saturating_add(1), &wrapped_json);
+                self.root_object_wrapped = self.table.is_some();
+                return;
+            }
+        }
+        self.root_object_wrapped = false;
         let mut found_array = false;
         let size = json.len() / 1024 / 1024;
         log!(
-            "open_json_content with size {}mb, found array {}",
+            "open_scanned_json with size {}mb, found array {}",
             size,
             found_array
         );
@@ -254,14 +441,38 @@ impl MyApp<'_> {
         if found_array || self.selected_pointer.is_some() {
             let start = crate::compatibility::now();
             let mut options = ParseOptions::default()
-                .parse_array(false)
+                .parse_array(self.flatten_arrays)
                 .max_depth(max_depth);
             if let Some(ref start_at) = self.selected_pointer {
                 options = options.start_parse_at(start_at.clone());
             }
-            let parse_result = JSONParser::parse_bytes(json, options);
-
-            let result = parse_result.unwrap().to_owned();
+            let mut result = if mem::take(&mut self.parse_lossy) {
+                match crate::parser::parse_lossy(&String::from_utf8_lossy(json), options) {
+                    Ok((parse_result, errors)) => {
+                        self.lossy_parse_errors = errors;
+                        parse_result
+                    }
+                    Err(err) => {
+                        self.on_parsing_error(err);
+                        return;
+                    }
+                }
+            } else {
+                self.lossy_parse_errors.clear();
+                match JSONParser::parse_bytes(json, options) {
+                    Ok(parse_result) => parse_result.to_owned(),
+                    Err(err) => {
+                        self.on_parsing_error(err);
+                        return;
+                    }
+                }
+            };
+            self.partially_opened = !self.lossy_parse_errors.is_empty();
+            self.unnormalized_keys = if self.normalize_keys {
+                crate::parser::normalize_keys(&mut result, crate::parser::snake_case_key)
+            } else {
+                vec![]
+            };
             let parsing_max_depth = result.parsing_max_depth;
             log!(
                 "Custom parser took {}ms for a {}mb file, max depth {}, {}",
@@ -273,13 +484,32 @@ impl MyApp<'_> {
             let parse_result = result.clone_except_json();
 
             let start = crate::compatibility::now();
-            let (result1, columns) = crate::parser::as_array(result).unwrap();
+            let max_rows = self.load_first_rows_only.then_some(FIRST_ROWS_COUNT);
+            let (result1, columns, truncation) =
+                match crate::parser::as_array_with_max_elements(result, max_rows) {
+                    Ok(array) => array,
+                    Err(err) => {
+                        self.on_parsing_error(err);
+                        return;
+                    }
+                };
+            self.truncation = truncation;
+            self.partially_opened |= truncation.is_some();
+            // Rows which are not loaded still have their columns listed, keys of the whole array are
+            // walked without storing values. Flattened arrays and normalized keys are not walked.
+            let columns = if truncation.is_some() && !self.flatten_arrays && !self.normalize_keys {
+                crate::parser::parse_keys_only(json, self.selected_pointer.as_deref(), max_depth)
+                    .unwrap_or(columns)
+            } else {
+                columns
+            };
             log!(
                 "Transformation to array took {}ms, root array len {}, columns {}",
                 start.elapsed().as_millis(),
                 result1.len(),
                 columns.len()
             );
+            self.duplicate_pointers = crate::parser::find_duplicate_pointers(&result1);
 
             let max_depth = parse_result.max_json_depth;
             let depth =
@@ -294,13 +524,19 @@ impl MyApp<'_> {
                 prefix = start_at.clone();
             }
             let len = result1.len();
-            let table = ArrayTable::new(
+            let mut table = ArrayTable::new(
                 Some(parse_result),
                 result1,
                 columns,
                 depth,
                 PointerKey::from_pointer(prefix, ValueType::Array(len), 1, 0),
             );
+            if self.flatten_arrays {
+                table.set_flatten_arrays(true);
+            }
+            if self.all_as_strings {
+                table.set_all_as_strings(true);
+            }
             self.table = Some(table);
             self.depth = depth;
             self.max_depth = max_depth as u8;
@@ -310,11 +546,18 @@ impl MyApp<'_> {
             self.parsing_invalid = false;
             self.selected_pointer = None;
             self.unsaved_changes = false;
+            self.parsing_error = None;
         } else {
             let options = ParseOptions::default()
                 .parse_array(false)
                 .max_depth(max_depth);
-            let result = JSONParser::parse_bytes(json, options.clone()).unwrap();
+            let result = match JSONParser::parse_bytes(json, options.clone()) {
+                Ok(parse_result) => parse_result,
+                Err(err) => {
+                    self.on_parsing_error(err);
+                    return;
+                }
+            };
             self.should_parse_again = true;
             self.parsing_invalid = true;
             self.unsaved_changes = false;
@@ -331,12 +574,101 @@ impl MyApp<'_> {
         }
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    fn is_jsonl_file(path: &std::path::Path) -> bool {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| {
+                extension.eq_ignore_ascii_case("jsonl") || extension.eq_ignore_ascii_case("ndjson")
+            })
+            .unwrap_or(false)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn is_json_with_comments_file(path: &std::path::Path) -> bool {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| {
+                extension.eq_ignore_ascii_case("jsonc") || extension.eq_ignore_ascii_case("json5")
+            })
+            .unwrap_or(false)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn delimiter_of_file(path: &std::path::Path) -> Option<char> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => Some(','),
+            Some(extension) if extension.eq_ignore_ascii_case("tsv") => Some('\t'),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn is_yaml_file(path: &std::path::Path) -> bool {
+        path.extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| {
+                extension.eq_ignore_ascii_case("yaml") || extension.eq_ignore_ascii_case("yml")
+            })
+            .unwrap_or(false)
+    }
+
+    fn open_pasted_json(&mut self, text: &str) {
+        // Pasted json can't be parsed leniently
+        let (json, wrapped) = match crate::parser::pasted_json_document(text, true) {
+            Ok(document) => document,
+            // Opened table is kept, error is shown until another paste or cancel
+            Err(err) if self.table.is_some() => {
+                self.paste_error = Some(err);
+                return;
+            }
+            Err(err) => {
+                self.paste_json_requested = false;
+                self.selected_file = None;
+                self.on_parsing_error(err);
+                return;
+            }
+        };
+        self.paste_json_requested = false;
+        self.paste_error = None;
+        if !self.confirm_discard_changes() {
+            return;
+        }
+        self.reset_document_options();
+        self.table = None;
+        self.selected_file = None;
+        self.selected_pointer = None;
+        self.is_jsonl = false;
+        self.parsing_invalid = false;
+        self.parsing_invalid_pointers.clear();
+        self.parsing_error = None;
+        self.open_json_content(&json, true, json.len() / 1024 / 1024 < 100);
+        self.root_object_wrapped = wrapped && self.table.is_some();
+        if self.table.is_some() {
+            // Content only exists in the table until it is saved
+            self.unsaved_changes = true;
+        }
+    }
+
+    fn on_parsing_error(&mut self, err: String) {
+        log!("Failed to parse json: {}", err);
+        self.parsing_error = Some(err);
+        self.should_parse_again = false;
+        self.parsing_invalid = false;
+        self.parsing_invalid_pointers.clear();
+        self.selected_pointer = None;
+    }
+
     fn file_picker(&mut self) {
+        if !self.confirm_discard_changes() {
+            return;
+        }
         #[cfg(not(target_arch = "wasm32"))]
         {
             if let Some(path) = rfd::FileDialog::new().pick_file() {
-                self.selected_file = Some(path);
+                self.select_file(path);
                 self.should_parse_again = true;
+                self.parsing_error = None;
                 self.table = None;
             }
         }
@@ -380,28 +712,69 @@ impl MyApp<'_> {
         true
     }
 
+    /// File overwritten by Save, None when Save As has to be used: table opened from pasted json,
+    /// from a csv or yaml file which is not overwritten with json, or holding only part of the file
+    #[cfg(not(target_arch = "wasm32"))]
+    fn overwritten_file(
+        selected_file: Option<&PathBuf>,
+        partially_opened: bool,
+    ) -> Option<&PathBuf> {
+        selected_file.filter(|path| !partially_opened && !Self::is_converted_file(path))
+    }
+
+    /// Whether file content is converted to json when opened, it is then saved to a json file
+    #[cfg(not(target_arch = "wasm32"))]
+    fn is_converted_file(path: &std::path::Path) -> bool {
+        Self::delimiter_of_file(path).is_some() || Self::is_yaml_file(path)
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     fn save(&mut self) {
+        match Self::overwritten_file(self.selected_file.as_ref(), self.partially_opened) {
+            Some(path) => {
+                let path = path.clone();
+                self.save_to(&path);
+            }
+            None => self.save_as(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_to(&mut self, path: &std::path::Path) {
         let table = self.table.as_ref().unwrap();
-        save_to_file(
-            table.parent_pointer.pointer.as_str(),
-            table.nodes(),
-            self.selected_file.as_ref().unwrap(),
-        )
-        .unwrap();
-        self.unsaved_changes = false;
+        let result = if self.is_jsonl {
+            save_jsonl_to_file(table.nodes(), path)
+        } else if self.root_object_wrapped {
+            write_atomically(path, |file| save_root_object_to_buffer(table.nodes(), file))
+        } else {
+            save_to_path(table.parent_pointer.pointer.as_str(), table.nodes(), path)
+        };
+        match result {
+            Ok(()) => self.on_saved(),
+            Err(error) => {
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Failed to save file")
+                    .set_description(error)
+                    .show();
+            }
+        }
     }
 
     #[cfg(target_arch = "wasm32")]
     fn save(&mut self) {
         let table = self.table.as_ref().unwrap();
         let mut buffer = vec![];
-        save_to_buffer(
-            table.parent_pointer.pointer.as_str(),
-            table.nodes(),
-            &mut buffer,
-        )
-        .unwrap();
+        if self.root_object_wrapped {
+            save_root_object_to_buffer(table.nodes(), &mut buffer).unwrap();
+        } else {
+            save_to_buffer(
+                table.parent_pointer.pointer.as_str(),
+                table.nodes(),
+                &mut buffer,
+            )
+            .unwrap();
+        }
         use eframe::wasm_bindgen::JsCast;
         use js_sys::Array;
         use web_sys::js_sys;
@@ -419,21 +792,292 @@ impl MyApp<'_> {
         a.dyn_ref::<web_sys::HtmlElement>().unwrap().click();
         // revoke url
         web_sys::Url::revoke_object_url(&url).unwrap();
+        self.on_saved();
+    }
+
+    fn on_saved(&mut self) {
         self.unsaved_changes = false;
+        // Saved file holds the whole table
+        self.partially_opened = false;
+        if let Some(ref mut table) = self.table {
+            table.mark_saved();
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        self.remove_autosave();
+    }
+
+    fn select_file(&mut self, path: PathBuf) {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.autosave_recovered = Self::ask_autosave_recovery(&path);
+        }
+        self.reset_document_options();
+        self.selected_file = Some(path);
+    }
+
+    /// Options chosen to parse again the opened document, which don't apply to the next one
+    fn reset_document_options(&mut self) {
+        self.lenient = false;
+        self.open_root_object_as_row = false;
     }
 
+    /// Ask to open changes autosaved by a previous session, an autosave which is not recovered is deleted
     #[cfg(not(target_arch = "wasm32"))]
-    fn save_as(&mut self) {
-        if let Some(path) = rfd::FileDialog::new().save_file() {
-            self.selected_file = Some(path);
-            let table = self.table.as_ref().unwrap();
-            save_to_file(
+    fn ask_autosave_recovery(path: &std::path::Path) -> bool {
+        let Some(autosave_path) = crate::autosave::recoverable_autosave(path) else {
+            return false;
+        };
+        let recover = rfd::MessageDialog::new()
+            .set_level(rfd::MessageLevel::Warning)
+            .set_title("Recover autosaved changes")
+            .set_description(format!(
+                "{} has changes which were autosaved but not saved, open them?",
+                path.display()
+            ))
+            .set_buttons(rfd::MessageButtons::YesNo)
+            .show()
+            == rfd::MessageDialogResult::Yes;
+        if !recover {
+            let _ = std::fs::remove_file(autosave_path);
+        }
+        recover
+    }
+
+    /// Write the document to its autosave file when it is dirty and autosave interval elapsed
+    #[cfg(not(target_arch = "wasm32"))]
+    fn autosave_if_due(&mut self, ctx: &Context) {
+        let is_dirty = self.is_dirty();
+        let time = ctx.input(|i| i.time);
+        if self.autosave.should_save(is_dirty, time) {
+            self.write_autosave();
+        }
+        if is_dirty {
+            if let Some(remaining) = self.autosave.remaining(time) {
+                ctx.request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn write_autosave(&mut self) {
+        // Pasted json has no file
+        let Some(ref path) = self.selected_file else {
+            return;
+        };
+        let Some(ref table) = self.table else {
+            return;
+        };
+        let mut buffer = vec![];
+        let result = if self.is_jsonl {
+            save_jsonl_to_buffer(table.nodes(), &mut buffer)
+        } else if self.root_object_wrapped {
+            save_root_object_to_buffer(table.nodes(), &mut buffer)
+        } else {
+            save_to_buffer(
                 table.parent_pointer.pointer.as_str(),
                 table.nodes(),
-                self.selected_file.as_ref().unwrap(),
+                &mut buffer,
             )
-            .unwrap();
-            self.unsaved_changes = false;
+        };
+        if let Err(err) = result {
+            log!("Failed to autosave: {}", err);
+            return;
+        }
+        // Table is serialized on the ui thread, file is written without blocking it
+        self.autosave.write(path, buffer);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn remove_autosave(&mut self) {
+        self.autosave_recovered = false;
+        if let Some(ref path) = self.selected_file {
+            self.autosave.remove(path);
+        }
+    }
+
+    /// Difference between depths of entries and depths displayed to user
+    fn depth_offset(&self) -> u8 {
+        match self.table {
+            Some(ref table) if self.depth_relative_to_start => {
+                crate::parser::pointer_depth(&table.parent_pointer.pointer)
+            }
+            _ => 0,
+        }
+    }
+
+    /// Whether there are changes which would be lost by closing the document
+    fn is_dirty(&self) -> bool {
+        self.unsaved_changes || self.table.as_ref().is_some_and(|table| table.is_dirty())
+    }
+
+    /// Ask before discarding unsaved changes, return true when there are none or user accepts to lose them
+    fn confirm_discard_changes(&self) -> bool {
+        if !self.is_dirty() {
+            return true;
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Warning)
+                .set_title("Unsaved changes")
+                .set_description("Changes are not saved, discard them?")
+                .set_buttons(rfd::MessageButtons::YesNo)
+                .show()
+                == rfd::MessageDialogResult::Yes
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            web_sys::window()
+                .and_then(|window| {
+                    window
+                        .confirm_with_message("Changes are not saved, discard them?")
+                        .ok()
+                })
+                .unwrap_or(true)
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_csv(&mut self, scope: ExportScope) {
+        let with_index = self.export_row_index;
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("csv", &["csv"])
+            .save_file()
+        {
+            let table = self.table.as_ref().unwrap();
+            if let Err(error) = std::fs::write(path, table.export_csv(scope, with_index)) {
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Failed to export CSV")
+                    .set_description(error.to_string())
+                    .show();
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_pretty_json(&mut self, scope: ExportScope) {
+        let options = SerializeOptions {
+            escape_unicode: self.export_escape_unicode,
+            ..SerializeOptions::default()
+        };
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("json", &["json"])
+            .save_file()
+        {
+            let table = self.table.as_ref().unwrap();
+            // Root object is the single row of the table, it is exported whole
+            let json = if self.root_object_wrapped {
+                let mut buffer = vec![];
+                save_root_object_to_buffer(table.nodes(), &mut buffer)
+                    .map_err(|e| e.to_string())
+                    .and_then(|_| format_json(&String::from_utf8_lossy(&buffer), &options))
+            } else {
+                table.export_json(scope, &options)
+            };
+            let result =
+                json.and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+            if let Err(error) = result {
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Failed to export JSON")
+                    .set_description(error)
+                    .show();
+            }
+        }
+    }
+
+    /// Entries of an export submenu, return the clicked scope
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_scope_menu(ui: &mut egui::Ui, has_selection: bool) -> Option<ExportScope> {
+        let mut scope = None;
+        if ui.button("All rows").clicked() {
+            scope = Some(ExportScope::All);
+        }
+        if ui.button("Filtered rows").clicked() {
+            scope = Some(ExportScope::Filtered);
+        }
+        if ui
+            .add_enabled(has_selection, Button::new("Selected rows"))
+            .clicked()
+        {
+            scope = Some(ExportScope::Selected);
+        }
+        scope
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn export_json_patch(&mut self) {
+        let table = self.table.as_ref().unwrap();
+        let Some(patch) = table.export_json_patch() else {
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Failed to export JSON Patch")
+                .set_description(
+                    "Some changes made since the document was opened are not in the edit history",
+                )
+                .show();
+            return;
+        };
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("json", &["json"])
+            .save_file()
+        {
+            if let Err(error) = std::fs::write(path, patch) {
+                rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Error)
+                    .set_title("Failed to export JSON Patch")
+                    .set_description(error.to_string())
+                    .show();
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn apply_json_patch(&mut self) {
+        if let Some(path) = rfd::FileDialog::new()
+            .add_filter("json", &["json"])
+            .pick_file()
+        {
+            let table = self.table.as_mut().unwrap();
+            let mut array_response = ArrayResponse::default();
+            let result = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|patch| table.apply_json_patch(&mut array_response, &patch));
+            match result {
+                // Applied operations are recorded like edits, see `ArrayTable::is_dirty`
+                Ok(_) => {}
+                Err(error) => {
+                    rfd::MessageDialog::new()
+                        .set_level(rfd::MessageLevel::Error)
+                        .set_title("Failed to apply JSON Patch")
+                        .set_description(error)
+                        .show();
+                }
+            }
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save_as(&mut self) {
+        let mut dialog = rfd::FileDialog::new();
+        // Suggest a json file next to the converted one
+        if let Some(path) = self
+            .selected_file
+            .as_ref()
+            .filter(|path| Self::is_converted_file(path))
+        {
+            if let Some(file_name) = path.with_extension("json").file_name() {
+                dialog = dialog.set_file_name(file_name.to_string_lossy());
+            }
+            if let Some(directory) = path.parent() {
+                dialog = dialog.set_directory(directory);
+            }
+        }
+        if let Some(path) = dialog.save_file() {
+            self.is_jsonl = Self::is_jsonl_file(&path);
+            self.selected_file = Some(path.clone());
+            self.save_to(&path);
         }
     }
 }
@@ -474,6 +1118,10 @@ impl eframe::App for MyApp<'_> {
             }
         }
         #[cfg(not(target_arch = "wasm32"))]
+        if ctx.input(|i| i.viewport().close_requested()) && !self.confirm_discard_changes() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
         {
             let mut title = format!(
                 "json table editor - {}{}",
@@ -481,7 +1129,7 @@ impl eframe::App for MyApp<'_> {
                     .as_ref()
                     .map(|p| p.display().to_string())
                     .unwrap_or("No file selected".to_string()),
-                if self.unsaved_changes { " *" } else { "" }
+                if self.is_dirty() { " *" } else { "" }
             );
 
             #[cfg(not(feature = "dist"))]
@@ -495,8 +1143,24 @@ impl eframe::App for MyApp<'_> {
                 ctx.parent_viewport_id(),
                 egui::ViewportCommand::Title(title),
             );
+            self.autosave_if_due(ctx);
         }
         self.windows(ctx);
+        if self.table.is_none() || self.paste_json_requested {
+            let pasted_text = ctx.input_mut(|i| {
+                let position = i
+                    .events
+                    .iter()
+                    .position(|event| matches!(event, egui::Event::Paste(_)))?;
+                match i.events.remove(position) {
+                    egui::Event::Paste(text) => Some(text),
+                    _ => None,
+                }
+            });
+            if let Some(text) = pasted_text {
+                self.open_pasted_json(&text);
+            }
+        }
         egui::TopBottomPanel::top("top").show(ctx, |ui| {
             ui.horizontal_wrapped(|ui| {
                 if self.table.is_some() {
@@ -507,6 +1171,10 @@ impl eframe::App for MyApp<'_> {
                             ui.close_menu();
                             self.file_picker();
                         }
+                        if ui.button("New from clipboard").on_hover_text("Open json pasted with Ctrl+V as a new table").clicked() {
+                            ui.close_menu();
+                            self.paste_json_requested = true;
+                        }
                         ui.separator();
                         let button = Button::new("Save").shortcut_text(ui.ctx().format_shortcut(&SHORTCUT_SAVE));
                         if ui.add(button).clicked() {
@@ -520,6 +1188,52 @@ impl eframe::App for MyApp<'_> {
                                 ui.close_menu();
                                 self.save_as();
                             }
+                            ui.menu_button("Autosave", |ui| {
+                                ui.radio_value(&mut self.autosave.interval, None, "Off");
+                                for interval in AUTOSAVE_INTERVALS {
+                                    let label = if interval < 60 { format!("Every {} s", interval) } else { format!("Every {} min", interval / 60) };
+                                    ui.radio_value(&mut self.autosave.interval, Some(interval), label);
+                                }
+                            }).response.on_hover_text("Save changes to a .autosave file next to the document, offered on next opening when changes were not saved");
+                            ui.separator();
+                            let has_selection = self.table.as_ref().is_some_and(|table| table.selected_range().is_some());
+                            ui.menu_button("Export as CSV", |ui| {
+                                ui.checkbox(&mut self.export_row_index, "Include # column");
+                                ui.separator();
+                                if let Some(scope) = Self::export_scope_menu(ui, has_selection) {
+                                    ui.close_menu();
+                                    self.export_csv(scope);
+                                }
+                            });
+                            ui.menu_button("Export as pretty JSON", |ui| {
+                                ui.checkbox(&mut self.export_escape_unicode, "Escape non ascii chars")
+                                    .on_hover_text("Write chars like é or emoji as \\uXXXX escapes");
+                                ui.separator();
+                                if let Some(scope) = Self::export_scope_menu(ui, has_selection) {
+                                    ui.close_menu();
+                                    self.export_pretty_json(scope);
+                                }
+                            });
+                            let edits_complete = self
+                                .table
+                                .as_ref()
+                                .is_some_and(|table| table.edit_history.is_complete());
+                            let button = Button::new("Export edits as JSON Patch");
+                            if ui
+                                .add_enabled(edits_complete, button)
+                                .on_disabled_hover_text(
+                                    "Some changes made since the document was opened are not in the edit history",
+                                )
+                                .clicked()
+                            {
+                                ui.close_menu();
+                                self.export_json_patch();
+                            }
+                            ui.separator();
+                            if ui.button("Apply JSON Patch").clicked() {
+                                ui.close_menu();
+                                self.apply_json_patch();
+                            }
                         }
                     });
 
@@ -531,13 +1245,131 @@ impl eframe::App for MyApp<'_> {
                             ui.close_menu();
                             self.table.as_mut().unwrap().open_replace_panel(None);
                         }
+                        if ui.button("Raw JSON of row").clicked() {
+                            ui.close_menu();
+                            self.table.as_mut().unwrap().open_raw_json_panel();
+                        }
+                        if ui.button("Sort all keys").on_hover_text("Reorder keys of objects alphabetically at every level, e.g. to compare files, arrays keep their order").clicked() {
+                            ui.close_menu();
+                            // Recorded as a single edit, see `ArrayTable::is_dirty`
+                            self.table.as_mut().unwrap().sort_all_keys(&mut ArrayResponse::default());
+                        }
+                        ui.separator();
+                        let table = self.table.as_mut().unwrap();
+                        let mut enforce_types = table.edit_options.enforce_types;
+                        if ui.checkbox(&mut enforce_types, "Enforce column types").on_hover_text("Reject edits of numeric and boolean columns with a value of another type").changed() {
+                            table.set_edit_options(EditOptions { enforce_types });
+                        }
+                    });
+
+                    ui.separator();
+                    ui.menu_button("View", |ui| {
+                        ui.set_min_width(220.0);
+                        let table = self.table.as_mut().unwrap();
+                        let mut display_options = table.display_options;
+                        ui.horizontal(|ui| {
+                            ui.label("Truncate cells after");
+                            ui.add(egui::DragValue::new(&mut display_options.max_cell_chars).range(10..=10000));
+                            ui.label("characters");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Numbers");
+                            for number_format in [NumberFormat::AsParsed, NumberFormat::Scientific, NumberFormat::Plain] {
+                                ui.radio_value(&mut display_options.number_format, number_format, number_format.as_str());
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Group digits");
+                            for (thousands_separator, label) in [(None, "No"), (Some(','), "1,000"), (Some(' '), "1 000"), (Some('\''), "1'000")] {
+                                ui.radio_value(&mut display_options.thousands_separator, thousands_separator, label);
+                            }
+                        });
+                        ui.checkbox(&mut display_options.show_aggregates, "Show aggregates of focused column")
+                            .on_hover_text("Sum, min, max and average of numbers of the column, over filtered rows");
+                        ui.checkbox(&mut display_options.hide_empty_columns, "Hide empty columns")
+                            .on_hover_text("Columns without any value other than null in filtered rows are not displayed");
+                        ui.checkbox(&mut display_options.show_null, "Show null values")
+                            .on_hover_text("Cells holding null show a greyed null, cells of missing keys stay blank");
+                        ui.checkbox(&mut display_options.indent_object_keys, "Indent keys of objects")
+                            .on_hover_text("Sub tables of objects show keys indented by depth instead of full pointers");
+                        ui.checkbox(&mut display_options.show_value_types, "Show types of object values")
+                            .on_hover_text("Sub tables of objects have a Type column with the json type of each value");
+                        if display_options != table.display_options {
+                            table.set_display_options(display_options);
+                        }
+                        if !table.parent_pointer.pointer.is_empty() {
+                            ui.checkbox(&mut self.depth_relative_to_start, "Depths relative to start pointer")
+                                .on_hover_text("Depth 1 is the keys of elements of the array at start pointer, instead of levels counted from the document root");
+                        }
+                        #[cfg(not(target_arch = "wasm32"))]
+                        if self.selected_file.is_some() {
+                            ui.separator();
+                            let checkbox = egui::Checkbox::new(&mut self.flatten_arrays, "Flatten nested arrays into columns");
+                            let response = ui.add_enabled(!self.is_dirty(), checkbox)
+                                .on_hover_text("Elements of nested arrays are shown as field/0, field/1... columns, file is parsed again")
+                                .on_disabled_hover_text("Save changes before parsing file again");
+                            if response.changed() {
+                                ui.close_menu();
+                                self.should_parse_again = true;
+                            }
+                            let checkbox = egui::Checkbox::new(&mut self.load_first_rows_only, format!("Load first {} rows only", FIRST_ROWS_COUNT));
+                            let response = ui.add_enabled(!self.is_dirty(), checkbox)
+                                .on_hover_text("Rows are not built for next elements of the array, which makes large files faster to open. Save writes to another file, file is parsed again")
+                                .on_disabled_hover_text("Save changes before parsing file again");
+                            if response.changed() {
+                                ui.close_menu();
+                                self.should_parse_again = true;
+                            }
+                            let checkbox = egui::Checkbox::new(&mut self.all_as_strings, "Parse all values as text");
+                            let response = ui.add_enabled(!self.is_dirty(), checkbox)
+                                .on_hover_text("Numbers, booleans and nulls are shown and edited as strings, they keep their type on save unless edited to another value, file is parsed again")
+                                .on_disabled_hover_text("Save changes before parsing file again");
+                            if response.changed() {
+                                ui.close_menu();
+                                self.should_parse_again = true;
+                            }
+                            if Self::delimiter_of_file(self.selected_file.as_ref().unwrap()).is_some() {
+                                let checkbox = egui::Checkbox::new(&mut self.csv_has_header, "First row is header");
+                                let response = ui.add_enabled(!self.is_dirty(), checkbox)
+                                    .on_hover_text("Values of the first row are the keys of other rows, instead of column_1, column_2... File is parsed again")
+                                    .on_disabled_hover_text("Save changes before parsing file again");
+                                if response.changed() {
+                                    ui.close_menu();
+                                    self.should_parse_again = true;
+                                }
+                            }
+                            let checkbox = egui::Checkbox::new(&mut self.normalize_keys, "Normalize keys to snake_case");
+                            let response = ui.add_enabled(!self.is_dirty(), checkbox)
+                                .on_hover_text("Keys like userID and user_id share a column, file is saved with normalized keys. Keys normalized to the name of another key of their object are kept as written. File is parsed again")
+                                .on_disabled_hover_text("Save changes before parsing file again");
+                            if response.changed() {
+                                ui.close_menu();
+                                self.should_parse_again = true;
+                            }
+                        }
                     });
                 }
+                if self.paste_json_requested {
+                    ui.separator();
+                    ui.label("Paste json with Ctrl+V to open it as a new table");
+                    if let Some(ref paste_error) = self.paste_error {
+                        ui.colored_label(Color32::RED, paste_error);
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.paste_json_requested = false;
+                        self.paste_error = None;
+                    }
+                }
+                let depth_offset = self.depth_offset();
                 if let Some(ref mut table) = self.table {
                     ui.separator();
+                    let mut displayed_depth = self.depth.saturating_sub(depth_offset);
                     let change_depth_slider_response = ui.add(
-                        egui::Slider::new(&mut self.depth, self.min_depth..=self.max_depth).text("Depth"),
+                        egui::Slider::new(&mut displayed_depth, self.min_depth.saturating_sub(depth_offset)..=self.max_depth.saturating_sub(depth_offset)).text("Depth"),
                     );
+                    self.depth = displayed_depth + depth_offset;
+                    ui.add(Separator::default().vertical());
+                    table.column_picker(ui);
                     ui.add(Separator::default().vertical());
                     let scroll_to_column_response = ui.allocate_ui(Vec2::new(180.0, ui.spacing().interact_size.y), |ui| {
                         ui.horizontal(|ui| {
@@ -601,6 +1433,73 @@ impl eframe::App for MyApp<'_> {
                         }).inner
                     }).inner;
 
+                    ui.add(Separator::default().vertical());
+
+                    let go_to_pointer = ui.horizontal(|ui| self.go_to_pointer.ui(ui)).inner;
+                    if let Some(pointer) = go_to_pointer {
+                        if let Err(error) = table.scroll_to_pointer(&pointer) {
+                            self.go_to_pointer.set_error(ui, error);
+                        }
+                    }
+
+                    ui.add(Separator::default().vertical());
+
+                    let filter_rows_changed = ui.allocate_ui(Vec2::new(300.0, ui.spacing().interact_size.y), |ui| {
+                        ui.horizontal(|ui| {
+                            ui.add(Label::new("Filter rows: ").extend());
+                            let hint_text = if table.filter_rows_query { "price > 100 AND status == \"active\"" } else { "Type term contained in any cell" };
+                            let text_edit = TextEdit::singleline(&mut table.filter_rows).hint_text(hint_text);
+                            let filter_rows_response = ui.add(text_edit);
+                            let mut match_case_text = RichText::new("Cc");
+                            if table.filter_rows_case_sensitive {
+                                match_case_text = match_case_text.color(ACTIVE_COLOR);
+                            }
+                            let match_case_response = ui.add(Button::new(match_case_text)).on_hover_ui(|ui| {
+                                ui.label("Matching case");
+                            });
+                            if match_case_response.clicked() {
+                                table.filter_rows_case_sensitive = !table.filter_rows_case_sensitive;
+                            }
+                            let mut regex_text = RichText::new(".*");
+                            if table.filter_rows_regex {
+                                regex_text = regex_text.color(ACTIVE_COLOR);
+                            }
+                            let regex_response = ui.add(Button::new(regex_text)).on_hover_ui(|ui| {
+                                ui.label("Regex");
+                            });
+                            if regex_response.clicked() {
+                                table.filter_rows_regex = !table.filter_rows_regex;
+                                table.filter_rows_query = false;
+                            }
+                            let mut query_text = RichText::new("Q");
+                            if table.filter_rows_query {
+                                query_text = query_text.color(ACTIVE_COLOR);
+                            }
+                            let query_response = ui.add(Button::new(query_text)).on_hover_ui(|ui| {
+                                ui.label("Query: compare columns with ==, !=, <, <=, >, >= or contains, combined with AND, OR and parentheses");
+                            });
+                            if query_response.clicked() {
+                                table.filter_rows_query = !table.filter_rows_query;
+                                table.filter_rows_regex = false;
+                            }
+                            if filter_rows_response.changed() && !table.filter_rows.is_empty() {
+                                // Typing in a large array would filter it on each key stroke
+                                table.filter_rows_debounce.on_change(ctx.input(|i| i.time));
+                            }
+                            // Cleared field and Enter filter right away
+                            (filter_rows_response.changed() && table.filter_rows.is_empty())
+                                || (filter_rows_response.lost_focus() && ctx.input(|i| i.key_pressed(Key::Enter)))
+                                || match_case_response.clicked() || regex_response.clicked() || query_response.clicked()
+                        }).inner
+                    }).inner;
+                    if let Some(ref error) = table.filter_rows_error {
+                        if table.filter_rows_query {
+                            ui.colored_label(Color32::RED, error);
+                        } else {
+                            ui.colored_label(Color32::RED, "Invalid regex").on_hover_text(error);
+                        }
+                    }
+
 
                     // interaction handling
                     if scroll_to_column_response.changed() {
@@ -619,6 +1518,12 @@ impl eframe::App for MyApp<'_> {
                     if scroll_to_row_mode_response.inner.is_some() && scroll_to_row_mode_response.inner.unwrap() {
                         table.reset_search();
                     }
+                    let time = ctx.input(|i| i.time);
+                    if filter_rows_changed || table.filter_rows_debounce.is_due(time) {
+                        table.do_filter_column();
+                    } else if let Some(remaining) = table.filter_rows_debounce.remaining(time) {
+                        ctx.request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+                    }
                     if change_depth_slider_response.changed() {
                         table.changed_scroll_to_column_value = true;
                         if let Some(new_max_depth) = table.update_max_depth(self.depth) {
@@ -643,7 +1548,129 @@ impl eframe::App for MyApp<'_> {
                     ui.separator();
                     ui.label(format!("{} columns ", table.all_columns().len()));
                     ui.separator();
-                    ui.label(format!("{} depth level", self.max_depth));
+                    ui.label(format!(
+                        "{} depth level",
+                        self.max_depth.saturating_sub(self.depth_offset())
+                    ));
+                    if let Some(ref parse_stats) = self.parse_stats {
+                        ui.separator();
+                        ui.label(format!("{} values", parse_stats.values()))
+                            .on_hover_text(parse_stats.to_string());
+                    }
+                    if !self.jsonl_invalid_lines.is_empty() {
+                        ui.separator();
+                        if ui
+                            .label(
+                                RichText::new(format!(
+                                    "{} invalid lines skipped",
+                                    self.jsonl_invalid_lines.len()
+                                ))
+                                .color(Color32::RED)
+                                .underline(),
+                            )
+                            .on_hover_ui(|ui| {
+                                ui.vertical(|ui| {
+                                    self.jsonl_invalid_lines.iter().for_each(|(line, err)| {
+                                        ui.label(format!("line {}: {}", line, err));
+                                    })
+                                });
+                            })
+                            .hovered()
+                        {
+                            ui.ctx().set_cursor_icon(CursorIcon::Help);
+                        }
+                    }
+                    if let Some(truncation) = self.truncation.filter(|_| self.partially_opened) {
+                        ui.separator();
+                        ui.label(format!(
+                            "Showing {} of {} rows",
+                            group_thousands(&truncation.kept.to_string(), ','),
+                            group_thousands(&truncation.len.to_string(), ',')
+                        ))
+                        .on_hover_text("Only first rows are loaded, Save writes to another file, not to the opened one");
+                    }
+                    if !self.lossy_parse_errors.is_empty() {
+                        ui.separator();
+                        if ui
+                            .label(
+                                RichText::new("Only valid part of the file is opened")
+                                    .color(Color32::RED)
+                                    .underline(),
+                            )
+                            .on_hover_ui(|ui| {
+                                ui.vertical(|ui| {
+                                    self.lossy_parse_errors.iter().for_each(|err| {
+                                        ui.label(err);
+                                    });
+                                    ui.label("Save writes to another file, not to the opened one");
+                                });
+                            })
+                            .hovered()
+                        {
+                            ui.ctx().set_cursor_icon(CursorIcon::Help);
+                        }
+                    }
+                    if let Some(ref nesting_warning) = self.nesting_warning {
+                        ui.separator();
+                        ui.label(
+                            RichText::new("Deepest values kept as json").color(Color32::ORANGE),
+                        )
+                        .on_hover_text(nesting_warning);
+                    }
+                    if !self.duplicate_pointers.is_empty() {
+                        ui.separator();
+                        if ui
+                            .label(
+                                RichText::new(format!(
+                                    "{} duplicate keys",
+                                    self.duplicate_pointers.len()
+                                ))
+                                .color(Color32::ORANGE)
+                                .underline(),
+                            )
+                            .on_hover_ui(|ui| {
+                                ui.vertical(|ui| {
+                                    ui.label("Objects have duplicate keys at:");
+                                    self.duplicate_pointers.iter().take(20).for_each(|pointer| {
+                                        ui.label(pointer);
+                                    });
+                                    if self.duplicate_pointers.len() > 20 {
+                                        ui.label("...");
+                                    }
+                                });
+                            })
+                            .hovered()
+                        {
+                            ui.ctx().set_cursor_icon(CursorIcon::Help);
+                        }
+                    }
+                    if !self.unnormalized_keys.is_empty() {
+                        ui.separator();
+                        if ui
+                            .label(
+                                RichText::new(format!(
+                                    "{} keys not normalized",
+                                    self.unnormalized_keys.len()
+                                ))
+                                .color(Color32::ORANGE)
+                                .underline(),
+                            )
+                            .on_hover_ui(|ui| {
+                                ui.vertical(|ui| {
+                                    ui.label("Another key of their object has the same normalized name:");
+                                    self.unnormalized_keys.iter().take(20).for_each(|pointer| {
+                                        ui.label(pointer);
+                                    });
+                                    if self.unnormalized_keys.len() > 20 {
+                                        ui.label("...");
+                                    }
+                                });
+                            })
+                            .hovered()
+                        {
+                            ui.ctx().set_cursor_icon(CursorIcon::Help);
+                        }
+                    }
                     if !table.parent_pointer.pointer.is_empty() {
                         ui.separator();
                         ui.label(format!("Start pointer: {}", table.parent_pointer.pointer));
@@ -713,27 +1740,53 @@ impl eframe::App for MyApp<'_> {
             }
 
             // Collect dropped files:
-            ctx.input(|i| {
-                if !i.raw.dropped_files.is_empty() {
-                    let file = i.raw.dropped_files.clone().pop().unwrap();
-                    self.table = None;
-                    self.selected_pointer = None;
-                    self.should_parse_again = true;
-                    self.parsing_invalid = false;
-                    self.parsing_invalid_pointers.clear();
-                    if let Some(bytes) = file.bytes {
-                        self.open_json_content(u8::MAX, bytes.as_ref());
-                    } else {
-                        self.selected_file = Some(file.path.unwrap());
-                    }
+            let dropped_file = ctx.input(|i| i.raw.dropped_files.last().cloned());
+            if let Some(file) = dropped_file.filter(|_| self.confirm_discard_changes()) {
+                self.table = None;
+                self.selected_pointer = None;
+                self.should_parse_again = true;
+                self.parsing_invalid = false;
+                self.parsing_invalid_pointers.clear();
+                self.parsing_error = None;
+                if let Some(bytes) = file.bytes {
+                    self.reset_document_options();
+                    self.open_json_content(bytes.as_ref(), true, true);
+                } else {
+                    self.select_file(file.path.unwrap());
                 }
-            });
+            }
 
             if let Some(ref mut table) = self.table {
-                let response1 = table.ui(ui);
-                if !response1.edited_value.is_empty() {
-                    self.unsaved_changes = true;
-                }
+                table.ui(ui);
+            } else if let Some(parsing_error) = self.parsing_error.clone() {
+                let mut rect = ui.max_rect();
+                rect.min.y = rect.max.y / 2.0 - 40.0;
+                ui.allocate_ui_at_rect(rect,
+                                       |ui| {
+                                           ui.vertical_centered(|ui| {
+                                               ui.heading("Failed to parse json");
+                                               ui.colored_label(Color32::RED, parsing_error);
+                                               if self.selected_file.is_some() {
+                                                   if Button::new("Open valid part").sense(Sense::click()).ui(ui)
+                                                       .on_hover_text("Open elements of the root array before the error")
+                                                       .clicked() {
+                                                       self.parse_lossy = true;
+                                                       self.parsing_error = None;
+                                                       self.open_json();
+                                                   }
+                                                   if !self.lenient && Button::new("Parse leniently (NaN, Infinity, control characters)").sense(Sense::click()).ui(ui).clicked() {
+                                                       self.lenient = true;
+                                                       self.parsing_error = None;
+                                                       self.open_json();
+                                                   }
+                                               }
+                                               if Button::new("Select another file").sense(Sense::click()).ui(ui).clicked() {
+                                                   self.parsing_error = None;
+                                                   self.selected_file = None;
+                                                   self.file_picker();
+                                               }
+                                           });
+                                       });
             } else if self.selected_file.is_none() {
                 let max_rect = ui.max_rect();
                 let mut rect = ui.max_rect();
@@ -750,6 +1803,7 @@ impl eframe::App for MyApp<'_> {
                                        |ui| {
                                            ui.vertical_centered(|ui| {
                                                ui.heading("Select or drop a json file");
+                                               ui.label("or paste json with Ctrl+V");
 
                                                #[cfg(target_arch = "wasm32")] {
                                                    if ui.button("Or load sample json file of 1mb").clicked() {
@@ -783,7 +1837,7 @@ impl eframe::App for MyApp<'_> {
                                        },
                 );
             }
-            if self.selected_file.is_some() {
+            if self.selected_file.is_some() && self.parsing_error.is_none() {
                 if self.parsing_invalid {
                     let mut rect = ui.max_rect();
                     rect.min.y = 40.0_f32.max(rect.max.y / 2.0 - (20.0 * self.parsing_invalid_pointers.len() as f32));
@@ -809,6 +1863,11 @@ impl eframe::App for MyApp<'_> {
                                                        if Button::new("Parse again").sense(sense).ui(ui).clicked() {
                                                            self.open_json();
                                                        }
+                                                       if Button::new("Show object as a single row").sense(Sense::click()).ui(ui).clicked() {
+                                                           self.open_root_object_as_row = true;
+                                                           self.selected_pointer = None;
+                                                           self.open_json();
+                                                       }
                                                        if Button::new("Select another file").sense(Sense::click()).ui(ui).clicked() {
                                                            self.selected_file = None;
                                                            self.selected_pointer = None;
@@ -844,3 +1903,23 @@ impl eframe::App for MyApp<'_> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::MyApp;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_save_does_not_overwrite_partially_opened_file() {
+        let path = PathBuf::from("data.json");
+        assert_eq!(MyApp::overwritten_file(Some(&path), false), Some(&path));
+        // Only elements before a parse error are in the table
+        assert_eq!(MyApp::overwritten_file(Some(&path), true), None);
+        let csv_path = PathBuf::from("data.csv");
+        assert_eq!(MyApp::overwritten_file(Some(&csv_path), false), None);
+        // Yaml is converted to json, it would be overwritten with another format
+        let yaml_path = PathBuf::from("config.yaml");
+        assert_eq!(MyApp::overwritten_file(Some(&yaml_path), false), None);
+        assert_eq!(MyApp::overwritten_file(None, false), None);
+    }
+}
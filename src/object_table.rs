@@ -1,27 +1,52 @@
-use crate::array_table::ArrayTable;
+use crate::array_table::{ArrayTable, CellType, DisplayOptions, EditOptions};
+use crate::components::cell_text::{cap_rendered_value, show_rejected_edit, truncate_cell_value};
 use crate::components::icon::ButtonWithIcon;
 use crate::components::table::CellLocation;
-use crate::fonts::{COPY, PENCIL};
-use crate::{ArrayResponse, SHORTCUT_COPY, SHORTCUT_DELETE};
+use crate::fonts::{CHEVRON_DOWN, CHEVRON_RIGHT, COPY, LOCK, LOCK_OPEN, PENCIL};
+use crate::history::{Edit, EditHistory, RowChange};
+use crate::parser::{
+    array_element_span, child_count, edited_value_type, escape_pointer_token, is_pointer_prefix,
+    strip_non_finite_number_marker, unescape_pointer_token,
+};
+use crate::{
+    concat_string, ArrayResponse, SHORTCUT_COPY, SHORTCUT_DELETE, SHORTCUT_REDO, SHORTCUT_UNDO,
+};
 use eframe::egui::scroll_area::ScrollBarVisibility;
-use eframe::egui::{Id, Key, Label, Sense, TextEdit};
+use eframe::egui::{Color32, Id, Key, Label, Sense, TextEdit};
 use eframe::emath::Align;
 use egui::{EventFilter, Modifiers, Ui};
 use json_flat_parser::serializer::serialize_to_json_with_option;
 use json_flat_parser::{FlatJsonValue, PointerKey, ValueType};
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::mem;
 
 pub struct ObjectTable {
     pub table_id: Id,
+    // Pointer of the displayed object
+    pointer: String,
     pub nodes: Vec<FlatJsonValue<String>>,
     filtered_nodes: Vec<usize>,
     arrays: Vec<FlatJsonValue<String>>,
+    // Pointers of nested objects and arrays whose descendants are hidden
+    collapsed: HashSet<String>,
+    // Pointers of keys which values, and values nested in them, can't be edited
+    locked_keys: HashSet<String>,
+    // Depth of keys of the displayed object, keys are indented relatively to it
+    keys_depth: u8,
     pub scroll_to_row_number: usize,
 
     pub editing_index: RefCell<Option<usize>>,
     pub editing_value: RefCell<String>,
     pub focused_cell: Option<CellLocation>,
+    pub edit_history: EditHistory,
+    pub edit_options: EditOptions,
+    pub display_options: DisplayOptions,
+    // Editing index of a rejected edit, and why it was rejected
+    rejected_edit: Option<(usize, String)>,
+    // Name typed in "Add key" field of the context menu
+    new_key: String,
 
     // Handling interaction
     pub changed_arrow_vertical_scroll: bool,
@@ -30,29 +55,111 @@ pub struct ObjectTable {
 
 impl ObjectTable {
     pub fn new(nodes: Vec<FlatJsonValue<String>>, name: String) -> Self {
-        let mut filtered_nodes = Vec::with_capacity(nodes.len());
-        let mut arrays = vec![];
-        for (index, entry) in nodes.iter().enumerate() {
-            if !matches!(entry.pointer.value_type, ValueType::Array(_))
-                && !matches!(entry.pointer.value_type, ValueType::Object(..))
-            {
-                filtered_nodes.push(index);
-            } else if matches!(entry.pointer.value_type, ValueType::Array(_)) {
-                arrays.push(entry.clone());
-            }
-        }
-        Self {
+        let arrays = nodes
+            .iter()
+            .filter(|entry| matches!(entry.pointer.value_type, ValueType::Array(_)))
+            .cloned()
+            .collect();
+        let keys_depth = nodes
+            .iter()
+            .filter(|entry| entry.pointer.pointer.len() > name.len())
+            .map(|entry| entry.pointer.depth)
+            .min()
+            .unwrap_or_default();
+        let mut object_table = Self {
             table_id: Id::new(format!("table-object-{}", name)),
+            pointer: name,
             nodes,
-            filtered_nodes,
+            filtered_nodes: vec![],
             arrays,
+            collapsed: HashSet::new(),
+            locked_keys: HashSet::new(),
+            keys_depth,
             editing_index: RefCell::new(None),
             editing_value: RefCell::new("".to_string()),
             focused_cell: None,
+            edit_history: EditHistory::default(),
+            edit_options: EditOptions::default(),
+            display_options: DisplayOptions::default(),
+            rejected_edit: None,
+            new_key: String::new(),
             scroll_to_row_number: 0,
             changed_arrow_vertical_scroll: false,
             was_editing: false,
+        };
+        object_table.filter_nodes();
+        object_table
+    }
+
+    /// Rows are every value nested in the displayed object, except descendants of collapsed nodes
+    fn filter_nodes(&mut self) {
+        self.filtered_nodes = self
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                let pointer = entry.pointer.pointer.as_str();
+                pointer.len() > self.pointer.len()
+                    && !self.collapsed.iter().any(|collapsed| {
+                        pointer.len() > collapsed.len() && is_pointer_prefix(collapsed, pointer)
+                    })
+            })
+            .map(|(index, _)| index)
+            .collect();
+        if let Some(focused_cell) = self.focused_cell {
+            if focused_cell.row_index >= self.filtered_nodes.len() {
+                self.focused_cell = None;
+            }
+        }
+    }
+
+    #[inline]
+    pub fn is_collapsed(&self, pointer: &str) -> bool {
+        self.collapsed.contains(pointer)
+    }
+
+    /// Hide or show descendants of the nested object or array at `pointer`
+    pub fn set_collapsed(&mut self, pointer: &str, collapsed: bool) {
+        if collapsed {
+            self.collapsed.insert(pointer.to_string());
+        } else {
+            self.collapsed.remove(pointer);
         }
+        self.filter_nodes();
+    }
+
+    pub fn is_key_locked(&self, pointer: &str) -> bool {
+        self.locked_keys
+            .iter()
+            .any(|locked| is_pointer_prefix(locked, pointer))
+    }
+
+    /// Locked keys can't be edited, pasted into or cleared from the table, nor get new sibling keys
+    /// when they are nested in a locked object
+    pub fn set_key_locked(&mut self, pointer: &str, locked: bool) {
+        if locked {
+            self.locked_keys.insert(pointer.to_string());
+        } else {
+            self.locked_keys.remove(pointer);
+        }
+    }
+
+    pub fn collapse_all(&mut self) {
+        self.collapsed = self
+            .nodes
+            .iter()
+            .filter(|entry| {
+                entry.pointer.pointer.len() > self.pointer.len()
+                    && container_summary(&entry.pointer.value_type).is_some()
+            })
+            .map(|entry| entry.pointer.pointer.clone())
+            .collect();
+        self.filter_nodes();
+    }
+
+    pub fn expand_all(&mut self) {
+        self.collapsed.clear();
+        self.filter_nodes();
     }
 
     fn table_ui(&mut self, ui: &mut egui::Ui, _pinned: bool) -> ArrayResponse {
@@ -76,29 +183,70 @@ impl ObjectTable {
             self.changed_arrow_vertical_scroll = false;
             table = table.scroll_to_row(self.scroll_to_row_number, Some(Align::Center));
         }
+        let show_value_types = self.display_options.show_value_types;
         table = table.column(Column::auto().clip(true).resizable(true));
         table = table.column(Column::remainder().clip(true).resizable(true));
+        if show_value_types {
+            // Last so the value column keeps its index
+            table = table.column(Column::auto().clip(true).resizable(true));
+        }
         table
             .header(text_height * 2.0, |mut header| {
                 header.col(|ui, _| Some(ui.label("Pointer")));
                 header.col(|ui, _| Some(ui.label("Value")));
+                if show_value_types {
+                    header.col(|ui, _| Some(ui.label("Type")));
+                }
             })
             .body(None, None, self.focused_cell, |body| {
                 let mut updated_value: Option<(PointerKey, String)> = None;
+                let mut toggled_pointer: Option<String> = None;
+                let mut added_key: Option<(usize, String)> = None;
+                let mut locked_key: Option<(String, bool)> = None;
+                let mut force_edit = false;
+                let mut cancel_rejected_edit = false;
                 array_response.hover_data =
                     body.rows(text_height, self.filtered_nodes.len(), |mut row| {
                         let table_row_index = row.index();
                         let row_index = self.filtered_nodes[table_row_index];
-                        let entry = &self.nodes[row_index];
-                        row.col(|c, _| Some(c.label(&entry.pointer.pointer)));
+                        // Cloned as cells borrow the table, e.g. to copy it as json
+                        let entry = self.nodes[row_index].clone();
+                        row.col(|c, _| {
+                            if self.display_options.indent_object_keys {
+                                let key = indented_key(&entry.pointer, self.keys_depth);
+                                Some(c.label(key).on_hover_text(&entry.pointer.pointer))
+                            } else {
+                                Some(c.label(&entry.pointer.pointer))
+                            }
+                        });
                         row.col(|ui, _| {
                             let mut editing_index = self.editing_index.borrow_mut();
                             if editing_index.is_some() && editing_index.unwrap() == (row_index) {
                                 let ref_mut = &mut *self.editing_value.borrow_mut();
-                                let textedit_response = ui.add(TextEdit::singleline(ref_mut));
+                                let mut textedit_response = ui.add(TextEdit::singleline(ref_mut));
+                                if let Some((_, error)) = self
+                                    .rejected_edit
+                                    .as_ref()
+                                    .filter(|(index, _)| *index == row_index)
+                                {
+                                    let cancelled;
+                                    (textedit_response, cancelled) =
+                                        show_rejected_edit(ui, textedit_response, error);
+                                    if cancelled {
+                                        *editing_index = None;
+                                        cancel_rejected_edit = true;
+                                        return None;
+                                    }
+                                }
                                 if textedit_response.lost_focus() {
                                     let pointer = entry.pointer.clone();
-                                    updated_value = Some((pointer, mem::take(ref_mut)));
+                                    // Literals of lenient documents are edited without their marker
+                                    let value = crate::parser::keep_non_finite_number_marker(
+                                        entry.value.as_deref(),
+                                        mem::take(ref_mut),
+                                    );
+                                    updated_value = Some((pointer, value));
+                                    force_edit = ui.input(|i| i.modifiers.shift);
                                     self.focused_cell = Some(CellLocation {
                                         column_index: 1,
                                         row_index: table_row_index,
@@ -109,6 +257,25 @@ impl ObjectTable {
                                 }
 
                                 None
+                            } else if let Some(summary) =
+                                container_summary(&entry.pointer.value_type)
+                            {
+                                let icon = if self.is_collapsed(&entry.pointer.pointer) {
+                                    CHEVRON_RIGHT
+                                } else {
+                                    CHEVRON_DOWN
+                                };
+                                let response =
+                                    ui.add(ButtonWithIcon::new(summary, icon).frame(false));
+                                if response.clicked() {
+                                    toggled_pointer = Some(entry.pointer.pointer.clone());
+                                    self.focused_cell = Some(CellLocation {
+                                        column_index: 1,
+                                        row_index: table_row_index,
+                                        is_pinned_column_table: false,
+                                    });
+                                }
+                                Some(response)
                             } else {
                                 let rect = ui.available_rect_before_wrap();
                                 let cell_zone = ui.interact(
@@ -116,16 +283,45 @@ impl ObjectTable {
                                     Id::new(&entry.pointer.pointer),
                                     Sense::click(),
                                 );
+                                let display_options = self.display_options;
+                                let is_locked = self.is_key_locked(&entry.pointer.pointer);
                                 let response = cell_zone.union(
                                     entry
                                         .value
                                         .as_ref()
-                                        .map(|v| ui.add(Label::new(v).sense(Sense::click())))
+                                        .map(|v| {
+                                            let displayed_value = if matches!(
+                                                entry.pointer.value_type,
+                                                ValueType::Number
+                                            ) {
+                                                display_options.display_number(v)
+                                            } else {
+                                                Cow::from(
+                                                    crate::parser::strip_non_finite_number_marker(
+                                                        v,
+                                                    ),
+                                                )
+                                            };
+                                            let text = truncate_cell_value(
+                                                &displayed_value,
+                                                display_options.max_cell_chars,
+                                            );
+                                            let is_truncated = matches!(text, Cow::Owned(_));
+                                            let response = ui.add(
+                                                Label::new(text.into_owned()).sense(Sense::click()),
+                                            );
+                                            if is_truncated {
+                                                response.on_hover_text(
+                                                    cap_rendered_value(v).into_owned(),
+                                                )
+                                            } else {
+                                                response
+                                            }
+                                        })
                                         .unwrap_or_else(|| ui.label("")),
                                 );
-                                if response.double_clicked() {
-                                    *self.editing_value.borrow_mut() =
-                                        entry.value.clone().unwrap_or_default();
+                                if response.double_clicked() && !is_locked {
+                                    *self.editing_value.borrow_mut() = editable_value(&entry);
                                     *editing_index = Some(row_index);
                                 }
                                 if response.clicked() {
@@ -136,6 +332,7 @@ impl ObjectTable {
                                         is_pinned_column_table: false,
                                     });
                                 }
+                                drop(editing_index);
                                 response.context_menu(|ui| {
                                     self.focused_cell = Some(CellLocation {
                                         column_index: 1,
@@ -143,16 +340,25 @@ impl ObjectTable {
                                         is_pinned_column_table: false,
                                     });
                                     let button = ButtonWithIcon::new("Edit", PENCIL);
+                                    if ui.add_enabled(!is_locked, button).clicked() {
+                                        *self.editing_value.borrow_mut() = editable_value(&entry);
+                                        *self.editing_index.borrow_mut() = Some(row_index);
+                                        ui.close_menu();
+                                    }
+                                    let button = if is_locked {
+                                        ButtonWithIcon::new("Unlock key", LOCK_OPEN)
+                                    } else {
+                                        ButtonWithIcon::new("Lock key", LOCK)
+                                    };
                                     if ui.add(button).clicked() {
-                                        *self.editing_value.borrow_mut() =
-                                            entry.value.clone().unwrap_or_default();
-                                        *editing_index = Some(row_index);
+                                        locked_key =
+                                            Some((entry.pointer.pointer.clone(), !is_locked));
                                         ui.close_menu();
                                     }
                                     let button = ButtonWithIcon::new("Copy", COPY)
                                         .shortcut_text(ui.ctx().format_shortcut(&SHORTCUT_COPY));
                                     if ui.add(button).clicked() {
-                                        ui.ctx().copy_text(entry.value.clone().unwrap_or_default());
+                                        ui.ctx().copy_text(editable_value(&entry));
                                         ui.close_menu();
                                     }
                                     ui.separator();
@@ -160,17 +366,83 @@ impl ObjectTable {
                                         ui.ctx().copy_text(entry.pointer.pointer.clone());
                                         ui.close_menu();
                                     }
+                                    if ui.button("Copy object as JSON").clicked() {
+                                        ui.ctx().copy_text(self.to_json());
+                                        ui.close_menu();
+                                    }
+                                    ui.separator();
+                                    let new_key_pointer =
+                                        self.sibling_key_pointer(row_index, &self.new_key);
+                                    ui.horizontal(|ui| {
+                                        let response = ui.add(
+                                            TextEdit::singleline(&mut self.new_key)
+                                                .hint_text("key")
+                                                .desired_width(100.0),
+                                        );
+                                        let submitted = response.lost_focus()
+                                            && ui.input(|i| i.key_pressed(Key::Enter));
+                                        let add_button = ui.add_enabled(
+                                            new_key_pointer.is_ok(),
+                                            egui::Button::new("Add key"),
+                                        );
+                                        if new_key_pointer.is_ok()
+                                            && (add_button.clicked() || submitted)
+                                        {
+                                            added_key =
+                                                Some((row_index, mem::take(&mut self.new_key)));
+                                            ui.close_menu();
+                                        }
+                                    });
+                                    if let Err(error) = new_key_pointer {
+                                        if !self.new_key.is_empty() {
+                                            ui.colored_label(Color32::RED, error);
+                                        }
+                                    }
                                 });
 
                                 Some(response)
                             }
                         });
+                        if show_value_types {
+                            row.col(|ui, _| {
+                                Some(ui.weak(value_type_label(&entry.pointer.value_type)))
+                            });
+                        }
                     });
+                if cancel_rejected_edit {
+                    self.rejected_edit = None;
+                }
+                if let Some(pointer) = toggled_pointer {
+                    let collapsed = !self.is_collapsed(&pointer);
+                    self.set_collapsed(&pointer, collapsed);
+                }
+                if let Some((pointer, locked)) = locked_key {
+                    self.set_key_locked(&pointer, locked);
+                }
+                if let Some((row_index, key)) = added_key {
+                    if let Ok(row_index) =
+                        self.add_sibling_key(&mut array_response, row_index, &key)
+                    {
+                        // New key is edited right away
+                        self.editing_value.borrow_mut().clear();
+                        *self.editing_index.borrow_mut() = Some(row_index);
+                        let pointer = self.nodes[row_index].pointer.pointer.clone();
+                        let _ = self.scroll_to_pointer(&pointer);
+                    }
+                }
                 if let Some((updated_pointer, value)) = updated_value {
                     let editing_index = mem::take(&mut *self.editing_index.borrow_mut());
                     let row_index = editing_index.unwrap();
-                    self.update_value(&mut array_response, updated_pointer, value, row_index);
-                    self.was_editing = true;
+                    if let Err(error) = self.validate_edit(row_index, &value, force_edit) {
+                        // Keep editing so the value can be fixed
+                        *self.editing_index.borrow_mut() = editing_index;
+                        *self.editing_value.borrow_mut() = value;
+                        self.rejected_edit = Some((row_index, error));
+                    } else {
+                        self.rejected_edit = None;
+                        self.update_value(&mut array_response, updated_pointer, value, row_index);
+                        self.was_editing = true;
+                    }
                 }
             });
         if self.was_editing {
@@ -179,19 +451,176 @@ impl ObjectTable {
         array_response
     }
 
-    fn update_value(
+    /// Displayed object serialized as json, including its nested arrays and objects
+    pub fn to_json(&self) -> String {
+        let mut entries = self
+            .nodes
+            .iter()
+            .filter(|node| {
+                node.pointer.pointer.len() > self.pointer.len()
+                    && is_pointer_prefix(&self.pointer, &node.pointer.pointer)
+            })
+            .cloned()
+            .collect::<Vec<FlatJsonValue<String>>>();
+        let depth = entries.iter().map(|e| e.pointer.depth).min().unwrap_or(0);
+        let keys_count = entries.iter().filter(|e| e.pointer.depth == depth).count();
+        entries.push(FlatJsonValue {
+            pointer: PointerKey {
+                pointer: String::new(),
+                value_type: ValueType::Object(true, keys_count),
+                depth: 0,
+                position: 0,
+                column_id: 0,
+            },
+            value: None,
+        });
+        serialize_to_json_with_option::<String>(&mut entries, depth).to_json()
+    }
+
+    /// Scroll to and focus value at `pointer`
+    pub fn scroll_to_pointer(&mut self, pointer: &str) -> Result<(), String> {
+        let table_row_index = self
+            .filtered_nodes
+            .iter()
+            .position(|row_index| self.nodes[*row_index].pointer.pointer.eq(pointer))
+            .ok_or_else(|| format!("No value found at {}", pointer))?;
+        self.scroll_to_row_number = table_row_index;
+        self.changed_arrow_vertical_scroll = true;
+        self.focused_cell = Some(CellLocation {
+            column_index: 1,
+            row_index: table_row_index,
+            is_pinned_column_table: false,
+        });
+        Ok(())
+    }
+
+    /// Pointer of `key` in the object containing node at `row_index`. Error when the object already
+    /// has this key, or when node is an array element.
+    pub fn sibling_key_pointer(&self, row_index: usize, key: &str) -> Result<String, String> {
+        if key.is_empty() {
+            return Err("Key can't be empty".to_string());
+        }
+        let sibling_pointer = &self.nodes[row_index].pointer.pointer;
+        let parent_pointer = &sibling_pointer[..sibling_pointer.rfind('/').unwrap_or(0)];
+        if self
+            .arrays
+            .iter()
+            .any(|array| array.pointer.pointer.eq(parent_pointer))
+        {
+            return Err("Keys can't be added to an array".to_string());
+        }
+        let pointer = concat_string!(parent_pointer, "/", escape_pointer_token(key));
+        if self.is_key_locked(&pointer) {
+            return Err(format!("Key {} is locked", parent_pointer));
+        }
+        if self
+            .nodes
+            .iter()
+            .any(|node| node.pointer.pointer.eq(&pointer))
+        {
+            return Err(format!("Key {} already exists", key));
+        }
+        Ok(pointer)
+    }
+
+    /// Add `key` with a null value next to node at `row_index`, return index of the new node
+    pub fn add_sibling_key(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        row_index: usize,
+        key: &str,
+    ) -> Result<usize, String> {
+        let pointer = self.sibling_key_pointer(row_index, key)?;
+        let pointer = PointerKey {
+            pointer,
+            value_type: ValueType::Null,
+            depth: self.nodes[row_index].pointer.depth,
+            // Written after existing keys of the object
+            position: usize::MAX,
+            column_id: 0,
+        };
+        // Pushed last so indexes of existing nodes, kept in edit history, do not change
+        self.nodes.push(FlatJsonValue {
+            pointer: pointer.clone(),
+            value: None,
+        });
+        let new_row_index = self.nodes.len() - 1;
+        // Added key is recorded as a whole instead of as an edit of its value, undo removes it
+        let mut edit_history = mem::take(&mut self.edit_history);
+        self.update_value(array_response, pointer, "null".to_string(), new_row_index);
+        let node = self.nodes[new_row_index].clone();
+        edit_history.push(Edit {
+            row_index: new_row_index,
+            pointer: node.pointer.clone(),
+            old_value: None,
+            new_value: node.value.clone(),
+            old_value_type: None,
+            reorders_keys: false,
+            row_change: Some(RowChange::Inserted(vec![node])),
+        });
+        self.edit_history = edit_history;
+        self.filter_nodes();
+        Ok(new_row_index)
+    }
+
+    /// When types are enforced and `force` is not set, numeric and boolean values can only be
+    /// replaced by a value of the same type
+    pub fn validate_edit(&self, row_index: usize, value: &str, force: bool) -> Result<(), String> {
+        if !self.edit_options.enforce_types || force || value.is_empty() {
+            return Ok(());
+        }
+        if let Some(entry) = self.nodes.get(row_index) {
+            let cell_type = CellType::of(entry);
+            if matches!(
+                cell_type,
+                CellType::Integer | CellType::Float | CellType::Bool
+            ) && !cell_type.accepts(value)
+            {
+                return Err(format!(
+                    "{} is not a valid {} value",
+                    value,
+                    cell_type.as_str().to_lowercase()
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace value of node at `row_index` by `text`, an empty text clears it. Values of locked
+    /// keys are left unchanged. Return whether the value changed.
+    pub(crate) fn replace_value(
         &mut self,
         array_response: &mut ArrayResponse,
-        updated_pointer: PointerKey,
+        row_index: usize,
+        text: &str,
+    ) -> bool {
+        let pointer = self.nodes[row_index].pointer.clone();
+        if self.is_key_locked(&pointer.pointer) {
+            return false;
+        }
+        self.update_value(array_response, pointer, text.to_string(), row_index)
+    }
+
+    pub(crate) fn update_value(
+        &mut self,
+        array_response: &mut ArrayResponse,
+        mut updated_pointer: PointerKey,
         value: String,
         row_index: usize,
     ) -> bool {
         let value = if value.is_empty() { None } else { Some(value) };
         let mut value_changed = false;
+        let mut old_value = None;
         if let Some(entry) = self.nodes.get_mut(row_index) {
             if !entry.value.eq(&value) {
-                entry.value = value.clone();
+                old_value = mem::replace(&mut entry.value, value.clone());
                 value_changed = true;
+                // Null values, like added keys, take the type of their new value
+                if matches!(entry.pointer.value_type, ValueType::Null) {
+                    entry.pointer.value_type =
+                        edited_value_type(ValueType::Null, value.as_deref().unwrap_or_default());
+                    updated_pointer.value_type = entry.pointer.value_type;
+                }
             }
         } else if value.is_some() {
             value_changed = true;
@@ -206,39 +635,35 @@ impl ObjectTable {
         if !value_changed {
             return true;
         }
-        let mut maybe_parent_array = None;
-        for array in self.arrays.iter() {
-            if updated_pointer.pointer.starts_with(&array.pointer.pointer) {
-                maybe_parent_array = Some(array);
-                break;
-            }
-        }
+        self.edit_history.push(Edit {
+            row_index,
+            pointer: updated_pointer.clone(),
+            old_value,
+            new_value: value.clone(),
+            old_value_type: None,
+            reorders_keys: false,
+            row_change: None,
+        });
+        let maybe_parent_array = self
+            .arrays
+            .iter()
+            .position(|array| is_pointer_prefix(&array.pointer.pointer, &updated_pointer.pointer));
         // When edited value is an element of an array
-        if let Some(parent_array) = maybe_parent_array {
-            let mut array_entries = Vec::with_capacity(10);
-            let depth = parent_array.pointer.depth;
-            for node in self.nodes.iter() {
-                if node
-                    .pointer
-                    .pointer
-                    .starts_with(&parent_array.pointer.pointer)
-                {
-                    array_entries.push(node.clone());
-                }
-            }
-            let parent_pointer = PointerKey {
-                pointer: String::new(),
-                value_type: ValueType::Array(array_entries.len()),
-                depth: 0,
-                position: 0,
-                column_id: 0,
+        if let Some(array_index) = maybe_parent_array {
+            // A new key, a removed value or a value becoming (or no longer being) an array or an object
+            // change the shape of the element, then whole array is serialized again.
+            let is_structured = |value: &Option<String>| match value {
+                Some(value) => value.trim_start().starts_with(['{', '[']),
+                None => true,
             };
-            array_entries.push(FlatJsonValue {
-                pointer: parent_pointer,
-                value: None,
-            });
-            let updated_array =
-                serialize_to_json_with_option::<String>(&mut array_entries, depth + 1).to_json();
+            let updated_array = if is_structured(&old_value) || is_structured(&value) {
+                None
+            } else {
+                self.serialize_array_element(array_index, &updated_pointer.pointer)
+            }
+            .unwrap_or_else(|| self.serialize_array(array_index));
+            let parent_array = &mut self.arrays[array_index];
+            parent_array.value = Some(updated_array.clone());
             array_response.edited_value.push(FlatJsonValue {
                 pointer: parent_array.pointer.clone(),
                 value: Some(updated_array),
@@ -252,8 +677,89 @@ impl ObjectTable {
         false
     }
 
+    pub(crate) fn serialize_array(&self, array_index: usize) -> String {
+        let parent_array = &self.arrays[array_index];
+        let mut array_entries = Vec::with_capacity(10);
+        let depth = parent_array.pointer.depth;
+        let mut len = 0;
+        for node in self.nodes.iter() {
+            if is_pointer_prefix(&parent_array.pointer.pointer, &node.pointer.pointer) {
+                // Only direct elements are counted, not their descendants
+                if node.pointer.depth == depth + 1 {
+                    len += 1;
+                }
+                array_entries.push(node.clone());
+            }
+        }
+        let parent_pointer = PointerKey {
+            pointer: String::new(),
+            value_type: ValueType::Array(len),
+            depth: 0,
+            position: 0,
+            column_id: 0,
+        };
+        array_entries.push(FlatJsonValue {
+            pointer: parent_pointer,
+            value: None,
+        });
+        serialize_to_json_with_option::<String>(&mut array_entries, depth + 1).to_json()
+    }
+
+    /// Serialize only the element of the array containing `updated_pointer` and replace it in the
+    /// last serialized array, instead of walking all elements with `serialize_array`: cost depends on
+    /// the element size instead of the array size.
+    /// Return None when element can't be replaced: the array was never serialized, or element is a scalar.
+    pub(crate) fn serialize_array_element(
+        &self,
+        array_index: usize,
+        updated_pointer: &str,
+    ) -> Option<String> {
+        let parent_array = &self.arrays[array_index];
+        let serialized_array = parent_array.value.as_ref()?;
+        let array_pointer = parent_array.pointer.pointer.as_str();
+        let element_index = updated_pointer[array_pointer.len()..]
+            .split('/')
+            .nth(1)?
+            .parse::<usize>()
+            .ok()?;
+        let element_pointer = concat_string!(array_pointer, "/", element_index.to_string());
+        if element_pointer.eq(updated_pointer) {
+            return None;
+        }
+        let mut element_entries = self
+            .nodes
+            .iter()
+            .filter(|node| {
+                node.pointer.pointer.len() > element_pointer.len()
+                    && is_pointer_prefix(&element_pointer, &node.pointer.pointer)
+            })
+            .cloned()
+            .collect::<Vec<FlatJsonValue<String>>>();
+        let element = serialize_to_json_with_option::<String>(
+            &mut element_entries,
+            parent_array.pointer.depth + 2,
+        );
+        let element = serde_json::to_string(&element).ok()?;
+        let span = array_element_span(serialized_array, element_index)?;
+        let mut updated_array = serialized_array.clone();
+        updated_array.replace_range(span, &element);
+        Some(updated_array)
+    }
+
+    /// Serialized arrays containing `pointer` are no longer up to date when a node is updated from outside of the table
+    pub(crate) fn forget_serialized_arrays(&mut self, pointer: &str) {
+        for array in self.arrays.iter_mut() {
+            if is_pointer_prefix(&array.pointer.pointer, pointer) {
+                array.value = None;
+            }
+        }
+    }
+
     fn handle_shortcut(&mut self, ui: &mut Ui, array_response: &mut ArrayResponse) {
         let mut copied_value = None;
+        let mut toggled_pointer = None;
+        let mut should_undo = false;
+        let mut should_redo = false;
         let has_hovered_cell = array_response.hover_data.hovered_cell.is_some();
         let maybe_focused_id = ui.ctx().memory(|m| m.focused());
         ui.input_mut(|i| {
@@ -290,18 +796,25 @@ impl ObjectTable {
                         self.scroll_to_row_number = focused_cell.row_index;
                         self.changed_arrow_vertical_scroll = true;
                     }
+                    let row_index = self.filtered_nodes[focused_cell.row_index];
+                    let is_container =
+                        container_summary(&self.nodes[row_index].pointer.value_type).is_some();
                     let typed_alphanum = ArrayTable::get_typed_alphanum_from_events(i);
-                    if (typed_alphanum.is_some() || i.consume_key(Modifiers::NONE, Key::Enter))
+                    if is_container {
+                        if i.consume_key(Modifiers::NONE, Key::Enter) {
+                            toggled_pointer = Some(self.nodes[row_index].pointer.pointer.clone());
+                        }
+                    } else if (typed_alphanum.is_some()
+                        || i.consume_key(Modifiers::NONE, Key::Enter))
                         && !self.was_editing
+                        && !self.is_key_locked(&self.nodes[row_index].pointer.pointer)
                     {
-                        let row_index = self.filtered_nodes[focused_cell.row_index];
                         *self.editing_index.borrow_mut() = Some(row_index);
                         let entry = &self.nodes[row_index];
                         if let Some(typed_key) = typed_alphanum {
                             *self.editing_value.borrow_mut() = typed_key;
                         } else {
-                            *self.editing_value.borrow_mut() =
-                                entry.value.clone().unwrap_or_default();
+                            *self.editing_value.borrow_mut() = editable_value(entry);
                         }
                     }
                 }
@@ -314,6 +827,11 @@ impl ObjectTable {
                         modifiers: Default::default(),
                     })
                 }
+                if i.consume_shortcut(&SHORTCUT_REDO) {
+                    should_redo = true;
+                } else if i.consume_shortcut(&SHORTCUT_UNDO) {
+                    should_undo = true;
+                }
             }
             for event in i.events.iter().filter(|e| match e {
                 egui::Event::Copy => has_hovered_cell,
@@ -327,29 +845,21 @@ impl ObjectTable {
                 let row_index = self.filtered_nodes[cell_location.row_index];
 
                 let is_value_column = cell_location.column_index == 1;
-                if is_value_column {
+                let is_container =
+                    container_summary(&self.nodes[row_index].pointer.value_type).is_some();
+                if is_value_column && !is_container {
                     match event {
                         egui::Event::Key {
                             key: Key::Delete, ..
                         } => {
-                            self.update_value(
-                                array_response,
-                                self.nodes[row_index].pointer.clone(),
-                                "".to_string(),
-                                row_index,
-                            );
+                            self.replace_value(array_response, row_index, "");
                         }
                         egui::Event::Paste(v) => {
-                            self.update_value(
-                                array_response,
-                                self.nodes[row_index].pointer.clone(),
-                                v.clone(),
-                                row_index,
-                            );
+                            self.replace_value(array_response, row_index, v);
                         }
                         egui::Event::Copy => {
-                            if let Some(value) = &self.nodes[row_index].value {
-                                copied_value = Some(value.clone());
+                            if self.nodes[row_index].value.is_some() {
+                                copied_value = Some(editable_value(&self.nodes[row_index]));
                             }
                         }
                         _ => {}
@@ -361,6 +871,58 @@ impl ObjectTable {
         if let Some(value) = copied_value {
             ui.ctx().copy_text(value.clone());
         }
+        if let Some(pointer) = toggled_pointer {
+            let collapsed = !self.is_collapsed(&pointer);
+            self.set_collapsed(&pointer, collapsed);
+        }
+        if should_undo || should_redo {
+            self.replay_edit(array_response, should_redo);
+        }
+    }
+
+    /// Revert last edit, or with `redo` replay last reverted edit
+    pub fn replay_edit(&mut self, array_response: &mut ArrayResponse, redo: bool) {
+        // Re-applied edit goes through update_value, so parent array is serialized again, but it
+        // should not be recorded
+        let mut edit_history = mem::take(&mut self.edit_history);
+        let edits = if redo {
+            edit_history.redo()
+        } else {
+            edit_history.undo()
+        };
+        for edit in edits.unwrap_or_default() {
+            match edit.row_change {
+                // Added keys are the last nodes, see `add_sibling_key`
+                Some(RowChange::Inserted(_)) => {
+                    self.nodes.push(FlatJsonValue {
+                        pointer: edit.pointer.clone(),
+                        value: None,
+                    });
+                    self.update_value(
+                        array_response,
+                        edit.pointer,
+                        edit.new_value.unwrap_or_default(),
+                        edit.row_index,
+                    );
+                    self.filter_nodes();
+                }
+                Some(RowChange::Removed(_)) => {
+                    // Removed value is emitted before its node is dropped
+                    self.update_value(array_response, edit.pointer, String::new(), edit.row_index);
+                    self.nodes.remove(edit.row_index);
+                    self.filter_nodes();
+                }
+                _ => {
+                    self.update_value(
+                        array_response,
+                        edit.pointer,
+                        edit.new_value.unwrap_or_default(),
+                        edit.row_index,
+                    );
+                }
+            }
+        }
+        self.edit_history = edit_history;
     }
 }
 
@@ -373,6 +935,14 @@ impl super::View<ArrayResponse> for ObjectTable {
             Sense::focusable_noninteractive(),
         );
         ui.vertical(|ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Expand all").clicked() {
+                    self.expand_all();
+                }
+                if ui.button("Collapse all").clicked() {
+                    self.collapse_all();
+                }
+            });
             let scroll_area = egui::ScrollArea::horizontal();
             scroll_area.show(ui, |ui| {
                 array_response = self.table_ui(ui, false);
@@ -399,3 +969,227 @@ impl super::View<ArrayResponse> for ObjectTable {
         array_response
     }
 }
+
+/// Last token of `pointer`, indented by its depth relative to `keys_depth`, the depth of the keys of
+/// the displayed object
+pub(crate) fn indented_key(pointer: &PointerKey, keys_depth: u8) -> String {
+    let key = pointer.pointer.rsplit('/').next().unwrap_or_default();
+    let indent = "    ".repeat(pointer.depth.saturating_sub(keys_depth) as usize);
+    concat_string!(indent, unescape_pointer_token(key))
+}
+
+/// Shown instead of the value of a nested object or array, e.g `{3 keys}`
+fn container_summary(value_type: &ValueType) -> Option<String> {
+    let count = child_count(value_type)?;
+    if matches!(value_type, ValueType::Array(_)) {
+        Some(format!("[{} items]", count))
+    } else {
+        Some(format!("{{{} keys}}", count))
+    }
+}
+
+/// Json type of a value as shown in the Type column, with the count of children of arrays and objects
+pub(crate) fn value_type_label(value_type: &ValueType) -> String {
+    match value_type {
+        ValueType::String => "string".to_string(),
+        ValueType::Number => "number".to_string(),
+        ValueType::Bool => "bool".to_string(),
+        ValueType::Null => "null".to_string(),
+        ValueType::Array(len) => format!("array ({})", len),
+        ValueType::Object(_, len) => format!("object ({})", len),
+    }
+}
+
+// Value of `entry` as it is edited and copied, literals of lenient documents without their marker
+fn editable_value(entry: &FlatJsonValue<String>) -> String {
+    entry
+        .value
+        .as_deref()
+        .map(strip_non_finite_number_marker)
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{indented_key, value_type_label, ObjectTable};
+    use crate::ArrayResponse;
+    use json_flat_parser::{JSONParser, ParseOptions, PointerKey, ValueType};
+
+    #[test]
+    fn test_object_table_collapse() {
+        let json = r#"{"a": 1, "b": {"c": 2, "d": {"e": 3}}, "f": [4, 5]}"#;
+        let nodes = JSONParser::parse(json, ParseOptions::default().parse_array(true))
+            .unwrap()
+            .to_owned()
+            .json;
+        let mut table = ObjectTable::new(nodes, String::new());
+        let is_displayed =
+            |table: &mut ObjectTable, pointer: &str| table.scroll_to_pointer(pointer).is_ok();
+        for pointer in ["/a", "/b", "/b/c", "/b/d", "/b/d/e", "/f", "/f/1"] {
+            assert!(is_displayed(&mut table, pointer), "{}", pointer);
+        }
+        table.set_collapsed("/b", true);
+        assert!(table.is_collapsed("/b"));
+        assert!(is_displayed(&mut table, "/b"));
+        assert!(!is_displayed(&mut table, "/b/c"));
+        assert!(!is_displayed(&mut table, "/b/d/e"));
+        assert!(is_displayed(&mut table, "/f/0"));
+
+        table.collapse_all();
+        assert!(is_displayed(&mut table, "/a"));
+        assert!(is_displayed(&mut table, "/f"));
+        assert!(!is_displayed(&mut table, "/f/0"));
+        assert!(!is_displayed(&mut table, "/b/d"));
+
+        table.expand_all();
+        assert!(!table.is_collapsed("/b"));
+        assert!(is_displayed(&mut table, "/b/d/e"));
+    }
+
+    #[test]
+    fn test_object_table_add_key() {
+        let json = r#"{"a": 1, "b": {"c": 2}, "e": [3]}"#;
+        let nodes = JSONParser::parse(json, ParseOptions::default().parse_array(true))
+            .unwrap()
+            .to_owned()
+            .json;
+        let mut table = ObjectTable::new(nodes, String::new());
+        let row_index = |table: &ObjectTable, pointer: &str| {
+            table
+                .nodes
+                .iter()
+                .position(|node| node.pointer.pointer.eq(pointer))
+                .unwrap()
+        };
+        let c = row_index(&table, "/b/c");
+        assert_eq!(
+            table.sibling_key_pointer(c, "c"),
+            Err("Key c already exists".to_string())
+        );
+        assert!(table
+            .sibling_key_pointer(row_index(&table, "/e/0"), "x")
+            .is_err());
+        assert_eq!(
+            table.sibling_key_pointer(c, "d/1"),
+            Ok("/b/d~11".to_string())
+        );
+
+        let mut array_response = ArrayResponse::default();
+        let new_row_index = table.add_sibling_key(&mut array_response, c, "d").unwrap();
+        assert_eq!(table.nodes[new_row_index].pointer.pointer, "/b/d");
+        assert_eq!(
+            table.nodes[new_row_index].pointer.depth,
+            table.nodes[c].pointer.depth
+        );
+        assert!(table.scroll_to_pointer("/b/d").is_ok());
+        assert_eq!(array_response.edited_value[0].pointer.pointer, "/b/d");
+        let object = serde_json::from_str::<serde_json::Value>(&table.to_json()).unwrap();
+        assert_eq!(object["b"]["d"], serde_json::Value::Null);
+        assert!(object["b"].as_object().unwrap().contains_key("d"));
+
+        table.update_value(
+            &mut array_response,
+            table.nodes[new_row_index].pointer.clone(),
+            "12".to_string(),
+            new_row_index,
+        );
+        assert_eq!(
+            table.nodes[new_row_index].pointer.value_type,
+            ValueType::Number
+        );
+        let object = serde_json::from_str::<serde_json::Value>(&table.to_json()).unwrap();
+        assert_eq!(object["b"]["d"], 12);
+
+        // Undoing the added key removes it, redoing adds it back
+        let nodes_count = table.nodes.len();
+        table.replay_edit(&mut array_response, false);
+        table.replay_edit(&mut array_response, false);
+        assert_eq!(table.nodes.len(), nodes_count - 1);
+        let object = serde_json::from_str::<serde_json::Value>(&table.to_json()).unwrap();
+        assert!(!object["b"].as_object().unwrap().contains_key("d"));
+        table.replay_edit(&mut array_response, true);
+        assert_eq!(table.nodes[new_row_index].pointer.pointer, "/b/d");
+        let object = serde_json::from_str::<serde_json::Value>(&table.to_json()).unwrap();
+        assert_eq!(object["b"]["d"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_indented_key() {
+        let key = |pointer: &str, depth: u8| {
+            indented_key(
+                &PointerKey::from_pointer(pointer.to_string(), ValueType::String, depth, 0),
+                2,
+            )
+        };
+        assert_eq!(key("/0/config", 2), "config");
+        assert_eq!(key("/0/config/server", 3), "    server");
+        assert_eq!(key("/0/config/server/a~1b", 4), "        a/b");
+        assert_eq!(key("/0/config/server/ports/0", 5), "            0");
+        // Keys above keys depth are not indented
+        assert_eq!(key("/0", 1), "0");
+    }
+
+    #[test]
+    fn test_value_type_label() {
+        let json = r#"{"s": "a", "n": 1.5, "b": true, "z": null, "a": [1, 2], "o": {"k": 1, "l": 2, "m": 3}}"#;
+        let result = JSONParser::parse(
+            json,
+            ParseOptions::default().parse_array(true).max_depth(10),
+        )
+        .unwrap()
+        .to_owned();
+        let label = |pointer: &str| {
+            let entry = result
+                .json
+                .iter()
+                .find(|entry| entry.pointer.pointer == pointer)
+                .unwrap();
+            value_type_label(&entry.pointer.value_type)
+        };
+        assert_eq!(label("/s"), "string");
+        assert_eq!(label("/n"), "number");
+        assert_eq!(label("/b"), "bool");
+        assert_eq!(label("/z"), "null");
+        assert_eq!(label("/a"), "array (2)");
+        assert_eq!(label("/o"), "object (3)");
+        assert_eq!(label("/a/0"), "number");
+    }
+
+    #[test]
+    fn test_object_table_locked_key() {
+        let json = r#"{"a": 1, "b": {"c": 2}}"#;
+        let nodes = JSONParser::parse(json, ParseOptions::default().parse_array(true))
+            .unwrap()
+            .to_owned()
+            .json;
+        let mut table = ObjectTable::new(nodes, String::new());
+        let row_index = |table: &ObjectTable, pointer: &str| {
+            table
+                .nodes
+                .iter()
+                .position(|node| node.pointer.pointer.eq(pointer))
+                .unwrap()
+        };
+        let (a, c) = (row_index(&table, "/a"), row_index(&table, "/b/c"));
+        let mut array_response = ArrayResponse::default();
+        // Values nested in a locked key are locked too
+        table.set_key_locked("/b", true);
+        assert!(table.is_key_locked("/b/c"));
+        assert!(!table.is_key_locked("/a"));
+        assert!(!table.replace_value(&mut array_response, c, "3"));
+        assert!(!table.replace_value(&mut array_response, c, ""));
+        assert_eq!(table.nodes[c].value, Some("2".to_string()));
+        assert!(array_response.edited_value.is_empty());
+        assert_eq!(
+            table.sibling_key_pointer(c, "d"),
+            Err("Key /b is locked".to_string())
+        );
+        assert!(table.replace_value(&mut array_response, a, "3"));
+        assert_eq!(table.nodes[a].value, Some("3".to_string()));
+
+        table.set_key_locked("/b", false);
+        assert!(table.replace_value(&mut array_response, c, "3"));
+        assert_eq!(table.nodes[c].value, Some("3".to_string()));
+    }
+}
@@ -12,6 +12,7 @@ use std::cell::RefCell;
 
 pub const PANEL_ABOUT: &str = "About";
 pub const PANEL_REPLACE: &str = "Replace";
+pub const PANEL_RAW_JSON: &str = "Raw JSON";
 
 #[derive(Default)]
 pub struct AboutPanel {}
@@ -23,7 +24,19 @@ pub struct SearchReplacePanel<'array> {
     selected_columns: RefCell<Vec<Column<'array>>>,
     columns: Vec<Column<'array>>,
     replace_mode: ReplaceMode,
+    whole_cell: bool,
     title: Option<String>,
+    // Number of cells changed by the last replace
+    replaced_count: Option<usize>,
+}
+/// Json of the focused row, editable as text
+#[derive(Default)]
+pub struct RawJsonPanel {
+    row_index: Option<usize>,
+    // Row json when it was last synced with the table
+    serialized: String,
+    text: String,
+    error: Option<String>,
 }
 #[derive(Clone)]
 #[derive(Default)]
@@ -40,6 +53,8 @@ pub struct SearchReplaceResponse<'array> {
     pub replace_value: Option<String>,
     pub selected_column: Option<Vec<Column<'array>>>,
     pub replace_mode: ReplaceMode,
+    // Match the whole cell value instead of a substring
+    pub whole_cell: bool,
 }
 
 impl super::Window<()> for AboutPanel {
@@ -93,6 +108,9 @@ impl<'array> SearchReplacePanel<'array> {
     pub fn set_title(&mut self, title: String) {
         self.title = Some(title);
     }
+    pub fn set_replaced_count(&mut self, replaced_count: usize) {
+        self.replaced_count = Some(replaced_count);
+    }
     pub fn set_select_column(&mut self, selected_column: Column<'array>) {
         *self.selected_columns.borrow_mut() = vec![selected_column];
     }
@@ -195,16 +213,24 @@ impl<'array> super::View<Option<SearchReplaceResponse<'array>>> for SearchReplac
 
                 ui.end_row();
                 ui.label("Search: ");
-                ui.add(search);
+                if ui.add(search).changed() {
+                    self.replaced_count = None;
+                }
                 ui.end_row();
                 ui.label("Replace: ");
-                ui.add(replace);
+                if ui.add(replace).changed() {
+                    self.replaced_count = None;
+                }
                 ui.end_row();
 
                 ui.label("");
                 let mut replace_match_case_text = RichText::new("Cc");
                 let mut replace_exact_word_text = RichText::new("W");
                 let mut replace_regex_text = RichText::new(".*");
+                let mut replace_whole_cell_text = RichText::new("[ ]");
+                if self.whole_cell {
+                    replace_whole_cell_text = replace_whole_cell_text.color(ACTIVE_COLOR);
+                }
                 if matches!(self.replace_mode, ReplaceMode::MatchingCase) {
                     replace_match_case_text = replace_match_case_text.color(ACTIVE_COLOR);
                 }
@@ -217,6 +243,7 @@ impl<'array> super::View<Option<SearchReplaceResponse<'array>>> for SearchReplac
                 let replace_regex_mode = Button::new(replace_regex_text);
                 let replace_exact_word_mode = Button::new(replace_exact_word_text);
                 let replace_match_case_mode = Button::new(replace_match_case_text);
+                let replace_whole_cell_mode = Button::new(replace_whole_cell_text);
                 let replace_response = ui
                     .with_layout(Layout::right_to_left(Align::Center), |ui| {
                         if self.selected_columns.borrow().len() == 0 {
@@ -263,10 +290,23 @@ impl<'array> super::View<Option<SearchReplaceResponse<'array>>> for SearchReplac
                                 self.replace_mode = ReplaceMode::MatchingCase;
                             }
                         }
+                        let mut response_replace_whole_cell_mode = ui.add(replace_whole_cell_mode);
+                        response_replace_whole_cell_mode = response_replace_whole_cell_mode
+                            .on_hover_ui(|ui| {
+                                ui.label("Whole cell");
+                            });
+                        if response_replace_whole_cell_mode.clicked() {
+                            self.whole_cell = !self.whole_cell;
+                        }
                         (response_button_replace, response_button_replace_with_null)
                     })
                     .inner;
                 ui.end_row();
+                if let Some(replaced_count) = self.replaced_count {
+                    ui.label("");
+                    ui.label(format!("{} cell(s) replaced", replaced_count));
+                    ui.end_row();
+                }
 
                 replace_response
             });
@@ -275,6 +315,7 @@ impl<'array> super::View<Option<SearchReplaceResponse<'array>>> for SearchReplac
                 search_criteria: self.search_criteria.clone(),
                 replace_value: Some(self.replace_value.clone()),
                 replace_mode: self.replace_mode.clone(),
+                whole_cell: self.whole_cell,
                 selected_column: Some(self.selected_columns.borrow().clone()),
             });
         } else if grid_response.inner.1.clicked() {
@@ -282,6 +323,7 @@ impl<'array> super::View<Option<SearchReplaceResponse<'array>>> for SearchReplac
                 search_criteria: self.search_criteria.clone(),
                 replace_value: None,
                 replace_mode: self.replace_mode.clone(),
+                whole_cell: self.whole_cell,
                 selected_column: Some(self.selected_columns.borrow().clone()),
             });
         }
@@ -289,3 +331,79 @@ impl<'array> super::View<Option<SearchReplaceResponse<'array>>> for SearchReplac
         // return grid_response.inner
     }
 }
+
+impl RawJsonPanel {
+    /// Follow the row shown in the table: text is replaced by the row json, unless it has been
+    /// edited and the row is still the same.
+    pub fn sync(&mut self, row_index: Option<usize>, serialized: Option<String>) {
+        if self.row_index != row_index {
+            self.row_index = row_index;
+            self.error = None;
+            self.text = serialized.clone().unwrap_or_default();
+        } else if self.text == self.serialized {
+            self.text = serialized.clone().unwrap_or_default();
+        }
+        self.serialized = serialized.unwrap_or_default();
+    }
+
+    pub fn set_error(&mut self, error: Option<String>) {
+        self.error = error;
+    }
+}
+
+impl super::Window<Option<(usize, String)>> for RawJsonPanel {
+    fn name(&self) -> &'static str {
+        PANEL_RAW_JSON
+    }
+
+    fn show(&mut self, ctx: &Context, open: &mut bool) -> Option<(usize, String)> {
+        egui::Window::new(self.name())
+            .collapsible(true)
+            .open(open)
+            .resizable([true, true])
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                use super::View as _;
+                self.ui(ui)
+            })
+            .and_then(|inner_response| inner_response.inner)
+            .flatten()
+    }
+}
+
+impl super::View<Option<(usize, String)>> for RawJsonPanel {
+    /// Return row index and edited json when user applies changes
+    fn ui(&mut self, ui: &mut Ui) -> Option<(usize, String)> {
+        let Some(row_index) = self.row_index else {
+            ui.label("Focus a cell to edit its row as json");
+            return None;
+        };
+        let is_edited = self.text != self.serialized;
+        let mut applied = None;
+        ui.horizontal(|ui| {
+            if ui.add_enabled(is_edited, Button::new("Apply")).clicked() {
+                applied = Some((row_index, self.text.clone()));
+            }
+            if ui.add_enabled(is_edited, Button::new("Revert")).clicked() {
+                self.text = self.serialized.clone();
+                self.error = None;
+            }
+        });
+        if let Some(ref error) = self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        egui::ScrollArea::vertical()
+            .max_height(480.0)
+            .show(ui, |ui| {
+                let response = ui.add(
+                    TextEdit::multiline(&mut self.text)
+                        .code_editor()
+                        .desired_width(f32::INFINITY),
+                );
+                if response.changed() {
+                    self.error = None;
+                }
+            });
+        applied
+    }
+}
@@ -1,12 +1,15 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::hash::{DefaultHasher, Hasher};
-use std::io::{BufWriter, Write};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::{fs, mem};
 
-use crate::array_table::{Column, NON_NULL_FILTER_VALUE};
+use crate::array_table::{Column, SortOrder, NON_NULL_FILTER_VALUE};
+use crate::history::Edit;
 use crate::panels::{ReplaceMode, SearchReplaceResponse};
 use json_flat_parser::{
     FlatJsonValue, JSONParser, JsonArrayEntries, ParseOptions, ParseResult, PointerKey, ValueType,
@@ -33,6 +36,7 @@ pub fn change_depth_array<'array>(
     previous_parse_result: ParseResult<String>,
     mut json_array: Vec<JsonArrayEntries<String>>,
     depth: usize,
+    parse_array: bool,
 ) -> Result<(Vec<JsonArrayEntries<String>>, Vec<Column<'array>>, usize), String> {
     let mut len = json_array.len();
     let new_json_array = Arc::new(Mutex::new(Vec::with_capacity(json_array.len())));
@@ -50,7 +54,7 @@ pub fn change_depth_array<'array>(
                 let mut parse_result = previous_parse_result.clone_except_json();
                 parse_result.json = mem::take(&mut json_array_entry.entries);
                 let options = ParseOptions::default()
-                    .parse_array(false)
+                    .parse_array(parse_array)
                     .max_depth(depth as u8);
                 let last_index = parse_result.json.len().max(1) - 1;
                 JSONParser::change_depth_owned(&mut parse_result, options).unwrap();
@@ -90,7 +94,7 @@ pub fn change_depth_array<'array>(
                         if let Some(column) = unique_keys.iter_mut().find(|c| c.eq(&&column)) {
                             entry.pointer.column_id = column.id;
                             column.seen_count += 1;
-                        } else if !column.name.contains('#') {
+                        } else if column.name != ROW_NUMBER_COLUMN {
                             let mut hasher = DefaultHasher::new();
                             hasher.write(column.name.as_bytes());
                             column.id = hasher.finish() as usize;
@@ -113,7 +117,7 @@ pub fn change_depth_array<'array>(
         for column_chunk in unique_keys_chunk {
             if let Some(column) = unique_keys.iter_mut().find(|c| c.eq(&&column_chunk)) {
                 column.seen_count += column_chunk.seen_count;
-            } else if !column_chunk.name.contains('#') {
+            } else if column_chunk.name != ROW_NUMBER_COLUMN {
                 unique_keys.push(column_chunk);
             }
         }
@@ -124,125 +128,330 @@ pub fn change_depth_array<'array>(
 
     Ok((mem::take(&mut new_json_array_guard), unique_keys, 4))
 }
+
+/// Set when only first elements of parsed array have been kept
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Truncation {
+    pub kept: usize,
+    // Length of the parsed array
+    pub len: usize,
+}
+
 pub fn as_array<'array>(
-    mut previous_parse_result: ParseResult<String>,
+    previous_parse_result: ParseResult<String>,
 ) -> Result<(Vec<JsonArrayEntries<String>>, Vec<Column<'array>>), String> {
-    let (root_value, start_index, mut end_index) =
-        if let Some(ref started_parsing_at) = previous_parse_result.started_parsing_at {
-            let root_value = previous_parse_result.json
-                [previous_parse_result.started_parsing_at_index_start]
-                .clone();
-            (
-                root_value,
-                previous_parse_result.started_parsing_at_index_start,
-                previous_parse_result.started_parsing_at_index_end,
-            )
-        } else {
-            (previous_parse_result.json[0].clone(), 0, 0)
-        };
+    as_array_with_max_elements(previous_parse_result, None)
+        .map(|(array, columns, _)| (array, columns))
+}
 
-    if !matches!(root_value.pointer.value_type, ValueType::Array(_)) {
-        return Err("Parsed json root is not an array".to_string());
-    }
-    let root_array_len = match root_value.pointer.value_type {
+/// Same as as_array, keeping at most `max_array_elements` first elements of parsed array.
+pub fn as_array_with_max_elements<'array>(
+    previous_parse_result: ParseResult<String>,
+    max_array_elements: Option<usize>,
+) -> Result<
+    (
+        Vec<JsonArrayEntries<String>>,
+        Vec<Column<'array>>,
+        Option<Truncation>,
+    ),
+    String,
+> {
+    as_array_with_progress(previous_parse_result, max_array_elements, |_| {})
+}
+
+// Number of rows built between two progress reports
+const PROGRESS_INTERVAL: usize = 4096;
+
+/// Same as as_array_with_max_elements, `progress` is called every few thousand rows with the
+/// fraction of parsed entries consumed, and with 1.0 once done.
+pub fn as_array_with_progress<'array>(
+    mut previous_parse_result: ParseResult<String>,
+    max_array_elements: Option<usize>,
+    mut progress: impl FnMut(f32),
+) -> Result<
+    (
+        Vec<JsonArrayEntries<String>>,
+        Vec<Column<'array>>,
+        Option<Truncation>,
+    ),
+    String,
+> {
+    let start_index = if previous_parse_result.started_parsing_at.is_some() {
+        previous_parse_result.started_parsing_at_index_start
+    } else {
+        0
+    };
+    let root_value = previous_parse_result
+        .json
+        .get(start_index)
+        .ok_or_else(|| EMPTY_DOCUMENT.to_string())?;
+
+    let mut root_array_len = match root_value.pointer.value_type {
         ValueType::Array(root_array_len) => root_array_len,
-        _ => panic!(""),
+        _ => return Err("Parsed json root is not an array".to_string()),
     };
-    if end_index == 0 {
-        end_index = previous_parse_result.json.len() - 1;
+    let mut truncation = None;
+    if let Some(max_array_elements) = max_array_elements.filter(|max| *max < root_array_len) {
+        // Entries of dropped elements are never read
+        truncation = Some(Truncation {
+            kept: max_array_elements,
+            len: root_array_len,
+        });
+        root_array_len = max_array_elements;
     }
+    let parent_prefix =
+        if let Some(ref started_parsing_at) = previous_parse_result.started_parsing_at {
+            started_parsing_at.as_str()
+        } else if let Some(ref prefix) = previous_parse_result.parsing_prefix {
+            prefix.as_str()
+        } else {
+            ""
+        };
     let mut unique_keys: Vec<Column> = Vec::with_capacity(16);
+    // Index of the last entry of each column
+    let mut last_seen: Vec<usize> = Vec::with_capacity(16);
     let mut res: Vec<JsonArrayEntries<String>> = Vec::with_capacity(root_array_len);
-    let mut j = end_index;
-    let estimated_capacity = 16;
-    for i in (0..root_array_len).rev() {
-        let mut flat_json_values: Vec<FlatJsonValue<String>> =
-            Vec::with_capacity(estimated_capacity);
-        let mut is_first_entry = true;
+    let entries_count = previous_parse_result.json.len().max(1);
+    // Entries are in document order, elements are read one after the other. Entries following the
+    // array, when parsing started in a nested array, are not read either.
+    let mut entries = mem::take(&mut previous_parse_result.json)
+        .into_iter()
+        .enumerate()
+        .skip(start_index + 1)
+        .peekable();
+    let mut element_entries: Vec<FlatJsonValue<String>> = Vec::with_capacity(16);
+    for i in 0..root_array_len {
+        if (i + 1) % PROGRESS_INTERVAL == 0 {
+            let consumed = entries.peek().map_or(entries_count, |(k, _)| *k);
+            progress(consumed as f32 / entries_count as f32);
+        }
         let _i = i.to_string();
-        loop {
-            if !previous_parse_result.json.is_empty() {
-                let entry = &mut previous_parse_result.json[j];
-                let (match_prefix, prefix_len) = if let Some(ref started_parsing_at) =
-                    previous_parse_result.started_parsing_at
-                {
-                    let prefix = concat_string!(started_parsing_at, "/", _i);
-                    // println!("else if {}", prefix);
-                    (entry.pointer.pointer.starts_with(&prefix), prefix.len())
-                } else if let Some(ref prefix) = previous_parse_result.parsing_prefix {
-                    let prefix = concat_string!(prefix, "/", _i);
-                    // println!("else if {}", prefix);
-                    (entry.pointer.pointer.starts_with(&prefix), prefix.len())
-                } else {
-                    let prefix = concat_string!("/", _i);
-                    // println!("else {}", prefix);
-                    (entry.pointer.pointer.starts_with(&prefix), prefix.len())
-                };
-
-                if match_prefix {
-                    if !entry.pointer.pointer.is_empty() {
-                        if entry.pointer.pointer.len() < prefix_len {
-                            panic!("{} len is < {}", entry.pointer.pointer, prefix_len);
-                        }
-                        let key = &entry.pointer.pointer[prefix_len..entry.pointer.pointer.len()];
-                        let mut column = Column {
-                            name: Cow::from(key.to_string()),
-                            depth: entry.pointer.depth,
-                            value_type: entry.pointer.value_type,
-                            seen_count: 1,
-                            order: unique_keys.len(),
-                            id: 0,
-                        };
-                        if let Some(existing_column) =
-                            unique_keys.iter_mut().find(|c| c.eq(&&column))
-                        {
-                            existing_column.seen_count += 1;
-                            if existing_column.value_type.eq(&ValueType::Null) {
-                                existing_column.value_type = column.value_type;
-                            }
-                            entry.pointer.column_id = existing_column.id;
-                        } else {
-                            let mut hasher = DefaultHasher::new();
-                            hasher.write(column.name.as_bytes());
-                            column.id = hasher.finish() as usize;
-                            entry.pointer.column_id = column.id;
-                            unique_keys.push(column);
-                        }
-                    }
-                    if is_first_entry {
-                        is_first_entry = false;
-                        let prefix = &entry.pointer.pointer[0..prefix_len];
-                        flat_json_values.push(row_number_entry(i, entry.pointer.position, prefix));
-                    }
-                    let entry = previous_parse_result.json.pop().unwrap();
-                    flat_json_values.push(entry);
-                } else {
-                    break;
-                }
-                if j == 0 {
-                    break;
+        // Same prefix for every entry of the element, allocated once
+        let prefix = concat_string!(parent_prefix, "/", _i);
+        let prefix_len = prefix.len();
+        while let Some((k, mut entry)) =
+            entries.next_if(|(_, entry)| is_pointer_prefix(&prefix, &entry.pointer.pointer))
+        {
+            let key = &entry.pointer.pointer[prefix_len..];
+            // Key is only copied the first time it is seen, each element repeats same keys
+            if let Some(column_index) = unique_keys.iter().position(|c| c.name.eq(key)) {
+                let existing_column = &mut unique_keys[column_index];
+                existing_column.seen_count += 1;
+                // Column has the type of its last non null value
+                if !matches!(entry.pointer.value_type, ValueType::Null) {
+                    existing_column.value_type = entry.pointer.value_type;
                 }
-                j -= 1;
+                entry.pointer.column_id = existing_column.id;
+                last_seen[column_index] = k;
             } else {
-                break;
+                let mut hasher = DefaultHasher::new();
+                hasher.write(key.as_bytes());
+                let column = Column {
+                    name: Cow::from(key.to_string()),
+                    depth: entry.pointer.depth,
+                    value_type: entry.pointer.value_type,
+                    seen_count: 1,
+                    order: 0,
+                    id: hasher.finish() as usize,
+                };
+                entry.pointer.column_id = column.id;
+                unique_keys.push(column);
+                last_seen.push(k);
             }
+            element_entries.push(entry);
         }
-        if !flat_json_values.is_empty() {
+        if let Some(last_entry) = element_entries.last() {
+            // Entries of a row are in reverse document order, with element root node last,
+            // serialization still write keys in their parsed order.
+            let mut flat_json_values = Vec::with_capacity(element_entries.len() + 1);
+            flat_json_values.push(row_number_entry(i, last_entry.pointer.position, &prefix));
+            flat_json_values.extend(element_entries.drain(..).rev());
             res.push(JsonArrayEntries::<String> {
                 entries: flat_json_values,
                 index: i,
             });
         }
     }
-    res.reverse();
+    // Columns whose last entry comes later in the document have a lower order
+    let mut by_last_seen = (0..unique_keys.len()).collect::<Vec<usize>>();
+    by_last_seen.sort_unstable_by(|a, b| last_seen[*b].cmp(&last_seen[*a]));
+    for (order, column_index) in by_last_seen.into_iter().enumerate() {
+        unique_keys[column_index].order = order;
+    }
     unique_keys.sort();
-    Ok((res, unique_keys))
+    progress(1.0);
+    Ok((res, unique_keys, truncation))
+}
+
+/// Rename object keys of parsed entries with `normalizer`, as if the document had been written
+/// with normalized keys: pointers and serialized values below the parsed array are updated, so
+/// keys normalized to the same name share a column and saving writes normalized keys. Pointer
+/// tokens made of digits are taken as array indexes and left unchanged. A key is kept as written
+/// when another key of its object has the same normalized name, so no object gets duplicate keys.
+/// Return pointers of keys kept as written.
+pub fn normalize_keys(
+    result: &mut ParseResult<String>,
+    normalizer: fn(&str) -> String,
+) -> Vec<String> {
+    let prefix = result
+        .started_parsing_at
+        .clone()
+        .or_else(|| result.parsing_prefix.clone())
+        .unwrap_or_default();
+    let is_below_prefix =
+        |pointer: &str| pointer.len() > prefix.len() && is_pointer_prefix(&prefix, pointer);
+    // Keys by object and normalized name
+    let mut siblings: HashMap<(&str, String), Vec<&str>> = HashMap::new();
+    for entry in result.json.iter() {
+        let pointer = entry.pointer.pointer.as_str();
+        if !is_below_prefix(pointer) {
+            continue;
+        }
+        let (parent, token) = pointer.rsplit_once('/').unwrap();
+        if is_array_index(token) {
+            continue;
+        }
+        let keys = siblings
+            .entry((parent, normalizer(&unescape_pointer_token(token))))
+            .or_default();
+        if !keys.contains(&pointer) {
+            keys.push(pointer);
+        }
+    }
+    let mut kept_keys = HashSet::new();
+    for ((_, normalized), keys) in siblings.into_iter().filter(|(_, keys)| keys.len() > 1) {
+        for key in keys {
+            let (_, token) = key.rsplit_once('/').unwrap();
+            if unescape_pointer_token(token) != normalized {
+                kept_keys.insert(key.to_string());
+            }
+        }
+    }
+    for entry in result.json.iter_mut() {
+        if !is_below_prefix(&entry.pointer.pointer) {
+            continue;
+        }
+        if matches!(
+            entry.pointer.value_type,
+            ValueType::Object(..) | ValueType::Array(_)
+        ) {
+            if let Some(normalized) = entry.value.as_deref().and_then(|value| {
+                normalize_json_keys(value, normalizer, &entry.pointer.pointer, &mut kept_keys)
+            }) {
+                entry.value = Some(normalized);
+            }
+        }
+        let normalized_pointer =
+            normalize_pointer_keys(&prefix, &entry.pointer.pointer, normalizer, &kept_keys);
+        if normalized_pointer != entry.pointer.pointer {
+            entry.pointer.pointer = normalized_pointer;
+        }
+    }
+    let mut kept_keys = kept_keys.into_iter().collect::<Vec<String>>();
+    kept_keys.sort();
+    kept_keys
+}
+
+fn is_array_index(token: &str) -> bool {
+    !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Normalize key tokens of `pointer` following `prefix`, array indexes and `kept_keys` are kept
+fn normalize_pointer_keys(
+    prefix: &str,
+    pointer: &str,
+    normalizer: fn(&str) -> String,
+    kept_keys: &HashSet<String>,
+) -> String {
+    let mut normalized = String::with_capacity(pointer.len());
+    normalized.push_str(prefix);
+    let mut end = prefix.len();
+    for token in pointer[prefix.len()..].split('/').skip(1) {
+        end += 1 + token.len();
+        normalized.push('/');
+        if is_array_index(token) || kept_keys.contains(&pointer[..end]) {
+            normalized.push_str(token);
+        } else {
+            normalized.push_str(&escape_pointer_token(&normalizer(&unescape_pointer_token(
+                token,
+            ))));
+        }
+    }
+    normalized
+}
+
+// Keys of serialized `json` at `pointer` normalized, colliding keys are kept and added to `kept_keys`.
+// None when no key changed.
+fn normalize_json_keys(
+    json: &str,
+    normalizer: fn(&str) -> String,
+    pointer: &str,
+    kept_keys: &mut HashSet<String>,
+) -> Option<String> {
+    let mut reader = JsonNodeReader {
+        json: json.as_bytes(),
+        position: 0,
+        depth: 0,
+    };
+    let node = reader.read_value().ok()?;
+    let mut output = String::with_capacity(json.len());
+    let mut changed = false;
+    node.write_with_normalized_keys(&mut output, normalizer, &mut changed, pointer, kept_keys);
+    changed.then_some(output)
+}
+
+/// Lower case key with words separated by underscores: `userID`, `UserId` and `user-id` all
+/// become `user_id`
+pub fn snake_case_key(key: &str) -> String {
+    let chars = key.chars().collect::<Vec<char>>();
+    let mut snake_case = String::with_capacity(key.len() + 4);
+    for (i, c) in chars.iter().enumerate() {
+        if matches!(c, '-' | ' ' | '_') {
+            if !snake_case.is_empty() && !snake_case.ends_with('_') {
+                snake_case.push('_');
+            }
+            continue;
+        }
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lowercase = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            // New word after a lower case char or a digit, or last capital of an acronym: IDValue
+            if (previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lowercase))
+                && !snake_case.ends_with('_')
+            {
+                snake_case.push('_');
+            }
+        }
+        snake_case.extend(c.to_lowercase());
+    }
+    snake_case
+}
+
+/// Pointers seen more than once in a same array element, meaning an object had a duplicate key.
+pub fn find_duplicate_pointers(previous_parse_result: &[JsonArrayEntries<String>]) -> Vec<String> {
+    let mut duplicates = vec![];
+    let mut seen: HashSet<&str> = HashSet::with_capacity(16);
+    for row in previous_parse_result {
+        seen.clear();
+        for entry in row.entries() {
+            if !seen.insert(entry.pointer.pointer.as_str()) {
+                duplicates.push(entry.pointer.pointer.clone());
+            }
+        }
+    }
+    duplicates
 }
 
+/// Name of the row number column. `~` is escaped as `~0` in pointers of keys, so no key, not even
+/// `#`, gives this name.
+pub const ROW_NUMBER_COLUMN: &str = "/~#";
+
 pub fn row_number_entry(i: usize, position: usize, prefix: &str) -> FlatJsonValue<String> {
     FlatJsonValue {
         pointer: PointerKey::from_pointer(
-            concat_string!(prefix, "/#"),
+            concat_string!(prefix, ROW_NUMBER_COLUMN),
             ValueType::Number,
             0,
             position,
@@ -251,6 +460,37 @@ pub fn row_number_entry(i: usize, position: usize, prefix: &str) -> FlatJsonValu
     }
 }
 
+/// Escape an object key to be used as a json pointer (RFC 6901) reference token
+pub fn escape_pointer_token(key: &str) -> Cow<str> {
+    if key.contains(['~', '/']) {
+        Cow::from(key.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::from(key)
+    }
+}
+
+/// Reverse of escape_pointer_token, `~1` is replaced first so `~01` gives `~1`
+pub fn unescape_pointer_token(token: &str) -> Cow<str> {
+    if token.contains('~') {
+        Cow::from(token.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::from(token)
+    }
+}
+
+/// Count of reference tokens of `pointer`, it is the depth of the value it points to ("" is the
+/// root at depth 0)
+pub fn pointer_depth(pointer: &str) -> u8 {
+    pointer.split('/').skip(1).count().min(u8::MAX as usize) as u8
+}
+
+/// Whether `pointer` is `prefix` or one of its descendants, "/a" is not a prefix of "/ab"
+#[inline]
+pub fn is_pointer_prefix(prefix: &str, pointer: &str) -> bool {
+    pointer.starts_with(prefix)
+        && (pointer.len() == prefix.len() || pointer.as_bytes()[prefix.len()] == b'/')
+}
+
 #[cfg(windows)]
 const LINE_ENDING: &'static [u8] = ",\r\n".as_bytes();
 #[cfg(not(windows))]
@@ -260,6 +500,29 @@ pub fn save_to_buffer<T: Write>(
     parent_pointer: &str,
     array: &[JsonArrayEntries<String>],
     buffer: &mut T,
+) -> std::io::Result<()> {
+    write_array_to_buffer(parent_pointer, array.iter(), buffer)
+}
+
+/// Save only `rows` of the array, in the given order, like `save_to_buffer`: they are written as
+/// the elements of a standalone array, renumbered from 0.
+pub fn save_rows_to_buffer<T: Write>(
+    parent_pointer: &str,
+    array: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    buffer: &mut T,
+) -> std::io::Result<()> {
+    write_array_to_buffer(
+        parent_pointer,
+        rows.iter().filter_map(|row_index| array.get(*row_index)),
+        buffer,
+    )
+}
+
+fn write_array_to_buffer<'entries, T: Write>(
+    parent_pointer: &str,
+    rows: impl Iterator<Item = &'entries JsonArrayEntries<String>>,
+    buffer: &mut T,
 ) -> std::io::Result<()> {
     if !parent_pointer.is_empty() {
         let split = parent_pointer.split('/');
@@ -269,22 +532,20 @@ pub fn save_to_buffer<T: Write>(
             }
             let b = &frag.as_bytes()[0];
             if *b >= 0x30 && *b <= 0x39 {
-                buffer.write_all("[".as_bytes()).unwrap();
+                buffer.write_all("[".as_bytes())?;
             } else {
-                buffer
-                    .write_all(format!("{{\"{}\":", frag).as_bytes())
-                    .unwrap();
+                buffer.write_all(format!("{{\"{}\":", frag).as_bytes())?;
             }
         }
     }
-    buffer.write_all("[".as_bytes()).unwrap();
-    for (i, entry) in array.iter().enumerate() {
+    buffer.write_all("[".as_bytes())?;
+    let mut rows = rows.peekable();
+    while let Some(entry) = rows.next() {
         if let Some(serialized_entry) = entry.entries.last() {
-            buffer
-                .write_all(serialized_entry.value.as_ref().unwrap().as_bytes())
-                .unwrap();
-            if i < array.len() - 1 {
-                buffer.write_all(LINE_ENDING).unwrap();
+            let value = restore_non_finite_numbers(serialized_entry.value.as_ref().unwrap());
+            buffer.write_all(value.as_bytes())?;
+            if rows.peek().is_some() {
+                buffer.write_all(LINE_ENDING)?;
             }
         }
     }
@@ -297,9 +558,9 @@ pub fn save_to_buffer<T: Write>(
             }
             let b = &frag.as_bytes()[0];
             if *b >= 0x30 && *b <= 0x39 {
-                buffer.write_all("]".as_bytes()).unwrap();
+                buffer.write_all("]".as_bytes())?;
             } else {
-                buffer.write_all("}".as_bytes()).unwrap();
+                buffer.write_all("}".as_bytes())?;
             }
         }
     }
@@ -307,260 +568,4341 @@ pub fn save_to_buffer<T: Write>(
     Ok(())
 }
 
-pub fn save_to_file(
+/// Write the object shown as the single row of a table opened with `wrap_root_object`, without the
+/// array wrapping it
+pub fn save_root_object_to_buffer<T: Write>(
+    array: &[JsonArrayEntries<String>],
+    buffer: &mut T,
+) -> std::io::Result<()> {
+    if let Some(serialized_entry) = array.first().and_then(|entry| entry.entries.last()) {
+        let value = restore_non_finite_numbers(serialized_entry.value.as_ref().unwrap());
+        buffer.write_all(value.as_bytes())?;
+    }
+    buffer.flush()
+}
+
+/// Save array to `file_path` with `write_atomically`, keeping the trailing new line of the file
+pub fn save_to_path(
     parent_pointer: &str,
     array: &[JsonArrayEntries<String>],
     file_path: &Path,
-) -> std::io::Result<()> {
-    // let start = crate::compatibility::now();
-    let file = fs::File::create(file_path)?;
-    let mut file = BufWriter::new(file);
-    save_to_buffer(parent_pointer, array, &mut file)?;
-    // println!("serialize and save file took {}ms", start.elapsed().as_millis());
+) -> Result<(), String> {
+    let line_ending = trailing_line_ending(file_path);
+    write_atomically(file_path, |file| {
+        save_to_buffer(parent_pointer, array, file)?;
+        if let Some(line_ending) = line_ending {
+            file.write_all(line_ending)?;
+        }
+        file.flush()
+    })
+}
+
+/// Replace content of `file_path` by what `write` writes, through a temporary file of the same
+/// directory which is synced then renamed over it: a save interrupted by a crash leaves either the
+/// previous or the new content, never a truncated file.
+pub fn write_atomically(
+    file_path: &Path,
+    write: impl FnOnce(&mut BufWriter<fs::File>) -> std::io::Result<()>,
+) -> Result<(), String> {
+    let file_name = file_path
+        .file_name()
+        .ok_or_else(|| format!("{} is not a file path", file_path.display()))?;
+    let directory = file_path
+        .parent()
+        .filter(|directory| !directory.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let temporary_path = directory.join(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+    let result = (|| -> std::io::Result<()> {
+        let mut file = BufWriter::new(fs::File::create(&temporary_path)?);
+        write(&mut file)?;
+        let file = file.into_inner().map_err(|err| err.into_error())?;
+        file.sync_all()?;
+        if let Ok(metadata) = fs::metadata(file_path) {
+            fs::set_permissions(&temporary_path, metadata.permissions())?;
+        }
+        fs::rename(&temporary_path, file_path)
+    })();
+    if let Err(err) = result {
+        let _ = fs::remove_file(&temporary_path);
+        return Err(format!("Failed to save {}: {}", file_path.display(), err));
+    }
     Ok(())
 }
 
-pub fn filter_columns(
-    previous_parse_result: &Vec<JsonArrayEntries<String>>,
-    prefix: &str,
-    filters: &HashMap<String, Vec<String>>,
-) -> Vec<usize> {
-    let mut res: Vec<usize> = Vec::with_capacity(previous_parse_result.len());
-    for row in previous_parse_result {
-        let mut should_add_row = true;
-        for (pointer, filters) in filters {
-            let pointer_to_find = concat_string!(prefix, "/", row.index().to_string(), pointer);
-            let mut filters_clone = Vec::with_capacity(filters.len());
-            let mut should_filter_by_non_null = false;
-            for filter in filters {
-                if filter.eq(NON_NULL_FILTER_VALUE) {
-                    should_filter_by_non_null = true;
-                } else {
-                    filters_clone.push(filter.clone());
-                }
-            }
-            if let Some(entry) = row.find_node_at(&pointer_to_find) {
-                if should_filter_by_non_null && entry.value.is_none() {
-                    should_add_row = false;
-                    break;
-                }
-                if !filters_clone.is_empty()
-                    && (entry.value.as_ref().is_none()
-                        || !filters_clone.contains(entry.value.as_ref().unwrap()))
-                {
-                    should_add_row = false;
-                    break;
-                }
-            } else {
-                should_add_row = false;
-                break;
-            }
+// Line ending at the end of the file, None when file does not end with a new line or can't be read
+fn trailing_line_ending(file_path: &Path) -> Option<&'static [u8]> {
+    let mut file = fs::File::open(file_path).ok()?;
+    let tail_len = file.metadata().ok()?.len().min(2);
+    file.seek(SeekFrom::End(-(tail_len as i64))).ok()?;
+    let mut tail = Vec::with_capacity(2);
+    file.read_to_end(&mut tail).ok()?;
+    if tail.ends_with(b"\r\n") {
+        Some(b"\r\n".as_slice())
+    } else if tail.ends_with(b"\n") {
+        Some(b"\n".as_slice())
+    } else {
+        None
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SerializeOptions {
+    pub pretty: bool,
+    // Number of spaces per nesting level when pretty is set
+    pub indent: usize,
+    pub sort_keys: bool,
+    // Write non ascii chars of strings and keys as \uXXXX escapes
+    pub escape_unicode: bool,
+}
+
+impl Default for SerializeOptions {
+    fn default() -> Self {
+        Self {
+            pretty: true,
+            indent: 2,
+            sort_keys: false,
+            escape_unicode: false,
         }
+    }
+}
 
-        if should_add_row {
-            res.push(row.index);
+/// Serialize the array like `save_to_buffer`, nested in its parent pointer, then layout the output according to `options`.
+pub fn serialize_to_json_pretty(
+    parent_pointer: &str,
+    array: &[JsonArrayEntries<String>],
+    options: &SerializeOptions,
+) -> Result<String, String> {
+    let mut buffer: Vec<u8> = vec![];
+    save_to_buffer(parent_pointer, array, &mut buffer).map_err(|e| e.to_string())?;
+    format_json(&String::from_utf8_lossy(&buffer), options)
+}
+
+/// Re-layout a json document, scalar values are written as they are in `json` (numbers are not
+/// normalized), only whitespaces between tokens and, with `sort_keys`, object keys order change.
+/// With `escape_unicode`, non ascii chars are written as escapes.
+pub fn format_json(json: &str, options: &SerializeOptions) -> Result<String, String> {
+    let mut reader = JsonNodeReader {
+        json: json.as_bytes(),
+        position: 0,
+        depth: 0,
+    };
+    let mut node = reader.read_value()?;
+    reader.skip_whitespaces();
+    if reader.position < json.len() {
+        return Err(format!(
+            "Unexpected character after json value at position {}",
+            reader.position
+        ));
+    }
+    if options.sort_keys {
+        node.sort_keys();
+    }
+    let mut output = String::with_capacity(json.len() * 2);
+    node.write(&mut output, options, 0);
+    Ok(output)
+}
+
+/// Columns of the array at `start_parse_at` pointer, or of the root array, without storing any
+/// value: it gives the same columns as `as_array` on a document parsed with `parse_array(false)`.
+/// Objects are walked until `max_depth`, nested arrays are not.
+pub fn parse_keys_only<'array>(
+    json: &[u8],
+    start_parse_at: Option<&str>,
+    max_depth: u8,
+) -> Result<Vec<Column<'array>>, String> {
+    let mut reader = JsonNodeReader {
+        json,
+        position: 0,
+        depth: 0,
+    };
+    let mut depth = 0;
+    if let Some(start_parse_at) = start_parse_at {
+        for token in start_parse_at.split('/').skip(1) {
+            reader.seek(&unescape_pointer_token(token))?;
+            depth += 1;
         }
     }
-    res
+    reader.skip_whitespaces();
+    if reader.json.get(reader.position) != Some(&b'[') {
+        return Err("Parsed json root is not an array".to_string());
+    }
+    let mut columns: Vec<Column> = Vec::with_capacity(16);
+    let mut name = String::new();
+    reader.read_container(|reader, _| {
+        name.clear();
+        reader.read_keys(&mut name, depth, max_depth, &mut columns)?;
+        Ok(true)
+    })?;
+    // as_array walks rows backward, reverse order so ties are sorted the same way
+    let columns_count = columns.len();
+    for column in columns.iter_mut() {
+        column.order = columns_count - 1 - column.order;
+        let mut hasher = DefaultHasher::new();
+        hasher.write(column.name.as_bytes());
+        column.id = hasher.finish() as usize;
+    }
+    columns.sort();
+    Ok(columns)
 }
-pub fn search_occurrences(
-    previous_parse_result: &[JsonArrayEntries<String>],
-    term: &str,
-) -> Vec<usize> {
-    let mut res: Vec<usize> = vec![];
-    for json_array_entry in previous_parse_result.iter() {
-        for entry in &json_array_entry.entries {
-            if !matches!(entry.pointer.value_type, ValueType::String) {
-                continue;
+
+/// Number of direct children carried by array and object value types, None for scalars
+pub fn child_count(value_type: &ValueType) -> Option<usize> {
+    match value_type {
+        ValueType::Array(len) | ValueType::Object(_, len) => Some(*len),
+        _ => None,
+    }
+}
+
+/// Value type of a value typed or pasted in a cell of `column_value_type` column. For array and
+/// object columns, children of the raw value are counted and objects are not considered as parsed.
+/// For null columns, type is guessed from the value.
+pub fn edited_value_type(column_value_type: ValueType, value: &str) -> ValueType {
+    if matches!(column_value_type, ValueType::Null) {
+        return match value {
+            "" | "null" => ValueType::Null,
+            "true" | "false" => ValueType::Bool,
+            _ if value.starts_with(['[', '{']) => {
+                edited_value_type(ValueType::Object(false, 0), value)
             }
-            if let Some(ref value) = entry.value {
-                if value.to_lowercase().contains(term) {
-                    res.push(json_array_entry.index);
-                    break;
-                }
+            _ if value.starts_with(|c: char| c == '-' || c.is_ascii_digit())
+                && (is_integer(value) || is_float(value)) =>
+            {
+                ValueType::Number
             }
-        }
+            _ => ValueType::String,
+        };
+    }
+    if !matches!(
+        column_value_type,
+        ValueType::Array(_) | ValueType::Object(..)
+    ) {
+        return column_value_type;
+    }
+    let mut reader = JsonNodeReader {
+        json: value.as_bytes(),
+        position: 0,
+        depth: 0,
+    };
+    match reader.skip_value() {
+        Ok(ValueType::Array(len)) => ValueType::Array(len),
+        Ok(ValueType::Object(_, len)) => ValueType::Object(false, len),
+        _ => match column_value_type {
+            ValueType::Object(..) => ValueType::Object(false, 0),
+            value_type => value_type,
+        },
     }
-    res
 }
 
-pub fn replace_occurrences(
-    previous_parse_result: &Vec<JsonArrayEntries<String>>,
-    search_replace_response: SearchReplaceResponse,
-) -> Vec<(FlatJsonValue<String>, usize)> {
-    let column_ids = if let Some(ref selected_columns) = search_replace_response.selected_column {
-        selected_columns
-            .iter()
-            .map(|c| c.id)
-            .collect::<Vec<usize>>()
+// Object key without its quotes, unescaped
+fn unquote(key: &str) -> Result<Cow<str>, String> {
+    let key = &key[1..key.len() - 1];
+    if key.contains('\\') {
+        decode_json_string(key).map(Cow::from)
     } else {
-        vec![]
-    };
-    let mut new_values: Vec<(FlatJsonValue<String>, usize)> = vec![];
-    for json_array_entry in previous_parse_result.iter() {
-        for entry in json_array_entry.entries.iter() {
-            if column_ids.contains(&entry.pointer.column_id) {
-                if let Some(ref value) = entry.value {
-                    match search_replace_response.replace_mode {
-                        ReplaceMode::MatchingCase => {
-                            let new_value = if let Some(ref replace_value) =
-                                search_replace_response.replace_value
-                            {
-                                Some(value.replace(
-                                    search_replace_response.search_criteria.as_str(),
-                                    replace_value,
-                                ))
-                            } else if (search_replace_response.search_criteria.is_empty()
-                                && value.is_empty())
-                                || (!search_replace_response
-                                    .search_criteria
-                                    .is_empty()
-                                    && value.contains(
-                                        search_replace_response.search_criteria.as_str(),
-                                    ))
-                            {
-                                None
-                            } else {
-                                Some(value.clone())
-                            };
-                            new_values.push((
-                                FlatJsonValue {
-                                    pointer: entry.pointer.clone(),
-                                    value: new_value,
-                                },
-                                json_array_entry.index,
-                            ));
-                        }
-                        ReplaceMode::Regex => {
-                            let re = Regex::new(search_replace_response.search_criteria.as_str())
-                                .unwrap();
-                            let new_value = replace_with_regex(&search_replace_response, value, re);
-                            new_values.push((
-                                FlatJsonValue {
-                                    pointer: entry.pointer.clone(),
-                                    value: new_value,
-                                },
-                                json_array_entry.index,
-                            ));
-                        }
-                        ReplaceMode::ExactWord => {
-                            let re = Regex::new(&format!(
-                                r"\b{}\b",
-                                regex_lite::escape(
-                                    search_replace_response.search_criteria.as_str()
-                                )
-                            ))
-                            .unwrap();
-                            let new_value = replace_with_regex(&search_replace_response, value, re);
-                            new_values.push((
-                                FlatJsonValue {
-                                    pointer: entry.pointer.clone(),
-                                    value: new_value,
-                                },
-                                json_array_entry.index,
-                            ));
-                        }
-                        ReplaceMode::Simple => {
-                            let re = Regex::new(&format!(
-                                "(?i){}",
-                                regex_lite::escape(
-                                    search_replace_response.search_criteria.as_str()
-                                )
-                            ))
-                            .unwrap();
-                            let new_value = replace_with_regex(&search_replace_response, value, re);
-                            new_values.push((
-                                FlatJsonValue {
-                                    pointer: entry.pointer.clone(),
-                                    value: new_value,
-                                },
-                                json_array_entry.index,
-                            ));
-                        }
-                    }
+        Ok(Cow::from(key))
+    }
+}
+
+/// Decode escape sequences of a json string given without its quotes. A `\uXXXX` high surrogate
+/// has to be followed by a `\uXXXX` low surrogate, the pair is decoded as a single char.
+/// Raw control characters are rejected, they have to be escaped.
+pub fn decode_json_string(raw: &str) -> Result<String, String> {
+    let mut decoded = String::with_capacity(raw.len());
+    let mut chars = raw.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c < ' ' {
+            return Err(control_character_error(c, i));
+        }
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+        let unescaped = match chars.next() {
+            Some((_, '"')) => '"',
+            Some((_, '\\')) => '\\',
+            Some((_, '/')) => '/',
+            Some((_, 'b')) => '\u{8}',
+            Some((_, 'f')) => '\u{c}',
+            Some((_, 'n')) => '\n',
+            Some((_, 'r')) => '\r',
+            Some((_, 't')) => '\t',
+            Some((_, 'u')) => {
+                let code_unit = read_code_unit(raw, i)?;
+                // Escape is ascii, one char per byte
+                chars.nth(3);
+                if (0xD800..0xDC00).contains(&code_unit) {
+                    let low_surrogate = read_code_unit(raw, i + 6)
+                        .ok()
+                        .filter(|low| {
+                            raw[i + 6..].starts_with('\\') && (0xDC00..0xE000).contains(low)
+                        })
+                        .ok_or_else(|| unpaired_surrogate(code_unit, i))?;
+                    chars.nth(5);
+                    char::from_u32(
+                        0x10000 + ((code_unit - 0xD800) << 10) + (low_surrogate - 0xDC00),
+                    )
+                    .unwrap()
+                } else {
+                    char::from_u32(code_unit).ok_or_else(|| unpaired_surrogate(code_unit, i))?
                 }
             }
-        }
+            _ => return Err(format!("Invalid escape sequence at position {}", i)),
+        };
+        decoded.push(unescaped);
     }
-    new_values
+    Ok(decoded)
 }
 
-fn replace_with_regex(
-    search_replace_response: &SearchReplaceResponse,
-    value: &String,
-    re: Regex,
-) -> Option<String> {
-    let new_value = if let Some(ref replace_value) = search_replace_response.replace_value {
-        Some(re.replace_all(value, replace_value.as_str()).to_string())
-    } else if (search_replace_response.search_criteria.is_empty() && value.is_empty())
-        || (!search_replace_response.search_criteria.is_empty() && re.is_match(value))
-    {
-        None
+// Code unit of the \uXXXX escape starting at `start`
+fn read_code_unit(raw: &str, start: usize) -> Result<u32, String> {
+    raw.get(start + 1..start + 6)
+        .filter(|escape| {
+            escape.starts_with('u') && escape[1..].bytes().all(|b| b.is_ascii_hexdigit())
+        })
+        .map(|escape| u32::from_str_radix(&escape[1..], 16).unwrap())
+        .ok_or_else(|| format!("Invalid unicode escape at position {}", start))
+}
+
+fn unpaired_surrogate(code_unit: u32, position: usize) -> String {
+    format!(
+        "Unpaired surrogate \\u{:04X} at position {}",
+        code_unit, position
+    )
+}
+
+fn push_token(output: &mut String, token: &str, options: &SerializeOptions) {
+    let has_control_chars = token.bytes().any(|byte| byte < 0x20);
+    if (!options.escape_unicode || token.is_ascii()) && !has_control_chars {
+        output.push_str(token);
+        return;
+    }
+    let mut code_units = [0u16; 2];
+    for c in token.chars() {
+        if c < ' ' {
+            // Raw control characters are not valid json, e.g from a lenient document
+            push_control_escape(output, c);
+        } else if c.is_ascii() || !options.escape_unicode {
+            output.push(c);
+        } else {
+            // Chars outside the basic multilingual plane are written as a surrogate pair
+            for code_unit in c.encode_utf16(&mut code_units) {
+                output.push_str(&format!("\\u{:04x}", code_unit));
+            }
+        }
+    }
+}
+
+fn push_control_escape(output: &mut String, c: char) {
+    match c {
+        '\u{8}' => output.push_str("\\b"),
+        '\u{c}' => output.push_str("\\f"),
+        '\n' => output.push_str("\\n"),
+        '\r' => output.push_str("\\r"),
+        '\t' => output.push_str("\\t"),
+        _ => output.push_str(&format!("\\u{:04x}", c as u32)),
+    }
+}
+
+enum JsonNode<'json> {
+    Scalar(&'json str),
+    Array(Vec<JsonNode<'json>>),
+    Object(Vec<(&'json str, JsonNode<'json>)>),
+}
+
+impl JsonNode<'_> {
+    fn sort_keys(&mut self) {
+        match self {
+            JsonNode::Scalar(_) => {}
+            JsonNode::Array(values) => values.iter_mut().for_each(|value| value.sort_keys()),
+            JsonNode::Object(members) => {
+                // Stable sort, so duplicate keys keep their relative order
+                members.sort_by(|(a, _), (b, _)| a.cmp(b));
+                members.iter_mut().for_each(|(_, value)| value.sort_keys());
+            }
+        }
+    }
+
+    fn write(&self, output: &mut String, options: &SerializeOptions, level: usize) {
+        let new_line = |output: &mut String, level: usize| {
+            if options.pretty {
+                output.push('\n');
+                output.push_str(&" ".repeat(level * options.indent));
+            }
+        };
+        match self {
+            JsonNode::Scalar(value) => push_token(output, value, options),
+            JsonNode::Array(values) => {
+                output.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        output.push(',');
+                    }
+                    new_line(output, level + 1);
+                    value.write(output, options, level + 1);
+                }
+                if !values.is_empty() {
+                    new_line(output, level);
+                }
+                output.push(']');
+            }
+            JsonNode::Object(members) => {
+                output.push('{');
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        output.push(',');
+                    }
+                    new_line(output, level + 1);
+                    push_token(output, key, options);
+                    output.push(':');
+                    if options.pretty {
+                        output.push(' ');
+                    }
+                    value.write(output, options, level + 1);
+                }
+                if !members.is_empty() {
+                    new_line(output, level);
+                }
+                output.push('}');
+            }
+        }
+    }
+
+    fn write_with_normalized_keys(
+        &self,
+        output: &mut String,
+        normalizer: fn(&str) -> String,
+        changed: &mut bool,
+        pointer: &str,
+        kept_keys: &mut HashSet<String>,
+    ) {
+        match self {
+            JsonNode::Scalar(value) => output.push_str(value),
+            JsonNode::Array(values) => {
+                output.push('[');
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        output.push(',');
+                    }
+                    let pointer = concat_string!(pointer, "/", i.to_string());
+                    value.write_with_normalized_keys(
+                        output, normalizer, changed, &pointer, kept_keys,
+                    );
+                }
+                output.push(']');
+            }
+            JsonNode::Object(members) => {
+                let names = members
+                    .iter()
+                    .map(|(key, _)| unquote(key).ok())
+                    .collect::<Vec<Option<Cow<str>>>>();
+                let normalized_names = names
+                    .iter()
+                    .map(|name| name.as_deref().map(normalizer))
+                    .collect::<Vec<Option<String>>>();
+                output.push('{');
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        output.push(',');
+                    }
+                    let name = names[i].as_deref().unwrap_or(key);
+                    let pointer = concat_string!(pointer, "/", escape_pointer_token(name));
+                    match (&names[i], &normalized_names[i]) {
+                        (Some(original), Some(normalized)) if normalized != original => {
+                            // Another key of the object has the same normalized name
+                            let collides = (0..members.len()).any(|j| {
+                                j != i
+                                    && normalized_names[j] == normalized_names[i]
+                                    && names[j] != names[i]
+                            });
+                            if collides {
+                                output.push_str(key);
+                                kept_keys.insert(pointer.clone());
+                            } else {
+                                *changed = true;
+                                output.push_str(&serde_json::to_string(normalized).unwrap());
+                            }
+                        }
+                        _ => output.push_str(key),
+                    }
+                    output.push(':');
+                    value.write_with_normalized_keys(
+                        output, normalizer, changed, &pointer, kept_keys,
+                    );
+                }
+                output.push('}');
+            }
+        }
+    }
+}
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+
+/// Document without its leading utf-8 byte order mark, written by some Windows tools
+pub fn strip_bom(json: &[u8]) -> &[u8] {
+    json.strip_prefix(UTF8_BOM).unwrap_or(json)
+}
+
+const EMPTY_DOCUMENT: &str = "Empty document";
+
+/// Error when `json` holds nothing but whitespaces, which the parser does not report clearly
+fn check_not_empty(json: &[u8]) -> Result<(), String> {
+    if json.iter().all(|byte| is_json_whitespace(*byte)) {
+        Err(EMPTY_DOCUMENT.to_string())
     } else {
-        Some(value.clone())
+        Ok(())
+    }
+}
+
+fn control_character_error(c: char, position: usize) -> String {
+    format!(
+        "Unescaped control character U+{:04X} at position {}",
+        c as u32, position
+    )
+}
+
+/// Whitespaces allowed between json tokens: space, tab, line feed and carriage return. Unlike
+/// `u8::is_ascii_whitespace`, form feed is not one of them.
+#[inline]
+pub fn is_json_whitespace(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r')
+}
+
+struct JsonNodeReader<'json> {
+    json: &'json [u8],
+    position: usize,
+    // Number of arrays and objects the reader is in, values are read recursively
+    depth: usize,
+}
+
+impl<'json> JsonNodeReader<'json> {
+    fn skip_whitespaces(&mut self) {
+        while self.position < self.json.len() && is_json_whitespace(self.json[self.position]) {
+            self.position += 1;
+        }
+    }
+
+    fn error(&self, expected: &str) -> String {
+        format!("Expected {} at position {}", expected, self.position)
+    }
+
+    // Only ascii bytes are used as delimiters, so slices always fall on char boundaries, they may
+    // still hold invalid utf-8 as bytes are read undecoded
+    fn slice(&self, start: usize) -> Result<&'json str, String> {
+        std::str::from_utf8(&self.json[start..self.position])
+            .map_err(|err| invalid_utf8_error(start + err.valid_up_to()))
+    }
+
+    fn read_value(&mut self) -> Result<JsonNode<'json>, String> {
+        self.skip_whitespaces();
+        match self.json.get(self.position) {
+            Some(b'[') => {
+                let mut values = vec![];
+                self.read_container(|reader, _| {
+                    values.push(reader.read_value()?);
+                    Ok(true)
+                })?;
+                Ok(JsonNode::Array(values))
+            }
+            Some(b'{') => {
+                let mut members = vec![];
+                self.read_container(|reader, key| {
+                    members.push((key.unwrap(), reader.read_value()?));
+                    Ok(true)
+                })?;
+                Ok(JsonNode::Object(members))
+            }
+            Some(b'"') => Ok(JsonNode::Scalar(self.read_string()?)),
+            Some(_) => Ok(JsonNode::Scalar(self.read_literal()?)),
+            None => Err(self.error("value")),
+        }
+    }
+
+    /// Read array elements or object members, `on_value` is called with its key when reader is
+    /// positioned on each value. Reading stops early when `on_value` returns false, leaving reader
+    /// where `on_value` left it. Return number of values visited.
+    fn read_container(
+        &mut self,
+        mut on_value: impl FnMut(&mut Self, Option<&'json str>) -> Result<bool, String>,
+    ) -> Result<usize, String> {
+        let is_object = self.json.get(self.position) == Some(&b'{');
+        let (close, expected) = if is_object {
+            (b'}', "',' or '}'")
+        } else {
+            (b']', "',' or ']'")
+        };
+        if self.depth >= MAX_NESTING_DEPTH {
+            return Err(nesting_too_deep(self.position));
+        }
+        self.depth += 1;
+        self.position += 1;
+        self.skip_whitespaces();
+        if self.json.get(self.position) == Some(&close) {
+            self.position += 1;
+            self.depth -= 1;
+            return Ok(0);
+        }
+        let mut len = 0;
+        loop {
+            let key = if is_object {
+                self.skip_whitespaces();
+                if self.json.get(self.position) != Some(&b'"') {
+                    return Err(self.error("object key"));
+                }
+                let key = self.read_string()?;
+                self.skip_whitespaces();
+                if self.json.get(self.position) != Some(&b':') {
+                    return Err(self.error("':'"));
+                }
+                self.position += 1;
+                Some(key)
+            } else {
+                None
+            };
+            len += 1;
+            if !on_value(self, key)? {
+                return Ok(len);
+            }
+            self.skip_whitespaces();
+            match self.json.get(self.position) {
+                Some(b',') => self.position += 1,
+                Some(byte) if *byte == close => {
+                    self.position += 1;
+                    self.depth -= 1;
+                    return Ok(len);
+                }
+                _ => return Err(self.error(expected)),
+            }
+        }
+    }
+
+    // Number, boolean or null
+    fn read_literal(&mut self) -> Result<&'json str, String> {
+        let start = self.position;
+        while self.position < self.json.len()
+            && !matches!(self.json[self.position], b',' | b']' | b'}')
+            && !is_json_whitespace(self.json[self.position])
+        {
+            self.position += 1;
+        }
+        if start == self.position {
+            return Err(self.error("value"));
+        }
+        self.slice(start)
+    }
+
+    /// Read a value without keeping it, registering it as a column and, until `max_depth`, its object
+    /// members. `name` is the value pointer relative to array element.
+    fn read_keys(
+        &mut self,
+        name: &mut String,
+        depth: u8,
+        max_depth: u8,
+        columns: &mut Vec<Column<'_>>,
+    ) -> Result<(), String> {
+        self.skip_whitespaces();
+        let value_type = match self.json.get(self.position) {
+            Some(b'{') if depth < max_depth => {
+                let keys_count = self.read_container(|reader, key| {
+                    let name_len = name.len();
+                    name.push('/');
+                    name.push_str(&escape_pointer_token(&unquote(key.unwrap())?));
+                    reader.read_keys(name, depth + 1, max_depth, columns)?;
+                    name.truncate(name_len);
+                    Ok(true)
+                })?;
+                ValueType::Object(true, keys_count)
+            }
+            _ => self.skip_value()?,
+        };
+        let column = Column {
+            name: Cow::from(name.clone()),
+            depth,
+            value_type,
+            seen_count: 1,
+            order: columns.len(),
+            id: 0,
+        };
+        if let Some(existing_column) = columns.iter_mut().find(|c| c.eq(&&column)) {
+            existing_column.seen_count += 1;
+            if existing_column.value_type.eq(&ValueType::Null) {
+                existing_column.value_type = column.value_type;
+            }
+        } else {
+            columns.push(column);
+        }
+        Ok(())
+    }
+
+    fn skip_value(&mut self) -> Result<ValueType, String> {
+        self.skip_whitespaces();
+        match self.json.get(self.position) {
+            Some(b'[') => {
+                Ok(ValueType::Array(self.read_container(|reader, _| {
+                    reader.skip_value().map(|_| true)
+                })?))
+            }
+            Some(b'{') => Ok(ValueType::Object(
+                true,
+                self.read_container(|reader, _| reader.skip_value().map(|_| true))?,
+            )),
+            Some(b'"') => self.read_string().map(|_| ValueType::String),
+            Some(_) => match self.read_literal()? {
+                "null" => Ok(ValueType::Null),
+                "true" | "false" => Ok(ValueType::Bool),
+                _ => Ok(ValueType::Number),
+            },
+            None => Err(self.error("value")),
+        }
+    }
+
+    /// Move reader to value at `token` of current array or object
+    fn seek(&mut self, token: &str) -> Result<(), String> {
+        self.skip_whitespaces();
+        let mut found = false;
+        match self.json.get(self.position) {
+            Some(b'{') => {
+                self.read_container(|reader, key| {
+                    found = unquote(key.unwrap())?.eq(token);
+                    if !found {
+                        reader.skip_value()?;
+                    }
+                    Ok(!found)
+                })?;
+            }
+            Some(b'[') => {
+                let index = token.parse::<usize>().map_err(|e| e.to_string())?;
+                let mut i = 0;
+                self.read_container(|reader, _| {
+                    found = i == index;
+                    if !found {
+                        reader.skip_value()?;
+                    }
+                    i += 1;
+                    Ok(!found)
+                })?;
+            }
+            _ => {}
+        }
+        if found {
+            Ok(())
+        } else {
+            Err(format!("No value found at {}", token))
+        }
+    }
+
+    // Return string literal including its quotes
+    fn read_string(&mut self) -> Result<&'json str, String> {
+        let start = self.position;
+        self.position += 1;
+        while self.position < self.json.len() {
+            match self.json[self.position] {
+                b'\\' => self.position += 2,
+                b'"' => {
+                    self.position += 1;
+                    return self.slice(start);
+                }
+                _ => self.position += 1,
+            }
+        }
+        Err(format!(
+            "Unterminated string starting at position {}",
+            start
+        ))
+    }
+}
+
+/// Value of a `value_type` cell converted to `target`, Number, String or Bool.
+/// Return None when value can't be converted, e.g "abc" to a number.
+pub fn coerce_value(value: &str, value_type: ValueType, target: ValueType) -> Option<String> {
+    match (value_type, target) {
+        (ValueType::Number | ValueType::Bool, ValueType::String) => Some(value.to_string()),
+        (ValueType::String, ValueType::Number) => {
+            let number = value.trim();
+            // Only numbers allowed by json, e.g "1e3" but neither "+1" nor "NaN"
+            serde_json::from_str::<serde_json::Number>(number)
+                .is_ok()
+                .then(|| number.to_string())
+        }
+        (ValueType::Bool, ValueType::Number) => {
+            Some(if value == "true" { "1" } else { "0" }.to_string())
+        }
+        (ValueType::String | ValueType::Number, ValueType::Bool) => {
+            match value.trim().to_lowercase().as_str() {
+                "true" | "1" => Some("true".to_string()),
+                "false" | "0" => Some("false".to_string()),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Value and type of a scalar cycled to its next compatible type: strings holding a json number
+/// become numbers, strings holding a boolean become booleans, numbers and booleans become strings.
+/// None for other values, e.g. `"abc"` or null.
+pub fn cycle_value_type(value: &str, value_type: ValueType) -> Option<(String, ValueType)> {
+    match value_type {
+        ValueType::String => [ValueType::Number, ValueType::Bool]
+            .into_iter()
+            .find_map(|target| {
+                coerce_value(value, value_type, target).map(|value| (value, target))
+            }),
+        ValueType::Number | ValueType::Bool => Some((value.to_string(), ValueType::String)),
+        _ => None,
+    }
+}
+
+/// Type numbers, booleans and nulls entries as strings, so every scalar is viewed and edited as
+/// text. Original types are still written by serialized rows, `restore_coerced_types` uses them when
+/// a row is serialized again.
+pub fn coerce_scalars_to_strings(entries: &mut [FlatJsonValue<String>]) {
+    for entry in entries.iter_mut() {
+        if matches!(
+            entry.pointer.value_type,
+            ValueType::Number | ValueType::Bool | ValueType::Null
+        ) && !entry.pointer.pointer.ends_with(ROW_NUMBER_COLUMN)
+        {
+            entry.pointer.value_type = ValueType::String;
+        }
+    }
+}
+
+/// Give back their type to entries coerced by `coerce_scalars_to_strings`, from `serialized_entries`
+/// parsed from the previous serialization of the row. Values edited to something which is not a
+/// literal of their original type stay strings.
+pub fn restore_coerced_types(
+    entries: &mut [FlatJsonValue<String>],
+    serialized_entries: &[FlatJsonValue<String>],
+) {
+    for entry in entries.iter_mut() {
+        if !matches!(entry.pointer.value_type, ValueType::String) {
+            continue;
+        }
+        let Some(value) = entry.value.as_deref() else {
+            continue;
+        };
+        let original_type = serialized_entries
+            .iter()
+            .find(|serialized| serialized.pointer.pointer.eq(&entry.pointer.pointer))
+            .map(|serialized| serialized.pointer.value_type);
+        let is_literal = match original_type {
+            Some(ValueType::Number) => serde_json::from_str::<serde_json::Number>(value).is_ok(),
+            Some(ValueType::Bool) => value == "true" || value == "false",
+            Some(ValueType::Null) => value == "null",
+            _ => false,
+        };
+        if is_literal {
+            entry.pointer.value_type = original_type.unwrap();
+        }
+    }
+}
+
+/// `ValueType::Number` does not distinguish integers from floats, number values are kept as
+/// their lexed text so we can classify them by looking for a fraction or an exponent.
+pub fn is_integer(number: &str) -> bool {
+    let digits = number.strip_prefix('-').unwrap_or(number);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+pub fn is_float(number: &str) -> bool {
+    !is_integer(number) && number.parse::<f64>().is_ok()
+}
+
+pub fn save_jsonl_to_buffer<T: Write>(
+    array: &[JsonArrayEntries<String>],
+    buffer: &mut T,
+) -> std::io::Result<()> {
+    for entry in array.iter() {
+        if let Some(serialized_entry) = entry.entries.last() {
+            if let Some(ref value) = serialized_entry.value {
+                // Each element has to fit on a single line, keys order and numbers text are kept as
+                // written
+                let line = format_json(
+                    &restore_non_finite_numbers(value),
+                    &SerializeOptions {
+                        pretty: false,
+                        ..SerializeOptions::default()
+                    },
+                )
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+                buffer.write_all(line.as_bytes())?;
+                buffer.write_all(b"\n")?;
+            }
+        }
+    }
+    buffer.flush()?;
+    Ok(())
+}
+
+pub fn save_jsonl_to_file(
+    array: &[JsonArrayEntries<String>],
+    file_path: &Path,
+) -> Result<(), String> {
+    write_atomically(file_path, |file| save_jsonl_to_buffer(array, file))
+}
+
+/// Convert file content to text, error gives byte offset of the first invalid sequence
+pub fn decode_utf8(content: Vec<u8>) -> Result<String, String> {
+    String::from_utf8(content).map_err(|err| invalid_utf8_error(err.utf8_error().valid_up_to()))
+}
+
+/// When root of the document is an object, wrap it in an array so it can be shown as a table with a
+/// single row.
+pub fn wrap_root_object(json: &[u8]) -> Option<Vec<u8>> {
+    let json = strip_bom(json);
+    let first_byte = json.iter().find(|byte| !is_json_whitespace(**byte));
+    if first_byte != Some(&b'{') {
+        return None;
+    }
+    let mut wrapped_json = Vec::with_capacity(json.len() + 2);
+    wrapped_json.push(b'[');
+    wrapped_json.extend_from_slice(json);
+    wrapped_json.push(b']');
+    Some(wrapped_json)
+}
+
+/// Json document to open from text pasted by the user, with whether its root object has been
+/// wrapped in an array (see `wrap_root_object`). Document is scanned and parsed like an opened
+/// file, so an invalid paste is reported before the opened table is discarded.
+pub fn pasted_json_document(text: &str, strict: bool) -> Result<(Vec<u8>, bool), String> {
+    let text = text.trim_start_matches('\u{feff}').trim();
+    if !text.starts_with(['[', '{']) {
+        return Err("Pasted text is not a json array or object".to_string());
+    }
+    let (json, wrapped) = match wrap_root_object(text.as_bytes()) {
+        Some(wrapped_json) => (wrapped_json, true),
+        None => (text.as_bytes().to_vec(), false),
     };
-    new_value
+    if let Some(err) = scan_document(&json).error(strict) {
+        return Err(err);
+    }
+    JSONParser::parse_bytes(&json, ParseOptions::default())?;
+    Ok((json, wrapped))
+}
+
+/// Convert a JSONL (newline delimited json) document into a json array, so it can be parsed
+/// and displayed as any other array. Each line is validated on its own: invalid lines are
+/// skipped and returned along with their line number (starting at 1) and parser error.
+pub fn jsonl_to_json_array(content: &str) -> (String, Vec<(usize, String)>) {
+    let mut json_array = String::with_capacity(content.len() + 2);
+    let mut invalid_lines = vec![];
+    let mut is_first_line = true;
+    json_array.push('[');
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Err(err) = JSONParser::parse(line, ParseOptions::default()) {
+            invalid_lines.push((line_number + 1, err));
+            continue;
+        }
+        if !is_first_line {
+            json_array.push(',');
+        }
+        is_first_line = false;
+        json_array.push_str(line);
+    }
+    json_array.push(']');
+    (json_array, invalid_lines)
+}
+
+/// Convert a YAML document to json text, so it can be parsed like a json file. Aliases and merge keys
+/// are expanded, tags are ignored and keys which are not strings are written as their YAML representation.
+pub fn yaml_to_json(content: &str) -> Result<String, String> {
+    let mut value: serde_yaml::Value =
+        serde_yaml::from_str(content).map_err(|e| format!("Invalid yaml: {}", e))?;
+    value
+        .apply_merge()
+        .map_err(|e| format!("Invalid yaml: {}", e))?;
+    let mut json = String::with_capacity(content.len());
+    write_yaml_value_as_json(&value, &mut json)?;
+    Ok(json)
+}
+
+// Written by hand instead of converting to serde_json::Value, which does not keep order of keys
+fn write_yaml_value_as_json(value: &serde_yaml::Value, json: &mut String) -> Result<(), String> {
+    match value {
+        serde_yaml::Value::Null => json.push_str("null"),
+        serde_yaml::Value::Bool(value) => json.push_str(if *value { "true" } else { "false" }),
+        serde_yaml::Value::Number(number) => {
+            if number.is_nan() || number.is_infinite() {
+                return Err(format!("{} can't be converted to a json number", number));
+            }
+            json.push_str(&number.to_string());
+        }
+        serde_yaml::Value::String(value) => json.push_str(&serde_json::to_string(value).unwrap()),
+        serde_yaml::Value::Sequence(sequence) => {
+            json.push('[');
+            for (i, value) in sequence.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                write_yaml_value_as_json(value, json)?;
+            }
+            json.push(']');
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            json.push('{');
+            for (i, (key, value)) in mapping.iter().enumerate() {
+                if i > 0 {
+                    json.push(',');
+                }
+                let key = match key {
+                    serde_yaml::Value::String(key) => Cow::from(key),
+                    serde_yaml::Value::Null => Cow::from("null"),
+                    serde_yaml::Value::Bool(key) => Cow::from(key.to_string()),
+                    serde_yaml::Value::Number(key) => Cow::from(key.to_string()),
+                    _ => return Err("Only scalar yaml keys are supported".to_string()),
+                };
+                json.push_str(&serde_json::to_string(key.as_ref()).unwrap());
+                json.push(':');
+                write_yaml_value_as_json(value, json)?;
+            }
+            json.push('}');
+        }
+        serde_yaml::Value::Tagged(tagged) => write_yaml_value_as_json(&tagged.value, json)?,
+    }
+    Ok(())
+}
+
+/// Blank out `//` and `/* */` comments and trailing commas before `}` or `]`, outside of string literals,
+/// so json with comments (jsonc, json5 config files) can be parsed. New lines are kept so line numbers do not change.
+pub fn strip_comments_and_trailing_commas(content: &str) -> String {
+    let mut bytes = content.as_bytes().to_vec();
+    let mut in_string = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if in_string {
+            if byte == b'\\' {
+                i += 1;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+        } else if byte == b'"' {
+            in_string = true;
+        } else if byte == b'/' && bytes.get(i + 1) == Some(&b'/') {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                bytes[i] = b' ';
+                i += 1;
+            }
+            continue;
+        } else if byte == b'/' && bytes.get(i + 1) == Some(&b'*') {
+            bytes[i] = b' ';
+            bytes[i + 1] = b' ';
+            i += 2;
+            let mut previous = 0;
+            while i < bytes.len() {
+                let current = bytes[i];
+                if current != b'\n' {
+                    bytes[i] = b' ';
+                }
+                i += 1;
+                if previous == b'*' && current == b'/' {
+                    break;
+                }
+                previous = current;
+            }
+            continue;
+        }
+        i += 1;
+    }
+    // Comments are blanks now, a trailing comma is only followed by whitespaces
+    in_string = false;
+    let mut last_comma = None;
+    i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if in_string {
+            if byte == b'\\' {
+                i += 1;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+        } else if byte == b'"' {
+            in_string = true;
+            last_comma = None;
+        } else if byte == b',' {
+            last_comma = Some(i);
+        } else if byte == b'}' || byte == b']' {
+            if let Some(comma) = last_comma.take() {
+                bytes[comma] = b' ';
+            }
+        } else if !byte.is_ascii_whitespace() {
+            last_comma = None;
+        }
+        i += 1;
+    }
+    // Only ascii bytes outside of string literals were replaced by spaces
+    String::from_utf8(bytes).unwrap()
+}
+
+/// Prefix of the string literals `quote_non_finite_numbers` turns `NaN`, `Infinity` and
+/// `-Infinity` into, a private use char which is not expected in actual documents.
+pub const NON_FINITE_NUMBER_MARKER: char = '\u{E000}';
+const NON_FINITE_NUMBERS: [&str; 3] = ["-Infinity", "Infinity", "NaN"];
+
+/// Lenient number mode: quote bare `NaN`, `Infinity` and `-Infinity` literals outside of string
+/// literals with `NON_FINITE_NUMBER_MARKER` prefix, so they are parsed as raw strings and never converted
+/// to f64. `restore_non_finite_numbers` writes them back as the same literals.
+pub fn quote_non_finite_numbers(content: &str) -> String {
+    let bytes = content.as_bytes();
+    let mut quoted = String::with_capacity(content.len());
+    let mut in_string = false;
+    let mut copied_until = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if in_string {
+            if byte == b'\\' {
+                i += 1;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+        } else if byte == b'"' {
+            in_string = true;
+        } else if byte == b'-' || byte == b'I' || byte == b'N' {
+            let is_token_start = i == 0 || !bytes[i - 1].is_ascii_alphanumeric();
+            if let Some(literal) = NON_FINITE_NUMBERS.iter().find(|literal| {
+                is_token_start
+                    && bytes[i..].starts_with(literal.as_bytes())
+                    && bytes
+                        .get(i + literal.len())
+                        .map_or(true, |next| !next.is_ascii_alphanumeric())
+            }) {
+                quoted.push_str(&content[copied_until..i]);
+                quoted.push('"');
+                quoted.push(NON_FINITE_NUMBER_MARKER);
+                quoted.push_str(literal);
+                quoted.push('"');
+                i += literal.len();
+                copied_until = i;
+                continue;
+            }
+        }
+        i += 1;
+    }
+    quoted.push_str(&content[copied_until..]);
+    quoted
+}
+
+/// Write back literals quoted by `quote_non_finite_numbers` as bare `NaN`, `Infinity` and `-Infinity`.
+pub fn restore_non_finite_numbers(json: &str) -> Cow<str> {
+    if !json.contains(NON_FINITE_NUMBER_MARKER) && !json.contains("\\ue000") {
+        return Cow::Borrowed(json);
+    }
+    let mut restored = json.to_string();
+    for literal in NON_FINITE_NUMBERS {
+        restored = restored
+            .replace(
+                &format!("\"{}{}\"", NON_FINITE_NUMBER_MARKER, literal),
+                literal,
+            )
+            .replace(&format!("\"\\ue000{}\"", literal), literal);
+    }
+    Cow::Owned(restored)
+}
+
+/// Value of a cell without `NON_FINITE_NUMBER_MARKER`, as it is written in the document. Cells
+/// are shown, copied, exported, matched and edited without the marker.
+pub fn strip_non_finite_number_marker(value: &str) -> &str {
+    value
+        .strip_prefix(NON_FINITE_NUMBER_MARKER)
+        .unwrap_or(value)
+}
+
+/// `edited` value of a cell holding `value`, which is edited without its `NON_FINITE_NUMBER_MARKER`:
+/// the marker is kept when the literal is left as it is, so it is still saved as a bare literal.
+pub fn keep_non_finite_number_marker(value: Option<&str>, edited: String) -> String {
+    match value {
+        Some(value)
+            if value.starts_with(NON_FINITE_NUMBER_MARKER)
+                && strip_non_finite_number_marker(value) == edited =>
+        {
+            value.to_string()
+        }
+        _ => edited,
+    }
+}
+
+/// Parse `json` like `JSONParser::parse`. When it fails and root is an array, elements up to the
+/// last complete one are parsed instead, so most of a truncated or slightly corrupted file can
+/// still be viewed. Parsed part is a valid document on its own, pointers and array length are
+/// consistent. Return errors which made content to be skipped.
+pub fn parse_lossy(
+    json: &str,
+    options: ParseOptions,
+) -> Result<(ParseResult<String>, Vec<String>), String> {
+    check_not_empty(json.as_bytes())?;
+    let error = match JSONParser::parse(json, options.clone()) {
+        Ok(result) => return Ok((result.to_owned(), vec![])),
+        Err(error) => error,
+    };
+    let Some(valid_len) = valid_array_prefix_len(json) else {
+        return Err(error);
+    };
+    let valid_part = concat_string!(&json[..valid_len], "]");
+    match JSONParser::parse(&valid_part, options) {
+        Ok(result) => Ok((
+            result.to_owned(),
+            vec![format!(
+                "{}, content after position {} was skipped",
+                error, valid_len
+            )],
+        )),
+        Err(_) => Err(error),
+    }
+}
+
+// Byte length of root array of `json` up to the end of its last complete element, an element is
+// complete when followed by a ',' or the closing bracket. None when root is not an array.
+fn valid_array_prefix_len(json: &str) -> Option<usize> {
+    let mut reader = JsonNodeReader {
+        json: json.as_bytes(),
+        position: 0,
+        depth: 0,
+    };
+    reader.skip_whitespaces();
+    if reader.json.get(reader.position) != Some(&b'[') {
+        return None;
+    }
+    reader.position += 1;
+    let mut valid_len = reader.position;
+    while reader.skip_value().is_ok() {
+        let value_end = reader.position;
+        reader.skip_whitespaces();
+        match reader.json.get(reader.position) {
+            Some(b',') => {
+                valid_len = value_end;
+                reader.position += 1;
+            }
+            Some(b']') => {
+                valid_len = value_end;
+                break;
+            }
+            _ => break,
+        }
+    }
+    Some(valid_len)
+}
+
+/// Deepest nesting of arrays and objects parsed, the deepest parse options allow. Deeper values are
+/// kept as json text, as parsing recurses on each level and would overflow the stack.
+pub const MAX_NESTING_DEPTH: usize = u8::MAX as usize;
+
+fn nesting_too_deep(position: usize) -> String {
+    format!(
+        "Nesting deeper than {} levels at position {}",
+        MAX_NESTING_DEPTH, position
+    )
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::array_table::Column;
-    use crate::panels::{ReplaceMode, SearchReplaceResponse};
-    use crate::parser::{as_array, replace_occurrences};
-    use json_flat_parser::{JSONParser, ParseOptions};
+/// Counts of the values of a document, a quick profile of its content
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    // Punctuation, keys and values
+    pub tokens: usize,
+    pub objects: usize,
+    pub arrays: usize,
+    pub keys: usize,
+    // String values, keys are not counted
+    pub strings: usize,
+    pub numbers: usize,
+    pub bools: usize,
+    pub nulls: usize,
+    pub max_depth: usize,
+}
+
+impl ParseStats {
+    /// Count of objects, arrays and scalars
+    pub fn values(&self) -> usize {
+        self.objects + self.arrays + self.strings + self.numbers + self.bools + self.nulls
+    }
+}
+
+impl std::fmt::Display for ParseStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} objects, {} arrays, {} keys, {} strings, {} numbers, {} booleans, {} nulls, {} depth levels, {} tokens",
+            self.objects,
+            self.arrays,
+            self.keys,
+            self.strings,
+            self.numbers,
+            self.bools,
+            self.nulls,
+            self.max_depth,
+            self.tokens
+        )
+    }
+}
+
+/// What a single scan of a document tells before it is parsed, see `scan_document`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DocumentScan {
+    pub stats: ParseStats,
+    // First raw control character of a string and its position, json only allows them escaped,
+    // e.g. a tab has to be written `\t`
+    pub control_character: Option<(char, usize)>,
+    // Position of the first byte of a string which is not valid utf-8, bytes are read undecoded
+    pub invalid_utf8_at: Option<usize>,
+    // Position of the first array or object nested deeper than `MAX_NESTING_DEPTH`
+    pub too_deep_at: Option<usize>,
+}
+
+impl DocumentScan {
+    /// Whether document holds nothing but whitespaces, which the parser does not report clearly
+    pub fn is_empty(&self) -> bool {
+        self.stats.tokens == 0
+    }
+
+    /// Depth to parse the document up to
+    pub fn max_depth(&self) -> u8 {
+        self.stats.max_depth.min(u8::MAX as usize) as u8
+    }
+
+    /// First error which prevents the document from being parsed, raw control characters are only
+    /// an error when `strict`
+    pub fn error(&self, strict: bool) -> Option<String> {
+        if self.is_empty() {
+            return Some(EMPTY_DOCUMENT.to_string());
+        }
+        if let Some(position) = self.invalid_utf8_at {
+            return Some(invalid_utf8_error(position));
+        }
+        self.control_character
+            .filter(|_| strict)
+            .map(|(c, position)| control_character_error(c, position))
+    }
+
+    /// Warning when the document is nested deeper than `MAX_NESTING_DEPTH`, it is parsed up to it
+    pub fn nesting_warning(&self) -> Option<String> {
+        self.too_deep_at.map(nesting_too_deep)
+    }
+
+    // Skip the string starting after its opening quote at `start`, return position after its
+    // closing quote
+    fn skip_string(&mut self, json: &[u8], start: usize) -> usize {
+        let mut i = start;
+        while i < json.len() {
+            match json[i] {
+                b'"' => return i + 1,
+                b'\\' => i += 2,
+                byte @ 0x00..=0x1F => {
+                    self.control_character.get_or_insert((byte as char, i));
+                    i += 1;
+                }
+                0x80..=0xFF => match utf8_char_len(&json[i..]) {
+                    Some(len) => i += len,
+                    None => {
+                        self.invalid_utf8_at.get_or_insert(i);
+                        i += 1;
+                    }
+                },
+                _ => i += 1,
+            }
+        }
+        i
+    }
+}
+
+// Length of the utf-8 encoded char `bytes` start with, none when it is not valid utf-8
+fn utf8_char_len(bytes: &[u8]) -> Option<usize> {
+    let bytes = &bytes[..bytes.len().min(4)];
+    let valid = match std::str::from_utf8(bytes) {
+        Ok(valid) => valid,
+        // Bytes after the char may be cut in the middle of the next one
+        Err(err) => std::str::from_utf8(&bytes[..err.valid_up_to()]).unwrap(),
+    };
+    valid.chars().next().map(char::len_utf8)
+}
+
+fn invalid_utf8_error(position: usize) -> String {
+    format!("Invalid utf-8 at byte {}", position)
+}
+
+/// Scan `json` once without parsing it, nor recursing: values are counted by type, nesting depth is
+/// measured, braces and brackets of strings being ignored, and errors the parser would not report
+/// clearly are found. Scan is cheap compared to parsing, document is otherwise expected to be valid.
+pub fn scan_document(json: &[u8]) -> DocumentScan {
+    let mut scan = DocumentScan::default();
+    let mut depth: usize = 0;
+    let mut i = 0;
+    while i < json.len() {
+        let byte = json[i];
+        i += 1;
+        if is_json_whitespace(byte) {
+            continue;
+        }
+        scan.stats.tokens += 1;
+        match byte {
+            b'"' => {
+                i = scan.skip_string(json, i);
+                let next = json[i.min(json.len())..]
+                    .iter()
+                    .find(|byte| !is_json_whitespace(**byte));
+                if next == Some(&b':') {
+                    scan.stats.keys += 1;
+                } else {
+                    scan.stats.strings += 1;
+                }
+            }
+            b'{' | b'[' => {
+                if byte == b'{' {
+                    scan.stats.objects += 1;
+                } else {
+                    scan.stats.arrays += 1;
+                }
+                depth += 1;
+                if depth > MAX_NESTING_DEPTH {
+                    scan.too_deep_at.get_or_insert(i - 1);
+                }
+                scan.stats.max_depth = scan.stats.max_depth.max(depth);
+            }
+            b'}' | b']' => depth = depth.saturating_sub(1),
+            b't' | b'f' | b'n' => {
+                while i < json.len() && json[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                if byte == b'n' {
+                    scan.stats.nulls += 1;
+                } else {
+                    scan.stats.bools += 1;
+                }
+            }
+            b'-' | b'0'..=b'9' => {
+                while i < json.len()
+                    && matches!(json[i], b'0'..=b'9' | b'.' | b'e' | b'E' | b'+' | b'-')
+                {
+                    i += 1;
+                }
+                scan.stats.numbers += 1;
+            }
+            // Punctuation, or a byte the parser will reject
+            _ => {}
+        }
+    }
+    scan
+}
+
+/// Byte range of the element at `index` in a serialized json array, whitespaces around it excluded
+pub fn array_element_span(array: &str, index: usize) -> Option<Range<usize>> {
+    let bytes = array.as_bytes();
+    let mut i = bytes.iter().position(|b| !is_json_whitespace(*b))?;
+    if bytes[i] != b'[' {
+        return None;
+    }
+    i += 1;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut current = 0;
+    let mut start = None;
+    let mut end = i;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if in_string {
+            if byte == b'\\' {
+                i += 1;
+            } else if byte == b'"' {
+                in_string = false;
+                end = i + 1;
+            }
+        } else {
+            match byte {
+                b'"' => {
+                    in_string = true;
+                    start.get_or_insert(i);
+                }
+                b'{' | b'[' => {
+                    depth += 1;
+                    start.get_or_insert(i);
+                }
+                b'}' | b']' if depth > 0 => {
+                    depth -= 1;
+                    end = i + 1;
+                }
+                b',' | b']' if depth == 0 => {
+                    if current == index {
+                        return start.map(|start| start..end);
+                    }
+                    if byte == b']' {
+                        return None;
+                    }
+                    current += 1;
+                    start = None;
+                }
+                _ if is_json_whitespace(byte) => {}
+                _ => {
+                    start.get_or_insert(i);
+                    end = i + 1;
+                }
+            }
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Serialize `rows` of the array as CSV (RFC 4180), with one field per column in the given order.
+/// Missing and null cells are empty fields, nested array and object are written as json. Row
+/// number column is written as `#` with the index of the element.
+pub fn to_csv(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    prefix: &str,
+    columns: &[Column],
+) -> String {
+    let mut csv = String::with_capacity(rows.len() * columns.len() * 8);
+    for (i, column) in columns.iter().enumerate() {
+        if i > 0 {
+            csv.push(',');
+        }
+        if column.name == ROW_NUMBER_COLUMN {
+            csv.push('#');
+        } else {
+            csv.push_str(&csv_escape(column.name.as_ref()));
+        }
+    }
+    csv.push_str("\r\n");
+    write_delimited_rows(
+        &mut csv,
+        previous_parse_result,
+        rows,
+        prefix,
+        columns,
+        ',',
+        "\r\n",
+    );
+    csv
+}
+
+/// Serialize `rows` of the array as tab separated values without header, like spreadsheets copy cells
+pub fn to_tsv(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    prefix: &str,
+    columns: &[Column],
+) -> String {
+    let mut tsv = String::with_capacity(rows.len() * columns.len() * 8);
+    write_delimited_rows(
+        &mut tsv,
+        previous_parse_result,
+        rows,
+        prefix,
+        columns,
+        '\t',
+        "\n",
+    );
+    // Last line ending makes spreadsheets paste an extra empty row
+    tsv.pop();
+    tsv
+}
+
+/// Values of `column` for `rows`, one per line. Nested arrays and objects are serialized as json on
+/// a single line, missing and null values are empty lines.
+pub fn column_to_lines(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    prefix: &str,
+    column: &Column,
+) -> String {
+    let mut lines = String::with_capacity(rows.len() * 8);
+    for (i, row_index) in rows.iter().enumerate() {
+        if i > 0 {
+            lines.push('\n');
+        }
+        let row = &previous_parse_result[*row_index];
+        let pointer = concat_string!(prefix, "/", row.index().to_string(), column.name);
+        let Some(entry) = row.find_node_at(&pointer) else {
+            continue;
+        };
+        let Some(ref value) = entry.value else {
+            continue;
+        };
+        match entry.pointer.value_type {
+            ValueType::Null => {}
+            ValueType::Array(_) | ValueType::Object(..) => {
+                match serde_json::from_str::<serde_json::Value>(value) {
+                    Ok(json) => lines.push_str(&json.to_string()),
+                    Err(_) => lines.push_str(value),
+                }
+            }
+            _ => lines.push_str(strip_non_finite_number_marker(value)),
+        }
+    }
+    lines
+}
+
+fn write_delimited_rows(
+    output: &mut String,
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    prefix: &str,
+    columns: &[Column],
+    separator: char,
+    line_ending: &str,
+) {
+    for row_index in rows {
+        let row = &previous_parse_result[*row_index];
+        let row_prefix = concat_string!(prefix, "/", row.index().to_string());
+        for (i, column) in columns.iter().enumerate() {
+            if i > 0 {
+                output.push(separator);
+            }
+            if column.name == ROW_NUMBER_COLUMN {
+                output.push_str(&row.index().to_string());
+                continue;
+            }
+            if let Some(entry) = row.find_node_at(&concat_string!(row_prefix, column.name)) {
+                if matches!(entry.pointer.value_type, ValueType::Null) {
+                    continue;
+                }
+                if let Some(ref value) = entry.value {
+                    let value = strip_non_finite_number_marker(value);
+                    output.push_str(&escape_field(value, separator));
+                }
+            }
+        }
+        output.push_str(line_ending);
+    }
+}
+
+/// Split text copied from a spreadsheet or a csv file into rows of cells.
+/// Return None when text is a single value, which should be pasted as is in one cell.
+/// Spreadsheets copy cells as tab separated values, lines without tab are read as csv only when
+/// they all have the same count of fields, otherwise each line is a single cell: a column copied
+/// from a spreadsheet can hold commas.
+pub fn parse_delimited_block(text: &str) -> Option<Vec<Vec<String>>> {
+    let text = text
+        .strip_suffix('\n')
+        .map(|t| t.strip_suffix('\r').unwrap_or(t))
+        .unwrap_or(text);
+    if text.contains('\t') {
+        return Some(split_delimited(text, '\t'));
+    }
+    if !text.contains('\n') {
+        return None;
+    }
+    let rows = split_delimited(text, ',');
+    let fields_count = rows[0].len();
+    if fields_count > 1 && rows.iter().all(|row| row.len() == fields_count) {
+        Some(rows)
+    } else {
+        Some(split_delimited(text, '\t'))
+    }
+}
+
+/// Split `text` into rows of fields separated by `separator`. Quoted fields (RFC 4180) can hold
+/// the separator, new lines and quotes doubled as `""`.
+fn split_delimited(text: &str, separator: char) -> Vec<Vec<String>> {
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c != '"' {
+                field.push(c);
+            } else if chars.peek() == Some(&'"') {
+                field.push('"');
+                chars.next();
+            } else {
+                in_quotes = false;
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == separator {
+            row.push(mem::take(&mut field));
+        } else if c == '\n' {
+            row.push(mem::take(&mut field));
+            rows.push(mem::take(&mut row));
+        } else if c != '\r' || chars.peek() != Some(&'\n') {
+            field.push(c);
+        }
+    }
+    row.push(field);
+    rows.push(row);
+    rows
+}
+
+/// Convert csv or tsv text to a json array, so it can be parsed like a json file. Each row is an
+/// object keyed by header names, or by `column_1`, `column_2`... without header, keys of missing
+/// trailing fields are left out. Columns whose non empty fields are all numbers are written as
+/// numbers, their empty fields as null, other columns are written as strings.
+pub fn csv_to_json(content: &str, has_header: bool, delimiter: char) -> Result<String, String> {
+    let content = content
+        .strip_suffix('\n')
+        .map(|c| c.strip_suffix('\r').unwrap_or(c))
+        .unwrap_or(content);
+    let mut rows = split_delimited(content, delimiter);
+    // Blank lines are not rows
+    rows.retain(|row| row.len() > 1 || !row[0].is_empty());
+    let header = if has_header && !rows.is_empty() {
+        rows.remove(0)
+    } else {
+        let len = rows.iter().map(Vec::len).max().unwrap_or(0);
+        (1..=len).map(|i| format!("column_{}", i)).collect()
+    };
+    // Keys of an object have to be unique, repeated names are suffixed
+    let mut keys: Vec<String> = Vec::with_capacity(header.len());
+    for name in header {
+        let mut key = name.clone();
+        let mut suffix = 2;
+        while keys.contains(&key) {
+            key = format!("{}_{}", name, suffix);
+            suffix += 1;
+        }
+        keys.push(key);
+    }
+    if let Some((i, row)) = rows
+        .iter()
+        .enumerate()
+        .find(|(_, row)| row.len() > keys.len())
+    {
+        return Err(format!(
+            "Row {} has {} fields, header has {}",
+            i + 1 + has_header as usize,
+            row.len(),
+            keys.len()
+        ));
+    }
+    let is_number = |field: &str| {
+        field.trim() == field && serde_json::from_str::<serde_json::Number>(field).is_ok()
+    };
+    let numeric_columns = (0..keys.len())
+        .map(|column| {
+            let mut fields = rows
+                .iter()
+                .filter_map(|row| row.get(column))
+                .filter(|field| !field.is_empty())
+                .peekable();
+            fields.peek().is_some() && fields.all(|field| is_number(field))
+        })
+        .collect::<Vec<bool>>();
+    let mut json = String::with_capacity(content.len() * 2);
+    json.push('[');
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push('{');
+        for (column, (key, field)) in keys.iter().zip(row).enumerate() {
+            if column > 0 {
+                json.push(',');
+            }
+            json.push_str(&serde_json::to_string(key).unwrap());
+            json.push(':');
+            if !numeric_columns[column] {
+                json.push_str(&serde_json::to_string(field).unwrap());
+            } else if field.is_empty() {
+                json.push_str("null");
+            } else {
+                json.push_str(field);
+            }
+        }
+        json.push('}');
+    }
+    json.push(']');
+    Ok(json)
+}
+
+pub fn csv_escape(value: &str) -> Cow<str> {
+    escape_field(value, ',')
+}
+
+fn escape_field(value: &str, separator: char) -> Cow<str> {
+    if value.contains([separator, '"', '\n', '\r']) {
+        Cow::from(concat_string!("\"", value.replace('"', "\"\""), "\""))
+    } else {
+        Cow::from(value)
+    }
+}
+
+/// Serialize edits as a JSON Patch (RFC 6902). Successive edits of a pointer are merged into a
+/// single operation, edits reverted to their original value are skipped. Inserted, removed and
+/// moved rows are added, removed and moved whole.
+pub fn json_patch<'a>(edits: impl Iterator<Item = &'a Edit>) -> String {
+    // First and last edits of merged edits
+    let mut merged_edits: Vec<(&Edit, &Edit)> = vec![];
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    for edit in edits {
+        if edit.row_change.is_some() {
+            // Following rows changed of index, edits after it are not merged with edits before
+            positions.clear();
+            merged_edits.push((edit, edit));
+        } else if let Some(position) = positions.get(edit.pointer.pointer.as_str()) {
+            merged_edits[*position].1 = edit;
+        } else {
+            positions.insert(edit.pointer.pointer.as_str(), merged_edits.len());
+            merged_edits.push((edit, edit));
+        }
+    }
+    let operations = merged_edits
+        .into_iter()
+        // A value changed of type only, e.g. string "42" to number 42, is replaced as well
+        .filter(|(first_edit, last_edit)| {
+            let old_value_type = first_edit
+                .old_value_type
+                .unwrap_or(first_edit.pointer.value_type);
+            first_edit.old_value != last_edit.new_value
+                || old_value_type != last_edit.pointer.value_type
+                || last_edit.row_change.is_some()
+        })
+        .map(|(first_edit, last_edit)| {
+            let pointer = &last_edit.pointer;
+            if let Some(moved_row_pointer) = last_edit.moved_row_pointer() {
+                return serde_json::json!({
+                    "op": "move",
+                    "from": pointer.pointer,
+                    "path": moved_row_pointer,
+                });
+            }
+            match &last_edit.new_value {
+                None => serde_json::json!({"op": "remove", "path": pointer.pointer}),
+                Some(value) => serde_json::json!({
+                    "op": if first_edit.old_value.is_none() { "add" } else { "replace" },
+                    "path": pointer.pointer,
+                    "value": patch_value(pointer.value_type, value),
+                }),
+            }
+        })
+        .collect::<Vec<serde_json::Value>>();
+    serde_json::to_string_pretty(&operations).unwrap()
+}
+
+fn patch_value(value_type: ValueType, value: &str) -> serde_json::Value {
+    if matches!(value_type, ValueType::String) {
+        return serde_json::Value::String(value.to_string());
+    }
+    serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_string()))
+}
+
+/// Entries to write in order to apply a JSON Patch (RFC 6902), a removal being an entry without value.
+/// Only "add", "replace" and "remove" operations are supported, column of entries is not resolved.
+pub fn parse_json_patch(patch: &str) -> Result<Vec<FlatJsonValue<String>>, String> {
+    let operations: Vec<serde_json::Value> =
+        serde_json::from_str(patch).map_err(|e| format!("Invalid JSON Patch: {}", e))?;
+    let mut entries = Vec::with_capacity(operations.len());
+    for operation in operations {
+        let op = operation.get("op").and_then(|op| op.as_str()).unwrap_or("");
+        let path = operation
+            .get("path")
+            .and_then(|path| path.as_str())
+            .ok_or_else(|| format!("Missing path in {}", operation))?;
+        let (value_type, value) = match op {
+            "remove" => (ValueType::Null, None),
+            "add" | "replace" => match operation.get("value") {
+                Some(serde_json::Value::String(value)) => (ValueType::String, Some(value.clone())),
+                Some(value) => {
+                    let value = value.to_string();
+                    (edited_value_type(ValueType::Null, &value), Some(value))
+                }
+                None => return Err(format!("Missing value for {} of {}", op, path)),
+            },
+            _ => return Err(format!("Unsupported patch operation {}", op)),
+        };
+        entries.push(FlatJsonValue {
+            pointer: PointerKey {
+                pointer: path.to_string(),
+                value_type,
+                depth: path.matches('/').count().saturating_sub(1) as u8,
+                position: 0,
+                column_id: 0,
+            },
+            value,
+        });
+    }
+    Ok(entries)
+}
+
+pub fn filter_columns(
+    previous_parse_result: &Vec<JsonArrayEntries<String>>,
+    prefix: &str,
+    filters: &HashMap<String, Vec<String>>,
+) -> Vec<usize> {
+    let mut res: Vec<usize> = Vec::with_capacity(previous_parse_result.len());
+    for row in previous_parse_result {
+        let mut should_add_row = true;
+        for (pointer, filters) in filters {
+            let pointer_to_find = concat_string!(prefix, "/", row.index().to_string(), pointer);
+            let mut filters_clone = Vec::with_capacity(filters.len());
+            let mut should_filter_by_non_null = false;
+            for filter in filters {
+                if filter.eq(NON_NULL_FILTER_VALUE) {
+                    should_filter_by_non_null = true;
+                } else {
+                    filters_clone.push(filter.clone());
+                }
+            }
+            if let Some(entry) = row.find_node_at(&pointer_to_find) {
+                if should_filter_by_non_null && entry.value.is_none() {
+                    should_add_row = false;
+                    break;
+                }
+                if !filters_clone.is_empty()
+                    && (entry.value.as_ref().is_none()
+                        || !filters_clone.contains(entry.value.as_ref().unwrap()))
+                {
+                    should_add_row = false;
+                    break;
+                }
+            } else {
+                should_add_row = false;
+                break;
+            }
+        }
+
+        if should_add_row {
+            res.push(row.index);
+        }
+    }
+    res
+}
+/// Sort `rows` (indexes of `previous_parse_result`) by value of `column`.
+/// Null and missing values are always at the end, whatever the sort order is.
+pub fn sort_rows(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &mut [usize],
+    prefix: &str,
+    column: &Column,
+    sort_order: SortOrder,
+) {
+    let mut rows_with_value = rows
+        .iter()
+        .map(|row_index| {
+            let row = &previous_parse_result[*row_index];
+            let pointer = concat_string!(prefix, "/", row.index().to_string(), column.name);
+            let value = row
+                .find_node_at(&pointer)
+                .filter(|entry| !matches!(entry.pointer.value_type, ValueType::Null))
+                .and_then(|entry| {
+                    entry
+                        .value
+                        .as_ref()
+                        .map(|value| (value.as_str(), entry.pointer.value_type))
+                });
+            (*row_index, value)
+        })
+        .collect::<Vec<(usize, Option<(&str, ValueType)>)>>();
+    rows_with_value.sort_by(|(_, a), (_, b)| match (a, b) {
+        (Some(a), Some(b)) => {
+            let ordering = compare_values(a, b);
+            match sort_order {
+                SortOrder::Ascending => ordering,
+                SortOrder::Descending => ordering.reverse(),
+            }
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    });
+    for (row, (row_index, _)) in rows.iter_mut().zip(rows_with_value) {
+        *row = row_index;
+    }
+}
+
+fn compare_values(
+    (value_a, value_type_a): &(&str, ValueType),
+    (value_b, value_type_b): &(&str, ValueType),
+) -> Ordering {
+    if matches!(value_type_a, ValueType::Number) && matches!(value_type_b, ValueType::Number) {
+        if let Some(ordering) = compare_numbers(value_a, value_b) {
+            return ordering;
+        }
+    }
+    value_a.cmp(value_b)
+}
+
+/// Compare numbers as lexed, integers are compared digit by digit so ids beyond 2^53 are not
+/// rounded by a conversion to f64. None when one of the values is not a number.
+pub fn compare_numbers(a: &str, b: &str) -> Option<Ordering> {
+    if is_integer(a) && is_integer(b) {
+        return Some(compare_integers(a, b));
+    }
+    let (a, b) = (a.parse::<f64>().ok()?, b.parse::<f64>().ok()?);
+    Some(a.partial_cmp(&b).unwrap_or(Ordering::Equal))
+}
+
+fn compare_integers(a: &str, b: &str) -> Ordering {
+    let split_sign = |number: &'_ str| {
+        let digits = number.strip_prefix('-').unwrap_or(number);
+        let digits = digits.trim_start_matches('0');
+        // -0 is 0
+        (number.starts_with('-') && !digits.is_empty(), digits)
+    };
+    let (negative_a, digits_a) = split_sign(a);
+    let (negative_b, digits_b) = split_sign(b);
+    let magnitude = digits_a
+        .len()
+        .cmp(&digits_b.len())
+        .then_with(|| digits_a.cmp(digits_b));
+    match (negative_a, negative_b) {
+        (false, false) => magnitude,
+        (true, true) => magnitude.reverse(),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    }
+}
+
+/// Value of `column` in `row`, column being a pointer relative to array elements like column names.
+pub fn column_value<'row>(
+    row: &'row JsonArrayEntries<String>,
+    prefix: &str,
+    column: &str,
+) -> Option<&'row str> {
+    let pointer = concat_string!(prefix, "/", row.index().to_string(), column);
+    row.find_node_at(&pointer)
+        .and_then(|entry| entry.value.as_deref())
+}
+
+/// Names of `columns` holding no value other than null in any of `rows`, keys missing from every
+/// row are empty as well
+pub fn empty_columns(
+    nodes: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    prefix: &str,
+    columns: &[Column],
+) -> BTreeSet<String> {
+    let mut non_empty_columns = HashSet::new();
+    for row_index in rows {
+        let row = &nodes[*row_index];
+        let row_prefix_len = prefix.len() + 1 + row.index().to_string().len();
+        for entry in row.entries.iter() {
+            if entry.value.is_none()
+                || matches!(entry.pointer.value_type, ValueType::Null)
+                || entry.pointer.pointer.len() <= row_prefix_len
+            {
+                continue;
+            }
+            non_empty_columns.insert(&entry.pointer.pointer[row_prefix_len..]);
+        }
+    }
+    columns
+        .iter()
+        .filter(|column| {
+            column.name != ROW_NUMBER_COLUMN
+                && !column.name.is_empty()
+                && !non_empty_columns.contains(column.name.as_ref())
+        })
+        .map(|column| column.name.to_string())
+        .collect()
+}
+
+/// Sum, min, max and mean of the numbers of a column
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Aggregates {
+    // Number of cells holding a number
+    pub count: usize,
+    // Number of cells not holding a number: missing, null or of another type
+    pub excluded_count: usize,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Aggregates {
+    pub fn mean(&self) -> f64 {
+        self.sum / self.count as f64
+    }
+}
+
+/// Aggregates of the numbers of `column` in `rows`, other values are only counted. `None` when
+/// column has no number in those rows.
+pub fn column_aggregates(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    prefix: &str,
+    column: &str,
+) -> Option<Aggregates> {
+    let mut aggregates: Option<Aggregates> = None;
+    let mut excluded_count = 0;
+    for row_index in rows {
+        let row = &previous_parse_result[*row_index];
+        let pointer = concat_string!(prefix, "/", row.index().to_string(), column);
+        let number = row
+            .find_node_at(&pointer)
+            .filter(|entry| matches!(entry.pointer.value_type, ValueType::Number))
+            .and_then(|entry| entry.value.as_deref())
+            .and_then(|value| value.parse::<f64>().ok());
+        match (number, aggregates.as_mut()) {
+            (None, _) => excluded_count += 1,
+            (Some(number), None) => {
+                aggregates = Some(Aggregates {
+                    count: 1,
+                    excluded_count: 0,
+                    sum: number,
+                    min: number,
+                    max: number,
+                })
+            }
+            (Some(number), Some(aggregates)) => {
+                aggregates.count += 1;
+                aggregates.sum += number;
+                aggregates.min = aggregates.min.min(number);
+                aggregates.max = aggregates.max.max(number);
+            }
+        }
+    }
+    aggregates.map(|aggregates| Aggregates {
+        excluded_count,
+        ..aggregates
+    })
+}
+
+/// Find position in `rows` of the array element containing `pointer`, with the remaining pointer after element index.
+pub fn find_row_by_pointer<'pointer>(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    prefix: &str,
+    pointer: &'pointer str,
+) -> Option<(usize, &'pointer str)> {
+    let relative_pointer = pointer.strip_prefix(prefix)?.strip_prefix('/')?;
+    let key_start = relative_pointer.find('/').unwrap_or(relative_pointer.len());
+    let index = relative_pointer[..key_start].parse::<usize>().ok()?;
+    let position = rows
+        .iter()
+        .position(|row_index| previous_parse_result[*row_index].index() == index)?;
+    Some((position, &relative_pointer[key_start..]))
+}
+
+/// Keep `rows` having at least one cell value containing `term`.
+pub fn filter_rows_by_term(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    term: &str,
+    case_sensitive: bool,
+) -> Vec<usize> {
+    let term = if case_sensitive {
+        Cow::from(term)
+    } else {
+        Cow::from(term.to_lowercase())
+    };
+    filter_rows_matching(previous_parse_result, rows, |value| {
+        if case_sensitive {
+            value.contains(term.as_ref())
+        } else {
+            value.to_lowercase().contains(term.as_ref())
+        }
+    })
+}
+
+/// Keep rows having at least one cell matching `pattern`.
+/// Return an error when `pattern` is not a valid regex.
+pub fn filter_rows_by_regex(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    pattern: &str,
+    case_sensitive: bool,
+) -> Result<Vec<usize>, String> {
+    let re = if case_sensitive {
+        Regex::new(pattern)
+    } else {
+        Regex::new(&format!("(?i){}", pattern))
+    }
+    .map_err(|error| error.to_string())?;
+    Ok(filter_rows_matching(previous_parse_result, rows, |value| {
+        re.is_match(value)
+    }))
+}
+
+fn filter_rows_matching(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    matches: impl Fn(&str) -> bool,
+) -> Vec<usize> {
+    rows.iter()
+        .filter(|row_index| {
+            previous_parse_result[**row_index]
+                .entries
+                .iter()
+                .any(|entry| {
+                    // Skip serialized nested values and row number entry
+                    if matches!(
+                        entry.pointer.value_type,
+                        ValueType::Object(..) | ValueType::Array(_)
+                    ) || entry.pointer.pointer.ends_with(ROW_NUMBER_COLUMN)
+                    {
+                        return false;
+                    }
+                    entry
+                        .value
+                        .as_deref()
+                        .map(strip_non_finite_number_marker)
+                        .is_some_and(&matches)
+                })
+        })
+        .copied()
+        .collect()
+}
+
+/// Keep rows matching a query such as `price > 100 AND status == "active"`.
+///
+/// Conditions compare a column, written as its name or as a pointer starting with `/`, to a
+/// number, a quoted string, `true`, `false` or `null` with `==`, `!=`, `<`, `<=`, `>`, `>=` or
+/// `contains`. They are combined with `AND`, `OR` and parentheses, `AND` binding tighter.
+/// A cell whose type does not match the value never satisfies a condition, except `!=`.
+pub fn filter_rows_by_query(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    rows: &[usize],
+    prefix: &str,
+    query: &str,
+    case_sensitive: bool,
+) -> Result<Vec<usize>, String> {
+    let tokens = tokenize_query(query)?;
+    let mut query_parser = QueryParser {
+        tokens: &tokens,
+        position: 0,
+    };
+    let expression = query_parser.parse_or()?;
+    if let Some((token, position)) = query_parser.tokens.get(query_parser.position) {
+        return Err(format!("Unexpected {} at position {}", token, position));
+    }
+    Ok(rows
+        .iter()
+        .filter(|row_index| {
+            expression.matches(&previous_parse_result[**row_index], prefix, case_sensitive)
+        })
+        .copied()
+        .collect())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum QueryOperator {
+    Equal,
+    NotEqual,
+    Lower,
+    LowerOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Contains,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum QueryToken {
+    Column(String),
+    Number(f64, String),
+    String(String),
+    Bool(bool),
+    Null,
+    Operator(QueryOperator),
+    And,
+    Or,
+    OpenParenthesis,
+    CloseParenthesis,
+}
+
+impl std::fmt::Display for QueryToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryToken::Column(column) => write!(f, "column `{}`", column),
+            QueryToken::Number(_, text) => write!(f, "number `{}`", text),
+            QueryToken::String(text) => write!(f, "string \"{}\"", text),
+            QueryToken::Bool(value) => write!(f, "`{}`", value),
+            QueryToken::Null => write!(f, "`null`"),
+            QueryToken::Operator(operator) => write!(f, "operator `{}`", operator.as_str()),
+            QueryToken::And => write!(f, "`AND`"),
+            QueryToken::Or => write!(f, "`OR`"),
+            QueryToken::OpenParenthesis => write!(f, "`(`"),
+            QueryToken::CloseParenthesis => write!(f, "`)`"),
+        }
+    }
+}
+
+impl QueryOperator {
+    fn as_str(&self) -> &'static str {
+        match self {
+            QueryOperator::Equal => "==",
+            QueryOperator::NotEqual => "!=",
+            QueryOperator::Lower => "<",
+            QueryOperator::LowerOrEqual => "<=",
+            QueryOperator::Greater => ">",
+            QueryOperator::GreaterOrEqual => ">=",
+            QueryOperator::Contains => "contains",
+        }
+    }
+
+    fn accepts(&self, ordering: Ordering) -> bool {
+        match self {
+            QueryOperator::Equal => ordering == Ordering::Equal,
+            QueryOperator::NotEqual => ordering != Ordering::Equal,
+            QueryOperator::Lower => ordering == Ordering::Less,
+            QueryOperator::LowerOrEqual => ordering != Ordering::Greater,
+            QueryOperator::Greater => ordering == Ordering::Greater,
+            QueryOperator::GreaterOrEqual => ordering != Ordering::Less,
+            QueryOperator::Contains => false,
+        }
+    }
+}
+
+/// Split a query into tokens with their character position, used in error messages
+fn tokenize_query(query: &str) -> Result<Vec<(QueryToken, usize)>, String> {
+    let chars = query.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let start = i;
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        let token = match c {
+            '(' => {
+                i += 1;
+                QueryToken::OpenParenthesis
+            }
+            ')' => {
+                i += 1;
+                QueryToken::CloseParenthesis
+            }
+            '"' | '\'' | '`' => {
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => {
+                            return Err(format!("Unterminated {} at position {}", c, start));
+                        }
+                        Some('\\') if c != '`' && i + 1 < chars.len() => {
+                            text.push(chars[i + 1]);
+                            i += 2;
+                        }
+                        Some(quote) if *quote == c => {
+                            i += 1;
+                            break;
+                        }
+                        Some(other) => {
+                            text.push(*other);
+                            i += 1;
+                        }
+                    }
+                }
+                if c == '`' {
+                    QueryToken::Column(concat_string!("/", text))
+                } else {
+                    QueryToken::String(text)
+                }
+            }
+            '=' | '!' | '<' | '>' => {
+                let double = chars.get(i + 1) == Some(&'=');
+                i += if double { 2 } else { 1 };
+                QueryToken::Operator(match (c, double) {
+                    ('=', _) => QueryOperator::Equal,
+                    ('!', true) => QueryOperator::NotEqual,
+                    ('<', false) => QueryOperator::Lower,
+                    ('<', true) => QueryOperator::LowerOrEqual,
+                    ('>', false) => QueryOperator::Greater,
+                    ('>', true) => QueryOperator::GreaterOrEqual,
+                    _ => return Err(format!("Expected `!=` at position {}", start)),
+                })
+            }
+            _ => {
+                while i < chars.len()
+                    && !chars[i].is_whitespace()
+                    && !matches!(
+                        chars[i],
+                        '(' | ')' | '"' | '\'' | '`' | '=' | '!' | '<' | '>'
+                    )
+                {
+                    i += 1;
+                }
+                let word = chars[start..i].iter().collect::<String>();
+                if c.is_ascii_digit() || c == '-' || c == '.' {
+                    match word.parse::<f64>() {
+                        Ok(number) => QueryToken::Number(number, word),
+                        Err(_) => {
+                            return Err(format!("Invalid number `{}` at position {}", word, start))
+                        }
+                    }
+                } else if word.eq_ignore_ascii_case("and") || word == "&&" {
+                    QueryToken::And
+                } else if word.eq_ignore_ascii_case("or") || word == "||" {
+                    QueryToken::Or
+                } else if word.eq_ignore_ascii_case("contains") {
+                    QueryToken::Operator(QueryOperator::Contains)
+                } else if word == "true" || word == "false" {
+                    QueryToken::Bool(word == "true")
+                } else if word == "null" {
+                    QueryToken::Null
+                } else if word.starts_with('/') {
+                    QueryToken::Column(word)
+                } else {
+                    QueryToken::Column(concat_string!("/", word))
+                }
+            }
+        };
+        tokens.push((token, start));
+    }
+    Ok(tokens)
+}
+
+enum QueryExpression {
+    And(Box<QueryExpression>, Box<QueryExpression>),
+    Or(Box<QueryExpression>, Box<QueryExpression>),
+    Condition {
+        column: String,
+        operator: QueryOperator,
+        value: QueryToken,
+    },
+}
+
+struct QueryParser<'tokens> {
+    tokens: &'tokens [(QueryToken, usize)],
+    position: usize,
+}
+
+impl QueryParser<'_> {
+    fn next(&mut self) -> Option<&(QueryToken, usize)> {
+        let token = self.tokens.get(self.position);
+        self.position += 1;
+        token
+    }
+
+    fn peek_is(&self, token: &QueryToken) -> bool {
+        self.tokens
+            .get(self.position)
+            .is_some_and(|(next, _)| next.eq(token))
+    }
+
+    fn parse_or(&mut self) -> Result<QueryExpression, String> {
+        let mut expression = self.parse_and()?;
+        while self.peek_is(&QueryToken::Or) {
+            self.position += 1;
+            expression = QueryExpression::Or(Box::new(expression), Box::new(self.parse_and()?));
+        }
+        Ok(expression)
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpression, String> {
+        let mut expression = self.parse_condition()?;
+        while self.peek_is(&QueryToken::And) {
+            self.position += 1;
+            expression =
+                QueryExpression::And(Box::new(expression), Box::new(self.parse_condition()?));
+        }
+        Ok(expression)
+    }
+
+    fn parse_condition(&mut self) -> Result<QueryExpression, String> {
+        let column = match self.next() {
+            Some((QueryToken::OpenParenthesis, position)) => {
+                let position = *position;
+                let expression = self.parse_or()?;
+                return match self.next() {
+                    Some((QueryToken::CloseParenthesis, _)) => Ok(expression),
+                    _ => Err(format!("Unclosed `(` at position {}", position)),
+                };
+            }
+            Some((QueryToken::Column(column), _)) => column.clone(),
+            Some((token, position)) => {
+                return Err(format!(
+                    "Expected a column at position {}, found {}",
+                    position, token
+                ))
+            }
+            None => return Err("Expected a column at end of query".to_string()),
+        };
+        let operator = match self.next() {
+            Some((QueryToken::Operator(operator), _)) => *operator,
+            Some((token, position)) => {
+                return Err(format!(
+                    "Expected an operator at position {}, found {}",
+                    position, token
+                ))
+            }
+            None => return Err("Expected an operator at end of query".to_string()),
+        };
+        let value = match self.next() {
+            Some((
+                value @ (QueryToken::Number(..)
+                | QueryToken::String(_)
+                | QueryToken::Bool(_)
+                | QueryToken::Null),
+                _,
+            )) => value.clone(),
+            Some((QueryToken::Column(column), position)) => {
+                return Err(format!(
+                    "Expected a value at position {}, quote text as \"{}\"",
+                    position,
+                    &column[1..]
+                ))
+            }
+            Some((token, position)) => {
+                return Err(format!(
+                    "Expected a value at position {}, found {}",
+                    position, token
+                ))
+            }
+            None => return Err("Expected a value at end of query".to_string()),
+        };
+        if operator == QueryOperator::Contains
+            && matches!(value, QueryToken::Bool(_) | QueryToken::Null)
+        {
+            return Err(format!(
+                "`contains` expects a string or a number, found {}",
+                value
+            ));
+        }
+        Ok(QueryExpression::Condition {
+            column,
+            operator,
+            value,
+        })
+    }
+}
+
+impl QueryExpression {
+    fn matches(&self, row: &JsonArrayEntries<String>, prefix: &str, case_sensitive: bool) -> bool {
+        match self {
+            QueryExpression::And(left, right) => {
+                left.matches(row, prefix, case_sensitive)
+                    && right.matches(row, prefix, case_sensitive)
+            }
+            QueryExpression::Or(left, right) => {
+                left.matches(row, prefix, case_sensitive)
+                    || right.matches(row, prefix, case_sensitive)
+            }
+            QueryExpression::Condition {
+                column,
+                operator,
+                value,
+            } => {
+                let pointer = concat_string!(prefix, "/", row.index().to_string(), column);
+                let cell = row
+                    .find_node_at(&pointer)
+                    .filter(|entry| !matches!(entry.pointer.value_type, ValueType::Null))
+                    .and_then(|entry| {
+                        entry
+                            .value
+                            .as_deref()
+                            .map(|cell_value| (entry.pointer.value_type, cell_value))
+                    });
+                condition_matches(cell, *operator, value, case_sensitive)
+            }
+        }
+    }
+}
+
+fn condition_matches(
+    cell: Option<(ValueType, &str)>,
+    operator: QueryOperator,
+    value: &QueryToken,
+    case_sensitive: bool,
+) -> bool {
+    let Some((value_type, cell_value)) = cell else {
+        // Missing and null cells only equal null
+        return match operator {
+            QueryOperator::Equal => matches!(value, QueryToken::Null),
+            QueryOperator::NotEqual => !matches!(value, QueryToken::Null),
+            _ => false,
+        };
+    };
+    if operator == QueryOperator::Contains {
+        let term = match value {
+            QueryToken::String(text) => text.as_str(),
+            QueryToken::Number(_, text) => text.as_str(),
+            _ => return false,
+        };
+        return if case_sensitive {
+            cell_value.contains(term)
+        } else {
+            cell_value.to_lowercase().contains(&term.to_lowercase())
+        };
+    }
+    let ordering = match (value, value_type) {
+        (QueryToken::Number(number, _), ValueType::Number | ValueType::String) => cell_value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(|cell_number| cell_number.partial_cmp(number)),
+        (QueryToken::String(text), ValueType::String | ValueType::Number) => {
+            Some(cell_value.cmp(text.as_str()))
+        }
+        (QueryToken::Bool(expected), ValueType::Bool) => Some((cell_value == "true").cmp(expected)),
+        _ => None,
+    };
+    match ordering {
+        Some(ordering) => operator.accepts(ordering),
+        // Type mismatch: the cell is different from the value but not comparable to it
+        None => operator == QueryOperator::NotEqual,
+    }
+}
+
+pub fn search_occurrences(
+    previous_parse_result: &[JsonArrayEntries<String>],
+    term: &str,
+) -> Vec<usize> {
+    let mut res: Vec<usize> = vec![];
+    for json_array_entry in previous_parse_result.iter() {
+        for entry in &json_array_entry.entries {
+            if !matches!(entry.pointer.value_type, ValueType::String) {
+                continue;
+            }
+            if let Some(ref value) = entry.value {
+                if value.to_lowercase().contains(term) {
+                    res.push(json_array_entry.index);
+                    break;
+                }
+            }
+        }
+    }
+    res
+}
+
+pub fn replace_occurrences(
+    previous_parse_result: &Vec<JsonArrayEntries<String>>,
+    search_replace_response: SearchReplaceResponse,
+) -> Vec<(FlatJsonValue<String>, usize)> {
+    let column_ids = if let Some(ref selected_columns) = search_replace_response.selected_column {
+        selected_columns
+            .iter()
+            .map(|c| c.id)
+            .collect::<Vec<usize>>()
+    } else {
+        vec![]
+    };
+    let mut new_values: Vec<(FlatJsonValue<String>, usize)> = vec![];
+    let search_criteria = search_replace_response.search_criteria.as_str();
+    let re = match search_replace_response.replace_mode {
+        ReplaceMode::MatchingCase => None,
+        ReplaceMode::Regex => Some(search_criteria.to_string()),
+        ReplaceMode::ExactWord => Some(format!(r"\b{}\b", regex_lite::escape(search_criteria))),
+        ReplaceMode::Simple => Some(format!("(?i){}", regex_lite::escape(search_criteria))),
+    }
+    .map(|pattern| {
+        if search_replace_response.whole_cell {
+            Regex::new(&format!("^(?:{})$", pattern)).unwrap()
+        } else {
+            Regex::new(&pattern).unwrap()
+        }
+    });
+    for json_array_entry in previous_parse_result.iter() {
+        for entry in json_array_entry.entries.iter() {
+            if column_ids.contains(&entry.pointer.column_id) {
+                if let Some(ref value) = entry.value {
+                    let new_value = if let Some(ref re) = re {
+                        replace_with_regex(&search_replace_response, value, re)
+                    } else if search_replace_response.whole_cell {
+                        if value.eq(search_criteria) {
+                            search_replace_response.replace_value.clone()
+                        } else {
+                            Some(value.clone())
+                        }
+                    } else if let Some(ref replace_value) = search_replace_response.replace_value {
+                        Some(value.replace(search_criteria, replace_value))
+                    } else if (search_criteria.is_empty() && value.is_empty())
+                        || (!search_criteria.is_empty() && value.contains(search_criteria))
+                    {
+                        None
+                    } else {
+                        Some(value.clone())
+                    };
+                    // Only cells whose value actually changes are returned
+                    if new_value.as_ref() != Some(value) {
+                        new_values.push((
+                            FlatJsonValue {
+                                pointer: entry.pointer.clone(),
+                                value: new_value,
+                            },
+                            json_array_entry.index,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    new_values
+}
+
+fn replace_with_regex(
+    search_replace_response: &SearchReplaceResponse,
+    value: &String,
+    re: &Regex,
+) -> Option<String> {
+    let new_value = if let Some(ref replace_value) = search_replace_response.replace_value {
+        Some(re.replace_all(value, replace_value.as_str()).to_string())
+    } else if (search_replace_response.search_criteria.is_empty() && value.is_empty())
+        || (!search_replace_response.search_criteria.is_empty() && re.is_match(value))
+    {
+        None
+    } else {
+        Some(value.clone())
+    };
+    new_value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        array_element_span, as_array, as_array_with_max_elements, as_array_with_progress,
+        check_not_empty, child_count, column_aggregates, column_to_lines, column_value,
+        compare_numbers, csv_escape, csv_to_json, decode_json_string, decode_utf8,
+        edited_value_type, escape_pointer_token, filter_rows_by_regex, filter_rows_by_term,
+        find_duplicate_pointers, find_row_by_pointer, format_json, is_float, is_integer,
+        is_pointer_prefix, jsonl_to_json_array, keep_non_finite_number_marker, normalize_keys,
+        parse_delimited_block, parse_keys_only, parse_lossy, pasted_json_document, pointer_depth,
+        quote_non_finite_numbers, replace_occurrences, row_number_entry, save_jsonl_to_buffer,
+        save_root_object_to_buffer, save_to_buffer, save_to_path, scan_document, snake_case_key,
+        sort_rows, strip_bom, strip_comments_and_trailing_commas, strip_non_finite_number_marker,
+        to_csv, to_tsv, unescape_pointer_token, wrap_root_object, yaml_to_json, Aggregates,
+        ParseStats, SerializeOptions, Truncation, LINE_ENDING, MAX_NESTING_DEPTH,
+        ROW_NUMBER_COLUMN,
+    };
+    use crate::array_table::{table_from_json, ArrayTable, Column, SortOrder};
+    use crate::object_table::ObjectTable;
+    use crate::panels::{ReplaceMode, SearchReplaceResponse};
+    use crate::ArrayResponse;
+    use json_flat_parser::{
+        FlatJsonValue, JSONParser, JsonArrayEntries, ParseOptions, ParseResult, PointerKey,
+        ValueType,
+    };
+    use std::borrow::Cow;
+    use std::cmp::Ordering;
+    use std::hash::{DefaultHasher, Hasher};
+
+    // as_array_with_progress before it read elements forward, kept to compare outputs
+    fn as_array_popping_from_end<'array>(
+        mut previous_parse_result: ParseResult<String>,
+        max_array_elements: Option<usize>,
+    ) -> Result<
+        (
+            Vec<JsonArrayEntries<String>>,
+            Vec<Column<'array>>,
+            Option<Truncation>,
+        ),
+        String,
+    > {
+        let (root_value, start_index, mut end_index) =
+            if previous_parse_result.started_parsing_at.is_some() {
+                let root_value = previous_parse_result.json
+                    [previous_parse_result.started_parsing_at_index_start]
+                    .clone();
+                (
+                    root_value,
+                    previous_parse_result.started_parsing_at_index_start,
+                    previous_parse_result.started_parsing_at_index_end,
+                )
+            } else {
+                (previous_parse_result.json[0].clone(), 0, 0)
+            };
+
+        if !matches!(root_value.pointer.value_type, ValueType::Array(_)) {
+            return Err("Parsed json root is not an array".to_string());
+        }
+        let mut root_array_len = match root_value.pointer.value_type {
+            ValueType::Array(root_array_len) => root_array_len,
+            _ => panic!(""),
+        };
+        if end_index == 0 {
+            end_index = previous_parse_result.json.len() - 1;
+        }
+        // When parsing started in a nested array, entries following that array are not part of any row.
+        // Rows are built by popping entries from the end, so those entries have to be dropped first.
+        previous_parse_result.json.truncate(end_index + 1);
+        if let Some(ref started_parsing_at) = previous_parse_result.started_parsing_at {
+            while end_index > start_index
+                && !is_pointer_prefix(
+                    started_parsing_at,
+                    &previous_parse_result.json[end_index].pointer.pointer,
+                )
+            {
+                previous_parse_result.json.pop();
+                end_index -= 1;
+            }
+        }
+        let mut truncation = None;
+        if let Some(max_array_elements) = max_array_elements.filter(|max| *max < root_array_len) {
+            let prefix =
+                if let Some(ref started_parsing_at) = previous_parse_result.started_parsing_at {
+                    started_parsing_at.as_str()
+                } else if let Some(ref prefix) = previous_parse_result.parsing_prefix {
+                    prefix.as_str()
+                } else {
+                    ""
+                };
+            let first_dropped_element = concat_string!(prefix, "/", max_array_elements.to_string());
+            // Elements are in document order, everything from the first dropped element can be removed
+            if let Some(cut_index) = (start_index + 1..=end_index).find(|k| {
+                is_pointer_prefix(
+                    &first_dropped_element,
+                    &previous_parse_result.json[*k].pointer.pointer,
+                )
+            }) {
+                previous_parse_result.json.truncate(cut_index);
+                end_index = cut_index - 1;
+            }
+            truncation = Some(Truncation {
+                kept: max_array_elements,
+                len: root_array_len,
+            });
+            root_array_len = max_array_elements;
+        }
+        let mut unique_keys: Vec<Column> = Vec::with_capacity(16);
+        let mut res: Vec<JsonArrayEntries<String>> = Vec::with_capacity(root_array_len);
+        let mut j = end_index;
+        let estimated_capacity = 16;
+        for i in (0..root_array_len).rev() {
+            let mut flat_json_values: Vec<FlatJsonValue<String>> =
+                Vec::with_capacity(estimated_capacity);
+            let mut is_first_entry = true;
+            let _i = i.to_string();
+            // Same prefix for every entry of the element, allocated once
+            let prefix =
+                if let Some(ref started_parsing_at) = previous_parse_result.started_parsing_at {
+                    concat_string!(started_parsing_at, "/", _i)
+                } else if let Some(ref prefix) = previous_parse_result.parsing_prefix {
+                    concat_string!(prefix, "/", _i)
+                } else {
+                    concat_string!("/", _i)
+                };
+            let prefix_len = prefix.len();
+            loop {
+                if !previous_parse_result.json.is_empty() {
+                    let entry = &mut previous_parse_result.json[j];
+                    let match_prefix = is_pointer_prefix(&prefix, &entry.pointer.pointer);
+
+                    if match_prefix {
+                        if !entry.pointer.pointer.is_empty() {
+                            if entry.pointer.pointer.len() < prefix_len {
+                                panic!("{} len is < {}", entry.pointer.pointer, prefix_len);
+                            }
+                            let key =
+                                &entry.pointer.pointer[prefix_len..entry.pointer.pointer.len()];
+                            // Key is only copied the first time it is seen, each element repeats same keys
+                            if let Some(existing_column) =
+                                unique_keys.iter_mut().find(|c| c.name.eq(key))
+                            {
+                                existing_column.seen_count += 1;
+                                if existing_column.value_type.eq(&ValueType::Null) {
+                                    existing_column.value_type = entry.pointer.value_type;
+                                }
+                                entry.pointer.column_id = existing_column.id;
+                            } else {
+                                let mut hasher = DefaultHasher::new();
+                                hasher.write(key.as_bytes());
+                                let column = Column {
+                                    name: Cow::from(key.to_string()),
+                                    depth: entry.pointer.depth,
+                                    value_type: entry.pointer.value_type,
+                                    seen_count: 1,
+                                    order: unique_keys.len(),
+                                    id: hasher.finish() as usize,
+                                };
+                                entry.pointer.column_id = column.id;
+                                unique_keys.push(column);
+                            }
+                        }
+                        if is_first_entry {
+                            is_first_entry = false;
+                            let prefix = &entry.pointer.pointer[0..prefix_len];
+                            flat_json_values.push(row_number_entry(
+                                i,
+                                entry.pointer.position,
+                                prefix,
+                            ));
+                        }
+                        let entry = previous_parse_result.json.pop().unwrap();
+                        flat_json_values.push(entry);
+                    } else {
+                        break;
+                    }
+                    if j == 0 {
+                        break;
+                    }
+                    j -= 1;
+                } else {
+                    break;
+                }
+            }
+            if !flat_json_values.is_empty() {
+                res.push(JsonArrayEntries::<String> {
+                    entries: flat_json_values,
+                    index: i,
+                });
+            }
+        }
+        // Rows are built from the last element. Entries of a row stay in reverse document order, with
+        // element root node last, serialization still write keys in their parsed order.
+        res.reverse();
+        unique_keys.sort();
+        Ok((res, unique_keys, truncation))
+    }
+
+    #[test]
+    fn test_replace() {
+        let json = r#"
+        {"skills": [
+        {
+          "description": "Cart Termination",
+          "duration2": 5000,
+          "element": "Weapon",
+          "damageType": "Single",
+          "hitCount": 1,
+          "id": 485,
+          "maxLevel": 10,
+          "name": "WS_CARTTERMINATION",
+          "range": -2,
+          "targetType": "Target",
+          "type": "Offensive",
+          "damageflags": {
+            "ignoreAtkCard": true
+          },
+          "flags": {
+            "ignoreAutoGuard": true,
+            "ignoreCicada": true
+          }
+        }
+    ]}"#;
+
+        let res = JSONParser::parse(
+            json,
+            ParseOptions::default()
+                .start_parse_at("/skills".to_string())
+                .parse_array(false),
+        )
+        .unwrap()
+        .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        let filter_column = columns
+            .iter()
+            .filter(|c| c.name.eq("/description"))
+            .cloned()
+            .collect::<Vec<Column>>();
+        let replaced_values = replace_occurrences(
+            &array,
+            SearchReplaceResponse {
+                search_criteria: "(.*)".to_string(),
+                replace_value: Some("A$1".to_string()),
+                selected_column: Some(filter_column),
+                replace_mode: ReplaceMode::Regex,
+                whole_cell: false,
+            },
+        );
+        assert_eq!(
+            replaced_values[0].0.value.as_ref().unwrap().as_str(),
+            "ACart Termination"
+        );
+    }
+
+    #[test]
+    fn test_jsonl_invalid_line_is_isolated() {
+        let jsonl = "{\"id\": 1, \"name\": \"a\"}\n{\"id\": 2, \"name\": \n\n{\"id\": 3, \"name\": \"c\"}\n";
+
+        let (json_array, invalid_lines) = jsonl_to_json_array(jsonl);
+        assert_eq!(invalid_lines.len(), 1);
+        assert_eq!(invalid_lines[0].0, 2);
+
+        let res = JSONParser::parse(json_array.as_str(), ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _) = as_array(res).unwrap();
+        assert_eq!(array.len(), 2);
+        assert_eq!(
+            array[1].find_node_at("/1/id").unwrap().value.as_ref().unwrap(),
+            "3"
+        );
+    }
+
+    #[test]
+    fn test_jsonl_save_keeps_keys_order_and_numbers() {
+        let json = "[{\"b\": 1.50, \"a\": [1e3, -0.0]},\n{\"z\": {\"y\": 10, \"x\": 2E-2}}]";
+        let table = table_from_json(json, 1);
+
+        let mut buffer: Vec<u8> = vec![];
+        save_jsonl_to_buffer(&table.nodes, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"b\":1.50,\"a\":[1e3,-0.0]}\n{\"z\":{\"y\":10,\"x\":2E-2}}\n"
+        );
+    }
+
+    #[test]
+    fn test_number_classification() {
+        assert!(is_integer("0"));
+        assert!(is_integer("-0"));
+        assert!(is_integer("100"));
+        assert!(!is_integer("3.14"));
+        assert!(!is_integer("6.022e23"));
+        assert!(!is_integer("-"));
+        assert!(is_float("3.14"));
+        assert!(is_float("6.022e23"));
+        assert!(!is_float("100"));
+        assert!(!is_float("abc"));
+    }
+
+    #[test]
+    fn test_unedited_array_round_trip_preserves_numbers() {
+        let line_ending = String::from_utf8(LINE_ENDING.to_vec()).unwrap();
+        let json = [
+            "{\"id\": 9007199254740993, \"ratio\": 1.0000}",
+            "{\"id\": 2, \"ratio\": 1e10}",
+            "{\"id\": -0, \"ratio\": 6.022E+23}",
+        ];
+        let json = format!("[{}]", json.join(line_ending.as_str()));
+
+        let res = JSONParser::parse(json.as_str(), ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _) = as_array(res).unwrap();
+        let mut buffer: Vec<u8> = vec![];
+        save_to_buffer("", &array, &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), json);
+    }
+
+    #[test]
+    fn test_sort_numbers_with_nulls() {
+        let json = r#"[{"a": 10}, {"a": null}, {"a": 2}, {"b": 1}, {"a": 33}, {"a": -1.5}]"#;
+        let res = JSONParser::parse(json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        let column = columns.iter().find(|c| c.name.eq("/a")).unwrap();
+
+        let mut rows = (0..array.len()).collect::<Vec<usize>>();
+        sort_rows(&array, &mut rows, "", column, SortOrder::Ascending);
+        assert_eq!(rows, vec![5, 2, 0, 4, 1, 3]);
+
+        let mut rows = (0..array.len()).collect::<Vec<usize>>();
+        sort_rows(&array, &mut rows, "", column, SortOrder::Descending);
+        assert_eq!(rows, vec![4, 0, 2, 5, 1, 3]);
+    }
+
+    #[test]
+    fn test_filter_rows_by_term() {
+        let json = r#"[{"name": "Foo", "n": 1}, {"name": "bar", "n": 12}, {"name": "FOOBAR"}]"#;
+        let res = JSONParser::parse(json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _) = as_array(res).unwrap();
+        let rows = (0..array.len()).collect::<Vec<usize>>();
+
+        assert_eq!(filter_rows_by_term(&array, &rows, "foo", false), vec![0, 2]);
+        assert_eq!(filter_rows_by_term(&array, &rows, "Foo", true), vec![0]);
+        // Row number of the third row is "2" but it is not a cell value
+        assert_eq!(filter_rows_by_term(&array, &rows, "2", false), vec![1]);
+        assert_eq!(filter_rows_by_term(&array, &[1, 2], "foo", false), vec![2]);
+        assert!(filter_rows_by_term(&array, &rows, "baz", false).is_empty());
+    }
+
+    #[test]
+    fn test_csv_escape() {
+        assert_eq!(csv_escape("plain"), "plain");
+        assert_eq!(csv_escape("x,y"), "\"x,y\"");
+        assert_eq!(csv_escape("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_escape("line1\nline2"), "\"line1\nline2\"");
+    }
+
+    #[test]
+    fn test_to_csv_with_missing_cells() {
+        let json = r#"[{"a": "x,y", "b": 1, "c": {"d": true}}, {"b": 2}, {"a": null, "c": [1, 2]}]"#;
+        let res = JSONParser::parse(
+            json,
+            ParseOptions::default().parse_array(false).max_depth(1),
+        )
+        .unwrap()
+        .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        let columns = ["/a", "/b", "/c"]
+            .iter()
+            .map(|name| columns.iter().find(|c| c.name.eq(name)).unwrap().clone())
+            .collect::<Vec<Column>>();
+        let rows = (0..array.len()).collect::<Vec<usize>>();
+
+        let csv = to_csv(&array, &rows, "", &columns);
+        let lines = csv.split("\r\n").collect::<Vec<&str>>();
+        assert_eq!(lines[0], "/a,/b,/c");
+        assert!(lines[1].starts_with("\"x,y\",1,"));
+        assert_eq!(lines[2], ",2,");
+        assert!(lines[3].starts_with(",,"));
+        assert_eq!(lines[4], "");
+    }
+
+    #[test]
+    fn test_to_csv_with_hash_key() {
+        let json = r##"[{"#": "x", "a": 1}, {"#": "y"}]"##;
+        let res = JSONParser::parse(json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        let hash_key = columns.iter().find(|c| c.name.eq("/#")).unwrap().clone();
+        let columns = vec![
+            Column::new(ROW_NUMBER_COLUMN.to_string(), ValueType::Number),
+            hash_key,
+        ];
+        let rows = (0..array.len()).collect::<Vec<usize>>();
+
+        // Key `#` is a column of its own, not the row number column
+        let csv = to_csv(&array, &rows, "", &columns);
+        assert_eq!(csv, "#,/#\r\n0,x\r\n1,y\r\n");
+        assert_eq!(to_tsv(&array, &rows, "", &columns), "0\tx\n1\ty");
+    }
+
+    #[test]
+    fn test_to_tsv_selection_with_missing_cell() {
+        let json = r#"[{"a": 1, "b": "x", "c": true}, {"a": 2, "c": false}, {"a": 3, "b": "z"}]"#;
+        let res = JSONParser::parse(json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        let columns = ["/a", "/b", "/c"]
+            .iter()
+            .map(|name| columns.iter().find(|c| c.name.eq(name)).unwrap().clone())
+            .collect::<Vec<Column>>();
+
+        let tsv = to_tsv(&array, &[0, 1], "", &columns);
+        assert_eq!(tsv, "1\tx\ttrue\n2\t\tfalse");
+    }
+
+    #[test]
+    fn test_parse_delimited_block() {
+        assert_eq!(parse_delimited_block("a, b"), None);
+        assert_eq!(
+            parse_delimited_block("1\t2\r\n3\t\r\n"),
+            Some(vec![
+                vec!["1".to_string(), "2".to_string()],
+                vec!["3".to_string(), "".to_string()]
+            ])
+        );
+        assert_eq!(
+            parse_delimited_block("\"x,\"\"y\"\"\",2\n3,4"),
+            Some(vec![
+                vec!["x,\"y\"".to_string(), "2".to_string()],
+                vec!["3".to_string(), "4".to_string()]
+            ])
+        );
+        // A single column copied from a spreadsheet has no tab
+        assert_eq!(
+            parse_delimited_block("Doe, John\nSmith\n1,5\n"),
+            Some(vec![
+                vec!["Doe, John".to_string()],
+                vec!["Smith".to_string()],
+                vec!["1,5".to_string()]
+            ])
+        );
+    }
+
+    #[test]
+    fn test_pointer_token_escaping() {
+        for key in ["a/b", "m~n", "~1", "/~/", "plain", ""] {
+            let token = escape_pointer_token(key);
+            assert!(!token.contains('/'));
+            assert_eq!(unescape_pointer_token(&token), key);
+        }
+        assert_eq!(escape_pointer_token("a/b~c"), "a~1b~0c");
+        assert_eq!(unescape_pointer_token("~01"), "~1");
+
+        let pointer = format!("/0/{}", escape_pointer_token("a/b"));
+        assert_eq!(pointer, "/0/a~1b");
+        assert!(!is_pointer_prefix("/0/a", &pointer));
+        assert!(is_pointer_prefix("/0/a~1b", &pointer));
+        assert!(is_pointer_prefix("/0", &pointer));
+        assert!(!is_pointer_prefix("/1", "/10/a"));
+    }
+
+    #[test]
+    fn test_find_row_by_pointer() {
+        let json = r#"[{"a": 1}, {"a": 2, "b": {"c": 3}}, {"a": 4}]"#;
+        let res = JSONParser::parse(json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _) = as_array(res).unwrap();
+
+        let rows = vec![0, 2];
+        assert_eq!(
+            find_row_by_pointer(&array, &rows, "", "/2/a"),
+            Some((1, "/a"))
+        );
+        assert_eq!(find_row_by_pointer(&array, &rows, "", "/0"), Some((0, "")));
+        // Row filtered out, out of bounds or not a row index
+        assert_eq!(find_row_by_pointer(&array, &rows, "", "/1/b/c"), None);
+        assert_eq!(find_row_by_pointer(&array, &rows, "", "/3/a"), None);
+        assert_eq!(find_row_by_pointer(&array, &rows, "", "/a"), None);
+
+        let rows = vec![0, 1, 2];
+        assert_eq!(
+            find_row_by_pointer(&array, &rows, "", "/1/b/c"),
+            Some((1, "/b/c"))
+        );
+    }
+
+    #[test]
+    fn test_strip_comments_and_trailing_commas() {
+        let commented = r#"{
+            // server configuration
+            "url": "http://localhost:8080", /* not a // comment */
+            "paths": ["/a", "/*b*/",],
+            "retry": { "count": 3, /* max */ },
+        }"#;
+        let stripped = r#"{
+            "url": "http://localhost:8080",
+            "paths": ["/a", "/*b*/"],
+            "retry": { "count": 3 }
+        }"#;
+        let content = strip_comments_and_trailing_commas(commented);
+        assert_eq!(content.lines().count(), commented.lines().count());
+
+        let flat = |json: &str| {
+            JSONParser::parse(json, ParseOptions::default())
+                .unwrap()
+                .to_owned()
+                .json
+                .into_iter()
+                .filter(|entry| {
+                    !matches!(
+                        entry.pointer.value_type,
+                        ValueType::Object(..) | ValueType::Array(_)
+                    )
+                })
+                .map(|entry| (entry.pointer.pointer, entry.value))
+                .collect::<Vec<(String, Option<String>)>>()
+        };
+        assert_eq!(flat(&content), flat(stripped));
+        assert_eq!(strip_comments_and_trailing_commas(stripped), stripped);
+    }
+
+    #[test]
+    fn test_find_duplicate_pointers() {
+        let json = r#"[{"a": 1, "b": 2}, {"a": 1, "b": 2, "a": 3}, {"b": {"c": 1, "c": 2}}]"#;
+        let res = JSONParser::parse(
+            json,
+            ParseOptions::default()
+                .parse_array(false)
+                .max_depth(u8::MAX),
+        )
+        .unwrap()
+        .to_owned();
+        let (array, _) = as_array(res).unwrap();
+        assert_eq!(find_duplicate_pointers(&array), vec!["/1/a", "/2/b/c"]);
+    }
+
+    #[test]
+    fn test_scan_max_depth() {
+        let max_depth = |json: &[u8]| scan_document(json).max_depth();
+        assert_eq!(max_depth(b"1"), 0);
+        assert_eq!(max_depth(br#"[{"a": 1, "b": "c"}, {"a": 2}]"#), 2);
+        assert_eq!(
+            max_depth(br#"[{"a": {"b": [{"c": [1, [2]]}]}}, {"d": []}]"#),
+            7
+        );
+        // Braces in strings and escaped quotes are not counted
+        assert_eq!(max_depth(br#"[{"a": "{[{[", "b": "\"}}]]", "c": {}}]"#), 3);
+    }
+
+    #[test]
+    fn test_as_array_start_parse_at_nested_array() {
+        let json = r#"{"results": {"items": [{"id": 1}, {"id": 2, "tags": ["a"]}, {"id": 3}], "total": 3}, "page": 1}"#;
+        let res = JSONParser::parse(
+            json,
+            ParseOptions::default()
+                .start_parse_at("/results/items".to_string())
+                .parse_array(false),
+        )
+        .unwrap()
+        .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        assert_eq!(array.len(), 3);
+        for (i, row) in array.iter().enumerate() {
+            assert_eq!(row.index, i);
+            assert!(row
+                .entries
+                .iter()
+                .all(|entry| is_pointer_prefix("/results/items", &entry.pointer.pointer)));
+        }
+        assert_eq!(
+            array[2]
+                .find_node_at("/results/items/2/id")
+                .and_then(|entry| entry.value.clone()),
+            Some("3".to_string())
+        );
+        assert!(columns.iter().all(|c| !c.name.contains("total")));
+    }
+
+    #[test]
+    fn test_format_json() {
+        let json = r#"{"b": {"d": [1, 2.50, {}], "c": null}, "a": "x \" }", "e": []}"#;
+        let compact = SerializeOptions {
+            pretty: false,
+            indent: 0,
+            sort_keys: false,
+            escape_unicode: false,
+        };
+        assert_eq!(
+            format_json(json, &compact).unwrap(),
+            r#"{"b":{"d":[1,2.50,{}],"c":null},"a":"x \" }","e":[]}"#
+        );
+        let pretty = SerializeOptions::default();
+        assert_eq!(
+            format_json(json, &pretty).unwrap(),
+            r#"{
+  "b": {
+    "d": [
+      1,
+      2.50,
+      {}
+    ],
+    "c": null
+  },
+  "a": "x \" }",
+  "e": []
+}"#
+        );
+        let sorted = SerializeOptions {
+            sort_keys: true,
+            ..compact
+        };
+        assert_eq!(
+            format_json(json, &sorted).unwrap(),
+            r#"{"a":"x \" }","b":{"c":null,"d":[1,2.50,{}]},"e":[]}"#
+        );
+        assert!(format_json(r#"{"a" 1}"#, &pretty).is_err());
+    }
+
+    #[test]
+    fn test_array_element_span() {
+        let array = r#" [1, {"a": "],\"}"},[2, [3]] ,"x"]"#;
+        let element = |index| array_element_span(array, index).map(|span| &array[span]);
+        assert_eq!(element(0), Some("1"));
+        assert_eq!(element(1), Some(r#"{"a": "],\"}"}"#));
+        assert_eq!(element(2), Some("[2, [3]]"));
+        assert_eq!(element(3), Some(r#""x""#));
+        assert_eq!(element(4), None);
+        assert_eq!(array_element_span("[]", 0), None);
+        assert_eq!(array_element_span(r#"{"a": 1}"#, 0), None);
+    }
+
+    #[test]
+    fn test_serialize_array_element() {
+        let elements = (0..10_000)
+            .map(|i| format!(r#"{{"id": {}, "name": "n{}", "tags": ["a", "b"]}}"#, i, i))
+            .collect::<Vec<String>>();
+        let json = format!(r#"{{"items": [{}]}}"#, elements.join(", "));
+        let nodes = JSONParser::parse(json.as_str(), ParseOptions::default().parse_array(true))
+            .unwrap()
+            .to_owned()
+            .json;
+        let mut table = ObjectTable::new(nodes, String::new());
+        let row_index = |table: &ObjectTable, pointer: &str| {
+            table
+                .nodes
+                .iter()
+                .position(|node| node.pointer.pointer.eq(pointer))
+                .unwrap()
+        };
+        let edit = |table: &mut ObjectTable, pointer: &str, value: &str| {
+            let row_index = row_index(table, pointer);
+            let updated_pointer = table.nodes[row_index].pointer.clone();
+            let mut array_response = ArrayResponse::default();
+            table.update_value(
+                &mut array_response,
+                updated_pointer,
+                value.to_string(),
+                row_index,
+            );
+            array_response.edited_value.pop().unwrap().value.unwrap()
+        };
+        let as_value = |json: &str| serde_json::from_str::<serde_json::Value>(json).unwrap();
+        // First edit serializes the whole array, next ones only the edited element
+        edit(&mut table, "/items/10/id", "-1");
+        let updated_array = edit(&mut table, "/items/5000/name", "edited");
+
+        let full = table.serialize_array(0);
+        let element = table.serialize_array_element(0, "/items/5000/name");
+        assert_eq!(as_value(&element.unwrap()), as_value(&full));
+        assert_eq!(as_value(&updated_array), as_value(&full));
+        assert_eq!(as_value(&full)[10]["id"], -1);
+        assert_eq!(as_value(&full)[5000]["name"], "edited");
+    }
+
+    #[test]
+    fn test_big_integers() {
+        assert_eq!(
+            compare_numbers("9007199254740993", "9007199254740992"),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(
+            compare_numbers("-9007199254740993", "-9007199254740992"),
+            Some(Ordering::Less)
+        );
+        assert_eq!(compare_numbers("-0", "0"), Some(Ordering::Equal));
+        assert_eq!(compare_numbers("-1", "0.5"), Some(Ordering::Less));
+        assert_eq!(compare_numbers("1", "a"), None);
+
+        // Editing another cell keeps a 19 digits id as it is
+        let json = r#"[{"id": 1234567890123456789, "name": "a"}, {"id": 1234567890123456788, "name": "b"}]"#;
+        let mut table = table_from_json(json, 1);
+        let name_column = table
+            .columns(false)
+            .iter()
+            .find(|c| c.name.eq("/name"))
+            .cloned()
+            .unwrap();
+        let entry = FlatJsonValue {
+            pointer: PointerKey {
+                pointer: "/0/name".to_string(),
+                value_type: ValueType::String,
+                depth: name_column.depth,
+                position: 0,
+                column_id: name_column.id,
+            },
+            value: Some("edited".to_string()),
+        };
+        let mut array_response = ArrayResponse::default();
+        assert_eq!(
+            table.try_edit_cell(&mut array_response, entry, 0, false),
+            Ok(true)
+        );
+        let mut buffer: Vec<u8> = vec![];
+        save_to_buffer("", &table.nodes, &mut buffer).unwrap();
+        let saved = String::from_utf8(buffer).unwrap();
+        assert!(saved.contains("edited"));
+        assert!(saved.contains("1234567890123456789"));
+        assert!(saved.contains("1234567890123456788"));
+
+        let mut rows = vec![0, 1];
+        let id_column = table
+            .columns(false)
+            .iter()
+            .find(|c| c.name.eq("/id"))
+            .cloned()
+            .unwrap();
+        sort_rows(
+            &table.nodes,
+            &mut rows,
+            "",
+            &id_column,
+            SortOrder::Ascending,
+        );
+        assert_eq!(rows, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_as_array_max_elements() {
+        let json = format!(
+            "[{}]",
+            (0..10000)
+                .map(|i| format!(r#"{{"id": {}, "tags": [{}]}}"#, i, i))
+                .collect::<Vec<String>>()
+                .join(",")
+        );
+        let res = JSONParser::parse(json.as_str(), ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _, truncation) = as_array_with_max_elements(res, Some(100)).unwrap();
+        assert_eq!(array.len(), 100);
+        assert_eq!(
+            truncation,
+            Some(Truncation {
+                kept: 100,
+                len: 10000
+            })
+        );
+        assert_eq!(array[99].index, 99);
+        assert_eq!(
+            array[99].find_node_at("/99/id").unwrap().value,
+            Some("99".to_string())
+        );
+        assert_eq!(
+            array[10].find_node_at("/10/id").unwrap().value,
+            Some("10".to_string())
+        );
+        assert!(array[99].find_node_at("/100/id").is_none());
+
+        let res = JSONParser::parse(json.as_str(), ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _, truncation) = as_array_with_max_elements(res, Some(20000)).unwrap();
+        assert_eq!(array.len(), 10000);
+        assert_eq!(truncation, None);
+    }
+
+    #[test]
+    fn test_parse_keys_only() {
+        let json = r#"[
+            {"id": 1, "name": null, "tags": ["a", "b"], "address": {"city": "x", "geo": {"lat": 1.5}}},
+            {"id": 2, "name": "b", "extra": true, "address": {"city": "y", "zip": "123"}},
+            {"id": 3}
+        ]"#;
+        let res = JSONParser::parse(
+            json,
+            ParseOptions::default().parse_array(false).max_depth(3),
+        )
+        .unwrap()
+        .to_owned();
+        let (_, columns) = as_array(res).unwrap();
+        let names = |columns: &Vec<Column>| {
+            let mut names = columns
+                .iter()
+                .map(|c| c.name.to_string())
+                .collect::<Vec<String>>();
+            names.sort();
+            names
+        };
+
+        let keys = parse_keys_only(json.as_bytes(), None, 3).unwrap();
+        assert_eq!(names(&keys), names(&columns));
+        let id = keys.iter().find(|c| c.name.eq("/id")).unwrap();
+        assert_eq!((id.depth, id.seen_count), (1, 3));
+        let name = keys.iter().find(|c| c.name.eq("/name")).unwrap();
+        assert_eq!(name.value_type, ValueType::String);
+        let lat = keys.iter().find(|c| c.name.eq("/address/geo/lat")).unwrap();
+        assert_eq!(lat.depth, 3);
+
+        let keys = parse_keys_only(json.as_bytes(), None, 1).unwrap();
+        assert!(keys.iter().all(|c| c.depth <= 1));
+        assert!(keys.iter().any(|c| c.name.eq("/address")));
+
+        let nested = format!(r#"{{"count": 3, "items": {}}}"#, json);
+        let keys = parse_keys_only(nested.as_bytes(), Some("/items"), 4).unwrap();
+        assert_eq!(names(&keys), names(&columns));
+    }
 
     #[test]
-    fn test_replace() {
-        let json = r#"
-        {"skills": [
-        {
-          "description": "Cart Termination",
-          "duration2": 5000,
-          "element": "Weapon",
-          "damageType": "Single",
-          "hitCount": 1,
-          "id": 485,
-          "maxLevel": 10,
-          "name": "WS_CARTTERMINATION",
-          "range": -2,
-          "targetType": "Target",
-          "type": "Offensive",
-          "damageflags": {
-            "ignoreAtkCard": true
-          },
-          "flags": {
-            "ignoreAutoGuard": true,
-            "ignoreCicada": true
-          }
-        }
-    ]}"#;
+    fn test_child_count() {
+        let object_column = ValueType::Object(true, 0);
+        let array_column = ValueType::Array(0);
+        let nested_object =
+            edited_value_type(object_column, r#"{"a": 1, "b": {"c": 2, "d": [3]}}"#);
+        assert_eq!(nested_object, ValueType::Object(false, 2));
+        assert_eq!(child_count(&nested_object), Some(2));
+        let nested_array = edited_value_type(array_column, "[1, [2, 3], {\"a\": [4, 5]}, []]");
+        assert_eq!(child_count(&nested_array), Some(4));
+        assert_eq!(child_count(&edited_value_type(array_column, "[]")), Some(0));
+        assert_eq!(
+            edited_value_type(ValueType::Number, "12"),
+            ValueType::Number
+        );
+        assert_eq!(child_count(&ValueType::String), None);
+        // Not a valid object, it is kept as a not parsed object
+        assert_eq!(
+            edited_value_type(object_column, "{\"a\":"),
+            ValueType::Object(false, 0)
+        );
+
+        let json = r#"[{"a": 1}, {"a": 2}, {"a": 3}]"#;
+        let mut table = table_from_json(json, 1);
+        let mut array_response = ArrayResponse::default();
+        table.delete_row(&mut array_response, 1);
+        assert_eq!(child_count(&table.parent_pointer.value_type), Some(2));
+        table.insert_row(&mut array_response, 0);
+        table.insert_row(&mut array_response, 0);
+        assert_eq!(child_count(&table.parent_pointer.value_type), Some(4));
+        assert_eq!(table.nodes.len(), 4);
+    }
+
+    #[test]
+    fn test_as_array_progress() {
+        let json = format!("[{}]", vec![r#"{"a": 1, "b": [2]}"#; 10000].join(","));
+        let res = JSONParser::parse(&json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let mut reported = vec![];
+        let (array, _, _) =
+            as_array_with_progress(res, None, |fraction| reported.push(fraction)).unwrap();
+        assert_eq!(array.len(), 10000);
+        assert!(reported.len() > 2);
+        assert!(reported.windows(2).all(|w| w[0] <= w[1]));
+        assert!(reported[0] > 0.0);
+        assert_eq!(reported.last(), Some(&1.0));
+    }
+
+    #[test]
+    fn test_decode_utf8() {
+        assert_eq!(
+            decode_utf8(r#"{"a": "été"}"#.as_bytes().to_vec()),
+            Ok(r#"{"a": "été"}"#.to_string())
+        );
+        let mut content = br#"{"a": ""#.to_vec();
+        content.extend_from_slice(&[0xff, b'"', b'}']);
+        assert_eq!(
+            decode_utf8(content),
+            Err("Invalid utf-8 at byte 7".to_string())
+        );
+    }
+
+    #[test]
+    fn test_invalid_utf8_bytes() {
+        // Plain json is read as bytes, invalid sequences are found by the scan before parsing
+        let mut content = br#"[{"a": "caf"#.to_vec();
+        content.extend_from_slice(&[0xe9, b'"', b'}', b']']);
+        let scan = scan_document(&content);
+        assert_eq!(scan.invalid_utf8_at, Some(11));
+        assert_eq!(
+            scan.error(false),
+            Some("Invalid utf-8 at byte 11".to_string())
+        );
+        assert_eq!(
+            parse_keys_only(&content, None, 10).err(),
+            Some("Invalid utf-8 at byte 11".to_string())
+        );
+        // Truncated sequence at the end of the document
+        assert_eq!(scan_document(&[b'"', 0xc3]).invalid_utf8_at, Some(1));
+        // Chars of 2, 3 and 4 bytes are valid
+        let scan = scan_document(r#"["é€😀", {"ü": ["日本"]}]"#.as_bytes());
+        assert_eq!(scan.error(true), None);
+        assert_eq!(scan.stats.strings, 2);
+        assert_eq!(scan.stats.keys, 1);
+    }
+
+    #[test]
+    fn test_decode_json_string_surrogate_pair() {
+        assert_eq!(
+            decode_json_string(r#"smile \uD83D\uDE00 \u00e9\n"#),
+            Ok("smile \u{1F600} é\n".to_string())
+        );
+        assert_eq!(
+            decode_json_string(r#"a\uD83Db"#),
+            Err("Unpaired surrogate \\uD83D at position 1".to_string())
+        );
+        assert_eq!(
+            decode_json_string(r#"\uDE00"#),
+            Err("Unpaired surrogate \\uDE00 at position 0".to_string())
+        );
+        assert!(decode_json_string(r#"\uD83D\u0041"#).is_err());
+        assert!(decode_json_string(r#"\u12"#).is_err());
+
+        let escaped = SerializeOptions {
+            pretty: false,
+            escape_unicode: true,
+            ..SerializeOptions::default()
+        };
+        assert_eq!(
+            format_json("{\"é\": \"\u{1F600}\"}", &escaped).unwrap(),
+            r#"{"\u00e9":"\ud83d\ude00"}"#
+        );
+        assert_eq!(
+            format_json("[\"\u{1F600}\"]", &SerializeOptions::default()).unwrap(),
+            "[\n  \"\u{1F600}\"\n]"
+        );
+    }
 
+    #[test]
+    fn test_column_value() {
+        let json = r#"{"items": [{"a": 1, "b": {"c": "x"}}, {"a": 2}]}"#;
         let res = JSONParser::parse(
             json,
             ParseOptions::default()
-                .start_parse_at("/skills".to_string())
-                .parse_array(false),
+                .parse_array(false)
+                .start_parse_at("/items".to_string()),
         )
         .unwrap()
         .to_owned();
+        let (array, _) = as_array(res).unwrap();
+        assert_eq!(column_value(&array[0], "/items", "/a"), Some("1"));
+        assert_eq!(column_value(&array[0], "/items", "/b/c"), Some("x"));
+        assert_eq!(column_value(&array[1], "/items", "/b/c"), None);
+        assert_eq!(column_value(&array[1], "/items", "/missing"), None);
+    }
+
+    #[test]
+    fn test_yaml_same_flat_structure_as_json() {
+        let yaml = r#"
+defaults: &defaults
+  retries: 3
+  verbose: false
+services:
+  - name: "api"
+    <<: *defaults
+    ports: [80, 443]
+  - name: worker
+    settings: *defaults
+    timeout: 1.5
+    owner: ~
+"#;
+        let json = r#"{"defaults": {"retries": 3, "verbose": false}, "services": [
+            {"name": "api", "ports": [80, 443], "retries": 3, "verbose": false},
+            {"name": "worker", "settings": {"retries": 3, "verbose": false}, "timeout": 1.5, "owner": null}
+        ]}"#;
+        let flat = |result: ParseResult<String>| {
+            result
+                .json
+                .into_iter()
+                .map(|entry| {
+                    let value = if matches!(
+                        entry.pointer.value_type,
+                        ValueType::Array(_) | ValueType::Object(..)
+                    ) {
+                        None
+                    } else {
+                        entry.value
+                    };
+                    (entry.pointer.pointer, entry.pointer.value_type, value)
+                })
+                .collect::<Vec<(String, ValueType, Option<String>)>>()
+        };
+        let yaml_json = yaml_to_json(yaml).unwrap();
+        let mut yaml_entries = flat(
+            JSONParser::parse(yaml_json.as_str(), ParseOptions::default())
+                .unwrap()
+                .to_owned(),
+        );
+        let mut json_entries = flat(
+            JSONParser::parse(json, ParseOptions::default())
+                .unwrap()
+                .to_owned(),
+        );
+        // Merged keys come after keys of the mapping
+        yaml_entries.sort_by(|a, b| a.0.cmp(&b.0));
+        json_entries.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(yaml_entries, json_entries);
+        assert!(yaml_to_json("a: .nan").is_err());
+    }
+
+    #[test]
+    fn test_wrap_root_object() {
+        let json = r#" {"a": 1, "b": {"c": [1, 2]}, "d": "text"}"#;
+        assert_eq!(wrap_root_object(br#"[{"a": 1}]"#), None);
+        let wrapped_json = wrap_root_object(json.as_bytes()).unwrap();
+        let res =
+            JSONParser::parse_bytes(&wrapped_json, ParseOptions::default().parse_array(false))
+                .unwrap()
+                .to_owned();
         let (array, columns) = as_array(res).unwrap();
-        let filter_column = columns
+        assert_eq!(array.len(), 1);
+        let depth = columns[0].depth;
+        let table = ArrayTable::new(
+            None,
+            array,
+            columns,
+            depth,
+            PointerKey::from_pointer("".to_string(), ValueType::Array(1), 0, 0),
+        );
+        assert!(table.all_columns().iter().any(|c| c.name.eq("/d")));
+
+        let mut buffer = vec![];
+        save_root_object_to_buffer(table.nodes(), &mut buffer).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&buffer).unwrap(),
+            serde_json::from_str::<serde_json::Value>(json).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pasted_json_document() {
+        assert!(pasted_json_document("not json", true).is_err());
+        // Invalid json is rejected before anything is opened
+        assert!(pasted_json_document(r#"[{"a": 1}, {"a": "#, true).is_err());
+        assert!(pasted_json_document("[\"a\nb\"]", true).is_err());
+        let (json, wrapped) = pasted_json_document("\n [{\"a\": 1}, {\"a\": 2}] \n", true).unwrap();
+        assert!(!wrapped);
+        assert_eq!(json, br#"[{"a": 1}, {"a": 2}]"#);
+
+        let (json, wrapped) = pasted_json_document(r#"{"a": {"b": true}}"#, true).unwrap();
+        assert!(wrapped);
+        let res = JSONParser::parse_bytes(&json, ParseOptions::default())
+            .unwrap()
+            .to_owned();
+        let entries = res
+            .json
             .iter()
-            .filter(|c| c.name.eq("/description"))
-            .cloned()
-            .collect::<Vec<Column>>();
-        let replaced_values = replace_occurrences(
-            &array,
-            SearchReplaceResponse {
-                search_criteria: "(.*)".to_string(),
-                replace_value: Some("A$1".to_string()),
-                selected_column: Some(filter_column),
-                replace_mode: ReplaceMode::Regex,
-            },
+            .map(|entry| (entry.pointer.pointer.as_str(), entry.pointer.value_type))
+            .collect::<Vec<(&str, ValueType)>>();
+        assert!(entries.contains(&("", ValueType::Array(1))));
+        assert!(entries.contains(&("/0/a/b", ValueType::Bool)));
+    }
+
+    #[test]
+    fn test_bom_and_crlf_documents() {
+        let json = "[{\"a\": 1},\n{\"a\": 2}]";
+        let with_bom = [&[0xEF, 0xBB, 0xBF], json.as_bytes()].concat();
+        assert_eq!(strip_bom(&with_bom), json.as_bytes());
+        assert_eq!(strip_bom(json.as_bytes()), json.as_bytes());
+        let res = JSONParser::parse_bytes(
+            strip_bom(&with_bom),
+            ParseOptions::default().parse_array(false),
+        )
+        .unwrap()
+        .to_owned();
+        assert_eq!(as_array(res).unwrap().0.len(), 2);
+        let object_with_bom = [&[0xEF, 0xBB, 0xBF], br#"{"a": 1}"#.as_slice()].concat();
+        assert_eq!(
+            wrap_root_object(&object_with_bom).unwrap(),
+            br#"[{"a": 1}]"#
+        );
+
+        let crlf_json = "[\r\n\t{\"a\": 1},\r\n\t{\"a\": [true,\r\nnull]}\r\n]\r\n";
+        let res = JSONParser::parse(crlf_json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        assert_eq!(as_array(res).unwrap().0.len(), 2);
+        let options = SerializeOptions::default();
+        assert_eq!(
+            format_json(crlf_json, &options),
+            format_json(&crlf_json.replace("\r\n", "\n"), &options)
         );
         assert_eq!(
-            replaced_values[0].0.value.as_ref().unwrap().as_str(),
-            "ACart Termination"
+            array_element_span(crlf_json, 1).map(|span| &crlf_json[span]),
+            Some("{\"a\": [true,\r\nnull]}")
+        );
+    }
+
+    #[test]
+    fn test_column_aggregates() {
+        let json = r#"[{"a": 4}, {"a": null}, {"a": -1.5}, {"b": 1}, {"a": "7"}, {"a": 10}]"#;
+        let res = JSONParser::parse(json, ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _) = as_array(res).unwrap();
+        let all_rows = (0..array.len()).collect::<Vec<usize>>();
+        let aggregates = column_aggregates(&array, &all_rows, "", "/a").unwrap();
+        assert_eq!(
+            aggregates,
+            Aggregates {
+                count: 3,
+                excluded_count: 3,
+                sum: 12.5,
+                min: -1.5,
+                max: 10.0,
+            }
+        );
+        assert_eq!(aggregates.mean(), 12.5 / 3.0);
+        // Only filtered rows are aggregated
+        let aggregates = column_aggregates(&array, &[1, 2, 3], "", "/a").unwrap();
+        assert_eq!((aggregates.count, aggregates.excluded_count), (1, 2));
+        assert_eq!(aggregates.sum, -1.5);
+        assert_eq!(column_aggregates(&array, &[1, 3, 4], "", "/a"), None);
+    }
+
+    #[test]
+    fn test_save_to_path() {
+        let directory =
+            std::env::temp_dir().join(format!("json-editor-save-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("data.json");
+        std::fs::write(&path, "[{\"a\": 1},\n{\"a\": 2}]\n").unwrap();
+        let json = std::fs::read_to_string(&path).unwrap();
+        let res = JSONParser::parse(json.as_str(), ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _) = as_array(res).unwrap();
+
+        save_to_path("", &array, &path).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.ends_with("]\n"));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&saved).unwrap(),
+            serde_json::json!([{"a": 1}, {"a": 2}])
+        );
+        // Only the saved file is left in the directory
+        assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 1);
+
+        let error = save_to_path("", &array, &directory.join("missing").join("data.json"));
+        assert!(error.unwrap_err().starts_with("Failed to save"));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_filter_rows_by_regex() {
+        let json = r#"[{"date": "2024-01-31"}, {"date": "31/01/2024"}, {"date": "2023-12-01", "note": "X"}, {"date": null}]"#;
+        let res = JSONParser::parse(
+            json,
+            ParseOptions::default().parse_array(false).max_depth(1),
+        )
+        .unwrap()
+        .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        let rows = (0..array.len()).collect::<Vec<usize>>();
+        assert_eq!(
+            filter_rows_by_regex(&array, &rows, r"^\d{4}-\d{2}-\d{2}$", true),
+            Ok(vec![0, 2])
+        );
+        assert_eq!(
+            filter_rows_by_regex(&array, &rows, "^x$", false),
+            Ok(vec![2])
+        );
+        assert_eq!(filter_rows_by_regex(&array, &rows, "^x$", true), Ok(vec![]));
+        assert!(filter_rows_by_regex(&array, &rows, "(\\d{4}", true).is_err());
+
+        // Invalid pattern is reported and does not filter rows
+        let mut table = ArrayTable::new(
+            None,
+            array,
+            columns,
+            1,
+            PointerKey::from_pointer("".to_string(), ValueType::Array(4), 0, 0),
+        );
+        table.filter_rows_regex = true;
+        table.filter_rows = "[0-9".to_string();
+        table.do_filter_column();
+        assert!(table.filter_rows_error.is_some());
+        assert_eq!(table.filtered_nodes, vec![0, 1, 2, 3]);
+        table.filter_rows = "^[0-9]{2}/".to_string();
+        table.do_filter_column();
+        assert_eq!(table.filter_rows_error, None);
+        assert_eq!(table.filtered_nodes, vec![1]);
+    }
+
+    #[test]
+    fn test_lenient_non_finite_numbers() {
+        let json = r#"[{"a": NaN, "b": "NaN"}, {"a": Infinity, "b": 1}, {"a": -Infinity, "b": 2}]"#;
+        assert!(JSONParser::parse(json, ParseOptions::default().parse_array(false)).is_err());
+
+        let lenient = quote_non_finite_numbers(json);
+        let res = JSONParser::parse(lenient.as_str(), ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, _) = as_array(res).unwrap();
+        let values = array
+            .iter()
+            .map(|row| {
+                let entry = row
+                    .entries
+                    .iter()
+                    .find(|e| e.pointer.pointer.ends_with("/a"));
+                strip_non_finite_number_marker(entry.unwrap().value.as_ref().unwrap()).to_string()
+            })
+            .collect::<Vec<String>>();
+        assert_eq!(values, vec!["NaN", "Infinity", "-Infinity"]);
+
+        let mut buffer: Vec<u8> = vec![];
+        save_to_buffer("", &array, &mut buffer).unwrap();
+        let line_ending = String::from_utf8(LINE_ENDING.to_vec()).unwrap();
+        let saved = String::from_utf8(buffer).unwrap();
+        assert_eq!(saved.replace(line_ending.as_str(), ", "), json);
+    }
+
+    #[test]
+    fn test_non_finite_number_marker_stays_internal() {
+        let json = quote_non_finite_numbers(r#"[{"a": NaN}, {"a": 1}]"#);
+        let table = table_from_json(&json, 1);
+        let columns = table.columns(false).clone();
+        let a = columns.iter().find(|column| column.name == "/a").unwrap();
+        let rows = [0, 1];
+        assert_eq!(
+            to_csv(&table.nodes, &rows, "", std::slice::from_ref(a)),
+            "/a\r\nNaN\r\n1\r\n"
+        );
+        assert_eq!(
+            to_tsv(&table.nodes, &rows, "", std::slice::from_ref(a)),
+            "NaN\n1"
+        );
+        assert_eq!(column_to_lines(&table.nodes, &rows, "", a), "NaN\n1");
+        assert_eq!(
+            filter_rows_by_regex(&table.nodes, &rows, "^NaN$", true),
+            Ok(vec![0])
+        );
+        let mut buffer: Vec<u8> = vec![];
+        save_jsonl_to_buffer(&table.nodes, &mut buffer).unwrap();
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "{\"a\":NaN}\n{\"a\":1}\n"
+        );
+
+        // Edited without its marker, the literal stays a literal unless it is changed
+        let value = table.nodes[0]
+            .find_node_at("/0/a")
+            .unwrap()
+            .value
+            .as_deref();
+        assert_eq!(
+            keep_non_finite_number_marker(value, "NaN".to_string()),
+            value.unwrap()
+        );
+        assert_eq!(
+            keep_non_finite_number_marker(value, "Infinity".to_string()),
+            "Infinity"
+        );
+        assert_eq!(
+            keep_non_finite_number_marker(Some("NaN"), "NaN".to_string()),
+            "NaN"
+        );
+    }
+
+    #[test]
+    fn test_parse_lossy_truncated_array() {
+        let json = r#"[{"id": 1, "tags": ["a"]}, {"id": 2, "tags": []}, {"id": 3, "tags": ["b", "#;
+        let options = ParseOptions::default().parse_array(false).max_depth(2);
+        assert!(JSONParser::parse(json, options.clone()).is_err());
+
+        let (result, errors) = parse_lossy(json, options.clone()).unwrap();
+        assert_eq!(errors.len(), 1);
+        let expected = JSONParser::parse(
+            r#"[{"id": 1, "tags": ["a"]}, {"id": 2, "tags": []}]"#,
+            options.clone(),
+        )
+        .unwrap()
+        .to_owned();
+        let pointers = |result: &ParseResult<String>| {
+            result
+                .json
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.pointer.pointer.clone(),
+                        entry.pointer.value_type,
+                        entry.value.clone(),
+                    )
+                })
+                .collect::<Vec<(String, ValueType, Option<String>)>>()
+        };
+        assert_eq!(pointers(&result), pointers(&expected));
+        let (array, _) = as_array(result).unwrap();
+        assert_eq!(array.len(), 2);
+
+        let (_, errors) = parse_lossy(r#"[{"id": 1}]"#, options.clone()).unwrap();
+        assert!(errors.is_empty());
+        assert!(parse_lossy(r#"{"id": "#, options).is_err());
+    }
+
+    #[test]
+    fn test_normalize_keys() {
+        let json = r#"[{"userID": 1, "Name": "a"}, {"userid": 2, "name": "b"}, {"USERID": 3, "Address": {"City": "x", "Tags": ["A"]}}, {"id": 4, "ID": 5}, {"Meta": {"a": {"X": 1, "x": 2}}}]"#;
+        let mut res = JSONParser::parse(
+            json,
+            ParseOptions::default().parse_array(false).max_depth(2),
+        )
+        .unwrap()
+        .to_owned();
+        // Keys normalized to the name of another key of their object are kept as written
+        assert_eq!(
+            normalize_keys(&mut res, str::to_lowercase),
+            vec!["/3/ID".to_string(), "/4/Meta/a/X".to_string()]
+        );
+        let (array, columns) = as_array(res).unwrap();
+        let column = |name: &str| columns.iter().find(|c| c.name == name);
+        assert_eq!(column("/userid").unwrap().seen_count, 3);
+        assert_eq!(column("/name").unwrap().seen_count, 2);
+        assert!(column("/address/city").is_some());
+        assert!(column("/userID").is_none() && column("/Address/City").is_none());
+        assert_eq!(column_value(&array[2], "", "/userid"), Some("3"));
+        // Serialized values are normalized too, values are kept as written
+        let mut buffer = vec![];
+        save_to_buffer("", &array, &mut buffer).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&buffer).unwrap(),
+            serde_json::json!([{"userid": 1, "name": "a"}, {"userid": 2, "name": "b"}, {"userid": 3, "address": {"city": "x", "tags": ["A"]}}, {"id": 4, "ID": 5}, {"meta": {"a": {"X": 1, "x": 2}}}])
+        );
+        assert!(column("/ID").is_some());
+        assert!(find_duplicate_pointers(&array).is_empty());
+
+        assert_eq!(snake_case_key("userID"), "user_id");
+        assert_eq!(snake_case_key("UserId"), "user_id");
+        assert_eq!(snake_case_key("user-id"), "user_id");
+        assert_eq!(snake_case_key("user_id"), "user_id");
+        assert_eq!(snake_case_key("HTTPStatus2xx"), "http_status2xx");
+    }
+
+    #[test]
+    fn test_relative_depth() {
+        assert_eq!(pointer_depth(""), 0);
+        assert_eq!(pointer_depth("/results/items"), 2);
+        assert_eq!(pointer_depth("/a~1b"), 1);
+
+        let json = r#"{"results": {"items": [{"id": 1, "user": {"name": "a"}}]}}"#;
+        let parse = |start_parse_at: Option<&str>| {
+            let json = if start_parse_at.is_some() {
+                json.to_string()
+            } else {
+                r#"[{"id": 1, "user": {"name": "a"}}]"#.to_string()
+            };
+            let mut options = ParseOptions::default().parse_array(false);
+            if let Some(start_parse_at) = start_parse_at {
+                options = options.start_parse_at(start_parse_at.to_string());
+            }
+            let res = JSONParser::parse(&json, options).unwrap().to_owned();
+            as_array(res).unwrap().1
+        };
+        let depth =
+            |columns: &[Column], name: &str| columns.iter().find(|c| c.name == name).unwrap().depth;
+        let extracted = parse(Some("/results/items"));
+        let root = parse(None);
+        // Depths of the extracted subtree are counted from the document root
+        assert_eq!(depth(&extracted, "/id"), depth(&root, "/id") + 2);
+        assert_eq!(
+            depth(&extracted, "/user/name"),
+            depth(&root, "/user/name") + 2
+        );
+        // Relative to the start pointer they are the depths of the same array at root
+        for name in ["/id", "/user/name"] {
+            assert_eq!(
+                depth(&extracted, name) - pointer_depth("/results/items"),
+                depth(&root, name)
+            );
+        }
+    }
+
+    #[test]
+    fn test_csv_to_json() {
+        let csv =
+            "name,age,note\r\n\"Doe, John\",42,\"said \"\"hi\"\"\"\r\nJane,,\"two\nlines\"\r\n";
+        // Opened like any json document
+        let json = csv_to_json(csv, true, ',').unwrap();
+        let res = JSONParser::parse(json.as_str(), ParseOptions::default().parse_array(false))
+            .unwrap()
+            .to_owned();
+        let (array, columns) = as_array(res).unwrap();
+        assert_eq!(array.len(), 2);
+        let column_type = |name: &str| {
+            columns
+                .iter()
+                .find(|column| column.name == name)
+                .unwrap()
+                .value_type
+        };
+        assert!(matches!(column_type("/age"), ValueType::Number));
+        assert!(matches!(column_type("/name"), ValueType::String));
+        let value = |row_index: usize, pointer: &str| {
+            array[row_index]
+                .entries()
+                .iter()
+                .find(|entry| entry.pointer.pointer.eq(pointer))
+                .and_then(|entry| entry.value.clone())
+        };
+        assert_eq!(value(0, "/0/name"), Some("Doe, John".to_string()));
+        assert_eq!(value(0, "/0/note"), Some("said \"hi\"".to_string()));
+        assert_eq!(value(1, "/1/note"), Some("two\nlines".to_string()));
+        assert_eq!(value(0, "/0/age"), Some("42".to_string()));
+
+        // Without header, columns are numbered
+        let json = csv_to_json("1\tx\n2\t007", false, '\t').unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::json!([
+                {"column_1": 1, "column_2": "x"},
+                {"column_1": 2, "column_2": "007"}
+            ])
+        );
+        assert!(csv_to_json("a\n1,2", true, ',').is_err());
+    }
+
+    #[test]
+    fn test_as_array_same_as_popping_from_end() {
+        let documents = [
+            (r#"[]"#, None),
+            (r#"[{"a": 1}]"#, None),
+            (r#"[1, "two", null, [3], {"b": true}]"#, None),
+            (
+                r#"[{"a": 1, "b": {"c": null}}, {"b": {"c": "x"}, "d": [1, 2]}, {"a": null}, {}]"#,
+                None,
+            ),
+            (r#"[{"a": 1}, {"a": "x", "b": 2}, {"b": null}]"#, Some(2)),
+        ];
+        let columns_summary = |columns: &[Column]| {
+            columns
+                .iter()
+                .map(|c| {
+                    (
+                        c.name.to_string(),
+                        c.depth,
+                        c.value_type,
+                        c.seen_count,
+                        c.order,
+                        c.id,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let rows_summary = |rows: &[JsonArrayEntries<String>]| {
+            rows.iter()
+                .map(|row| {
+                    let entries = row
+                        .entries()
+                        .iter()
+                        .map(|e| {
+                            (
+                                e.pointer.pointer.clone(),
+                                e.pointer.value_type,
+                                e.pointer.depth,
+                                e.pointer.position,
+                                e.pointer.column_id,
+                                e.value.clone(),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    (row.index(), entries)
+                })
+                .collect::<Vec<_>>()
+        };
+        let parse = |json: &str, options: ParseOptions| {
+            JSONParser::parse(json, options.parse_array(false))
+                .unwrap()
+                .to_owned()
+        };
+        let nested =
+            r#"{"data": {"items": [{"id": 1, "tags": ["a"]}, {"id": 2}]}, "after": [{"id": 3}]}"#;
+        for (json, max_array_elements, options) in documents
+            .iter()
+            .map(|(json, max)| (*json, *max, ParseOptions::default()))
+            .chain([
+                (
+                    nested,
+                    None,
+                    ParseOptions::default().start_parse_at("/data/items".to_string()),
+                ),
+                (
+                    nested,
+                    Some(1),
+                    ParseOptions::default().start_parse_at("/data/items".to_string()),
+                ),
+                (
+                    r#"[{"a": {"b": {"c": 1}}}, {"a": 2}]"#,
+                    None,
+                    ParseOptions::default().max_depth(1),
+                ),
+            ])
+        {
+            let (rows, columns, truncation) =
+                as_array_with_progress(parse(json, options.clone()), max_array_elements, |_| {})
+                    .unwrap();
+            let (expected_rows, expected_columns, expected_truncation) =
+                as_array_popping_from_end(parse(json, options), max_array_elements).unwrap();
+            assert_eq!(
+                rows_summary(&rows),
+                rows_summary(&expected_rows),
+                "{}",
+                json
+            );
+            assert_eq!(
+                columns_summary(&columns),
+                columns_summary(&expected_columns),
+                "{}",
+                json
+            );
+            assert_eq!(
+                truncation.map(|t| (t.kept, t.len)),
+                expected_truncation.map(|t| (t.kept, t.len))
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_document() {
+        for json in ["", " ", " \n\t\r\n"] {
+            assert_eq!(
+                check_not_empty(json.as_bytes()),
+                Err("Empty document".to_string())
+            );
+            assert_eq!(
+                parse_lossy(json, ParseOptions::default()).err(),
+                Some("Empty document".to_string())
+            );
+        }
+        assert_eq!(check_not_empty(b" [] "), Ok(()));
+        assert_eq!(
+            scan_document(b" \n ").error(true),
+            Some("Empty document".to_string())
+        );
+        assert!(!scan_document(b" [] ").is_empty());
+        let parse = |json: &str| {
+            JSONParser::parse(json, ParseOptions::default().parse_array(false))
+                .unwrap()
+                .to_owned()
+        };
+        let (array, columns) = as_array(parse(" [] ")).unwrap();
+        assert!(array.is_empty());
+        assert!(columns.is_empty());
+        let mut res = parse("[]");
+        // No entry at all, e.g. parse result of an empty document
+        res.json.clear();
+        assert_eq!(as_array(res).err(), Some("Empty document".to_string()));
+    }
+
+    #[test]
+    fn test_parse_stats() {
+        let json = r#"{"a": [1, 2.5, "x"], "b": {"c": null, "d": true}, "e": "y"}"#;
+        assert_eq!(
+            scan_document(json.as_bytes()).stats,
+            ParseStats {
+                tokens: 27,
+                objects: 2,
+                arrays: 1,
+                keys: 5,
+                strings: 2,
+                numbers: 2,
+                bools: 1,
+                nulls: 1,
+                max_depth: 2,
+            }
+        );
+        // Quotes, brackets and colons in strings are not tokens
+        let stats = scan_document(br#" [ "a\"] :" , -1.5e-3,false ] "#).stats;
+        assert_eq!(stats.tokens, 7);
+        assert_eq!(stats.strings, 1);
+        assert_eq!(stats.keys, 0);
+        assert_eq!(stats.numbers, 1);
+        assert_eq!(stats.bools, 1);
+        assert_eq!(stats.arrays, 1);
+        assert_eq!(stats.max_depth, 1);
+        assert_eq!(stats.values(), 4);
+        assert_eq!(scan_document(b"").stats, ParseStats::default());
+        // Bytes which are not json are not counted as numbers
+        let stats = scan_document(b"[x, 1]").stats;
+        assert_eq!(stats.numbers, 1);
+        assert_eq!(stats.values(), 2);
+    }
+
+    #[test]
+    fn test_decode_json_string_escapes() {
+        assert_eq!(
+            decode_json_string(r#"\" \\ \/ \b \f \n \r \t Aé"#),
+            Ok("\" \\ / \u{8} \u{c} \n \r \t Aé".to_string())
+        );
+        assert_eq!(
+            decode_json_string("tab\there"),
+            Err("Unescaped control character U+0009 at position 3".to_string())
+        );
+        assert_eq!(scan_document(br#"{"a\tb": "c\n"}"#).error(true), None);
+        let scan = scan_document(b"[\"line\nbreak\"]");
+        assert_eq!(
+            scan.error(true),
+            Some("Unescaped control character U+000A at position 6".to_string())
+        );
+        // Accepted when parsing leniently
+        assert_eq!(scan.error(false), None);
+        // Whitespaces between tokens are not in a string
+        assert_eq!(scan_document(b"[\n\t\"a\"\r\n]").error(true), None);
+
+        let compact = SerializeOptions {
+            pretty: false,
+            ..SerializeOptions::default()
+        };
+        assert_eq!(
+            format_json("[\"a\u{8}b\u{c}c\td\u{1}\"]", &compact).unwrap(),
+            r#"["a\bb\fc\td\u0001"]"#
+        );
+    }
+
+    #[test]
+    fn test_deep_nesting_fails_gracefully() {
+        let nested = |levels: usize| format!("{}{}", "[".repeat(levels), "]".repeat(levels));
+        let deep = nested(5000);
+        let scan = scan_document(deep.as_bytes());
+        // Opened up to the deepest level parse options allow
+        assert_eq!(scan.error(true), None);
+        assert_eq!(
+            scan.nesting_warning(),
+            Some(format!(
+                "Nesting deeper than {} levels at position {}",
+                MAX_NESTING_DEPTH, MAX_NESTING_DEPTH
+            ))
+        );
+        // Recursive readers stop at the same depth instead of overflowing the stack
+        let default = SerializeOptions::default();
+        assert!(format_json(&deep, &default).is_err());
+        assert!(parse_keys_only(format!("[{}]", deep).as_bytes(), None, 10).is_err());
+        assert_eq!(scan.max_depth(), u8::MAX);
+
+        let bounded = nested(MAX_NESTING_DEPTH);
+        assert_eq!(scan_document(bounded.as_bytes()).nesting_warning(), None);
+        assert_eq!(
+            format_json(
+                &bounded,
+                &SerializeOptions {
+                    pretty: false,
+                    ..default
+                }
+            )
+            .unwrap(),
+            bounded
         );
+        // Brackets of strings are not nesting
+        let in_string = format!("[\"{}\"]", "[".repeat(5000));
+        assert_eq!(scan_document(in_string.as_bytes()).nesting_warning(), None);
     }
 }
@@ -1,5 +1,7 @@
-use crate::array_table::ArrayTable;
+use crate::array_table::{ArrayTable, DisplayOptions, EditOptions};
+use crate::components::go_to_pointer::GoToPointer;
 use crate::object_table::ObjectTable;
+use crate::parser::is_pointer_prefix;
 use crate::{ArrayResponse, View};
 use eframe::egui::{Context, Ui};
 use egui::Order;
@@ -12,6 +14,11 @@ pub struct SubTable<'array> {
     array_table: Option<ArrayTable<'array>>,
     object_table: Option<ObjectTable>,
     row_index: usize,
+    go_to_pointer: GoToPointer,
+    pointer: PointerKey,
+    // Opened from a cell holding an array or an object: edits are written back to the cell as
+    // its serialized value, instead of as nested entries of the row
+    pub(crate) is_cell_value: bool,
 }
 
 impl SubTable<'_> {
@@ -23,6 +30,7 @@ impl SubTable<'_> {
         depth: u8,
     ) -> Self {
         let name = parent_pointer.pointer.clone();
+        let pointer = parent_pointer.clone();
         if matches!(parent_value_type, ValueType::Array(_)) {
             let options = ParseOptions::default()
                 .parse_array(false)
@@ -39,6 +47,9 @@ impl SubTable<'_> {
                 array_table: Some(array_table),
                 object_table: None,
                 row_index: index_in_json_entries_array,
+                go_to_pointer: GoToPointer::default(),
+                pointer,
+                is_cell_value: false,
             }
         } else {
             let options = ParseOptions::default()
@@ -54,6 +65,9 @@ impl SubTable<'_> {
                 array_table: None,
                 object_table: Some(ObjectTable::new(result.json, name)),
                 row_index: index_in_json_entries_array,
+                go_to_pointer: GoToPointer::default(),
+                pointer,
+                is_cell_value: false,
             }
         }
     }
@@ -81,7 +95,72 @@ impl SubTable<'_> {
         self.row_index
     }
 
+    pub fn pointer(&self) -> &PointerKey {
+        &self.pointer
+    }
+
+    /// Displayed array or object serialized as json
+    pub fn to_json(&self) -> String {
+        if let Some(ref array_table) = self.array_table {
+            array_table.serialize_parent_array().value.unwrap()
+        } else {
+            self.object_table.as_ref().unwrap().to_json()
+        }
+    }
+
+    pub fn set_edit_options(&mut self, edit_options: EditOptions) {
+        if let Some(ref mut array_table) = self.array_table {
+            array_table.set_edit_options(edit_options);
+        } else if let Some(ref mut object_table) = self.object_table {
+            object_table.edit_options = edit_options;
+        }
+    }
+
+    pub fn set_display_options(&mut self, display_options: DisplayOptions) {
+        if let Some(ref mut array_table) = self.array_table {
+            array_table.set_display_options(display_options);
+        } else if let Some(ref mut object_table) = self.object_table {
+            object_table.display_options = display_options;
+        }
+    }
+
+    /// Lock values at `pointers` and values nested in them, an array is locked as a whole
+    pub fn lock_pointers(&mut self, pointers: &[String]) {
+        if let Some(ref mut array_table) = self.array_table {
+            if pointers
+                .iter()
+                .any(|pointer| is_pointer_prefix(pointer, &self.name))
+            {
+                array_table.set_all_columns_locked(true);
+            }
+        } else if let Some(ref mut object_table) = self.object_table {
+            for pointer in pointers {
+                object_table.set_key_locked(pointer, true);
+            }
+        }
+    }
+
     pub fn update_nodes(&mut self, pointer: PointerKey, value: Option<String>) {
+        if self.is_cell_value && pointer.pointer.eq(&self.name) {
+            // Cell was replaced as a whole, e.g. on undo, content is parsed again
+            if let Some(value) = value.filter(|_| {
+                matches!(
+                    pointer.value_type,
+                    ValueType::Array(_) | ValueType::Object(..)
+                )
+            }) {
+                let depth = self.pointer.depth;
+                *self = Self::new(
+                    pointer,
+                    value,
+                    self.pointer.value_type,
+                    self.row_index,
+                    depth,
+                );
+                self.is_cell_value = true;
+            }
+            return;
+        }
         if let Some(ref mut array_table) = self.array_table {
             if let Some(entry) = array_table.nodes[self.row_index]
                 .entries
@@ -96,6 +175,7 @@ impl SubTable<'_> {
             }
         } else {
             let table = self.object_table.as_mut().unwrap();
+            table.forget_serialized_arrays(&pointer.pointer);
             if let Some(entry) = table
                 .nodes
                 .iter_mut()
@@ -138,6 +218,20 @@ impl SubTable<'_> {
 impl super::View<ArrayResponse> for SubTable<'_> {
     fn ui(&mut self, ui: &mut Ui) -> ArrayResponse {
         ui.vertical(|ui| {
+            let go_to_pointer = ui.horizontal(|ui| self.go_to_pointer.ui(ui)).inner;
+            if let Some(pointer) = go_to_pointer {
+                let result = if let Some(ref mut array_table) = self.array_table {
+                    array_table.scroll_to_pointer(&pointer)
+                } else {
+                    self.object_table
+                        .as_mut()
+                        .unwrap()
+                        .scroll_to_pointer(&pointer)
+                };
+                if let Err(error) = result {
+                    self.go_to_pointer.set_error(ui, error);
+                }
+            }
             if let Some(ref mut array_table) = self.array_table {
                 array_table.ui(ui)
             } else {